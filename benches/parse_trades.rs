@@ -0,0 +1,53 @@
+//! Throughput of the in-place aggTrade decoding against the generic
+//! `serde_json::Value` path it replaced, over recorded combined-stream frames.
+//! Run with `cargo bench --bench parse_trades`.
+
+use anyhow::Result;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+#[allow(dead_code)]
+#[path = "../src/data/binance_trades.rs"]
+mod binance_trades;
+
+use binance_trades::{decode_agg_trade, BinanceAggTrade, BinanceAggTradeMessage};
+
+const CORPUS: &str = include_str!("../tests/fixtures/agg_trade_frames.jsonl");
+
+type Decoder = fn(&str) -> Result<BinanceAggTrade>;
+
+/// What `decode_value_frame` does with a trade frame
+fn decode_value(frame: &str) -> Result<BinanceAggTrade> {
+    let mut value: serde_json::Value = serde_json::from_str(frame)?;
+    if let Some(data) = value.get_mut("data") {
+        value = data.take();
+    }
+    serde_json::from_value::<BinanceAggTradeMessage>(value)?.decode()
+}
+
+fn decode_borrowed(frame: &str) -> Result<BinanceAggTrade> {
+    decode_agg_trade(frame).expect("not an aggTrade frame")
+}
+
+fn parse_trades(c: &mut Criterion) {
+    let frames: Vec<&str> = CORPUS.lines().collect();
+    for frame in &frames {
+        assert_eq!(decode_borrowed(frame).unwrap(), decode_value(frame).unwrap());
+    }
+
+    let mut group = c.benchmark_group("parse_trades");
+    group.throughput(Throughput::Bytes(CORPUS.len() as u64));
+    let decoders: [(&str, Decoder); 2] = [("serde_json::Value", decode_value), ("borrowed", decode_borrowed)];
+    for (name, decode) in decoders {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                for frame in &frames {
+                    black_box(decode(black_box(frame)).unwrap());
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse_trades);
+criterion_main!(benches);
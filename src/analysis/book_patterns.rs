@@ -0,0 +1,289 @@
+use std::collections::{HashMap, VecDeque};
+use ordered_float::OrderedFloat;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::config::settings::BookPatternConfig;
+use crate::data::{BookDelta, BookPattern, BookPatternAlert, GuiUpdate, LatencyHistogram, LevelChange, Metrics,
+                  OrderflowEvent, ScreenerAlert, Subscription};
+
+/// Share of the shown size that must trade before a re-posted level counts as a refill
+const ICEBERG_REFILL_FILL_RATIO: f64 = 0.5;
+/// Share of a large order that must disappear at once for it to count as pulled
+const SPOOF_PULLED_RATIO: f64 = 0.5;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct LevelKey {
+    symbol: String,
+    side: String,
+    price: OrderedFloat<f64>,
+}
+
+/// A large order resting at a level, waiting to be either traded or pulled
+#[derive(Debug, Clone)]
+struct LargeOrder {
+    size: f64,
+    /// `LevelActivity::traded` when the order was placed
+    traded_mark: f64,
+}
+
+#[derive(Debug, Default)]
+struct LevelActivity {
+    /// Volume printed against the level
+    traded: f64,
+    traded_since_refill: f64,
+    /// Largest size shown since the last refill
+    displayed: f64,
+    peak_displayed: f64,
+    large_order: Option<LargeOrder>,
+    /// (time, volume traded while the order rested) of untraded pulls near the price
+    spoof_pulls: VecDeque<(u64, f64)>,
+    /// (time, volume traded since the previous refill)
+    refills: VecDeque<(u64, f64)>,
+    last_activity: u64,
+}
+
+/// Correlates depth changes at a price with the trades printed there. Large
+/// orders that are pulled untraded whenever price comes near are spoof
+/// candidates; levels that are re-posted after trading through are iceberg
+/// candidates. The depth stream is batched while trades are not, so the prints
+/// that consumed a level normally arrive before the diff that shows it.
+pub struct BookPatternDetector {
+    config: BookPatternConfig,
+    levels: HashMap<LevelKey, LevelActivity>,
+    mids: HashMap<String, f64>,
+    last_prune: u64,
+    latency: LatencyHistogram,
+}
+
+impl BookPatternDetector {
+    pub fn new(config: BookPatternConfig) -> Self {
+        Self {
+            config,
+            levels: HashMap::new(),
+            mids: HashMap::new(),
+            last_prune: 0,
+            latency: LatencyHistogram::default(),
+        }
+    }
+
+    /// Record the time spent per depth diff or trade in the shared metrics registry
+    pub fn set_metrics(&mut self, metrics: &Metrics) {
+        self.latency = metrics.analyzer_latency("book patterns");
+    }
+
+    fn window_ms(&self) -> u64 {
+        self.config.window_secs * 1000
+    }
+
+    /// Attribute a print to the level it executed against: sellers hit bids
+    pub fn process_trade(&mut self, trade: &OrderflowEvent) {
+        let side = if trade.is_buyer_maker { "BID" } else { "ASK" };
+        let key = LevelKey { symbol: trade.symbol.clone(), side: side.to_string(), price: OrderedFloat(trade.price) };
+        let level = self.levels.entry(key).or_default();
+        level.traded += trade.quantity;
+        level.traded_since_refill += trade.quantity;
+        level.last_activity = level.last_activity.max(trade.timestamp);
+    }
+
+    /// Apply one diff's level changes, returning the patterns they complete
+    pub fn process_delta(&mut self, delta: &BookDelta) -> Vec<BookPatternAlert> {
+        let mid_price = delta.mid_price().or_else(|| self.mids.get(&delta.symbol).copied());
+        if let Some(mid_price) = mid_price {
+            self.mids.insert(delta.symbol.clone(), mid_price);
+        }
+
+        let alerts = delta.changes
+            .iter()
+            .filter_map(|change| self.process_change(&delta.symbol, delta.timestamp, mid_price, change))
+            .collect();
+
+        if delta.timestamp >= self.last_prune + self.window_ms() {
+            let horizon = delta.timestamp.saturating_sub(self.window_ms());
+            self.levels.retain(|_, level| level.last_activity >= horizon || level.large_order.is_some());
+            self.last_prune = delta.timestamp;
+        }
+        alerts
+    }
+
+    fn process_change(&mut self, symbol: &str, timestamp: u64, mid_price: Option<f64>, change: &LevelChange) -> Option<BookPatternAlert> {
+        let config = self.config.clone();
+        let horizon = timestamp.saturating_sub(self.window_ms());
+        let key = LevelKey { symbol: symbol.to_string(), side: change.side.clone(), price: OrderedFloat(change.price) };
+        let level = self.levels.entry(key).or_default();
+        level.last_activity = level.last_activity.max(timestamp);
+        level.spoof_pulls.retain(|(at, _)| *at >= horizon);
+        level.refills.retain(|(at, _)| *at >= horizon);
+
+        let alert = |pattern, occurrences, traded_quantity, displayed_quantity| BookPatternAlert {
+            symbol: symbol.to_string(),
+            timestamp,
+            pattern,
+            side: change.side.clone(),
+            price: change.price,
+            displayed_quantity,
+            occurrences,
+            traded_quantity,
+        };
+
+        if change.quantity > change.previous {
+            let added = change.quantity - change.previous;
+            let refilled = level.traded_since_refill > 0.0
+                && level.traded_since_refill >= level.displayed * ICEBERG_REFILL_FILL_RATIO;
+
+            if refilled {
+                level.refills.push_back((timestamp, level.traded_since_refill));
+                level.traded_since_refill = 0.0;
+                level.displayed = change.quantity;
+            } else {
+                level.displayed = level.displayed.max(change.quantity);
+            }
+            level.peak_displayed = level.peak_displayed.max(change.quantity);
+            if added * change.price >= config.min_notional {
+                level.large_order = Some(LargeOrder { size: added, traded_mark: level.traded });
+            }
+
+            let executed: f64 = level.refills.iter().map(|(_, traded)| traded).sum();
+            if refilled && level.refills.len() >= config.min_iceberg_refills && executed * change.price >= config.min_notional {
+                let found = alert(BookPattern::Iceberg, level.refills.len(), executed, level.peak_displayed);
+                level.refills.clear();
+                level.peak_displayed = change.quantity;
+                return Some(found);
+            }
+            return None;
+        }
+
+        let removed = change.previous - change.quantity;
+        let large_order = level.large_order.take_if(|order| removed >= order.size * SPOOF_PULLED_RATIO)?;
+        let traded = level.traded - large_order.traded_mark;
+        let near = mid_price.is_some_and(|mid| (change.price - mid).abs() / mid * 100.0 <= config.approach_pct);
+        if traded > large_order.size * config.max_spoof_fill_ratio || !near {
+            return None;
+        }
+
+        level.spoof_pulls.push_back((timestamp, traded));
+        if level.spoof_pulls.len() < config.min_spoof_cycles {
+            return None;
+        }
+        let traded: f64 = level.spoof_pulls.iter().map(|(_, traded)| traded).sum();
+        let found = alert(BookPattern::Spoof, level.spoof_pulls.len(), traded, level.peak_displayed);
+        level.spoof_pulls.clear();
+        Some(found)
+    }
+
+    pub async fn start(
+        mut self,
+        mut deltas: Subscription<BookDelta>,
+        mut orderflow: Subscription<OrderflowEvent>,
+        gui_sender: mpsc::Sender<GuiUpdate>,
+    ) {
+        info!("Starting book pattern detector");
+        loop {
+            tokio::select! {
+                Some(trade) = orderflow.recv() => {
+                    let started = std::time::Instant::now();
+                    self.process_trade(&trade);
+                    self.latency.observe(started.elapsed());
+                }
+                Some(delta) = deltas.recv() => {
+                    let started = std::time::Instant::now();
+                    for alert in self.process_delta(&delta) {
+                        info!(
+                            "{:?} candidate {} {} @ {} ({} times, {} traded)",
+                            alert.pattern, alert.symbol, alert.side, alert.price, alert.occurrences, alert.traded_quantity
+                        );
+                        if gui_sender.try_send(GuiUpdate::Alert(ScreenerAlert::BookPattern(alert))).is_err() {
+                            warn!("Dropped a book pattern alert, GUI channel full");
+                        }
+                    }
+                    self.latency.observe(started.elapsed());
+                }
+                else => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MID: f64 = 100.0;
+
+    fn detector() -> BookPatternDetector {
+        BookPatternDetector::new(BookPatternConfig {
+            min_notional: 10_000.0,
+            approach_pct: 0.2,
+            max_spoof_fill_ratio: 0.1,
+            min_spoof_cycles: 3,
+            min_iceberg_refills: 3,
+            window_secs: 60,
+        })
+    }
+
+    fn delta(timestamp: u64, side: &str, price: f64, previous: f64, quantity: f64) -> BookDelta {
+        BookDelta {
+            symbol: "BTCUSDT".to_string(),
+            timestamp,
+            best_bid: Some(MID - 0.05),
+            best_ask: Some(MID + 0.05),
+            changes: vec![LevelChange { side: side.to_string(), price, previous, quantity }],
+        }
+    }
+
+    fn trade(timestamp: u64, price: f64, quantity: f64, is_buyer_maker: bool) -> OrderflowEvent {
+        OrderflowEvent { symbol: "BTCUSDT".to_string(), timestamp, price, quantity, is_buyer_maker, trade_id: timestamp }
+    }
+
+    #[test]
+    fn test_bid_placed_and_pulled_untraded_near_price_is_a_spoof() {
+        let mut detector = detector();
+        let mut alerts = Vec::new();
+        for cycle in 0..3 {
+            let t = cycle * 1_000;
+            // 200 lots at 99.9 is 19,980 notional; a token 5 lots trade against it
+            alerts.extend(detector.process_delta(&delta(t, "BID", 99.9, 0.0, 200.0)));
+            detector.process_trade(&trade(t + 100, 99.9, 5.0, true));
+            alerts.extend(detector.process_delta(&delta(t + 500, "BID", 99.9, 195.0, 0.0)));
+        }
+        assert_eq!(alerts.len(), 1);
+        assert_eq!((alerts[0].pattern, alerts[0].side.as_str(), alerts[0].price), (BookPattern::Spoof, "BID", 99.9));
+        assert_eq!((alerts[0].occurrences, alerts[0].traded_quantity, alerts[0].displayed_quantity), (3, 15.0, 200.0));
+
+        // The same cycle far from price, or eaten by trades, is not a spoof
+        let mut detector = self::detector();
+        for cycle in 0..3 {
+            let t = cycle * 1_000;
+            alerts = detector.process_delta(&delta(t, "BID", 98.0, 0.0, 200.0));
+            alerts.extend(detector.process_delta(&delta(t + 500, "BID", 98.0, 200.0, 0.0)));
+            alerts.extend(detector.process_delta(&delta(t, "ASK", 100.1, 0.0, 200.0)));
+            detector.process_trade(&trade(t + 100, 100.1, 150.0, false));
+            alerts.extend(detector.process_delta(&delta(t + 300, "ASK", 100.1, 200.0, 50.0)));
+            alerts.extend(detector.process_delta(&delta(t + 500, "ASK", 100.1, 50.0, 0.0)));
+            assert!(alerts.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_level_refilling_after_trading_through_is_an_iceberg() {
+        let mut detector = detector();
+        let mut alerts = detector.process_delta(&delta(0, "ASK", 100.1, 0.0, 10.0));
+        for refill in 1..=4u64 {
+            let t = refill * 1_000;
+            // Buyers lift the 10 shown, the level empties and 10 more appear
+            detector.process_trade(&trade(t, 100.1, 40.0, false));
+            alerts.extend(detector.process_delta(&delta(t + 100, "ASK", 100.1, 10.0, 0.0)));
+            alerts.extend(detector.process_delta(&delta(t + 200, "ASK", 100.1, 0.0, 10.0)));
+        }
+        // Three refills of 40 executed (12,012 notional) complete the pattern once
+        assert_eq!(alerts.len(), 1);
+        assert_eq!((alerts[0].pattern, alerts[0].side.as_str()), (BookPattern::Iceberg, "ASK"));
+        assert_eq!((alerts[0].occurrences, alerts[0].traded_quantity, alerts[0].displayed_quantity), (3, 120.0, 10.0));
+
+        // Size added without trades in between is not a refill
+        let mut detector = self::detector();
+        for step in 0..6u64 {
+            assert!(detector.process_delta(&delta(step * 1_000, "BID", 99.9, step as f64, step as f64 + 1.0)).is_empty());
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::{HashMap, VecDeque};
+use anyhow::Result;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::config::settings::AnalysisConfig;
+use crate::data::{FundingRateData, GuiUpdate, PersistenceHandle, PersistRecord, ScreenerAlert};
+
+/// Norm samples needed before a symbol can alert
+const MIN_NORM_SAMPLES: usize = 60;
+/// Floor on the norm's standard deviation, so funding pinned at one value for
+/// hours does not turn the smallest move into an alert
+const MIN_NORM_STD_DEV: f64 = 0.00005;
+/// Minimum spacing between funding alerts for one symbol
+const FUNDING_ALERT_COOLDOWN_MS: u64 = 60 * 60_000;
+
+#[derive(Debug, Clone)]
+pub struct FundingConfig {
+    pub norm_window_hours: u64,
+    pub deviation_threshold: f64,
+    pub min_alert_rate: f64,
+}
+
+impl From<&AnalysisConfig> for FundingConfig {
+    fn from(config: &AnalysisConfig) -> Self {
+        Self {
+            norm_window_hours: config.funding_norm_window_hours,
+            deviation_threshold: config.funding_deviation_threshold,
+            min_alert_rate: config.funding_min_alert_rate,
+        }
+    }
+}
+
+/// Consumes mark price frames: forwards them to the GUI, keeps one funding
+/// sample per symbol and minute as the rolling norm (also persisted), and raises
+/// `FundingExtreme` alerts when funding moves too many standard deviations away
+/// from that norm.
+pub struct FundingMonitor {
+    receiver: mpsc::Receiver<Vec<FundingRateData>>,
+    config: FundingConfig,
+    gui_sender: mpsc::Sender<GuiUpdate>,
+    persistence: Option<PersistenceHandle>,
+    /// (timestamp, funding rate) per symbol, one per minute
+    samples: HashMap<String, VecDeque<(u64, f64)>>,
+    last_alert: HashMap<String, u64>,
+}
+
+impl FundingMonitor {
+    pub fn new(
+        receiver: mpsc::Receiver<Vec<FundingRateData>>,
+        config: FundingConfig,
+        gui_sender: mpsc::Sender<GuiUpdate>,
+    ) -> Self {
+        Self {
+            receiver,
+            config,
+            gui_sender,
+            persistence: None,
+            samples: HashMap::new(),
+            last_alert: HashMap::new(),
+        }
+    }
+
+    pub fn set_persistence(&mut self, persistence: PersistenceHandle) {
+        self.persistence = Some(persistence);
+    }
+
+    pub async fn start(mut self) -> Result<()> {
+        info!(
+            "Funding monitor started ({}h norm, alert beyond {} std devs)",
+            self.config.norm_window_hours, self.config.deviation_threshold
+        );
+
+        while let Some(readings) = self.receiver.recv().await {
+            let mut alerts = Vec::new();
+            for reading in &readings {
+                if let Some(alert) = self.check_extreme(reading) {
+                    alerts.push(alert);
+                }
+                if self.record_sample(reading) {
+                    if let Some(persistence) = &self.persistence {
+                        persistence.persist(PersistRecord::FundingRate(reading.clone()));
+                    }
+                }
+            }
+
+            let _ = self.gui_sender.try_send(GuiUpdate::FundingRates(readings));
+            for alert in alerts {
+                if let ScreenerAlert::FundingExtreme { symbol, funding_rate, deviation_from_norm, .. } = &alert {
+                    warn!(
+                        "Funding extreme {} {:.4}% ({:+.4}% from norm)",
+                        symbol, funding_rate * 100.0, deviation_from_norm * 100.0
+                    );
+                }
+                let _ = self.gui_sender.try_send(GuiUpdate::Alert(alert));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Keep the first reading of each minute as a norm sample; returns whether it was kept
+    pub fn record_sample(&mut self, reading: &FundingRateData) -> bool {
+        let window_ms = self.config.norm_window_hours * 3_600_000;
+        let samples = self.samples.entry(reading.symbol.clone()).or_default();
+
+        let minute = reading.timestamp / 60_000;
+        if samples.back().is_some_and(|(timestamp, _)| timestamp / 60_000 >= minute) {
+            return false;
+        }
+
+        samples.push_back((reading.timestamp, reading.funding_rate));
+        while samples.front().is_some_and(|(timestamp, _)| timestamp + window_ms < reading.timestamp) {
+            samples.pop_front();
+        }
+        true
+    }
+
+    /// Compare `reading` against the symbol's norm and return an alert if it is extreme
+    pub fn check_extreme(&mut self, reading: &FundingRateData) -> Option<ScreenerAlert> {
+        if !reading.is_extreme(self.config.min_alert_rate) {
+            return None;
+        }
+
+        let samples = self.samples.get(&reading.symbol)?;
+        if samples.len() < MIN_NORM_SAMPLES {
+            return None;
+        }
+
+        let count = samples.len() as f64;
+        let mean = samples.iter().map(|(_, rate)| rate).sum::<f64>() / count;
+        let variance = samples.iter().map(|(_, rate)| (rate - mean).powi(2)).sum::<f64>() / count;
+        let deviation = reading.funding_rate - mean;
+        if deviation.abs() < self.config.deviation_threshold * variance.sqrt().max(MIN_NORM_STD_DEV) {
+            return None;
+        }
+
+        let cooled_down = self.last_alert
+            .get(&reading.symbol)
+            .is_none_or(|last| reading.timestamp >= last + FUNDING_ALERT_COOLDOWN_MS);
+        if !cooled_down {
+            return None;
+        }
+        self.last_alert.insert(reading.symbol.clone(), reading.timestamp);
+
+        Some(ScreenerAlert::FundingExtreme {
+            symbol: reading.symbol.clone(),
+            funding_rate: reading.funding_rate,
+            deviation_from_norm: deviation,
+            predicted_rate: reading.predicted_rate,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor() -> FundingMonitor {
+        let (_, receiver) = mpsc::channel(1);
+        let (gui_sender, _) = mpsc::channel(1);
+        let config = FundingConfig { norm_window_hours: 24, deviation_threshold: 3.0, min_alert_rate: 0.0005 };
+        FundingMonitor::new(receiver, config, gui_sender)
+    }
+
+    fn reading(timestamp: u64, funding_rate: f64) -> FundingRateData {
+        FundingRateData::new("BTCUSDT".to_string(), timestamp, funding_rate)
+    }
+
+    #[test]
+    fn test_norm_keeps_one_sample_per_minute() {
+        let mut monitor = monitor();
+        assert!(monitor.record_sample(&reading(60_000, 0.0001)));
+        assert!(!monitor.record_sample(&reading(61_000, 0.0002)));
+        assert!(monitor.record_sample(&reading(120_000, 0.0002)));
+
+        // Samples older than the window fall out
+        assert!(monitor.record_sample(&reading(120_000 + 24 * 3_600_000 + 60_000, 0.0001)));
+        assert_eq!(monitor.samples["BTCUSDT"].len(), 1);
+    }
+
+    #[test]
+    fn test_extreme_funding_alerts_once_per_cooldown() {
+        let mut monitor = monitor();
+        for minute in 0..MIN_NORM_SAMPLES as u64 {
+            monitor.record_sample(&reading(minute * 60_000, 0.0001));
+        }
+        let now = MIN_NORM_SAMPLES as u64 * 60_000;
+
+        // Far from the norm but below the absolute minimum either way
+        assert!(monitor.check_extreme(&reading(now, 0.00045)).is_none());
+        assert!(monitor.check_extreme(&reading(now, -0.0004)).is_none());
+
+        match monitor.check_extreme(&reading(now, 0.0008)) {
+            Some(ScreenerAlert::FundingExtreme { symbol, funding_rate, deviation_from_norm, .. }) => {
+                assert_eq!(symbol, "BTCUSDT");
+                assert_eq!(funding_rate, 0.0008);
+                assert!((deviation_from_norm - 0.0007).abs() < 1e-12);
+            }
+            other => panic!("expected a funding alert, got {:?}", other),
+        }
+        assert!(monitor.check_extreme(&reading(now + 1_000, 0.0009)).is_none());
+        assert!(monitor.check_extreme(&reading(now + FUNDING_ALERT_COOLDOWN_MS, -0.0009)).is_some());
+    }
+}
@@ -5,17 +5,18 @@ use anyhow::Result;
 use tokio::time::{sleep, Duration};
 use tracing::{info, error, debug};
 
-use crate::data::{OrderflowEvent, OrderImbalance};
+use crate::data::{LatencyHistogram, Metrics, OrderflowEvent, OrderImbalance, Subscription};
 
 pub struct ImbalanceAnalyzer {
     sender: mpsc::Sender<OrderImbalance>,
     window_duration_seconds: u64,
     calculation_interval_ms: u64,
+    latency: LatencyHistogram,
 }
 
 struct SymbolImbalanceTracker {
     symbol: String,
-    trades: VecDeque<OrderflowEvent>,
+    trades: VecDeque<Arc<OrderflowEvent>>,
     current_bid_volume: f64,
     current_ask_volume: f64,
     last_calculation_time: u64,
@@ -28,13 +29,16 @@ impl ImbalanceAnalyzer {
             sender,
             window_duration_seconds: 60,
             calculation_interval_ms: 1000, // Calculate every second
+            latency: LatencyHistogram::default(),
         }
     }
 
-    pub async fn start(
-        &self,
-        orderflow_receiver: Arc<tokio::sync::Mutex<mpsc::Receiver<OrderflowEvent>>>,
-    ) -> Result<()> {
+    /// Record the time spent per trade in the shared metrics registry
+    pub fn set_metrics(&mut self, metrics: &Metrics) {
+        self.latency = metrics.analyzer_latency("imbalance");
+    }
+
+    pub async fn start(&self, mut orderflow: Subscription<OrderflowEvent>) -> Result<()> {
         info!("Starting imbalance analyzer");
         
         let mut trackers: HashMap<String, SymbolImbalanceTracker> = HashMap::new();
@@ -42,12 +46,9 @@ impl ImbalanceAnalyzer {
         
         loop {
             // Process incoming trades with timeout
-            let mut receiver = orderflow_receiver.lock().await;
-            
-            match tokio::time::timeout(Duration::from_millis(100), receiver.recv()).await {
+            match tokio::time::timeout(Duration::from_millis(100), orderflow.recv()).await {
                 Ok(Some(event)) => {
-                    drop(receiver); // Release the lock early
-
+                    let started = std::time::Instant::now();
                     debug!("Imbalance analyzer received event for {}: price={}, qty={}", event.symbol, event.price, event.quantity);
 
                     // Get or create tracker for this symbol
@@ -65,14 +66,13 @@ impl ImbalanceAnalyzer {
                             debug!("Failed to send imbalance update: {}", e);
                         }
                     }
+                    self.latency.observe(started.elapsed());
                 }
                 Ok(None) => {
-                    drop(receiver);
                     error!("Orderflow receiver channel closed");
                     break;
                 }
                 Err(_) => {
-                    drop(receiver);
                     // Timeout occurred, continue with periodic tasks
                 }
             }
@@ -117,11 +117,8 @@ impl SymbolImbalanceTracker {
         }
     }
 
-    fn process_trade(&mut self, event: OrderflowEvent) -> Option<OrderImbalance> {
+    fn process_trade(&mut self, event: Arc<OrderflowEvent>) -> Option<OrderImbalance> {
         let current_time = event.timestamp;
-        
-        // Add new trade
-        self.trades.push_back(event.clone());
 
         // Update volume counters
         if event.is_buyer_maker {
@@ -132,6 +129,9 @@ impl SymbolImbalanceTracker {
             self.current_bid_volume += event.quantity;
         }
 
+        // Add new trade
+        self.trades.push_back(event);
+
         // Remove old trades outside the window
         self.cleanup_old_trades(current_time);
 
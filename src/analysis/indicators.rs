@@ -26,7 +26,7 @@ impl Indicator for SimpleMovingAverage {
             if i < self.period - 1 {
                 result.push(f64::NAN);
             } else {
-                let sum: f64 = candles[i - self.period + 1..=i]
+                let sum: f64 = candles[i + 1 - self.period..=i]
                     .iter()
                     .map(|c| c.close_price)
                     .sum();
@@ -123,7 +123,7 @@ impl BollingerBands {
                 upper.push(f64::NAN);
                 lower.push(f64::NAN);
             } else {
-                let prices: Vec<f64> = candles[i - self.period + 1..=i]
+                let prices: Vec<f64> = candles[i + 1 - self.period..=i]
                     .iter()
                     .map(|c| c.close_price)
                     .collect();
@@ -320,7 +320,7 @@ impl Indicator for WeightedMovingAverage {
             if i < self.period - 1 {
                 result.push(f64::NAN);
             } else {
-                let weighted_sum: f64 = candles[i - self.period + 1..=i]
+                let weighted_sum: f64 = candles[i + 1 - self.period..=i]
                     .iter()
                     .enumerate()
                     .map(|(j, c)| c.close_price * (j + 1) as f64)
@@ -341,13 +341,29 @@ impl Indicator for WeightedMovingAverage {
 mod tests {
     use super::*;
 
+    fn candle(timestamp: u64, open: f64, high: f64, low: f64, close: f64, volume: f64) -> Candle {
+        Candle {
+            symbol: "TEST".to_string(),
+            timestamp,
+            timeframe: "1m".to_string(),
+            open_price: open,
+            high_price: high,
+            low_price: low,
+            close_price: close,
+            volume,
+            buy_volume: volume / 2.0,
+            sell_volume: volume / 2.0,
+            trade_count: 0,
+        }
+    }
+
     fn create_test_candles() -> Vec<Candle> {
         vec![
-            Candle { timestamp: 0, open: 100.0, high: 105.0, low: 99.0, close: 102.0, volume: 1000.0 },
-            Candle { timestamp: 1, open: 102.0, high: 106.0, low: 101.0, close: 104.0, volume: 1100.0 },
-            Candle { timestamp: 2, open: 104.0, high: 107.0, low: 103.0, close: 105.0, volume: 1200.0 },
-            Candle { timestamp: 3, open: 105.0, high: 108.0, low: 104.0, close: 106.0, volume: 1300.0 },
-            Candle { timestamp: 4, open: 106.0, high: 109.0, low: 105.0, close: 107.0, volume: 1400.0 },
+            candle(0, 100.0, 105.0, 99.0, 102.0, 1000.0),
+            candle(1, 102.0, 106.0, 101.0, 104.0, 1100.0),
+            candle(2, 104.0, 107.0, 103.0, 105.0, 1200.0),
+            candle(3, 105.0, 108.0, 104.0, 106.0, 1300.0),
+            candle(4, 106.0, 109.0, 105.0, 107.0, 1400.0),
         ]
     }
 
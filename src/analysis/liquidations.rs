@@ -5,13 +5,14 @@ use anyhow::Result;
 use tracing::{info, error, debug};
 use tokio::time::{sleep, Duration};
 
-use crate::data::{OrderflowEvent, LiquidationEvent};
+use crate::data::{LatencyHistogram, Metrics, OrderflowEvent, Subscription, LiquidationEvent};
 
 pub struct LiquidationDetector {
     sender: mpsc::Sender<LiquidationEvent>,
     volume_spike_threshold: f64,
     price_movement_threshold: f64,
     detection_window_ms: u64,
+    latency: LatencyHistogram,
 }
 
 struct LiquidationCandidate {
@@ -25,7 +26,7 @@ struct LiquidationCandidate {
 
 struct SymbolLiquidationTracker {
     symbol: String,
-    recent_trades: VecDeque<OrderflowEvent>,
+    recent_trades: VecDeque<Arc<OrderflowEvent>>,
     recent_liquidations: VecDeque<LiquidationEvent>,
     baseline_volume: f64,
     baseline_update_count: u32,
@@ -41,10 +42,16 @@ impl LiquidationDetector {
             volume_spike_threshold: 3.0, // 3x normal volume
             price_movement_threshold: 0.02, // 2% price movement
             detection_window_ms: 5000, // 5 second detection window
+            latency: LatencyHistogram::default(),
         }
     }
 
-    pub async fn start_with_receiver(&mut self, orderflow_rx: Arc<tokio::sync::Mutex<mpsc::Receiver<OrderflowEvent>>>) -> Result<()> {
+    /// Record the time spent per trade in the shared metrics registry
+    pub fn set_metrics(&mut self, metrics: &Metrics) {
+        self.latency = metrics.analyzer_latency("liquidation detector");
+    }
+
+    pub async fn start_with_receiver(&mut self, mut orderflow: Subscription<OrderflowEvent>) -> Result<()> {
         info!("Starting liquidation detector with orderflow receiver");
 
         let mut trackers: HashMap<String, SymbolLiquidationTracker> = HashMap::new();
@@ -53,12 +60,12 @@ impl LiquidationDetector {
         loop {
             tokio::select! {
                 // Process incoming orderflow events
-                event = async {
-                    let mut rx = orderflow_rx.lock().await;
-                    rx.recv().await
-                } => {
+                event = orderflow.recv() => {
                     if let Some(event) = event {
-                        if let Some(liquidation_event) = self.process_orderflow_event(&event, &mut trackers) {
+                        let started = std::time::Instant::now();
+                        let liquidation_event = self.process_orderflow_event(&event, &mut trackers);
+                        self.latency.observe(started.elapsed());
+                        if let Some(liquidation_event) = liquidation_event {
                             if let Err(e) = self.sender.try_send(liquidation_event) {
                                 debug!("Failed to send liquidation event: {}", e);
                             }
@@ -117,7 +124,7 @@ impl LiquidationDetector {
         }
     }
 
-    pub fn process_orderflow_event(&mut self, event: &OrderflowEvent, trackers: &mut HashMap<String, SymbolLiquidationTracker>) -> Option<LiquidationEvent> {
+    pub fn process_orderflow_event(&mut self, event: &Arc<OrderflowEvent>, trackers: &mut HashMap<String, SymbolLiquidationTracker>) -> Option<LiquidationEvent> {
         // Get or create tracker for this symbol
         let tracker = trackers
             .entry(event.symbol.clone())
@@ -186,7 +193,7 @@ impl LiquidationDetector {
     fn calculate_price_movement(&self, tracker: &SymbolLiquidationTracker, current_time: u64) -> f64 {
         let window_start = current_time.saturating_sub(self.detection_window_ms);
         
-        let relevant_trades: Vec<&OrderflowEvent> = tracker.recent_trades.iter()
+        let relevant_trades: Vec<&Arc<OrderflowEvent>> = tracker.recent_trades.iter()
             .filter(|trade| trade.timestamp >= window_start)
             .collect();
 
@@ -239,9 +246,10 @@ impl SymbolLiquidationTracker {
         }
     }
 
-    fn add_trade(&mut self, trade: OrderflowEvent) {
-        self.recent_trades.push_back(trade.clone());
+    fn add_trade(&mut self, trade: Arc<OrderflowEvent>) {
+        let timestamp = trade.timestamp;
         self.last_price = trade.price;
+        self.recent_trades.push_back(trade);
         
         // Maintain trade history limit
         while self.recent_trades.len() > self.max_trades_history {
@@ -252,7 +260,7 @@ impl SymbolLiquidationTracker {
         self.update_baseline_volume();
         
         // Cleanup old liquidations
-        let cutoff_time = trade.timestamp.saturating_sub(300_000); // 5 minutes
+        let cutoff_time = timestamp.saturating_sub(300_000); // 5 minutes
         self.recent_liquidations.retain(|liq| liq.timestamp >= cutoff_time);
     }
 
@@ -265,7 +273,7 @@ impl SymbolLiquidationTracker {
         let current_time = self.recent_trades.back().unwrap().timestamp;
         let window_start = current_time.saturating_sub(self.volume_window_ms);
         
-        let window_trades: Vec<&OrderflowEvent> = self.recent_trades.iter()
+        let window_trades: Vec<&Arc<OrderflowEvent>> = self.recent_trades.iter()
             .filter(|trade| trade.timestamp >= window_start)
             .collect();
 
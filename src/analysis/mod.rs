@@ -5,6 +5,9 @@ pub mod volume_analysis;
 // pub mod timeframe_manager; // Temporarily disabled - uses incompatible FootprintCandle type
 pub mod indicators;
 pub mod traded_volume_tracker;
+pub mod open_interest;
+pub mod funding;
+pub mod book_patterns;
 
 pub use imbalance::*;
 pub use footprint::*;
@@ -12,4 +15,7 @@ pub use liquidations::*;
 pub use volume_analysis::*;
 // pub use timeframe_manager::*; // Temporarily disabled
 pub use indicators::*;
-pub use traded_volume_tracker::*;
\ No newline at end of file
+pub use traded_volume_tracker::*;
+pub use open_interest::*;
+pub use funding::*;
+pub use book_patterns::*;
\ No newline at end of file
@@ -0,0 +1,260 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use anyhow::Result;
+use tokio::sync::{mpsc, watch};
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn, debug};
+
+use crate::config::settings::AnalysisConfig;
+use crate::data::{
+    GuiUpdate, MarketDataSource, OpenInterestData, PersistenceHandle, PersistRecord, ScreenerAlert,
+    retain_listed_symbols,
+};
+
+/// Spacing between per-symbol requests within one poll
+const OPEN_INTEREST_REQUEST_INTERVAL_MS: u64 = 100;
+/// History loaded at startup: one day of 5m readings
+const OPEN_INTEREST_HISTORY_PERIOD: &str = "5m";
+const OPEN_INTEREST_HISTORY_LIMIT: usize = 288;
+
+#[derive(Debug, Clone)]
+pub struct OpenInterestConfig {
+    pub poll_interval_secs: u64,
+    pub change_window_minutes: u64,
+    pub spike_threshold_percentage: f64,
+}
+
+impl From<&AnalysisConfig> for OpenInterestConfig {
+    fn from(config: &AnalysisConfig) -> Self {
+        Self {
+            poll_interval_secs: config.open_interest_poll_interval_secs,
+            change_window_minutes: config.open_interest_change_window_minutes,
+            spike_threshold_percentage: config.open_interest_spike_threshold_percentage,
+        }
+    }
+}
+
+/// Polls open interest for each symbol, measures its change against the reading
+/// one window back and raises `OpenInterestSpike` alerts, at most one per symbol
+/// per window. Readings and alerts go to the GUI; readings are also persisted.
+pub struct OpenInterestCollector {
+    source: Arc<dyn MarketDataSource>,
+    client: reqwest::Client,
+    symbols: Vec<String>,
+    universe: Option<watch::Receiver<Vec<String>>>,
+    config: OpenInterestConfig,
+    gui_sender: mpsc::Sender<GuiUpdate>,
+    persistence: Option<PersistenceHandle>,
+    /// (timestamp, open interest) per symbol, covering at least one window
+    readings: HashMap<String, VecDeque<(u64, f64)>>,
+    last_alert: HashMap<String, u64>,
+}
+
+impl OpenInterestCollector {
+    pub fn new(
+        source: Arc<dyn MarketDataSource>,
+        symbols: Vec<String>,
+        config: OpenInterestConfig,
+        gui_sender: mpsc::Sender<GuiUpdate>,
+    ) -> Self {
+        Self {
+            source,
+            client: reqwest::Client::new(),
+            symbols,
+            universe: None,
+            config,
+            gui_sender,
+            persistence: None,
+            readings: HashMap::new(),
+            last_alert: HashMap::new(),
+        }
+    }
+
+    pub fn set_persistence(&mut self, persistence: PersistenceHandle) {
+        self.persistence = Some(persistence);
+    }
+
+    /// Poll the market's listed symbols, following listings and delistings,
+    /// instead of the list given to `new`
+    pub fn set_symbol_universe(&mut self, universe: watch::Receiver<Vec<String>>) {
+        self.universe = Some(universe);
+    }
+
+    pub async fn start(mut self) -> Result<()> {
+        match &mut self.universe {
+            Some(universe) => self.symbols = universe.wait_for(|symbols| !symbols.is_empty()).await?.clone(),
+            None => retain_listed_symbols(self.source.as_ref(), &mut self.symbols).await,
+        }
+        if self.symbols.is_empty() {
+            warn!("Open interest collector for {} has no listed symbols to poll", self.source.name());
+        }
+        info!(
+            "Open interest collector for {} polling {} symbols every {}s",
+            self.source.name(), self.symbols.len(), self.config.poll_interval_secs
+        );
+
+        self.load_history().await;
+
+        let mut poll_timer = tokio::time::interval(Duration::from_secs(self.config.poll_interval_secs.max(1)));
+        loop {
+            poll_timer.tick().await;
+
+            if let Some(universe) = self.universe.as_mut().filter(|universe| universe.has_changed().unwrap_or(false)) {
+                self.symbols = universe.borrow_and_update().clone();
+            }
+            for symbol in self.symbols.clone() {
+                match self.fetch_current(&symbol).await {
+                    Ok(mut reading) => {
+                        let alert = self.process_reading(&mut reading);
+                        self.publish(reading, alert);
+                    }
+                    Err(e) => debug!("Open interest for {} failed: {}", symbol, e),
+                }
+                sleep(Duration::from_millis(OPEN_INTEREST_REQUEST_INTERVAL_MS)).await;
+            }
+
+            if self.gui_sender.is_closed() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Seed the change windows and the chart with recent history; past spikes are not alerted
+    async fn load_history(&mut self) {
+        for symbol in self.symbols.clone() {
+            let url = self.source.open_interest_history_url(&symbol, OPEN_INTEREST_HISTORY_PERIOD, OPEN_INTEREST_HISTORY_LIMIT);
+            let history = match self.get(&url).await {
+                Ok(body) => self.source.parse_open_interest_history(&symbol, &body),
+                Err(e) => Err(e),
+            };
+
+            match history {
+                Ok(history) => {
+                    for mut reading in history {
+                        self.process_reading(&mut reading);
+                        self.publish(reading, None);
+                    }
+                }
+                Err(e) => warn!("Open interest history for {} unavailable: {}", symbol, e),
+            }
+            sleep(Duration::from_millis(OPEN_INTEREST_REQUEST_INTERVAL_MS)).await;
+        }
+        self.last_alert.clear();
+    }
+
+    async fn fetch_current(&self, symbol: &str) -> Result<OpenInterestData> {
+        let body = self.get(&self.source.open_interest_url(symbol)).await?;
+        self.source.parse_open_interest(symbol, &body)
+    }
+
+    async fn get(&self, url: &str) -> Result<String> {
+        Ok(self.client.get(url).send().await?.error_for_status()?.text().await?)
+    }
+
+    /// Fill in the change since the newest reading at least one window older,
+    /// remember this reading, and return an alert if the change is a spike
+    pub fn process_reading(&mut self, reading: &mut OpenInterestData) -> Option<ScreenerAlert> {
+        let window_ms = self.config.change_window_minutes * 60_000;
+        let readings = self.readings.entry(reading.symbol.clone()).or_default();
+
+        if let Some(&(_, previous)) = readings.iter().rev().find(|(timestamp, _)| timestamp + window_ms <= reading.timestamp) {
+            reading.calculate_change(previous);
+        }
+
+        readings.push_back((reading.timestamp, reading.open_interest));
+        while readings.front().is_some_and(|(timestamp, _)| timestamp + 2 * window_ms < reading.timestamp) {
+            readings.pop_front();
+        }
+
+        if !reading.is_significant_change(self.config.spike_threshold_percentage) {
+            return None;
+        }
+
+        let cooled_down = self.last_alert
+            .get(&reading.symbol)
+            .is_none_or(|last| reading.timestamp >= last + window_ms);
+        if !cooled_down {
+            return None;
+        }
+        self.last_alert.insert(reading.symbol.clone(), reading.timestamp);
+
+        Some(ScreenerAlert::OpenInterestSpike {
+            symbol: reading.symbol.clone(),
+            oi_change: reading.oi_change_24h,
+            oi_change_percentage: reading.oi_change_percentage,
+            timeframe: format!("{}m", self.config.change_window_minutes),
+        })
+    }
+
+    fn publish(&self, reading: OpenInterestData, alert: Option<ScreenerAlert>) {
+        if let Some(persistence) = &self.persistence {
+            persistence.persist(PersistRecord::OpenInterest(reading.clone()));
+        }
+        let _ = self.gui_sender.try_send(GuiUpdate::OpenInterest(reading));
+
+        if let Some(alert) = alert {
+            if let ScreenerAlert::OpenInterestSpike { symbol, oi_change_percentage, timeframe, .. } = &alert {
+                info!("Open interest spike {} {:+.2}% over {}", symbol, oi_change_percentage, timeframe);
+            }
+            let _ = self.gui_sender.try_send(GuiUpdate::Alert(alert));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::BinanceUsdmSource;
+
+    fn collector() -> OpenInterestCollector {
+        let source = Arc::new(BinanceUsdmSource::new("ws://127.0.0.1:1".to_string(), "http://127.0.0.1:1".to_string()));
+        let config = OpenInterestConfig { poll_interval_secs: 30, change_window_minutes: 5, spike_threshold_percentage: 2.0 };
+        let (gui_sender, _) = mpsc::channel(10);
+        OpenInterestCollector::new(source, vec!["BTCUSDT".to_string()], config, gui_sender)
+    }
+
+    fn process(collector: &mut OpenInterestCollector, minute: u64, open_interest: f64) -> (OpenInterestData, Option<ScreenerAlert>) {
+        let mut reading = OpenInterestData::new("BTCUSDT".to_string(), minute * 60_000, open_interest);
+        let alert = collector.process_reading(&mut reading);
+        (reading, alert)
+    }
+
+    #[test]
+    fn test_change_is_measured_over_the_window() {
+        let mut collector = collector();
+
+        // No reading a full window back yet
+        for minute in 0..5 {
+            let (reading, alert) = process(&mut collector, minute, 1000.0 + minute as f64);
+            assert_eq!(reading.oi_change_percentage, 0.0);
+            assert!(alert.is_none());
+        }
+
+        // Minute 6 compares against minute 1
+        let (reading, alert) = process(&mut collector, 6, 1011.0);
+        assert!((reading.oi_change_24h - 10.0).abs() < 1e-9);
+        assert!((reading.oi_change_percentage - 10.0 / 1001.0 * 100.0).abs() < 1e-9);
+        assert!(alert.is_none());
+    }
+
+    #[test]
+    fn test_spike_alerts_respect_cooldown() {
+        let mut collector = collector();
+        process(&mut collector, 0, 1000.0);
+        process(&mut collector, 1, 1000.0);
+
+        match process(&mut collector, 5, 1030.0).1 {
+            Some(ScreenerAlert::OpenInterestSpike { symbol, oi_change_percentage, timeframe, .. }) => {
+                assert_eq!(symbol, "BTCUSDT");
+                assert!((oi_change_percentage - 3.0).abs() < 1e-9);
+                assert_eq!(timeframe, "5m");
+            }
+            other => panic!("expected a spike alert, got {:?}", other),
+        }
+
+        // Still a spike against minute 1, but inside the cooldown
+        assert!(process(&mut collector, 6, 970.0).1.is_none());
+        // A window later the drop back alerts again
+        assert!(process(&mut collector, 10, 990.0).1.is_some());
+    }
+}
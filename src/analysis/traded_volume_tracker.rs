@@ -1,5 +1,5 @@
 use std::collections::HashMap;
-use crate::data::OrderflowEvent;
+use crate::data::{OrderflowEvent, SymbolRegistry};
 
 /// Tracks traded volume at each price level
 #[derive(Debug, Clone, Default)]
@@ -45,6 +45,13 @@ impl TradedVolumeTracker {
         }
     }
 
+    /// Tracker binned at the symbol's contract tick, or `default_tick_size` when
+    /// the registry does not know the symbol
+    pub fn for_symbol(symbol: String, registry: &SymbolRegistry, default_tick_size: f64) -> Self {
+        let tick_size = registry.tick_size(&symbol).unwrap_or(default_tick_size);
+        Self::new(symbol, tick_size)
+    }
+
     pub fn with_max_levels(symbol: String, tick_size: f64, max_levels: usize) -> Self {
         Self {
             symbol,
@@ -100,6 +107,26 @@ impl TradedVolumeTracker {
         levels
     }
 
+    pub fn tick_size(&self) -> f64 {
+        self.tick_size
+    }
+
+    /// Levels re-binned to a coarser `granularity` (a price step, normally a
+    /// multiple of the tick), keyed by price / granularity
+    pub fn levels_at_granularity(&self, granularity: f64) -> HashMap<i64, VolumeAtPrice> {
+        let mut levels: HashMap<i64, VolumeAtPrice> = HashMap::new();
+        for (tick, volume) in &self.volume_at_price {
+            let bucket = (*tick as f64 * self.tick_size / granularity).round() as i64;
+            let level = levels.entry(bucket).or_default();
+            level.buy_volume += volume.buy_volume;
+            level.sell_volume += volume.sell_volume;
+            level.total_volume += volume.total_volume;
+            level.trade_count += volume.trade_count;
+            level.last_trade_time = level.last_trade_time.max(volume.last_trade_time);
+        }
+        levels
+    }
+
     /// Get price levels within a range
     pub fn get_levels_in_range(&self, min_price: f64, max_price: f64) -> Vec<(i64, &VolumeAtPrice)> {
         let min_tick = (min_price / self.tick_size).round() as i64;
@@ -358,4 +385,19 @@ mod tests {
         let eth_stats = multi_tracker.get_tracker("ETHUSDT").unwrap().get_stats();
         assert_eq!(eth_stats.total_volume, 2.0);
     }
+
+    #[test]
+    fn test_levels_rebin_to_coarser_granularity() {
+        let mut tracker = TradedVolumeTracker::new("BTCUSDT".to_string(), 0.1);
+        tracker.process_trade(&create_test_event("BTCUSDT", 50000.1, 1.0, false, 1000));
+        tracker.process_trade(&create_test_event("BTCUSDT", 50000.4, 2.0, true, 1001));
+        tracker.process_trade(&create_test_event("BTCUSDT", 50001.0, 4.0, false, 1002));
+        assert_eq!(tracker.volume_at_price.len(), 3);
+
+        let levels = tracker.levels_at_granularity(1.0);
+        assert_eq!(levels.len(), 2);
+        assert_eq!(levels[&50000].total_volume, 3.0);
+        assert_eq!(levels[&50000].delta(), -1.0);
+        assert_eq!(levels[&50001].buy_volume, 4.0);
+    }
 }
@@ -1,17 +1,17 @@
 use tokio::sync::mpsc;
 use std::collections::HashMap;
-use std::sync::Arc;
 use anyhow::Result;
 use tracing::{info, error, debug, warn};
 use tokio::time::{sleep, Duration};
 
-use crate::data::{OrderflowEvent, VolumeProfile, BigOrderflowAlert, DailyStats};
+use crate::data::{LatencyHistogram, Metrics, OrderflowEvent, Subscription, Topic, VolumeProfile, BigOrderflowAlert, DailyStats};
 
 pub struct VolumeAnalyzer {
     sender: mpsc::Sender<VolumeProfile>,
-    alert_sender: Option<mpsc::Sender<BigOrderflowAlert>>,
+    alerts: Option<Topic<BigOrderflowAlert>>,
     volume_threshold_percentage: f64,
     api_base_url: String,
+    latency: LatencyHistogram,
 }
 
 struct SymbolVolumeTracker {
@@ -26,17 +26,23 @@ impl VolumeAnalyzer {
     pub fn new(sender: mpsc::Sender<VolumeProfile>, api_base_url: String) -> Self {
         Self {
             sender,
-            alert_sender: None,
+            alerts: None,
             volume_threshold_percentage: 0.5,
             api_base_url,
+            latency: LatencyHistogram::default(),
         }
     }
 
-    pub fn set_alert_sender(&mut self, sender: mpsc::Sender<BigOrderflowAlert>) {
-        self.alert_sender = Some(sender);
+    /// Record the time spent per trade in the shared metrics registry
+    pub fn set_metrics(&mut self, metrics: &Metrics) {
+        self.latency = metrics.analyzer_latency("volume");
     }
 
-    pub async fn start_with_receiver(&mut self, orderflow_rx: Arc<tokio::sync::Mutex<mpsc::Receiver<OrderflowEvent>>>) -> Result<()> {
+    pub fn set_alert_topic(&mut self, alerts: Topic<BigOrderflowAlert>) {
+        self.alerts = Some(alerts);
+    }
+
+    pub async fn start_with_receiver(&mut self, mut orderflow: Subscription<OrderflowEvent>) -> Result<()> {
         info!("Starting volume analyzer with orderflow receiver");
 
         let mut trackers: HashMap<String, SymbolVolumeTracker> = HashMap::new();
@@ -46,10 +52,7 @@ impl VolumeAnalyzer {
         loop {
             tokio::select! {
                 // Process incoming orderflow events
-                event = async {
-                    let mut rx = orderflow_rx.lock().await;
-                    rx.recv().await
-                } => {
+                event = orderflow.recv() => {
                     if let Some(event) = event {
                         // Check if tracker exists, if not add symbol to pending init list
                         if !trackers.contains_key(&event.symbol) {
@@ -75,7 +78,9 @@ impl VolumeAnalyzer {
                         }
 
                         debug!("Volume analyzer received event for {}: price={}, qty={}", event.symbol, event.price, event.quantity);
+                        let started = std::time::Instant::now();
                         let results = self.process_orderflow_event(&event, &mut trackers);
+                        self.latency.observe(started.elapsed());
 
                         // Send volume profile updates
                         for result in results {
@@ -86,10 +91,8 @@ impl VolumeAnalyzer {
                                     }
                                 }
                                 VolumeAnalysisResult::BigOrderflowAlert(alert) => {
-                                    if let Some(alert_sender) = &self.alert_sender {
-                                        if let Err(e) = alert_sender.try_send(alert) {
-                                            debug!("Failed to send big orderflow alert: {}", e);
-                                        }
+                                    if let Some(alerts) = &self.alerts {
+                                        alerts.publish(alert).await;
                                     }
                                 }
                                 _ => {} // Handle other variants if needed
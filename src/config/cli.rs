@@ -0,0 +1,95 @@
+use std::path::PathBuf;
+use anyhow::{Result, anyhow};
+
+use crate::data::ReplaySpeed;
+
+/// Command line options
+#[derive(Debug, Clone)]
+pub struct CliArgs {
+    /// Append all live market data to this capture file
+    pub record: Option<PathBuf>,
+    /// Replay this capture file instead of connecting to the exchange
+    pub replay: Option<PathBuf>,
+    pub replay_speed: ReplaySpeed,
+    /// Run the collector without a GUI, persisting to the database until SIGTERM
+    pub headless: bool,
+    /// Open the GUI on the database of a running headless daemon instead of connecting
+    pub attach: bool,
+}
+
+impl Default for CliArgs {
+    fn default() -> Self {
+        Self {
+            record: None,
+            replay: None,
+            replay_speed: ReplaySpeed::RealTime,
+            headless: false,
+            attach: false,
+        }
+    }
+}
+
+impl CliArgs {
+    pub fn from_env() -> Result<Self> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut cli = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().ok_or_else(|| anyhow!("{} requires a value", name));
+
+            match arg.as_str() {
+                "--record" => cli.record = Some(PathBuf::from(value("--record")?)),
+                "--replay" => cli.replay = Some(PathBuf::from(value("--replay")?)),
+                "--replay-speed" => cli.replay_speed = ReplaySpeed::parse(&value("--replay-speed")?)?,
+                "--headless" => cli.headless = true,
+                "--attach" => cli.attach = true,
+                other => return Err(anyhow!("Unknown argument '{}'", other)),
+            }
+        }
+
+        if cli.record.is_some() && cli.replay.is_some() {
+            return Err(anyhow!("--record and --replay cannot be used together"));
+        }
+
+        if cli.attach && (cli.headless || cli.record.is_some() || cli.replay.is_some()) {
+            return Err(anyhow!("--attach only reads an existing database and cannot be combined with other modes"));
+        }
+
+        Ok(cli)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliArgs> {
+        CliArgs::parse(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_replay_args() {
+        let cli = parse(&["--replay", "session.cap", "--replay-speed", "max"]).unwrap();
+        assert_eq!(cli.replay, Some(PathBuf::from("session.cap")));
+        assert_eq!(cli.replay_speed, ReplaySpeed::AsFastAsPossible);
+        assert!(cli.record.is_none());
+
+        assert!(parse(&[]).unwrap().replay.is_none());
+        assert!(parse(&["--record"]).is_err());
+        assert!(parse(&["--record", "a.cap", "--replay", "b.cap"]).is_err());
+        assert!(parse(&["--verbose"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_daemon_modes() {
+        let cli = parse(&["--headless", "--record", "a.cap"]).unwrap();
+        assert!(cli.headless && !cli.attach);
+
+        assert!(parse(&["--attach"]).unwrap().attach);
+        assert!(parse(&["--attach", "--headless"]).is_err());
+    }
+}
@@ -1,5 +1,7 @@
 pub mod settings;
 pub mod app_settings;
+pub mod cli;
 
 pub use settings::Settings;
-pub use app_settings::AppSettings;
\ No newline at end of file
+pub use app_settings::AppSettings;
+pub use cli::CliArgs;
\ No newline at end of file
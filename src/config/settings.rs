@@ -7,15 +7,118 @@ pub struct Settings {
     pub database: DatabaseConfig,
     pub analysis: AnalysisConfig,
     pub gui: GuiConfig,
+    #[serde(default)]
+    pub metrics: MetricsConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BinanceConfig {
     pub websocket_base_url: String,
     pub api_base_url: String,
-    pub max_reconnect_attempts: u32,
+    /// First reconnect delay; it doubles per failed attempt up to `max_reconnect_delay_ms`
     pub reconnect_delay_ms: u64,
+    #[serde(default = "default_max_reconnect_delay_ms")]
+    pub max_reconnect_delay_ms: u64,
     pub symbols: Vec<String>,
+    /// Markets to stream: "usdm" (USDT-margined) and/or "coinm" (coin-margined inverse)
+    #[serde(default = "default_markets")]
+    pub markets: Vec<String>,
+    #[serde(default = "default_coinm_websocket_base_url")]
+    pub coinm_websocket_base_url: String,
+    #[serde(default = "default_coinm_api_base_url")]
+    pub coinm_api_base_url: String,
+    /// How often exchangeInfo is re-read to pick up listings and delistings
+    #[serde(default = "default_symbol_refresh_interval_secs")]
+    pub symbol_refresh_interval_secs: u64,
+    /// exchangeInfo trading rules are mirrored here for starts without network
+    #[serde(default = "default_symbol_cache_path")]
+    pub symbol_cache_path: String,
+    /// Client pings keep quiet connections verifiably alive and measure latency
+    #[serde(default = "default_ping_interval_secs")]
+    pub ping_interval_secs: u64,
+    /// A connection with no frame at all for this long is force-reconnected
+    #[serde(default = "default_stale_stream_timeout_secs")]
+    pub stale_stream_timeout_secs: u64,
+    /// Trade and depth connections are replaced at this age, ahead of Binance's 24h cut-off
+    #[serde(default = "default_connection_max_age_secs")]
+    pub connection_max_age_secs: u64,
+    /// Time both connections are read during a replacement
+    #[serde(default = "default_rotation_overlap_ms")]
+    pub rotation_overlap_ms: u64,
+    /// aggTrades pages fetched to fill one jump in a symbol's trade ids; 0 only counts gaps
+    #[serde(default = "default_gap_backfill_max_pages")]
+    pub gap_backfill_max_pages: usize,
+}
+
+fn default_markets() -> Vec<String> {
+    vec!["usdm".to_string()]
+}
+
+fn default_coinm_websocket_base_url() -> String {
+    "wss://dstream.binance.com".to_string()
+}
+
+fn default_coinm_api_base_url() -> String {
+    "https://dapi.binance.com".to_string()
+}
+
+fn default_symbol_refresh_interval_secs() -> u64 {
+    300
+}
+
+fn default_symbol_cache_path() -> String {
+    "symbols.json".to_string()
+}
+
+fn default_max_reconnect_delay_ms() -> u64 {
+    60_000
+}
+
+fn default_connection_max_age_secs() -> u64 {
+    23 * 3600
+}
+
+fn default_rotation_overlap_ms() -> u64 {
+    5_000
+}
+
+fn default_gap_backfill_max_pages() -> usize {
+    10
+}
+
+fn default_ping_interval_secs() -> u64 {
+    20
+}
+
+fn default_stale_stream_timeout_secs() -> u64 {
+    60
+}
+
+/// Prometheus `/metrics` endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsConfig {
+    #[serde(default = "default_metrics_enabled")]
+    pub enabled: bool,
+    /// Address the endpoint listens on; keep it on loopback unless scraped remotely
+    #[serde(default = "default_metrics_listen_address")]
+    pub listen_address: String,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_metrics_enabled(),
+            listen_address: default_metrics_listen_address(),
+        }
+    }
+}
+
+fn default_metrics_enabled() -> bool {
+    true
+}
+
+fn default_metrics_listen_address() -> String {
+    "127.0.0.1:9184".to_string()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +126,27 @@ pub struct DatabaseConfig {
     pub path: String,
     pub max_connections: u32,
     pub backup_interval_hours: u64,
+    /// Rows queued for the persistence writer before new ones are dropped
+    #[serde(default = "default_persistence_queue_capacity")]
+    pub persistence_queue_capacity: usize,
+    /// Maximum rows written per transaction
+    #[serde(default = "default_persistence_batch_size")]
+    pub persistence_batch_size: usize,
+    /// Longest a queued row waits before its batch is committed
+    #[serde(default = "default_persistence_flush_interval_ms")]
+    pub persistence_flush_interval_ms: u64,
+}
+
+fn default_persistence_queue_capacity() -> usize {
+    100_000
+}
+
+fn default_persistence_batch_size() -> usize {
+    5000
+}
+
+fn default_persistence_flush_interval_ms() -> u64 {
+    500
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +155,399 @@ pub struct AnalysisConfig {
     pub imbalance_window_seconds: u64,
     pub footprint_timeframes: Vec<String>,
     pub liquidation_size_threshold: f64,
+    /// Minutes of footprint history rebuilt at startup
+    #[serde(default = "default_backfill_minutes")]
+    pub backfill_minutes: u64,
+    /// Upper bound on aggTrades pages fetched per symbol to fill database gaps
+    #[serde(default = "default_backfill_max_rest_pages")]
+    pub backfill_max_rest_pages: usize,
+    #[serde(default = "default_open_interest_poll_interval_secs")]
+    pub open_interest_poll_interval_secs: u64,
+    /// Open interest change is measured against the reading this far back
+    #[serde(default = "default_open_interest_change_window_minutes")]
+    pub open_interest_change_window_minutes: u64,
+    /// Absolute change (%) over the window that raises an OpenInterestSpike alert
+    #[serde(default = "default_open_interest_spike_threshold_percentage")]
+    pub open_interest_spike_threshold_percentage: f64,
+    /// Hours of per-minute funding samples forming each symbol's norm
+    #[serde(default = "default_funding_norm_window_hours")]
+    pub funding_norm_window_hours: u64,
+    /// Standard deviations from the norm that make funding extreme
+    #[serde(default = "default_funding_deviation_threshold")]
+    pub funding_deviation_threshold: f64,
+    /// Funding rates below this absolute value never alert (0.0005 = 0.05%)
+    #[serde(default = "default_funding_min_alert_rate")]
+    pub funding_min_alert_rate: f64,
+    #[serde(default)]
+    pub lob: LobConfig,
+    #[serde(default)]
+    pub event_bus: EventBusConfig,
+    #[serde(default)]
+    pub book: BookAnalyticsConfig,
+    #[serde(default)]
+    pub patterns: BookPatternConfig,
+    #[serde(default)]
+    pub book_events: BookEventConfig,
+    #[serde(default)]
+    pub heatmap: HeatmapConfig,
+}
+
+/// Order book history settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LobConfig {
+    /// Depth history price step, in contract ticks
+    #[serde(default = "default_depth_tick_multiple")]
+    pub tick_multiple: u32,
+    /// Per-symbol overrides of `tick_multiple`
+    #[serde(default)]
+    pub tick_multiples: HashMap<String, u32>,
+}
+
+impl Default for LobConfig {
+    fn default() -> Self {
+        Self {
+            tick_multiple: default_depth_tick_multiple(),
+            tick_multiples: HashMap::new(),
+        }
+    }
+}
+
+/// Order book analytics computed on the live local books
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookAnalyticsConfig {
+    /// How often each synced book is analyzed
+    #[serde(default = "default_book_analytics_interval_ms")]
+    pub interval_ms: u64,
+    /// Distances from mid (%) within which bid and ask size are compared
+    #[serde(default = "default_depth_bands_pct")]
+    pub depth_bands_pct: Vec<f64>,
+    /// Market order sizes (quote notional) whose slippage is estimated
+    #[serde(default = "default_slippage_notionals")]
+    pub slippage_notionals: Vec<f64>,
+    /// A level at least this many times the median level size is a wall
+    #[serde(default = "default_wall_size_multiple")]
+    pub wall_size_multiple: f64,
+    /// Distance from mid (%) searched for walls
+    #[serde(default = "default_wall_range_pct")]
+    pub wall_range_pct: f64,
+    /// Samples of each symbol's spread kept for its statistics
+    #[serde(default = "default_spread_window")]
+    pub spread_window: usize,
+    /// At most one sample per symbol is stored per interval
+    #[serde(default = "default_book_analytics_persist_interval_ms")]
+    pub persist_interval_ms: u64,
+    /// Symbols whose samples are stored; empty stores every analyzed book
+    #[serde(default)]
+    pub persist_symbols: Vec<String>,
+    /// Stored samples older than this are deleted
+    #[serde(default = "default_book_analytics_retention_secs")]
+    pub retention_secs: u64,
+}
+
+impl Default for BookAnalyticsConfig {
+    fn default() -> Self {
+        Self {
+            interval_ms: default_book_analytics_interval_ms(),
+            depth_bands_pct: default_depth_bands_pct(),
+            slippage_notionals: default_slippage_notionals(),
+            wall_size_multiple: default_wall_size_multiple(),
+            wall_range_pct: default_wall_range_pct(),
+            spread_window: default_spread_window(),
+            persist_interval_ms: default_book_analytics_persist_interval_ms(),
+            persist_symbols: Vec::new(),
+            retention_secs: default_book_analytics_retention_secs(),
+        }
+    }
+}
+
+fn default_book_analytics_interval_ms() -> u64 {
+    1_000
+}
+
+fn default_depth_bands_pct() -> Vec<f64> {
+    vec![0.1, 0.5, 1.0]
+}
+
+fn default_slippage_notionals() -> Vec<f64> {
+    vec![10_000.0, 100_000.0, 1_000_000.0]
+}
+
+fn default_wall_size_multiple() -> f64 {
+    5.0
+}
+
+fn default_wall_range_pct() -> f64 {
+    1.0
+}
+
+fn default_spread_window() -> usize {
+    300
+}
+
+fn default_book_analytics_persist_interval_ms() -> u64 {
+    60_000
+}
+
+fn default_book_analytics_retention_secs() -> u64 {
+    86_400
+}
+
+/// Spoof and iceberg detection from depth changes and trade prints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookPatternConfig {
+    /// Smallest order (quote notional) watched for spoofing, and smallest volume
+    /// executed against a refilling level that makes it an iceberg
+    #[serde(default = "default_pattern_min_notional")]
+    pub min_notional: f64,
+    /// A large order pulled within this distance from mid (%) counts as a spoof cycle
+    #[serde(default = "default_pattern_approach_pct")]
+    pub approach_pct: f64,
+    /// Pulled orders that saw more than this fraction of their size traded were fills
+    #[serde(default = "default_pattern_max_spoof_fill_ratio")]
+    pub max_spoof_fill_ratio: f64,
+    /// Spoof cycles at one level that raise an alert
+    #[serde(default = "default_pattern_min_spoof_cycles")]
+    pub min_spoof_cycles: usize,
+    /// Refills at one level that raise an iceberg alert
+    #[serde(default = "default_pattern_min_iceberg_refills")]
+    pub min_iceberg_refills: usize,
+    /// Cycles and refills older than this are forgotten
+    #[serde(default = "default_pattern_window_secs")]
+    pub window_secs: u64,
+}
+
+impl Default for BookPatternConfig {
+    fn default() -> Self {
+        Self {
+            min_notional: default_pattern_min_notional(),
+            approach_pct: default_pattern_approach_pct(),
+            max_spoof_fill_ratio: default_pattern_max_spoof_fill_ratio(),
+            min_spoof_cycles: default_pattern_min_spoof_cycles(),
+            min_iceberg_refills: default_pattern_min_iceberg_refills(),
+            window_secs: default_pattern_window_secs(),
+        }
+    }
+}
+
+fn default_pattern_min_notional() -> f64 {
+    250_000.0
+}
+
+fn default_pattern_approach_pct() -> f64 {
+    0.2
+}
+
+fn default_pattern_max_spoof_fill_ratio() -> f64 {
+    0.1
+}
+
+fn default_pattern_min_spoof_cycles() -> usize {
+    3
+}
+
+fn default_pattern_min_iceberg_refills() -> usize {
+    3
+}
+
+fn default_pattern_window_secs() -> u64 {
+    300
+}
+
+/// Classification of level changes into adds, cancels and executions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookEventConfig {
+    /// Span of the per-symbol buckets the classified events are summed into
+    #[serde(default = "default_book_event_bucket_ms")]
+    pub bucket_ms: u64,
+    /// Trades printed this long before a size decrease can account for it
+    #[serde(default = "default_book_event_match_window_ms")]
+    pub match_window_ms: u64,
+}
+
+impl Default for BookEventConfig {
+    fn default() -> Self {
+        Self {
+            bucket_ms: default_book_event_bucket_ms(),
+            match_window_ms: default_book_event_match_window_ms(),
+        }
+    }
+}
+
+fn default_book_event_bucket_ms() -> u64 {
+    1_000
+}
+
+fn default_book_event_match_window_ms() -> u64 {
+    500
+}
+
+/// Downsampled, persisted order book heatmap history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeatmapConfig {
+    #[serde(default = "default_heatmap_enabled")]
+    pub enabled: bool,
+    /// Column width and retention of each history tier
+    #[serde(default = "default_heatmap_tiers")]
+    pub tiers: Vec<HeatmapTierConfig>,
+    /// Symbols recorded from startup; others are recorded once opened in the footprint panel
+    #[serde(default = "default_heatmap_symbols")]
+    pub symbols: Vec<String>,
+    /// Full-depth snapshots for a few focus symbols
+    #[serde(default)]
+    pub wide: WideHeatmapConfig,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeatmapTierConfig {
+    /// Snapshots within this span are averaged into one column
+    pub resolution_ms: u64,
+    /// Columns older than this are dropped, in memory and on disk
+    pub retention_secs: u64,
+}
+
+impl Default for HeatmapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_heatmap_enabled(),
+            tiers: default_heatmap_tiers(),
+            symbols: default_heatmap_symbols(),
+            wide: WideHeatmapConfig::default(),
+        }
+    }
+}
+
+fn default_heatmap_enabled() -> bool {
+    true
+}
+
+fn default_heatmap_tiers() -> Vec<HeatmapTierConfig> {
+    vec![
+        HeatmapTierConfig { resolution_ms: 1_000, retention_secs: 3_600 },
+        HeatmapTierConfig { resolution_ms: 10_000, retention_secs: 86_400 },
+    ]
+}
+
+fn default_heatmap_symbols() -> Vec<String> {
+    vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()]
+}
+
+/// Wide-range heatmap built from deep snapshots of the full local book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WideHeatmapConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Focus symbols whose books are seeded and published at full depth
+    #[serde(default = "default_wide_heatmap_symbols")]
+    pub symbols: Vec<String>,
+    /// Levels per side in each snapshot (the REST seed allows up to 1000)
+    #[serde(default = "default_wide_heatmap_levels")]
+    pub levels: usize,
+    #[serde(default = "default_wide_heatmap_interval_ms")]
+    pub interval_ms: u64,
+    /// How much snapshot history the footprint panel keeps per symbol
+    #[serde(default = "default_wide_heatmap_history_secs")]
+    pub history_secs: u64,
+}
+
+impl Default for WideHeatmapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            symbols: default_wide_heatmap_symbols(),
+            levels: default_wide_heatmap_levels(),
+            interval_ms: default_wide_heatmap_interval_ms(),
+            history_secs: default_wide_heatmap_history_secs(),
+        }
+    }
+}
+
+fn default_wide_heatmap_symbols() -> Vec<String> {
+    vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()]
+}
+
+fn default_wide_heatmap_levels() -> usize {
+    1_000
+}
+
+fn default_wide_heatmap_interval_ms() -> u64 {
+    250
+}
+
+fn default_wide_heatmap_history_secs() -> u64 {
+    900
+}
+
+/// What the event bus does with an event for a subscriber whose queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverflowPolicy {
+    /// Skip the event for that subscriber and count it as dropped
+    Drop,
+    /// Wait for room, slowing the publisher down
+    Block,
+}
+
+/// Event bus settings for the analyzers; the GUI and daemon sinks always drop
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventBusConfig {
+    /// Events queued per subscriber
+    #[serde(default = "default_subscriber_capacity")]
+    pub subscriber_capacity: usize,
+    #[serde(default = "default_overflow_policy")]
+    pub overflow_policy: OverflowPolicy,
+}
+
+impl Default for EventBusConfig {
+    fn default() -> Self {
+        Self {
+            subscriber_capacity: default_subscriber_capacity(),
+            overflow_policy: default_overflow_policy(),
+        }
+    }
+}
+
+fn default_subscriber_capacity() -> usize {
+    10_000
+}
+
+fn default_overflow_policy() -> OverflowPolicy {
+    OverflowPolicy::Drop
+}
+
+fn default_depth_tick_multiple() -> u32 {
+    10
+}
+
+fn default_backfill_minutes() -> u64 {
+    180
+}
+
+fn default_backfill_max_rest_pages() -> usize {
+    30
+}
+
+fn default_open_interest_poll_interval_secs() -> u64 {
+    30
+}
+
+fn default_open_interest_change_window_minutes() -> u64 {
+    5
+}
+
+fn default_open_interest_spike_threshold_percentage() -> f64 {
+    2.0
+}
+
+fn default_funding_norm_window_hours() -> u64 {
+    24
+}
+
+fn default_funding_deviation_threshold() -> f64 {
+    3.0
+}
+
+fn default_funding_min_alert_rate() -> f64 {
+    0.0005
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,8 +572,8 @@ impl Default for Settings {
             binance: BinanceConfig {
                 websocket_base_url: "wss://fstream.binance.com".to_string(),
                 api_base_url: "https://fapi.binance.com".to_string(),
-                max_reconnect_attempts: 10,
                 reconnect_delay_ms: 5000,
+                max_reconnect_delay_ms: default_max_reconnect_delay_ms(),
                 symbols: vec![
                     "BTCUSDT".to_string(),
                     "ETHUSDT".to_string(),
@@ -69,17 +586,44 @@ impl Default for Settings {
                     "EOSUSDT".to_string(),
                     "TRXUSDT".to_string(),
                 ],
+                markets: default_markets(),
+                coinm_websocket_base_url: default_coinm_websocket_base_url(),
+                coinm_api_base_url: default_coinm_api_base_url(),
+                symbol_refresh_interval_secs: default_symbol_refresh_interval_secs(),
+                symbol_cache_path: default_symbol_cache_path(),
+                ping_interval_secs: default_ping_interval_secs(),
+                stale_stream_timeout_secs: default_stale_stream_timeout_secs(),
+                connection_max_age_secs: default_connection_max_age_secs(),
+                rotation_overlap_ms: default_rotation_overlap_ms(),
+                gap_backfill_max_pages: default_gap_backfill_max_pages(),
             },
             database: DatabaseConfig {
                 path: "data.db".to_string(),
                 max_connections: 10,
                 backup_interval_hours: 24,
+                persistence_queue_capacity: default_persistence_queue_capacity(),
+                persistence_batch_size: default_persistence_batch_size(),
+                persistence_flush_interval_ms: default_persistence_flush_interval_ms(),
             },
             analysis: AnalysisConfig {
                 volume_threshold_percentage: 0.5,
                 imbalance_window_seconds: 60,
                 footprint_timeframes: vec!["1m".to_string(), "5m".to_string(), "15m".to_string()],
                 liquidation_size_threshold: 100000.0,
+                backfill_minutes: default_backfill_minutes(),
+                backfill_max_rest_pages: default_backfill_max_rest_pages(),
+                open_interest_poll_interval_secs: default_open_interest_poll_interval_secs(),
+                open_interest_change_window_minutes: default_open_interest_change_window_minutes(),
+                open_interest_spike_threshold_percentage: default_open_interest_spike_threshold_percentage(),
+                funding_norm_window_hours: default_funding_norm_window_hours(),
+                funding_deviation_threshold: default_funding_deviation_threshold(),
+                funding_min_alert_rate: default_funding_min_alert_rate(),
+                lob: LobConfig::default(),
+                event_bus: EventBusConfig::default(),
+                book: BookAnalyticsConfig::default(),
+                patterns: BookPatternConfig::default(),
+                book_events: BookEventConfig::default(),
+                heatmap: HeatmapConfig::default(),
             },
             gui: GuiConfig {
                 refresh_rate_ms: 16, // 60fps
@@ -92,6 +636,7 @@ impl Default for Settings {
                     neutral_color: "#ffaa00".to_string(),
                 },
             },
+            metrics: MetricsConfig::default(),
         }
     }
 }
@@ -112,37 +657,4 @@ impl Settings {
             }
         }
     }
-
-    pub async fn get_active_symbols(&self) -> anyhow::Result<Vec<String>> {
-        // Fetch active USDT perpetual symbols from Binance API
-        let client = reqwest::Client::new();
-        let url = format!("{}/fapi/v1/exchangeInfo", self.binance.api_base_url);
-        
-        let response: serde_json::Value = client
-            .get(&url)
-            .send()
-            .await?
-            .json()
-            .await?;
-
-        let mut symbols = Vec::new();
-        if let Some(symbols_array) = response["symbols"].as_array() {
-            for symbol in symbols_array {
-                if let (Some(symbol_name), Some(status), Some(contract_type), Some(quote_asset)) = (
-                    symbol["symbol"].as_str(),
-                    symbol["status"].as_str(),
-                    symbol["contractType"].as_str(),
-                    symbol["quoteAsset"].as_str(),
-                ) {
-                    if status == "TRADING" 
-                        && contract_type == "PERPETUAL" 
-                        && quote_asset == "USDT" {
-                        symbols.push(symbol_name.to_string());
-                    }
-                }
-            }
-        }
-
-        Ok(symbols)
-    }
 }
\ No newline at end of file
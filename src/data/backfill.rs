@@ -0,0 +1,448 @@
+use std::sync::Arc;
+use anyhow::Result;
+use tokio::sync::mpsc;
+use tokio::time::{sleep, Duration};
+use tracing::{debug, info, warn};
+
+use crate::config::settings::AnalysisConfig;
+use super::{DatabaseManager, MarketDataSource, OrderflowEvent, StoredFootprintCandle, FOOTPRINT_BASE_TIMEFRAME, retain_listed_symbols};
+
+/// Largest page the aggTrades endpoint serves
+pub const AGG_TRADES_PAGE_LIMIT: usize = 1000;
+/// Spacing between aggTrades requests (20 weight each)
+const AGG_TRADES_REQUEST_INTERVAL_MS: u64 = 250;
+/// Footprint candles are built from one-minute bases
+const BASE_TIMEFRAME_MS: u64 = 60_000;
+/// A symbol quiet for longer than this at the start of the window is checked over REST
+const LEADING_GAP_TOLERANCE_MS: u64 = 60_000;
+/// Newest stored trade older than this means the database stopped before startup
+const TRAILING_GAP_TOLERANCE_MS: u64 = 5_000;
+
+#[derive(Debug, Clone)]
+pub struct BackfillConfig {
+    pub lookback_minutes: u64,
+    /// aggTrades pages fetched per symbol; 0 reads the database only
+    pub max_rest_pages: usize,
+    pub request_interval_ms: u64,
+}
+
+impl From<&AnalysisConfig> for BackfillConfig {
+    fn from(config: &AnalysisConfig) -> Self {
+        Self {
+            lookback_minutes: config.backfill_minutes,
+            max_rest_pages: config.backfill_max_rest_pages,
+            request_interval_ms: AGG_TRADES_REQUEST_INTERVAL_MS,
+        }
+    }
+}
+
+/// Recent footprint history of one symbol: the stored candles, and trades
+/// covering the whole minutes they are missing
+#[derive(Debug, Clone, Default)]
+pub struct FootprintHistory {
+    pub symbol: String,
+    pub candles: Vec<StoredFootprintCandle>,
+    /// Ordered by trade id
+    pub trades: Vec<OrderflowEvent>,
+}
+
+/// Trades missing from the stored history
+#[derive(Debug, Clone, PartialEq)]
+enum TradeGap {
+    /// Nothing stored after `after_id` (or nothing at all) up to the end of the window
+    Trailing { after_id: Option<u64>, after_ms: u64, end_ms: u64 },
+    /// Ids strictly between two consecutive stored trades
+    Between { after_id: u64, before_id: u64, before_ms: u64 },
+    /// Trades inside the window older than the first stored one
+    Leading { before_id: u64, before_ms: u64 },
+}
+
+/// Rebuilds recent per-symbol footprint history. Stored footprint candles are
+/// used as they are; the minutes they miss are rebuilt from `raw_trades`, and
+/// the most recent stretch also from the REST aggTrades endpoint. Aggregate
+/// trade ids are contiguous per symbol, so a jump between consecutive stored
+/// ids marks a gap.
+///
+/// Gaps are walked backwards from their newest end. When the page budget runs
+/// out or a request fails, the history is cut at the nearest whole minute so no
+/// candle is built from partial data.
+pub struct TradeBackfill {
+    db: Arc<DatabaseManager>,
+    source: Arc<dyn MarketDataSource>,
+    client: reqwest::Client,
+    config: BackfillConfig,
+}
+
+impl TradeBackfill {
+    pub fn new(db: Arc<DatabaseManager>, source: Arc<dyn MarketDataSource>, config: BackfillConfig) -> Self {
+        Self {
+            db,
+            source,
+            client: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    /// Load each symbol in turn and hand its history to the footprint panel
+    pub async fn run(self, mut symbols: Vec<String>, sender: mpsc::Sender<FootprintHistory>) {
+        if self.config.max_rest_pages > 0 {
+            retain_listed_symbols(self.source.as_ref(), &mut symbols).await;
+        }
+
+        info!("Backfilling {} minutes of footprint history for {} symbols", self.config.lookback_minutes, symbols.len());
+
+        for symbol in symbols {
+            let now_ms = chrono::Utc::now().timestamp_millis() as u64;
+            match self.load_symbol(&symbol, now_ms).await {
+                Ok(history) if history.candles.is_empty() && history.trades.is_empty() => {}
+                Ok(history) => {
+                    if sender.send(history).await.is_err() {
+                        break;
+                    }
+                }
+                Err(e) => warn!("Footprint backfill for {} failed: {}", symbol, e),
+            }
+        }
+    }
+
+    /// Stored candles and trades for every complete minute of the lookback window before `now_ms`
+    pub async fn load_symbol(&self, symbol: &str, now_ms: u64) -> Result<FootprintHistory> {
+        let end_ms = now_ms / BASE_TIMEFRAME_MS * BASE_TIMEFRAME_MS;
+        let start_ms = end_ms.saturating_sub(self.config.lookback_minutes * BASE_TIMEFRAME_MS);
+
+        let candles = self.db.get_footprint_candles(symbol, FOOTPRINT_BASE_TIMEFRAME, start_ms, end_ms).await?;
+        let mut pages = 0;
+        let mut trades = Vec::new();
+        for (from_ms, until_ms) in missing_minutes(&candles, start_ms, end_ms) {
+            // aggTrades pages walk back from the newest trade, so only the stretch
+            // up to now is fetched; older holes come from the database alone
+            let max_pages = if until_ms == end_ms { self.config.max_rest_pages } else { 0 };
+            trades.extend(self.load_trades(symbol, from_ms, until_ms, max_pages, &mut pages).await?);
+        }
+
+        debug!(
+            "Footprint history for {}: {} stored candles, {} trades for the missing minutes, {} aggTrades pages",
+            symbol, candles.len(), trades.len(), pages
+        );
+        Ok(FootprintHistory { symbol: symbol.to_string(), candles, trades })
+    }
+
+    /// Trades for every complete minute of `[start_ms, end_ms)`, ordered by trade id
+    async fn load_trades(&self, symbol: &str, start_ms: u64, end_ms: u64, max_pages: usize, pages: &mut usize) -> Result<Vec<OrderflowEvent>> {
+        let mut trades = self.db.get_trades_between(symbol, start_ms, end_ms).await?;
+        trades.dedup_by_key(|trade| trade.trade_id);
+        let stored = trades.len();
+
+        let mut complete_from_ms = start_ms;
+        let mut complete_until_ms = end_ms;
+        for gap in find_gaps(&trades, start_ms, end_ms) {
+            let Some(filled_from_ms) = self.fill_gap(symbol, &gap, start_ms, max_pages, pages, &mut trades).await else {
+                continue;
+            };
+            match gap {
+                // Nothing newer could be fetched: keep the stored trades up to the minute they stop in
+                TradeGap::Trailing { after_ms, end_ms, .. } if filled_from_ms == end_ms => {
+                    complete_until_ms = after_ms / BASE_TIMEFRAME_MS * BASE_TIMEFRAME_MS;
+                }
+                _ => {
+                    complete_from_ms = filled_from_ms.div_ceil(BASE_TIMEFRAME_MS) * BASE_TIMEFRAME_MS;
+                    break;
+                }
+            }
+        }
+
+        trades.sort_by_key(|trade| trade.trade_id);
+        trades.dedup_by_key(|trade| trade.trade_id);
+        trades.retain(|trade| trade.timestamp >= complete_from_ms && trade.timestamp < complete_until_ms);
+
+        debug!(
+            "Loaded {} trades for {} ({} stored, complete {}..{})",
+            trades.len(), symbol, stored, complete_from_ms, complete_until_ms
+        );
+        Ok(trades)
+    }
+
+    /// Walk `gap` backwards page by page, appending trades inside the window to
+    /// `out`. Returns `None` once the gap is closed, or the timestamp above which
+    /// the history is complete when it cannot be.
+    async fn fill_gap(
+        &self,
+        symbol: &str,
+        gap: &TradeGap,
+        start_ms: u64,
+        max_pages: usize,
+        pages: &mut usize,
+        out: &mut Vec<OrderflowEvent>,
+    ) -> Option<u64> {
+        let (mut before_id, after_id, mut filled_from_ms) = match *gap {
+            TradeGap::Trailing { after_id, end_ms, .. } => (None, after_id, end_ms),
+            TradeGap::Between { after_id, before_id, before_ms } => (Some(before_id), Some(after_id), before_ms),
+            TradeGap::Leading { before_id, before_ms } => (Some(before_id), None, before_ms),
+        };
+        let lowest_id = after_id.map_or(0, |id| id + 1);
+
+        loop {
+            // The trailing gap starts from the newest trades, the others end just below a stored one
+            let (from_id, limit) = match before_id {
+                Some(before) if before <= lowest_id => return None,
+                Some(before) => {
+                    let from = before.saturating_sub(AGG_TRADES_PAGE_LIMIT as u64).max(lowest_id);
+                    (Some(from), (before - from) as usize)
+                }
+                None => (None, AGG_TRADES_PAGE_LIMIT),
+            };
+
+            if *pages >= max_pages {
+                return Some(filled_from_ms);
+            }
+            if *pages > 0 {
+                sleep(Duration::from_millis(self.config.request_interval_ms)).await;
+            }
+            *pages += 1;
+
+            let page = match self.fetch_page(symbol, from_id, limit).await {
+                Ok(page) => page,
+                Err(e) => {
+                    warn!("aggTrades backfill for {} failed: {}", symbol, e);
+                    return Some(filled_from_ms);
+                }
+            };
+
+            let page: Vec<_> = page
+                .into_iter()
+                .filter(|trade| trade.trade_id >= lowest_id && before_id.is_none_or(|before| trade.trade_id < before))
+                .collect();
+            let Some(oldest) = page.first() else {
+                // Nothing older exists
+                return None;
+            };
+            let (oldest_id, oldest_ms) = (oldest.trade_id, oldest.timestamp);
+
+            out.extend(page.into_iter().filter(|trade| trade.timestamp >= start_ms));
+            if oldest_ms < start_ms {
+                return None;
+            }
+            filled_from_ms = oldest_ms;
+            before_id = Some(oldest_id);
+        }
+    }
+
+    async fn fetch_page(&self, symbol: &str, from_id: Option<u64>, limit: usize) -> Result<Vec<OrderflowEvent>> {
+        let url = self.source.agg_trades_url(symbol, from_id, limit);
+        let body = self.client.get(&url).send().await?.error_for_status()?.text().await?;
+        self.source.parse_agg_trades(symbol, &body)
+    }
+}
+
+/// Stretches of `[start_ms, end_ms)` no stored candle covers, oldest first
+fn missing_minutes(candles: &[StoredFootprintCandle], start_ms: u64, end_ms: u64) -> Vec<(u64, u64)> {
+    let mut missing = Vec::new();
+    let mut covered_until = start_ms;
+    for candle in candles {
+        if candle.open_time > covered_until {
+            missing.push((covered_until, candle.open_time));
+        }
+        covered_until = covered_until.max(candle.open_time + BASE_TIMEFRAME_MS);
+    }
+    if covered_until < end_ms {
+        missing.push((covered_until, end_ms));
+    }
+    missing
+}
+
+/// Gaps in `trades` (sorted by id, deduplicated), newest first
+fn find_gaps(trades: &[OrderflowEvent], start_ms: u64, end_ms: u64) -> Vec<TradeGap> {
+    let (Some(first), Some(last)) = (trades.first(), trades.last()) else {
+        return vec![TradeGap::Trailing { after_id: None, after_ms: start_ms, end_ms }];
+    };
+
+    let mut gaps = Vec::new();
+    if end_ms.saturating_sub(last.timestamp) > TRAILING_GAP_TOLERANCE_MS {
+        gaps.push(TradeGap::Trailing { after_id: Some(last.trade_id), after_ms: last.timestamp, end_ms });
+    }
+
+    for pair in trades.windows(2).rev() {
+        if pair[1].trade_id > pair[0].trade_id + 1 {
+            gaps.push(TradeGap::Between {
+                after_id: pair[0].trade_id,
+                before_id: pair[1].trade_id,
+                before_ms: pair[1].timestamp,
+            });
+        }
+    }
+
+    if first.timestamp.saturating_sub(start_ms) > LEADING_GAP_TOLERANCE_MS {
+        gaps.push(TradeGap::Leading { before_id: first.trade_id, before_ms: first.timestamp });
+    }
+
+    gaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::BinanceUsdmSource;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Minute-aligned start of the synthetic history
+    const BASE_MS: u64 = 1_700_000_040_000;
+
+    /// One trade every `spacing_ms`, ids 0..count
+    fn history(count: u64, spacing_ms: u64) -> Vec<OrderflowEvent> {
+        (0..count)
+            .map(|id| OrderflowEvent {
+                symbol: "BTCUSDT".to_string(),
+                timestamp: BASE_MS + id * spacing_ms,
+                price: 100.0 + (id % 7) as f64 * 0.1,
+                quantity: 1.0 + (id % 3) as f64,
+                is_buyer_maker: id % 2 == 0,
+                trade_id: id,
+            })
+            .collect()
+    }
+
+    /// Minimal HTTP stand-in for the aggTrades endpoint over a fixed history
+    async fn serve_agg_trades(history: Vec<OrderflowEvent>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let hits_clone = hits.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 2048];
+                let n = socket.read(&mut buf).await.unwrap_or(0);
+                hits_clone.fetch_add(1, Ordering::SeqCst);
+
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let path = request.split_whitespace().nth(1).unwrap_or_default();
+                let query: HashMap<_, _> = path
+                    .split_once('?')
+                    .map(|(_, query)| query.split('&').filter_map(|pair| pair.split_once('=')).collect())
+                    .unwrap_or_default();
+                let limit: usize = query.get("limit").and_then(|v| v.parse().ok()).unwrap_or(500);
+
+                let page: Vec<&OrderflowEvent> = match query.get("fromId").and_then(|v| v.parse::<u64>().ok()) {
+                    Some(from_id) => history.iter().filter(|t| t.trade_id >= from_id).take(limit).collect(),
+                    None => history.iter().skip(history.len().saturating_sub(limit)).collect(),
+                };
+                let body = serde_json::to_string(&page.iter().map(|t| serde_json::json!({
+                    "a": t.trade_id, "p": t.price.to_string(), "q": t.quantity.to_string(),
+                    "f": t.trade_id, "l": t.trade_id, "T": t.timestamp, "m": t.is_buyer_maker,
+                })).collect::<Vec<_>>()).unwrap();
+
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+
+        (format!("http://{}", addr), hits)
+    }
+
+    async fn backfill(api_base_url: &str, stored: &[OrderflowEvent], max_rest_pages: usize) -> TradeBackfill {
+        let db = DatabaseManager::new(":memory:").await.unwrap();
+        db.initialize_schema().await.unwrap();
+        db.insert_orderflow_batch(stored).await.unwrap();
+
+        let source = Arc::new(BinanceUsdmSource::new("ws://127.0.0.1:1".to_string(), api_base_url.to_string()));
+        let config = BackfillConfig { lookback_minutes: 10, max_rest_pages, request_interval_ms: 0 };
+        TradeBackfill::new(db, source, config)
+    }
+
+    fn ids(trades: &[OrderflowEvent]) -> Vec<u64> {
+        trades.iter().map(|trade| trade.trade_id).collect()
+    }
+
+    #[tokio::test]
+    async fn test_complete_database_needs_no_requests() {
+        let trades = history(600, 1000);
+        let (url, hits) = serve_agg_trades(trades.clone()).await;
+
+        // Stored twice, as after a replay into the same database
+        let mut stored = trades.clone();
+        stored.extend(trades.iter().take(10).cloned());
+        let loaded = backfill(&url, &stored, 10).await.load_symbol("BTCUSDT", BASE_MS + 600_500).await.unwrap().trades;
+
+        assert_eq!(ids(&loaded), (0..600).collect::<Vec<_>>());
+        assert_eq!(hits.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_fills_database_gaps_over_rest() {
+        let trades = history(600, 1000);
+        let (url, hits) = serve_agg_trades(trades.clone()).await;
+
+        // Missing: ids 200..300 while the daemon was down, and the last ten seconds
+        let stored: Vec<_> = trades.iter().filter(|t| t.trade_id < 200 || (300..590).contains(&t.trade_id)).cloned().collect();
+        let loaded = backfill(&url, &stored, 10).await.load_symbol("BTCUSDT", BASE_MS + 600_500).await.unwrap().trades;
+
+        assert_eq!(ids(&loaded), (0..600).collect::<Vec<_>>());
+        assert_eq!(loaded[250].price, trades[250].price);
+        assert_eq!(loaded[250].is_buyer_maker, trades[250].is_buyer_maker);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_page_budget_keeps_only_complete_minutes() {
+        // Five trades a second: 10 minutes need three full pages
+        let trades = history(3000, 200);
+        let (url, hits) = serve_agg_trades(trades).await;
+
+        let loaded = backfill(&url, &[], 2).await.load_symbol("BTCUSDT", BASE_MS + 600_000).await.unwrap().trades;
+
+        // Two pages reach back to id 1000 at 3m20s; the partial fourth minute is dropped
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        assert_eq!(loaded.first().unwrap().timestamp, BASE_MS + 240_000);
+        assert_eq!(ids(&loaded), (1200..3000).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_stored_candles_are_not_rebuilt() {
+        let trades = history(600, 1000);
+        let (url, hits) = serve_agg_trades(trades.clone()).await;
+
+        // Candles for minutes 1-2 and 5-8 were saved; trades missing for minute 3 and the last ten seconds
+        let stored: Vec<_> = trades.iter().filter(|t| !(180..240).contains(&t.trade_id) && t.trade_id < 590).cloned().collect();
+        let backfill = backfill(&url, &stored, 10).await;
+        let candle = |minute: u64| StoredFootprintCandle {
+            symbol: "BTCUSDT".to_string(),
+            timeframe: FOOTPRINT_BASE_TIMEFRAME.to_string(),
+            open_time: BASE_MS + minute * BASE_TIMEFRAME_MS,
+            tick_size: 0.1,
+            open: 100.0,
+            high: 100.6,
+            low: 100.0,
+            close: 100.3,
+            levels: vec![],
+        };
+        let candles: Vec<_> = [1, 2, 5, 6, 7, 8].into_iter().map(candle).collect();
+        backfill.db.insert_footprint_candles(&candles).await.unwrap();
+
+        let loaded = backfill.load_symbol("BTCUSDT", BASE_MS + 600_500).await.unwrap();
+        assert_eq!(loaded.candles, candles);
+        // Minutes 0 and 4 come from the database and minute 3 has nothing to rebuild
+        // from; only the gap in the newest minute is fetched
+        let expected: Vec<u64> = (0..60).chain(240..300).chain(540..600).collect();
+        assert_eq!(ids(&loaded.trades), expected);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_rest_keeps_whole_stored_minutes() {
+        let trades = history(600, 1000);
+        let stored: Vec<_> = trades.iter().filter(|t| t.trade_id < 200 || (300..590).contains(&t.trade_id)).cloned().collect();
+
+        let loaded = backfill("http://127.0.0.1:1", &stored, 10).await.load_symbol("BTCUSDT", BASE_MS + 600_500).await.unwrap().trades;
+
+        // Minutes 5 to 8 are whole: older ones sit behind the gap, the ninth stops early
+        assert_eq!(ids(&loaded), (300..540).collect::<Vec<_>>());
+    }
+}
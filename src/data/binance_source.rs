@@ -0,0 +1,734 @@
+use serde::Deserialize;
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use super::binance_trades::{BinanceAggTrade, BinanceAggTradeMessage, decode_agg_trade};
+use super::{OrderflowEvent, LiquidationEvent, DepthUpdate, DepthSnapshotResponse, OpenInterestData, FundingRateData, MarketDataSource, MarketEvent, SymbolInfo, parse_symbol_infos};
+
+/// Entry of a REST aggTrades response: the stream fields without the symbol
+#[derive(Debug, Deserialize)]
+struct BinanceRestAggTrade {
+    #[serde(rename = "a")]
+    aggregate_trade_id: u64,
+    #[serde(rename = "p")]
+    price: String,
+    #[serde(rename = "q")]
+    quantity: String,
+    #[serde(rename = "T")]
+    trade_time: u64,
+    #[serde(rename = "m")]
+    is_buyer_maker: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct BinanceLiquidationMessage {
+    #[serde(rename = "E")]
+    event_time: u64,
+    #[serde(rename = "o")]
+    order: LiquidationOrder,
+}
+
+#[derive(Debug, Deserialize)]
+struct LiquidationOrder {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "S")]
+    side: String,
+    #[serde(rename = "q")]
+    original_quantity: String,
+    #[serde(rename = "p")]
+    price: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BinanceMarkPriceMessage {
+    #[serde(rename = "E")]
+    event_time: u64,
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "p")]
+    mark_price: String,
+    #[serde(rename = "i")]
+    index_price: String,
+    /// Empty for contracts without funding
+    #[serde(rename = "r")]
+    funding_rate: String,
+    #[serde(rename = "T")]
+    next_funding_time: u64,
+}
+
+/// Decoded Binance futures frame, before market-specific quantity handling
+enum BinanceFrame {
+    AggTrade(BinanceAggTrade),
+    Depth(DepthUpdate),
+    ForceOrder(BinanceLiquidationMessage),
+    MarkPrices(Vec<BinanceMarkPriceMessage>),
+    Control,
+    Other,
+}
+
+/// Decode the frame formats shared by the USD-M and COIN-M futures streams.
+/// Trades make up most of the traffic and are deserialized in place; everything
+/// else, and any trade the fast path rejects, goes through `serde_json::Value`.
+fn decode_binance_frame(text: &str) -> Result<BinanceFrame> {
+    if let Some(trade) = decode_agg_trade(text) {
+        return Ok(BinanceFrame::AggTrade(trade?));
+    }
+    decode_value_frame(serde_json::from_str(text)?)
+}
+
+/// Decode a frame through a generic JSON tree, unwrapping combined-stream envelopes
+fn decode_value_frame(mut value: serde_json::Value) -> Result<BinanceFrame> {
+    if value.get("stream").is_some() {
+        if let Some(data) = value.get_mut("data") {
+            return decode_value_frame(data.take());
+        }
+    }
+
+    // All-market streams such as !markPrice@arr deliver a list of events
+    if let serde_json::Value::Array(events) = value {
+        let mark_prices = events
+            .into_iter()
+            .filter(|event| event["e"] == "markPriceUpdate")
+            .map(serde_json::from_value)
+            .collect::<Result<Vec<_>, _>>()?;
+        return Ok(BinanceFrame::MarkPrices(mark_prices));
+    }
+
+    // Subscription acks look like {"result":null,"id":1}
+    if value.get("id").is_some() && value.get("e").is_none() {
+        return Ok(BinanceFrame::Control);
+    }
+
+    let event_type = value["e"].as_str().unwrap_or_default().to_string();
+    match event_type.as_str() {
+        "aggTrade" => Ok(BinanceFrame::AggTrade(serde_json::from_value::<BinanceAggTradeMessage>(value)?.decode()?)),
+        "depthUpdate" => Ok(BinanceFrame::Depth(serde_json::from_value(value)?)),
+        "forceOrder" => Ok(BinanceFrame::ForceOrder(serde_json::from_value(value)?)),
+        "markPriceUpdate" => Ok(BinanceFrame::MarkPrices(vec![serde_json::from_value(value)?])),
+        _ => Ok(BinanceFrame::Other),
+    }
+}
+
+/// Mark price events to funding samples; prices are quoted the same way on both markets.
+/// Entries without a funding rate are skipped.
+fn decode_mark_prices(updates: Vec<BinanceMarkPriceMessage>) -> Result<Vec<FundingRateData>> {
+    updates
+        .into_iter()
+        .filter(|update| !update.funding_rate.is_empty())
+        .map(|update| {
+            let mut reading = FundingRateData::new(update.symbol, update.event_time, update.funding_rate.parse()?);
+            reading.mark_price = update.mark_price.parse()?;
+            reading.index_price = update.index_price.parse()?;
+            reading.funding_interval = update.next_funding_time.saturating_sub(update.event_time) / 1000;
+            Ok(reading)
+        })
+        .collect()
+}
+
+/// Binance returns decimals as strings and some timestamps as either strings or numbers
+fn json_f64(value: &serde_json::Value) -> Option<f64> {
+    value.as_str().and_then(|s| s.parse().ok()).or_else(|| value.as_f64())
+}
+
+fn json_u64(value: &serde_json::Value) -> Option<u64> {
+    value.as_str().and_then(|s| s.parse().ok()).or_else(|| value.as_u64())
+}
+
+/// Decode `{"symbol":..,"openInterest":"..","time":..}`, shared by both markets
+fn decode_open_interest(symbol: &str, body: &str) -> Result<OpenInterestData> {
+    let value: serde_json::Value = serde_json::from_str(body)?;
+    let open_interest = json_f64(&value["openInterest"])
+        .ok_or_else(|| anyhow!("openInterest missing for {}: {}", symbol, body))?;
+    let time = json_u64(&value["time"]).unwrap_or_default();
+    Ok(OpenInterestData::new(symbol.to_string(), time, open_interest))
+}
+
+/// Decode an openInterestHist array, shared by both markets
+fn decode_open_interest_history(symbol: &str, body: &str) -> Result<Vec<OpenInterestData>> {
+    let value: serde_json::Value = serde_json::from_str(body)?;
+    let entries = value.as_array().ok_or_else(|| anyhow!("openInterestHist for {} is not a list: {}", symbol, body))?;
+
+    let mut history = entries
+        .iter()
+        .map(|entry| {
+            match (json_f64(&entry["sumOpenInterest"]), json_u64(&entry["timestamp"])) {
+                (Some(open_interest), Some(timestamp)) => Ok(OpenInterestData::new(symbol.to_string(), timestamp, open_interest)),
+                _ => Err(anyhow!("Malformed openInterestHist entry for {}: {}", symbol, entry)),
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    history.sort_by_key(|reading| reading.timestamp);
+    Ok(history)
+}
+
+/// `/stream` carries every stream subscribed on the connection, each event wrapped
+/// with the name of its stream
+fn combined_stream_url(websocket_base_url: &str, streams: &[String]) -> String {
+    if streams.is_empty() {
+        format!("{}/stream", websocket_base_url)
+    } else {
+        format!("{}/stream?streams={}", websocket_base_url, streams.join("/"))
+    }
+}
+
+fn agg_trades_query(symbol: &str, from_id: Option<u64>, limit: usize) -> String {
+    match from_id {
+        Some(from_id) => format!("symbol={}&fromId={}&limit={}", symbol, from_id, limit),
+        None => format!("symbol={}&limit={}", symbol, limit),
+    }
+}
+
+/// Binance USD-M perpetual futures (fstream / fapi)
+pub struct BinanceUsdmSource {
+    websocket_base_url: String,
+    api_base_url: String,
+}
+
+impl BinanceUsdmSource {
+    pub fn new(websocket_base_url: String, api_base_url: String) -> Self {
+        Self { websocket_base_url, api_base_url }
+    }
+}
+
+impl MarketDataSource for BinanceUsdmSource {
+    fn name(&self) -> &str {
+        "binance-usdm"
+    }
+
+    fn exchange_info_url(&self) -> String {
+        format!("{}/fapi/v1/exchangeInfo", self.api_base_url)
+    }
+
+    fn depth_snapshot_url(&self, symbol: &str, limit: usize) -> String {
+        format!("{}/fapi/v1/depth?symbol={}&limit={}", self.api_base_url, symbol, limit)
+    }
+
+    fn agg_trades_url(&self, symbol: &str, from_id: Option<u64>, limit: usize) -> String {
+        format!("{}/fapi/v1/aggTrades?{}", self.api_base_url, agg_trades_query(symbol, from_id, limit))
+    }
+
+    fn open_interest_url(&self, symbol: &str) -> String {
+        format!("{}/fapi/v1/openInterest?symbol={}", self.api_base_url, symbol)
+    }
+
+    fn open_interest_history_url(&self, symbol: &str, period: &str, limit: usize) -> String {
+        format!("{}/futures/data/openInterestHist?symbol={}&period={}&limit={}", self.api_base_url, symbol, period, limit)
+    }
+
+    fn combined_stream_url(&self, streams: &[String]) -> String {
+        combined_stream_url(&self.websocket_base_url, streams)
+    }
+
+    fn liquidation_stream_url(&self) -> String {
+        format!("{}/ws/!forceOrder@arr", self.websocket_base_url)
+    }
+
+    fn mark_price_stream_url(&self) -> Option<String> {
+        Some(format!("{}/ws/!markPrice@arr@1s", self.websocket_base_url))
+    }
+
+    fn trade_stream(&self, symbol: &str) -> String {
+        format!("{}@aggTrade", symbol.to_lowercase())
+    }
+
+    fn depth_stream(&self, symbol: &str) -> String {
+        format!("{}@depth@100ms", symbol.to_lowercase())
+    }
+
+    fn parse_symbols(&self, exchange_info: &serde_json::Value) -> Vec<String> {
+        let mut symbols = Vec::new();
+        if let Some(symbols_array) = exchange_info["symbols"].as_array() {
+            for symbol in symbols_array {
+                if let (Some(symbol_name), Some(status), Some(contract_type), Some(quote_asset)) = (
+                    symbol["symbol"].as_str(),
+                    symbol["status"].as_str(),
+                    symbol["contractType"].as_str(),
+                    symbol["quoteAsset"].as_str(),
+                ) {
+                    if status == "TRADING"
+                        && contract_type == "PERPETUAL"
+                        && quote_asset == "USDT" {
+                        symbols.push(symbol_name.to_string());
+                    }
+                }
+            }
+        }
+        symbols
+    }
+
+    fn parse_symbol_info(&self, exchange_info: &serde_json::Value) -> Vec<SymbolInfo> {
+        parse_symbol_infos(self.name(), exchange_info)
+    }
+
+    fn parse_frame(&self, text: &str) -> Result<Option<MarketEvent>> {
+        match decode_binance_frame(text)? {
+            BinanceFrame::AggTrade(trade) => Ok(Some(MarketEvent::Trade(OrderflowEvent {
+                symbol: trade.symbol,
+                timestamp: trade.trade_time,
+                price: trade.price,
+                quantity: trade.quantity,
+                is_buyer_maker: trade.is_buyer_maker,
+                trade_id: trade.aggregate_trade_id,
+            }))),
+            BinanceFrame::Depth(update) => Ok(Some(MarketEvent::Depth(update))),
+            BinanceFrame::ForceOrder(liquidation) => {
+                let order = liquidation.order;
+                let price: f64 = order.price.parse()?;
+                let quantity: f64 = order.original_quantity.parse()?;
+                Ok(Some(MarketEvent::Liquidation(LiquidationEvent {
+                    symbol: order.symbol,
+                    timestamp: liquidation.event_time,
+                    side: order.side,
+                    price,
+                    quantity,
+                    is_forced: true,
+                    notional_value: price * quantity,
+                })))
+            }
+            BinanceFrame::MarkPrices(updates) => Ok(Some(MarketEvent::MarkPrices(decode_mark_prices(updates)?))),
+            BinanceFrame::Control | BinanceFrame::Other => Ok(None),
+        }
+    }
+
+    fn parse_depth_snapshot(&self, _symbol: &str, body: &str) -> Result<DepthSnapshotResponse> {
+        Ok(serde_json::from_str(body)?)
+    }
+
+    fn parse_agg_trades(&self, symbol: &str, body: &str) -> Result<Vec<OrderflowEvent>> {
+        let trades: Vec<BinanceRestAggTrade> = serde_json::from_str(body)?;
+        trades
+            .into_iter()
+            .map(|trade| Ok(OrderflowEvent {
+                symbol: symbol.to_string(),
+                timestamp: trade.trade_time,
+                price: trade.price.parse()?,
+                quantity: trade.quantity.parse()?,
+                is_buyer_maker: trade.is_buyer_maker,
+                trade_id: trade.aggregate_trade_id,
+            }))
+            .collect()
+    }
+
+    fn parse_open_interest(&self, symbol: &str, body: &str) -> Result<OpenInterestData> {
+        decode_open_interest(symbol, body)
+    }
+
+    fn parse_open_interest_history(&self, symbol: &str, body: &str) -> Result<Vec<OpenInterestData>> {
+        decode_open_interest_history(symbol, body)
+    }
+}
+
+/// Binance COIN-M (inverse) perpetual futures (dstream / dapi)
+///
+/// Quantities on this market are contract counts worth a fixed USD amount each.
+/// They are converted to base-asset units so inverse contracts share the same
+/// volume and notional math as USD-M symbols.
+pub struct BinanceCoinmSource {
+    websocket_base_url: String,
+    api_base_url: String,
+    contract_sizes: RwLock<HashMap<String, f64>>,
+}
+
+impl BinanceCoinmSource {
+    pub fn new(websocket_base_url: String, api_base_url: String) -> Self {
+        Self {
+            websocket_base_url,
+            api_base_url,
+            contract_sizes: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// USD value of one contract; Binance uses 100 for BTC and 10 for everything else
+    pub fn contract_size(&self, symbol: &str) -> f64 {
+        if let Some(size) = self.contract_sizes.read().unwrap().get(symbol) {
+            return *size;
+        }
+        if symbol.starts_with("BTCUSD") { 100.0 } else { 10.0 }
+    }
+
+    fn to_base_quantity(&self, symbol: &str, price: f64, contracts: f64) -> f64 {
+        if price > 0.0 {
+            contracts * self.contract_size(symbol) / price
+        } else {
+            0.0
+        }
+    }
+
+    fn normalize_levels(&self, symbol: &str, levels: &mut [(f64, f64)]) {
+        for (price, quantity) in levels.iter_mut() {
+            *quantity = self.to_base_quantity(symbol, *price, *quantity);
+        }
+    }
+}
+
+impl MarketDataSource for BinanceCoinmSource {
+    fn name(&self) -> &str {
+        "binance-coinm"
+    }
+
+    fn exchange_info_url(&self) -> String {
+        format!("{}/dapi/v1/exchangeInfo", self.api_base_url)
+    }
+
+    fn depth_snapshot_url(&self, symbol: &str, limit: usize) -> String {
+        format!("{}/dapi/v1/depth?symbol={}&limit={}", self.api_base_url, symbol, limit)
+    }
+
+    fn agg_trades_url(&self, symbol: &str, from_id: Option<u64>, limit: usize) -> String {
+        format!("{}/dapi/v1/aggTrades?{}", self.api_base_url, agg_trades_query(symbol, from_id, limit))
+    }
+
+    fn open_interest_url(&self, symbol: &str) -> String {
+        format!("{}/dapi/v1/openInterest?symbol={}", self.api_base_url, symbol)
+    }
+
+    /// History is published per pair and contract type, e.g. BTCUSD + PERPETUAL for BTCUSD_PERP
+    fn open_interest_history_url(&self, symbol: &str, period: &str, limit: usize) -> String {
+        let pair = symbol.split('_').next().unwrap_or(symbol);
+        format!(
+            "{}/futures/data/openInterestHist?pair={}&contractType=PERPETUAL&period={}&limit={}",
+            self.api_base_url, pair, period, limit
+        )
+    }
+
+    fn combined_stream_url(&self, streams: &[String]) -> String {
+        combined_stream_url(&self.websocket_base_url, streams)
+    }
+
+    fn liquidation_stream_url(&self) -> String {
+        format!("{}/ws/!forceOrder@arr", self.websocket_base_url)
+    }
+
+    /// dstream only publishes mark prices per pair, there is no all-market stream
+    fn mark_price_stream_url(&self) -> Option<String> {
+        None
+    }
+
+    fn trade_stream(&self, symbol: &str) -> String {
+        format!("{}@aggTrade", symbol.to_lowercase())
+    }
+
+    fn depth_stream(&self, symbol: &str) -> String {
+        format!("{}@depth@100ms", symbol.to_lowercase())
+    }
+
+    fn parse_symbols(&self, exchange_info: &serde_json::Value) -> Vec<String> {
+        let mut symbols = Vec::new();
+        let mut contract_sizes = self.contract_sizes.write().unwrap();
+
+        if let Some(symbols_array) = exchange_info["symbols"].as_array() {
+            for symbol in symbols_array {
+                if let (Some(symbol_name), Some(status), Some(contract_type)) = (
+                    symbol["symbol"].as_str(),
+                    symbol["contractStatus"].as_str(),
+                    symbol["contractType"].as_str(),
+                ) {
+                    if let Some(size) = symbol["contractSize"].as_f64() {
+                        contract_sizes.insert(symbol_name.to_string(), size);
+                    }
+                    if status == "TRADING" && contract_type == "PERPETUAL" {
+                        symbols.push(symbol_name.to_string());
+                    }
+                }
+            }
+        }
+        symbols
+    }
+
+    fn parse_symbol_info(&self, exchange_info: &serde_json::Value) -> Vec<SymbolInfo> {
+        parse_symbol_infos(self.name(), exchange_info)
+    }
+
+    fn parse_frame(&self, text: &str) -> Result<Option<MarketEvent>> {
+        match decode_binance_frame(text)? {
+            BinanceFrame::AggTrade(trade) => {
+                Ok(Some(MarketEvent::Trade(OrderflowEvent {
+                    quantity: self.to_base_quantity(&trade.symbol, trade.price, trade.quantity),
+                    symbol: trade.symbol,
+                    timestamp: trade.trade_time,
+                    price: trade.price,
+                    is_buyer_maker: trade.is_buyer_maker,
+                    trade_id: trade.aggregate_trade_id,
+                })))
+            }
+            BinanceFrame::Depth(mut update) => {
+                self.normalize_levels(&update.symbol, &mut update.bids);
+                self.normalize_levels(&update.symbol, &mut update.asks);
+                Ok(Some(MarketEvent::Depth(update)))
+            }
+            BinanceFrame::ForceOrder(liquidation) => {
+                let order = liquidation.order;
+                let price: f64 = order.price.parse()?;
+                let contracts: f64 = order.original_quantity.parse()?;
+                Ok(Some(MarketEvent::Liquidation(LiquidationEvent {
+                    quantity: self.to_base_quantity(&order.symbol, price, contracts),
+                    notional_value: contracts * self.contract_size(&order.symbol),
+                    symbol: order.symbol,
+                    timestamp: liquidation.event_time,
+                    side: order.side,
+                    price,
+                    is_forced: true,
+                })))
+            }
+            BinanceFrame::MarkPrices(updates) => Ok(Some(MarketEvent::MarkPrices(decode_mark_prices(updates)?))),
+            BinanceFrame::Control | BinanceFrame::Other => Ok(None),
+        }
+    }
+
+    fn parse_depth_snapshot(&self, symbol: &str, body: &str) -> Result<DepthSnapshotResponse> {
+        let mut snapshot: DepthSnapshotResponse = serde_json::from_str(body)?;
+        self.normalize_levels(symbol, &mut snapshot.bids);
+        self.normalize_levels(symbol, &mut snapshot.asks);
+        Ok(snapshot)
+    }
+
+    fn parse_agg_trades(&self, symbol: &str, body: &str) -> Result<Vec<OrderflowEvent>> {
+        let trades: Vec<BinanceRestAggTrade> = serde_json::from_str(body)?;
+        trades
+            .into_iter()
+            .map(|trade| {
+                let price: f64 = trade.price.parse()?;
+                let contracts: f64 = trade.quantity.parse()?;
+                Ok(OrderflowEvent {
+                    symbol: symbol.to_string(),
+                    timestamp: trade.trade_time,
+                    price,
+                    quantity: self.to_base_quantity(symbol, price, contracts),
+                    is_buyer_maker: trade.is_buyer_maker,
+                    trade_id: trade.aggregate_trade_id,
+                })
+            })
+            .collect()
+    }
+
+    fn parse_open_interest(&self, symbol: &str, body: &str) -> Result<OpenInterestData> {
+        decode_open_interest(symbol, body)
+    }
+
+    fn parse_open_interest_history(&self, symbol: &str, body: &str) -> Result<Vec<OpenInterestData>> {
+        decode_open_interest_history(symbol, body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USDM_AGG_TRADE: &str = r#"{"e":"aggTrade","E":1700000000100,"s":"BTCUSDT","a":5933014,"p":"37000.10","q":"0.250","f":100,"l":105,"T":1700000000099,"m":true}"#;
+    const USDM_DEPTH: &str = r#"{"e":"depthUpdate","E":1700000000200,"T":1700000000199,"s":"BTCUSDT","U":157,"u":160,"pu":149,"b":[["36999.90","1.5"]],"a":[["37000.20","0.0"]]}"#;
+    const USDM_FORCE_ORDER: &str = r#"{"e":"forceOrder","E":1700000000300,"o":{"s":"BTCUSDT","S":"SELL","o":"LIMIT","f":"IOC","q":"0.014","p":"36900.00","ap":"36950.00","X":"FILLED","l":"0.014","z":"0.014","T":1700000000299}}"#;
+    const COINM_AGG_TRADE: &str = r#"{"e":"aggTrade","E":1700000000100,"a":416690,"s":"BTCUSD_PERP","p":"40000.0","q":"8","f":1,"l":2,"T":1700000000099,"m":false}"#;
+    const COINM_DEPTH: &str = r#"{"e":"depthUpdate","E":1700000000200,"T":1700000000199,"s":"ETHUSD_PERP","ps":"ETHUSD","U":10,"u":12,"pu":9,"b":[["2000.00","40"]],"a":[]}"#;
+
+    fn usdm() -> BinanceUsdmSource {
+        BinanceUsdmSource::new("wss://fstream.binance.com".to_string(), "https://fapi.binance.com".to_string())
+    }
+
+    fn coinm() -> BinanceCoinmSource {
+        BinanceCoinmSource::new("wss://dstream.binance.com".to_string(), "https://dapi.binance.com".to_string())
+    }
+
+    #[test]
+    fn test_usdm_agg_trade() {
+        match usdm().parse_frame(USDM_AGG_TRADE).unwrap() {
+            Some(MarketEvent::Trade(trade)) => {
+                assert_eq!(trade.symbol, "BTCUSDT");
+                assert_eq!(trade.trade_id, 5933014);
+                assert_eq!(trade.price, 37000.10);
+                assert_eq!(trade.quantity, 0.25);
+                assert!(trade.is_buyer_maker);
+                assert_eq!(trade.timestamp, 1700000000099);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_combined_stream_frames() {
+        assert_eq!(
+            usdm().combined_stream_url(&["btcusdt@aggTrade".to_string(), "ethusdt@aggTrade".to_string()]),
+            "wss://fstream.binance.com/stream?streams=btcusdt@aggTrade/ethusdt@aggTrade"
+        );
+        assert_eq!(coinm().combined_stream_url(&[]), "wss://dstream.binance.com/stream");
+
+        let combined = format!(r#"{{"stream":"btcusdt@aggTrade","data":{}}}"#, USDM_AGG_TRADE);
+        match (usdm().parse_frame(&combined).unwrap(), usdm().parse_frame(USDM_AGG_TRADE).unwrap()) {
+            (Some(MarketEvent::Trade(trade)), Some(MarketEvent::Trade(raw))) => {
+                assert_eq!((trade.symbol.as_str(), trade.trade_id, trade.price, trade.quantity), ("BTCUSDT", 5933014, 37000.10, 0.25));
+                assert_eq!((trade.timestamp, trade.is_buyer_maker), (raw.timestamp, raw.is_buyer_maker));
+            }
+            other => panic!("unexpected events: {:?}", other),
+        }
+
+        // Other streams are unwrapped on the generic path; escaped strings fall back to it too
+        let depth = format!(r#"{{"stream":"ethusd_perp@depth@100ms","data":{}}}"#, COINM_DEPTH);
+        assert!(matches!(coinm().parse_frame(&depth).unwrap(), Some(MarketEvent::Depth(update)) if update.last_update_id == 12));
+        let escaped = r#"{"stream":"btcusdt@aggTrade","data":{"e":"aggTrade","s":"BTC\u0055SDT","a":1,"p":"1.5","q":"2","T":1,"m":false}}"#;
+        assert!(matches!(usdm().parse_frame(escaped).unwrap(), Some(MarketEvent::Trade(trade)) if trade.symbol == "BTCUSDT"));
+        assert!(usdm().parse_frame(r#"{"stream":"btcusdt@aggTrade","data":{"e":"aggTrade","s":"BTCUSDT","a":1,"p":"x","q":"2","T":1,"m":false}}"#).is_err());
+    }
+
+    #[test]
+    fn test_usdm_depth_and_liquidation() {
+        let source = usdm();
+        match source.parse_frame(USDM_DEPTH).unwrap() {
+            Some(MarketEvent::Depth(update)) => {
+                assert_eq!(update.prev_final_update_id, 149);
+                assert_eq!(update.bids, vec![(36999.90, 1.5)]);
+                assert_eq!(update.asks, vec![(37000.20, 0.0)]);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        match source.parse_frame(USDM_FORCE_ORDER).unwrap() {
+            Some(MarketEvent::Liquidation(liquidation)) => {
+                assert_eq!(liquidation.side, "SELL");
+                assert!((liquidation.notional_value - 516.6).abs() < 1e-9);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_control_and_malformed_frames() {
+        let source = usdm();
+        assert!(source.parse_frame(r#"{"result":null,"id":1}"#).unwrap().is_none());
+        assert!(source.parse_frame(r#"{"e":"kline","E":1,"s":"BTCUSDT"}"#).unwrap().is_none());
+        assert!(source.parse_frame("not json").is_err());
+        assert!(source.parse_frame(r#"{"e":"aggTrade","s":"BTCUSDT"}"#).is_err());
+    }
+
+    #[test]
+    fn test_coinm_converts_contracts_to_base_quantity() {
+        let source = coinm();
+        match source.parse_frame(COINM_AGG_TRADE).unwrap() {
+            Some(MarketEvent::Trade(trade)) => {
+                // 8 contracts * $100 / $40000 = 0.02 BTC
+                assert!((trade.quantity - 0.02).abs() < 1e-12);
+                assert!(!trade.is_buyer_maker);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        match source.parse_frame(COINM_DEPTH).unwrap() {
+            Some(MarketEvent::Depth(update)) => {
+                // 40 contracts * $10 / $2000 = 0.2 ETH
+                assert!((update.bids[0].1 - 0.2).abs() < 1e-12);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_rest_agg_trades() {
+        let body = r#"[{"a":26129,"p":"0.01633102","q":"4.70443515","f":27781,"l":27781,"T":1498793709153,"m":true},{"a":26130,"p":"0.01633200","q":"1.0","f":27782,"l":27783,"T":1498793709160,"m":false}]"#;
+        assert_eq!(
+            usdm().agg_trades_url("BTCUSDT", Some(26129), 1000),
+            "https://fapi.binance.com/fapi/v1/aggTrades?symbol=BTCUSDT&fromId=26129&limit=1000"
+        );
+        assert_eq!(
+            coinm().agg_trades_url("BTCUSD_PERP", None, 1),
+            "https://dapi.binance.com/dapi/v1/aggTrades?symbol=BTCUSD_PERP&limit=1"
+        );
+
+        let trades = usdm().parse_agg_trades("BTCUSDT", body).unwrap();
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].symbol, "BTCUSDT");
+        assert_eq!(trades[0].trade_id, 26129);
+        assert!(trades[0].is_buyer_maker);
+        assert_eq!(trades[1].timestamp, 1498793709160);
+
+        // 1 contract * $100 / $0.016332 per BTC
+        let inverse = coinm().parse_agg_trades("BTCUSD_PERP", body).unwrap();
+        assert!((inverse[1].quantity - 100.0 / 0.016332).abs() < 1e-6);
+        assert!(usdm().parse_agg_trades("BTCUSDT", r#"{"code":-1121}"#).is_err());
+    }
+
+    #[test]
+    fn test_open_interest() {
+        let reading = usdm().parse_open_interest("BTCUSDT", r#"{"openInterest":"10659.509","symbol":"BTCUSDT","time":1589437530011}"#).unwrap();
+        assert_eq!((reading.open_interest, reading.timestamp), (10659.509, 1589437530011));
+        assert!(usdm().parse_open_interest("BTCUSDT", r#"{"code":-1121,"msg":"Invalid symbol."}"#).is_err());
+
+        // Timestamps arrive as numbers or strings; entries are returned oldest first
+        let body = r#"[{"symbol":"BTCUSDT","sumOpenInterest":"20500.5","sumOpenInterestValue":"1","timestamp":1583128200000},
+                       {"symbol":"BTCUSDT","sumOpenInterest":"20403.637","sumOpenInterestValue":"1","timestamp":"1583127900000"}]"#;
+        let history = usdm().parse_open_interest_history("BTCUSDT", body).unwrap();
+        assert_eq!(history.iter().map(|r| r.timestamp).collect::<Vec<_>>(), vec![1583127900000, 1583128200000]);
+        assert_eq!(history[1].open_interest, 20500.5);
+
+        assert_eq!(
+            coinm().open_interest_history_url("BTCUSD_PERP", "5m", 30),
+            "https://dapi.binance.com/futures/data/openInterestHist?pair=BTCUSD&contractType=PERPETUAL&period=5m&limit=30"
+        );
+    }
+
+    #[test]
+    fn test_mark_price_frames() {
+        let frame = r#"[{"e":"markPriceUpdate","E":1700000000000,"s":"BTCUSDT","p":"37010.5","P":"37005.1","i":"37000.0","r":"0.00030000","T":1700006400000},{"e":"markPriceUpdate","E":1700000000000,"s":"BTCUSDT_240329","p":"37500.0","P":"37480.0","i":"37000.0","r":"","T":0}]"#;
+        assert_eq!(usdm().mark_price_stream_url().unwrap(), "wss://fstream.binance.com/ws/!markPrice@arr@1s");
+        assert!(coinm().mark_price_stream_url().is_none());
+
+        match usdm().parse_frame(frame).unwrap() {
+            Some(MarketEvent::MarkPrices(readings)) => {
+                // The dated future has no funding and is skipped
+                assert_eq!(readings.len(), 1);
+                let reading = &readings[0];
+                assert_eq!(reading.symbol, "BTCUSDT");
+                assert_eq!(reading.funding_rate, 0.0003);
+                assert_eq!(reading.mark_price, 37010.5);
+                assert_eq!(reading.index_price, 37000.0);
+                assert_eq!(reading.funding_interval, 6400);
+                assert_eq!(reading.next_funding_time(), 1700006400000);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        // Single-symbol streams deliver one object
+        let single = r#"{"e":"markPriceUpdate","E":1,"s":"BTCUSD_PERP","p":"1.0","P":"1.0","i":"1.0","r":"-0.0001","T":1001}"#;
+        match coinm().parse_frame(single).unwrap() {
+            Some(MarketEvent::MarkPrices(readings)) => assert_eq!(readings[0].funding_rate, -0.0001),
+            other => panic!("unexpected event: {:?}", other),
+        }
+        assert!(usdm().parse_frame(r#"{"e":"markPriceUpdate","E":1,"s":"BTCUSDT"}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_symbols() {
+        let usdm_info = serde_json::json!({"symbols": [
+            {"symbol": "BTCUSDT", "status": "TRADING", "contractType": "PERPETUAL", "quoteAsset": "USDT"},
+            {"symbol": "BTCUSDT_240628", "status": "TRADING", "contractType": "CURRENT_QUARTER", "quoteAsset": "USDT"},
+            {"symbol": "OLDUSDT", "status": "SETTLING", "contractType": "PERPETUAL", "quoteAsset": "USDT"}
+        ]});
+        assert_eq!(usdm().parse_symbols(&usdm_info), vec!["BTCUSDT".to_string()]);
+
+        let source = coinm();
+        let coinm_info = serde_json::json!({"symbols": [
+            {"symbol": "ETHUSD_PERP", "contractStatus": "TRADING", "contractType": "PERPETUAL", "contractSize": 10},
+            {"symbol": "DOGEUSD_PERP", "contractStatus": "TRADING", "contractType": "PERPETUAL", "contractSize": 1}
+        ]});
+        assert_eq!(source.parse_symbols(&coinm_info).len(), 2);
+        assert_eq!(source.contract_size("DOGEUSD_PERP"), 1.0);
+    }
+
+    #[test]
+    fn test_borrowed_trade_decoding_matches_value_path() {
+        let corpus = include_str!("../../tests/fixtures/agg_trade_frames.jsonl");
+        let decode_trade = |frame: BinanceFrame| match frame {
+            BinanceFrame::AggTrade(trade) => trade,
+            _ => panic!("not a trade"),
+        };
+
+        for frame in corpus.lines() {
+            // The same event as sent on a raw `/ws/<symbol>@aggTrade` stream
+            let raw = frame.split_once(r#""data":"#).and_then(|(_, data)| data.strip_suffix('}')).unwrap();
+            assert!(raw.starts_with(r#"{"e":"aggTrade""#));
+            for text in [frame, raw] {
+                let expected = decode_trade(decode_value_frame(serde_json::from_str(text).unwrap()).unwrap());
+                assert_eq!(decode_trade(decode_binance_frame(text).unwrap()), expected, "{}", text);
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use serde::Deserialize;
+use anyhow::Result;
+
+/// aggTrade as it appears in a generic JSON tree
+#[derive(Debug, Deserialize)]
+pub(super) struct BinanceAggTradeMessage {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "a")]
+    aggregate_trade_id: u64,
+    #[serde(rename = "p")]
+    price: String,
+    #[serde(rename = "q")]
+    quantity: String,
+    #[serde(rename = "T")]
+    trade_time: u64,
+    #[serde(rename = "m")]
+    is_buyer_maker: bool,
+}
+
+impl BinanceAggTradeMessage {
+    pub(super) fn decode(self) -> Result<BinanceAggTrade> {
+        Ok(BinanceAggTrade {
+            price: self.price.parse()?,
+            quantity: self.quantity.parse()?,
+            symbol: self.symbol,
+            aggregate_trade_id: self.aggregate_trade_id,
+            trade_time: self.trade_time,
+            is_buyer_maker: self.is_buyer_maker,
+        })
+    }
+}
+
+/// aggTrade borrowing its strings from the frame, so nothing is copied before
+/// the decimals are parsed. Binance never escapes these fields.
+#[derive(Debug, Deserialize)]
+struct BinanceAggTradeRef<'a> {
+    #[serde(rename = "s")]
+    symbol: &'a str,
+    #[serde(rename = "a")]
+    aggregate_trade_id: u64,
+    #[serde(rename = "p")]
+    price: &'a str,
+    #[serde(rename = "q")]
+    quantity: &'a str,
+    #[serde(rename = "T")]
+    trade_time: u64,
+    #[serde(rename = "m")]
+    is_buyer_maker: bool,
+}
+
+impl BinanceAggTradeRef<'_> {
+    fn decode(&self) -> Result<BinanceAggTrade> {
+        Ok(BinanceAggTrade {
+            symbol: self.symbol.to_string(),
+            aggregate_trade_id: self.aggregate_trade_id,
+            price: fast_float2::parse(self.price)?,
+            quantity: fast_float2::parse(self.quantity)?,
+            trade_time: self.trade_time,
+            is_buyer_maker: self.is_buyer_maker,
+        })
+    }
+}
+
+/// Decoded aggTrade; the quantity is still in the market's own unit
+#[derive(Debug, PartialEq)]
+pub(super) struct BinanceAggTrade {
+    pub(super) symbol: String,
+    pub(super) aggregate_trade_id: u64,
+    pub(super) price: f64,
+    pub(super) quantity: f64,
+    pub(super) trade_time: u64,
+    pub(super) is_buyer_maker: bool,
+}
+
+/// Envelope of every event on the `/stream?streams=` endpoint:
+/// `{"stream":"btcusdt@aggTrade","data":{...}}`
+#[derive(Debug, Deserialize)]
+struct CombinedStreamFrame<T> {
+    data: T,
+}
+
+/// Stream a combined-stream frame belongs to, read off the start of the text.
+/// Binance writes `stream` before `data`, so no parsing is needed to route it.
+fn combined_stream_name(text: &str) -> Option<&str> {
+    text.strip_prefix(r#"{"stream":""#)?.split('"').next()
+}
+
+/// Deserialize an aggTrade frame, combined-stream or raw, in place. `None` when
+/// the frame is something else or the fast path rejects it; such frames go
+/// through `serde_json::Value` instead. Nothing here depends on the rest of
+/// the crate, so `benches/parse_trades.rs` compiles this file on its own.
+pub(super) fn decode_agg_trade(text: &str) -> Option<Result<BinanceAggTrade>> {
+    match combined_stream_name(text) {
+        Some(stream) if stream.ends_with("@aggTrade") => {
+            serde_json::from_str::<CombinedStreamFrame<BinanceAggTradeRef>>(text).ok().map(|frame| frame.data.decode())
+        }
+        None if text.starts_with(r#"{"e":"aggTrade""#) => {
+            serde_json::from_str::<BinanceAggTradeRef>(text).ok().map(|trade| trade.decode())
+        }
+        _ => None,
+    }
+}
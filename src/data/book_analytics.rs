@@ -0,0 +1,372 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use tokio::time::Duration;
+use tracing::{info, warn};
+
+use crate::config::settings::BookAnalyticsConfig;
+use super::{DatabaseManager, OrderBook, PersistRecord, PersistenceHandle, Subscription};
+
+/// Walls reported per side, largest first
+const MAX_WALLS_PER_SIDE: usize = 3;
+/// Spacing of the deletes applying the retention to stored samples
+const BOOK_ANALYTICS_PRUNE_INTERVAL_SECS: u64 = 600;
+
+/// Resting size on each side within `band_pct` of mid
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BandImbalance {
+    pub band_pct: f64,
+    pub bid_quantity: f64,
+    pub ask_quantity: f64,
+    /// (bid - ask) / (bid + ask), from -1 (all asks) to 1 (all bids)
+    pub imbalance: f64,
+}
+
+/// Cost of sweeping the book with a market order of `notional`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlippageEstimate {
+    pub notional: f64,
+    /// Average fill of a market buy above mid, in basis points; `None` when the
+    /// local book holds less than `notional`
+    pub buy_bps: Option<f64>,
+    /// Average fill of a market sell below mid, in basis points
+    pub sell_bps: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpreadStats {
+    pub spread: f64,
+    pub spread_bps: f64,
+    /// Over the symbol's last `spread_window` samples, this one included
+    pub mean_bps: f64,
+    pub min_bps: f64,
+    pub max_bps: f64,
+}
+
+/// A resting level much larger than its neighbours
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiquidityWall {
+    /// "BID" or "ASK"
+    pub side: String,
+    pub price: f64,
+    pub quantity: f64,
+    pub distance_pct: f64,
+    /// Quantity over the median level size within the wall range
+    pub size_multiple: f64,
+}
+
+/// Book-level analytics of one symbol at one point in time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookAnalytics {
+    pub symbol: String,
+    pub timestamp: u64,
+    pub mid_price: f64,
+    pub spread: SpreadStats,
+    /// One entry per configured band, narrowest first
+    pub bands: Vec<BandImbalance>,
+    pub slippage: Vec<SlippageEstimate>,
+    pub walls: Vec<LiquidityWall>,
+}
+
+/// Computes `BookAnalytics` from live local books, remembering each symbol's
+/// recent spreads for the spread statistics
+pub struct BookAnalyzer {
+    config: BookAnalyticsConfig,
+    spreads: HashMap<String, VecDeque<f64>>,
+}
+
+impl BookAnalyzer {
+    pub fn new(mut config: BookAnalyticsConfig) -> Self {
+        config.depth_bands_pct.sort_by(f64::total_cmp);
+        Self {
+            config,
+            spreads: HashMap::new(),
+        }
+    }
+
+    pub fn interval_ms(&self) -> u64 {
+        self.config.interval_ms
+    }
+
+    /// Analytics of `book`; `None` while a side is empty or the book is crossed
+    pub fn analyze(&mut self, book: &OrderBook) -> Option<BookAnalytics> {
+        let best_bid = book.bids.keys().next_back()?.0;
+        let best_ask = book.asks.keys().next()?.0;
+        if best_ask <= best_bid {
+            return None;
+        }
+        let mid_price = (best_bid + best_ask) / 2.0;
+
+        let spread = best_ask - best_bid;
+        let spread_bps = spread / mid_price * 10_000.0;
+        let history = self.spreads.entry(book.symbol.clone()).or_default();
+        history.push_back(spread_bps);
+        while history.len() > self.config.spread_window.max(1) {
+            history.pop_front();
+        }
+
+        Some(BookAnalytics {
+            symbol: book.symbol.clone(),
+            timestamp: book.timestamp,
+            mid_price,
+            spread: SpreadStats {
+                spread,
+                spread_bps,
+                mean_bps: history.iter().sum::<f64>() / history.len() as f64,
+                min_bps: history.iter().copied().fold(f64::INFINITY, f64::min),
+                max_bps: history.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            },
+            bands: self.config.depth_bands_pct
+                .iter()
+                .map(|band_pct| band_imbalance(book, mid_price, *band_pct))
+                .collect(),
+            slippage: self.config.slippage_notionals
+                .iter()
+                .map(|notional| SlippageEstimate {
+                    notional: *notional,
+                    buy_bps: sweep(book.asks.iter().map(|(price, quantity)| (price.0, *quantity)), *notional)
+                        .map(|average| (average - mid_price) / mid_price * 10_000.0),
+                    sell_bps: sweep(book.bids.iter().rev().map(|(price, quantity)| (price.0, *quantity)), *notional)
+                        .map(|average| (mid_price - average) / mid_price * 10_000.0),
+                })
+                .collect(),
+            walls: liquidity_walls(book, mid_price, &self.config),
+        })
+    }
+}
+
+fn band_imbalance(book: &OrderBook, mid_price: f64, band_pct: f64) -> BandImbalance {
+    let reach = mid_price * band_pct / 100.0;
+    let bid_quantity: f64 = book.bids
+        .iter()
+        .rev()
+        .take_while(|(price, _)| price.0 >= mid_price - reach)
+        .map(|(_, quantity)| quantity)
+        .sum();
+    let ask_quantity: f64 = book.asks
+        .iter()
+        .take_while(|(price, _)| price.0 <= mid_price + reach)
+        .map(|(_, quantity)| quantity)
+        .sum();
+    let total = bid_quantity + ask_quantity;
+
+    BandImbalance {
+        band_pct,
+        bid_quantity,
+        ask_quantity,
+        imbalance: if total > 0.0 { (bid_quantity - ask_quantity) / total } else { 0.0 },
+    }
+}
+
+/// Average fill price of a market order of `notional` walking `levels` from the
+/// touch outwards, or `None` when they run out first
+fn sweep(levels: impl Iterator<Item = (f64, f64)>, notional: f64) -> Option<f64> {
+    let mut remaining = notional;
+    let mut filled_quantity = 0.0;
+    for (price, quantity) in levels {
+        let taken = remaining.min(price * quantity);
+        filled_quantity += taken / price;
+        remaining -= taken;
+        if remaining <= notional * 1e-9 {
+            return Some(notional / filled_quantity);
+        }
+    }
+    None
+}
+
+fn liquidity_walls(book: &OrderBook, mid_price: f64, config: &BookAnalyticsConfig) -> Vec<LiquidityWall> {
+    let reach = mid_price * config.wall_range_pct / 100.0;
+    let bids: Vec<(f64, f64)> = book.bids
+        .iter()
+        .rev()
+        .take_while(|(price, _)| price.0 >= mid_price - reach)
+        .map(|(price, quantity)| (price.0, *quantity))
+        .collect();
+    let asks: Vec<(f64, f64)> = book.asks
+        .iter()
+        .take_while(|(price, _)| price.0 <= mid_price + reach)
+        .map(|(price, quantity)| (price.0, *quantity))
+        .collect();
+
+    let mut sizes: Vec<f64> = bids.iter().chain(&asks).map(|(_, quantity)| *quantity).collect();
+    if sizes.len() < 3 {
+        return Vec::new();
+    }
+    sizes.sort_by(f64::total_cmp);
+    let median = sizes[sizes.len() / 2];
+    if median <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut walls = Vec::new();
+    for (side, levels) in [("BID", bids), ("ASK", asks)] {
+        let mut side_walls: Vec<LiquidityWall> = levels
+            .into_iter()
+            .filter(|(_, quantity)| *quantity >= median * config.wall_size_multiple)
+            .map(|(price, quantity)| LiquidityWall {
+                side: side.to_string(),
+                price,
+                quantity,
+                distance_pct: (price - mid_price).abs() / mid_price * 100.0,
+                size_multiple: quantity / median,
+            })
+            .collect();
+        side_walls.sort_by(|a, b| b.quantity.total_cmp(&a.quantity));
+        side_walls.truncate(MAX_WALLS_PER_SIDE);
+        walls.extend(side_walls);
+    }
+    walls
+}
+
+/// Stores a thinned series of the published analytics: the configured symbols
+/// only (all when none are listed), at most one sample per symbol per
+/// `persist_interval_ms`, deleted once older than `retention_secs`
+pub struct BookAnalyticsRecorder {
+    persistence: PersistenceHandle,
+    database: Arc<DatabaseManager>,
+    symbols: HashSet<String>,
+    interval_ms: u64,
+    retention_ms: u64,
+    last_stored: HashMap<String, u64>,
+}
+
+impl BookAnalyticsRecorder {
+    pub fn new(config: &BookAnalyticsConfig, persistence: PersistenceHandle, database: Arc<DatabaseManager>) -> Self {
+        Self {
+            persistence,
+            database,
+            symbols: config.persist_symbols.iter().cloned().collect(),
+            interval_ms: config.persist_interval_ms,
+            retention_ms: config.retention_secs * 1000,
+            last_stored: HashMap::new(),
+        }
+    }
+
+    /// Whether `analytics` is the next sample of the stored series
+    fn admit(&mut self, analytics: &BookAnalytics) -> bool {
+        if !self.symbols.is_empty() && !self.symbols.contains(&analytics.symbol) {
+            return false;
+        }
+        match self.last_stored.get(&analytics.symbol) {
+            Some(&last) if analytics.timestamp < last + self.interval_ms => false,
+            _ => {
+                self.last_stored.insert(analytics.symbol.clone(), analytics.timestamp);
+                true
+            }
+        }
+    }
+
+    async fn prune(&self) {
+        let before = (chrono::Utc::now().timestamp_millis() as u64).saturating_sub(self.retention_ms);
+        if let Err(e) = self.database.delete_book_analytics_before(before).await {
+            warn!("Cannot prune book analytics: {}", e);
+        }
+    }
+
+    pub async fn start(mut self, mut analytics: Subscription<BookAnalytics>) {
+        info!("Starting book analytics recorder");
+        let mut prune_timer = tokio::time::interval(Duration::from_secs(BOOK_ANALYTICS_PRUNE_INTERVAL_SECS));
+        loop {
+            tokio::select! {
+                published = analytics.recv() => {
+                    let Some(published) = published else {
+                        break;
+                    };
+                    if self.admit(&published) {
+                        self.persistence.persist(PersistRecord::BookAnalytics((*published).clone()));
+                    }
+                }
+                _ = prune_timer.tick() => self.prune().await,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::DepthSnapshotResponse;
+
+    fn book(bids: Vec<(f64, f64)>, asks: Vec<(f64, f64)>) -> OrderBook {
+        let mut book = OrderBook::new("BTCUSDT".to_string());
+        book.apply_snapshot(&DepthSnapshotResponse { last_update_id: 1, event_time: 1_000, bids, asks });
+        book
+    }
+
+    #[test]
+    fn test_book_analytics_bands_slippage_and_walls() {
+        // Mid 100.0; one unit every 0.1 on both sides, plus a 50 unit bid wall at 99.45
+        let mut bids: Vec<(f64, f64)> = (1..=20).map(|i| (100.0 - 0.05 - (i - 1) as f64 * 0.1, 1.0)).collect();
+        bids[5].1 = 50.0;
+        let asks: Vec<(f64, f64)> = (1..=20).map(|i| (100.0 + 0.05 + (i - 1) as f64 * 0.1, 1.0)).collect();
+        let mut analyzer = BookAnalyzer::new(BookAnalyticsConfig {
+            depth_bands_pct: vec![1.0, 0.1],
+            slippage_notionals: vec![100.0, 300.0, 1_000_000.0],
+            ..Default::default()
+        });
+
+        let analytics = analyzer.analyze(&book(bids, asks)).unwrap();
+        assert!((analytics.mid_price - 100.0).abs() < 1e-9);
+        assert!((analytics.spread.spread_bps - 10.0).abs() < 1e-6);
+
+        // The 0.1% band reaches 99.9..100.1: the touch on each side only
+        assert_eq!(analytics.bands[0].band_pct, 0.1);
+        assert_eq!((analytics.bands[0].bid_quantity, analytics.bands[0].ask_quantity), (1.0, 1.0));
+        assert_eq!(analytics.bands[1].bid_quantity, 59.0);
+        assert!((analytics.bands[1].imbalance - (59.0 - 10.0) / 69.0).abs() < 1e-9);
+
+        // 100 fills at the touch; 300 takes two whole ask levels and part of a third
+        assert!((analytics.slippage[0].buy_bps.unwrap() - 5.0).abs() < 1e-9);
+        let average = 300.0 / (2.0 + (300.0 - 100.05 - 100.15) / 100.25);
+        assert!((analytics.slippage[1].buy_bps.unwrap() - (average - 100.0) * 100.0).abs() < 1e-6);
+        assert_eq!(analytics.slippage[2].buy_bps, None);
+
+        assert_eq!(analytics.walls.len(), 1);
+        assert_eq!((analytics.walls[0].side.as_str(), analytics.walls[0].quantity), ("BID", 50.0));
+        assert!((analytics.walls[0].price - 99.45).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_spread_statistics_roll_over_the_window() {
+        let mut analyzer = BookAnalyzer::new(BookAnalyticsConfig { spread_window: 2, ..Default::default() });
+        for ask in [100.1, 100.3, 100.2] {
+            analyzer.analyze(&book(vec![(100.0, 1.0)], vec![(ask, 1.0)]));
+        }
+        let spread = analyzer.analyze(&book(vec![(100.0, 1.0)], vec![(100.2, 1.0)])).unwrap().spread;
+        let bps = |ask: f64| (ask - 100.0) / ((ask + 100.0) / 2.0) * 10_000.0;
+        assert!((spread.min_bps - bps(100.2)).abs() < 1e-9);
+        assert!((spread.max_bps - bps(100.2)).abs() < 1e-9);
+
+        // Crossed and one-sided books are skipped
+        assert!(analyzer.analyze(&book(vec![(100.0, 1.0)], vec![(99.9, 1.0)])).is_none());
+        assert!(analyzer.analyze(&book(vec![(100.0, 1.0)], Vec::new())).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_recorder_stores_a_thinned_series_of_listed_symbols() {
+        use crate::data::PersistenceConfig;
+
+        let db = DatabaseManager::new(":memory:").await.unwrap();
+        db.initialize_schema().await.unwrap();
+        let (persistence, _writer) = PersistenceHandle::start(
+            db.clone(),
+            PersistenceConfig { queue_capacity: 10, batch_size: 10, flush_interval_ms: 10 },
+        );
+        let config = BookAnalyticsConfig {
+            persist_interval_ms: 60_000,
+            persist_symbols: vec!["BTCUSDT".to_string()],
+            ..Default::default()
+        };
+        let mut recorder = BookAnalyticsRecorder::new(&config, persistence, db);
+
+        let mut analyzer = BookAnalyzer::new(BookAnalyticsConfig::default());
+        let mut sample = analyzer.analyze(&book(vec![(100.0, 1.0)], vec![(100.1, 1.0)])).unwrap();
+        let mut admitted = Vec::new();
+        for (symbol, timestamp) in [("BTCUSDT", 0), ("BTCUSDT", 1_000), ("ETHUSDT", 2_000), ("BTCUSDT", 59_999), ("BTCUSDT", 60_000)] {
+            sample.symbol = symbol.to_string();
+            sample.timestamp = timestamp;
+            admitted.push(recorder.admit(&sample));
+        }
+        assert_eq!(admitted, vec![true, false, false, false, true]);
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use ordered_float::OrderedFloat;
+use tracing::info;
+
+use crate::config::settings::BookEventConfig;
+use super::{BookDelta, Counter, LevelChange, Metrics, OrderflowEvent, Subscription, Topic};
+
+/// What happened to the size at a level between two diffs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelEventKind {
+    Add,
+    Cancel,
+    /// Size removed by trades printed at the level
+    Execution,
+}
+
+impl LevelEventKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LevelEventKind::Add => "add",
+            LevelEventKind::Cancel => "cancel",
+            LevelEventKind::Execution => "execution",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelEvent {
+    pub kind: LevelEventKind,
+    /// "BID" or "ASK"
+    pub side: String,
+    pub price: f64,
+    pub quantity: f64,
+}
+
+/// Adds, cancels and executions at one level, as quantity and as event counts
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LevelFlow {
+    /// Size stacked onto the level
+    pub added: f64,
+    /// Size pulled from the level without trading
+    pub cancelled: f64,
+    pub executed: f64,
+    pub adds: u32,
+    pub cancels: u32,
+    pub executions: u32,
+}
+
+impl LevelFlow {
+    fn record(&mut self, kind: LevelEventKind, quantity: f64) {
+        match kind {
+            LevelEventKind::Add => {
+                self.added += quantity;
+                self.adds += 1;
+            }
+            LevelEventKind::Cancel => {
+                self.cancelled += quantity;
+                self.cancels += 1;
+            }
+            LevelEventKind::Execution => {
+                self.executed += quantity;
+                self.executions += 1;
+            }
+        }
+    }
+
+    pub fn merge(&mut self, other: &LevelFlow) {
+        self.added += other.added;
+        self.cancelled += other.cancelled;
+        self.executed += other.executed;
+        self.adds += other.adds;
+        self.cancels += other.cancels;
+        self.executions += other.executions;
+    }
+
+    /// Cancel events per execution event; `None` before the first execution
+    pub fn cancel_to_fill_ratio(&self) -> Option<f64> {
+        (self.executions > 0).then(|| self.cancels as f64 / self.executions as f64)
+    }
+}
+
+/// Level flow of one symbol over one time bucket, per price and side
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookFlowBucket {
+    pub symbol: String,
+    pub start: u64,
+    pub duration_ms: u64,
+    pub bids: BTreeMap<OrderedFloat<f64>, LevelFlow>,
+    pub asks: BTreeMap<OrderedFloat<f64>, LevelFlow>,
+}
+
+impl BookFlowBucket {
+    fn new(symbol: String, start: u64, duration_ms: u64) -> Self {
+        Self { symbol, start, duration_ms, bids: BTreeMap::new(), asks: BTreeMap::new() }
+    }
+
+    /// Both sides summed over every price
+    pub fn totals(&self) -> LevelFlow {
+        let mut totals = LevelFlow::default();
+        for flow in self.bids.values().chain(self.asks.values()) {
+            totals.merge(flow);
+        }
+        totals
+    }
+}
+
+/// Symbol, side and price a trade printed at
+type PrintLevel = (String, &'static str, OrderedFloat<f64>);
+
+/// Splits each level change of a live book into adds, cancels and executions.
+/// A decrease is an execution up to the volume printed at that price and side
+/// within `match_window_ms` before the diff, and a cancel beyond it; trades
+/// lead the batched depth stream, so they are matched backwards in time.
+pub struct BookEventClassifier {
+    config: BookEventConfig,
+    /// Prints not yet matched to a decrease, by (symbol, side, price)
+    unmatched_trades: HashMap<PrintLevel, VecDeque<(u64, f64)>>,
+    open_buckets: HashMap<String, BookFlowBucket>,
+    last_prune: u64,
+    events: [Counter; 3],
+}
+
+impl BookEventClassifier {
+    pub fn new(config: BookEventConfig) -> Self {
+        Self {
+            config,
+            unmatched_trades: HashMap::new(),
+            open_buckets: HashMap::new(),
+            last_prune: 0,
+            events: Default::default(),
+        }
+    }
+
+    /// Count classified events in the shared metrics registry
+    pub fn set_metrics(&mut self, metrics: &Metrics) {
+        for (counter, kind) in self.events.iter_mut().zip([LevelEventKind::Add, LevelEventKind::Cancel, LevelEventKind::Execution]) {
+            *counter = metrics.book_level_events(kind.name());
+        }
+    }
+
+    /// Sellers hit bids, buyers lift asks
+    pub fn process_trade(&mut self, trade: &OrderflowEvent) {
+        let side = if trade.is_buyer_maker { "BID" } else { "ASK" };
+        self.unmatched_trades
+            .entry((trade.symbol.clone(), side, OrderedFloat(trade.price)))
+            .or_default()
+            .push_back((trade.timestamp, trade.quantity));
+    }
+
+    /// Classify every change of one diff
+    pub fn classify(&mut self, delta: &BookDelta) -> Vec<LevelEvent> {
+        let mut events = Vec::new();
+        for change in &delta.changes {
+            self.classify_change(&delta.symbol, delta.timestamp, change, &mut events);
+        }
+
+        let prune_interval = self.config.match_window_ms.max(1) * 10;
+        if delta.timestamp >= self.last_prune + prune_interval {
+            let horizon = delta.timestamp.saturating_sub(self.config.match_window_ms);
+            self.unmatched_trades.retain(|_, trades| trades.back().is_some_and(|(at, _)| *at >= horizon));
+            self.last_prune = delta.timestamp;
+        }
+        events
+    }
+
+    fn classify_change(&mut self, symbol: &str, timestamp: u64, change: &LevelChange, events: &mut Vec<LevelEvent>) {
+        let event = |kind, quantity| LevelEvent { kind, side: change.side.clone(), price: change.price, quantity };
+        if change.quantity > change.previous {
+            events.push(event(LevelEventKind::Add, change.quantity - change.previous));
+            return;
+        }
+
+        let mut removed = change.previous - change.quantity;
+        let side = if change.side == "BID" { "BID" } else { "ASK" };
+        let mut executed = 0.0;
+        if let Some(trades) = self.unmatched_trades.get_mut(&(symbol.to_string(), side, OrderedFloat(change.price))) {
+            let horizon = timestamp.saturating_sub(self.config.match_window_ms);
+            while let Some((at, quantity)) = trades.front_mut() {
+                if *at < horizon {
+                    trades.pop_front();
+                    continue;
+                }
+                if removed <= 0.0 {
+                    break;
+                }
+                let matched = quantity.min(removed);
+                executed += matched;
+                removed -= matched;
+                *quantity -= matched;
+                if *quantity <= 0.0 {
+                    trades.pop_front();
+                }
+            }
+        }
+
+        if executed > 0.0 {
+            events.push(event(LevelEventKind::Execution, executed));
+        }
+        if removed > 0.0 {
+            events.push(event(LevelEventKind::Cancel, removed));
+        }
+    }
+
+    /// Classify one diff into its symbol's open bucket, returning the bucket it closed
+    pub fn process_delta(&mut self, delta: &BookDelta) -> Option<BookFlowBucket> {
+        let events = self.classify(delta);
+        let duration_ms = self.config.bucket_ms.max(1);
+        let start = delta.timestamp / duration_ms * duration_ms;
+
+        let closed = match self.open_buckets.get(&delta.symbol) {
+            Some(bucket) if bucket.start < start => self.open_buckets.remove(&delta.symbol),
+            _ => None,
+        };
+        let bucket = self.open_buckets
+            .entry(delta.symbol.clone())
+            .or_insert_with(|| BookFlowBucket::new(delta.symbol.clone(), start, duration_ms));
+
+        for event in events {
+            let side = if event.side == "BID" { &mut bucket.bids } else { &mut bucket.asks };
+            side.entry(OrderedFloat(event.price)).or_default().record(event.kind, event.quantity);
+            self.events[event.kind as usize].inc();
+        }
+        closed
+    }
+
+    pub async fn start(
+        mut self,
+        mut deltas: Subscription<BookDelta>,
+        mut orderflow: Subscription<OrderflowEvent>,
+        topic: Topic<BookFlowBucket>,
+    ) {
+        info!("Starting book event classifier");
+        loop {
+            tokio::select! {
+                Some(trade) = orderflow.recv() => self.process_trade(&trade),
+                Some(delta) = deltas.recv() => {
+                    if let Some(bucket) = self.process_delta(&delta) {
+                        topic.try_publish(bucket);
+                    }
+                }
+                else => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta(timestamp: u64, side: &str, price: f64, previous: f64, quantity: f64) -> BookDelta {
+        BookDelta {
+            symbol: "BTCUSDT".to_string(),
+            timestamp,
+            best_bid: Some(99.9),
+            best_ask: Some(100.0),
+            changes: vec![LevelChange { side: side.to_string(), price, previous, quantity }],
+        }
+    }
+
+    fn trade(timestamp: u64, price: f64, quantity: f64, is_buyer_maker: bool) -> OrderflowEvent {
+        OrderflowEvent { symbol: "BTCUSDT".to_string(), timestamp, price, quantity, is_buyer_maker, trade_id: timestamp }
+    }
+
+    #[test]
+    fn test_decreases_split_into_executions_and_cancels() {
+        let mut classifier = BookEventClassifier::new(BookEventConfig { bucket_ms: 1_000, match_window_ms: 500 });
+        let kinds = |events: Vec<LevelEvent>| events.into_iter().map(|event| (event.kind, event.quantity)).collect::<Vec<_>>();
+
+        assert_eq!(kinds(classifier.classify(&delta(1_000, "BID", 99.9, 2.0, 5.0))), vec![(LevelEventKind::Add, 3.0)]);
+
+        // 1.5 sold into the bid; the diff takes 4 off it
+        classifier.process_trade(&trade(1_050, 99.9, 1.5, true));
+        assert_eq!(
+            kinds(classifier.classify(&delta(1_100, "BID", 99.9, 5.0, 1.0))),
+            vec![(LevelEventKind::Execution, 1.5), (LevelEventKind::Cancel, 2.5)]
+        );
+
+        // Buys print at the ask, not the bid; stale prints are never matched
+        classifier.process_trade(&trade(1_150, 99.9, 1.0, false));
+        classifier.process_trade(&trade(1_200, 100.0, 2.0, false));
+        assert_eq!(kinds(classifier.classify(&delta(1_250, "BID", 99.9, 1.0, 0.0))), vec![(LevelEventKind::Cancel, 1.0)]);
+        assert_eq!(kinds(classifier.classify(&delta(1_800, "ASK", 100.0, 3.0, 1.0))), vec![(LevelEventKind::Cancel, 2.0)]);
+    }
+
+    #[test]
+    fn test_events_aggregate_per_price_and_bucket() {
+        let mut classifier = BookEventClassifier::new(BookEventConfig { bucket_ms: 1_000, match_window_ms: 500 });
+        assert!(classifier.process_delta(&delta(1_000, "BID", 99.9, 0.0, 5.0)).is_none());
+        classifier.process_trade(&trade(1_100, 99.9, 2.0, true));
+        assert!(classifier.process_delta(&delta(1_200, "BID", 99.9, 5.0, 0.0)).is_none());
+        assert!(classifier.process_delta(&delta(1_900, "ASK", 100.0, 0.0, 1.0)).is_none());
+
+        let bucket = classifier.process_delta(&delta(2_000, "ASK", 100.0, 1.0, 0.0)).unwrap();
+        assert_eq!((bucket.start, bucket.duration_ms), (1_000, 1_000));
+        let bid = bucket.bids[&OrderedFloat(99.9)];
+        assert_eq!((bid.added, bid.executed, bid.cancelled), (5.0, 2.0, 3.0));
+        assert_eq!(bucket.asks[&OrderedFloat(100.0)].added, 1.0);
+        assert_eq!(bucket.totals().cancel_to_fill_ratio(), Some(1.0));
+
+        // The 2,000 cancel opened the next bucket
+        let bucket = classifier.process_delta(&delta(3_500, "ASK", 100.0, 0.0, 1.0)).unwrap();
+        assert_eq!(bucket.start, 2_000);
+        assert_eq!(bucket.totals().cancels, 1);
+        assert_eq!(bucket.totals().cancel_to_fill_ratio(), None);
+    }
+}
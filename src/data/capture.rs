@@ -0,0 +1,583 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{sleep_until, Duration, Instant};
+use anyhow::{Result, anyhow, Context};
+use tracing::{info, warn, error, debug};
+
+use super::{OrderflowEvent, LiquidationEvent, DepthUpdate, DepthSnapshotResponse};
+
+/// File header identifying the capture format version
+const CAPTURE_MAGIC: &[u8; 8] = b"BSCAPv1\n";
+
+const KIND_TRADE: u8 = 1;
+const KIND_DEPTH: u8 = 2;
+const KIND_LIQUIDATION: u8 = 3;
+const KIND_DEPTH_SNAPSHOT: u8 = 4;
+
+/// Records queued for the writer before new ones are dropped
+const RECORDER_QUEUE_SIZE: usize = 50_000;
+const RECORDER_FLUSH_INTERVAL_MS: u64 = 1000;
+
+/// A market data event as stored in a capture file
+#[derive(Debug, Clone)]
+pub enum CaptureEvent {
+    Trade(OrderflowEvent),
+    Depth(DepthUpdate),
+    Liquidation(LiquidationEvent),
+    /// REST snapshot used to seed a book, needed to rebuild depth on replay
+    DepthSnapshot { symbol: String, snapshot: DepthSnapshotResponse },
+}
+
+/// One captured event plus the local time (ms) it was received, used to pace replay
+#[derive(Debug, Clone)]
+pub struct CaptureRecord {
+    pub recv_time: u64,
+    pub event: CaptureEvent,
+}
+
+impl CaptureRecord {
+    pub fn now(event: CaptureEvent) -> Self {
+        Self {
+            recv_time: chrono::Utc::now().timestamp_millis() as u64,
+            event,
+        }
+    }
+
+    /// Append the little-endian binary encoding of this record to `buf`
+    pub fn encode(&self, buf: &mut Vec<u8>) {
+        match &self.event {
+            CaptureEvent::Trade(trade) => {
+                buf.push(KIND_TRADE);
+                put_u64(buf, self.recv_time);
+                put_str(buf, &trade.symbol);
+                put_u64(buf, trade.timestamp);
+                put_f64(buf, trade.price);
+                put_f64(buf, trade.quantity);
+                buf.push(trade.is_buyer_maker as u8);
+                put_u64(buf, trade.trade_id);
+            }
+            CaptureEvent::Depth(update) => {
+                buf.push(KIND_DEPTH);
+                put_u64(buf, self.recv_time);
+                put_str(buf, &update.symbol);
+                put_u64(buf, update.event_time);
+                put_u64(buf, update.first_update_id);
+                put_u64(buf, update.last_update_id);
+                put_u64(buf, update.prev_final_update_id);
+                put_levels(buf, &update.bids);
+                put_levels(buf, &update.asks);
+            }
+            CaptureEvent::Liquidation(liquidation) => {
+                buf.push(KIND_LIQUIDATION);
+                put_u64(buf, self.recv_time);
+                put_str(buf, &liquidation.symbol);
+                put_u64(buf, liquidation.timestamp);
+                put_str(buf, &liquidation.side);
+                put_f64(buf, liquidation.price);
+                put_f64(buf, liquidation.quantity);
+                buf.push(liquidation.is_forced as u8);
+                put_f64(buf, liquidation.notional_value);
+            }
+            CaptureEvent::DepthSnapshot { symbol, snapshot } => {
+                buf.push(KIND_DEPTH_SNAPSHOT);
+                put_u64(buf, self.recv_time);
+                put_str(buf, symbol);
+                put_u64(buf, snapshot.last_update_id);
+                put_u64(buf, snapshot.event_time);
+                put_levels(buf, &snapshot.bids);
+                put_levels(buf, &snapshot.asks);
+            }
+        }
+    }
+
+    /// Read the next record. Returns `Ok(None)` at end of file.
+    pub fn decode(reader: &mut impl Read) -> io::Result<Option<Self>> {
+        let mut kind = [0u8; 1];
+        if reader.read(&mut kind)? == 0 {
+            return Ok(None);
+        }
+
+        let recv_time = get_u64(reader)?;
+        let event = match kind[0] {
+            KIND_TRADE => CaptureEvent::Trade(OrderflowEvent {
+                symbol: get_str(reader)?,
+                timestamp: get_u64(reader)?,
+                price: get_f64(reader)?,
+                quantity: get_f64(reader)?,
+                is_buyer_maker: get_u8(reader)? != 0,
+                trade_id: get_u64(reader)?,
+            }),
+            KIND_DEPTH => CaptureEvent::Depth(DepthUpdate {
+                event_type: "depthUpdate".to_string(),
+                symbol: get_str(reader)?,
+                event_time: get_u64(reader)?,
+                first_update_id: get_u64(reader)?,
+                last_update_id: get_u64(reader)?,
+                prev_final_update_id: get_u64(reader)?,
+                bids: get_levels(reader)?,
+                asks: get_levels(reader)?,
+            }),
+            KIND_LIQUIDATION => CaptureEvent::Liquidation(LiquidationEvent {
+                symbol: get_str(reader)?,
+                timestamp: get_u64(reader)?,
+                side: get_str(reader)?,
+                price: get_f64(reader)?,
+                quantity: get_f64(reader)?,
+                is_forced: get_u8(reader)? != 0,
+                notional_value: get_f64(reader)?,
+            }),
+            KIND_DEPTH_SNAPSHOT => CaptureEvent::DepthSnapshot {
+                symbol: get_str(reader)?,
+                snapshot: DepthSnapshotResponse {
+                    last_update_id: get_u64(reader)?,
+                    event_time: get_u64(reader)?,
+                    bids: get_levels(reader)?,
+                    asks: get_levels(reader)?,
+                },
+            },
+            other => {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unknown record kind {}", other)));
+            }
+        };
+
+        Ok(Some(Self { recv_time, event }))
+    }
+}
+
+fn put_u64(buf: &mut Vec<u8>, value: u64) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+pub(super) fn put_f64(buf: &mut Vec<u8>, value: f64) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_str(buf: &mut Vec<u8>, value: &str) {
+    let bytes = &value.as_bytes()[..value.len().min(u8::MAX as usize)];
+    buf.push(bytes.len() as u8);
+    buf.extend_from_slice(bytes);
+}
+
+fn put_levels(buf: &mut Vec<u8>, levels: &[(f64, f64)]) {
+    buf.extend_from_slice(&(levels.len() as u32).to_le_bytes());
+    for (price, quantity) in levels {
+        put_f64(buf, *price);
+        put_f64(buf, *quantity);
+    }
+}
+
+/// Zigzag varints, shared with the footprint and heatmap blob formats
+pub(super) fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+pub(super) fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+pub(super) fn put_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+pub(super) fn get_u8(reader: &mut impl Read) -> io::Result<u8> {
+    let mut bytes = [0u8; 1];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+pub(super) fn get_varint(reader: &mut impl Read) -> io::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = get_u8(reader)?;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::InvalidData, "varint longer than 64 bits"))
+}
+
+fn get_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+pub(super) fn get_f64(reader: &mut impl Read) -> io::Result<f64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(f64::from_le_bytes(bytes))
+}
+
+fn get_str(reader: &mut impl Read) -> io::Result<String> {
+    let len = get_u8(reader)? as usize;
+    let mut bytes = vec![0u8; len];
+    reader.read_exact(&mut bytes)?;
+    String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn get_levels(reader: &mut impl Read) -> io::Result<Vec<(f64, f64)>> {
+    let mut count = [0u8; 4];
+    reader.read_exact(&mut count)?;
+    let count = u32::from_le_bytes(count) as usize;
+    let mut levels = Vec::with_capacity(count.min(10_000));
+    for _ in 0..count {
+        levels.push((get_f64(reader)?, get_f64(reader)?));
+    }
+    Ok(levels)
+}
+
+/// Cloneable handle that queues events for the background capture writer
+#[derive(Clone)]
+pub struct CaptureRecorder {
+    sender: mpsc::Sender<CaptureRecord>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl CaptureRecorder {
+    /// Open (or create) `path` for appending and spawn the writer task
+    pub fn start(path: impl AsRef<Path>) -> Result<(Self, tokio::task::JoinHandle<()>)> {
+        let path = path.as_ref().to_path_buf();
+        let is_new = std::fs::metadata(&path).map(|m| m.len() == 0).unwrap_or(true);
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open capture file {:?}", path))?;
+
+        let mut writer = BufWriter::with_capacity(1 << 20, file);
+        if is_new {
+            writer.write_all(CAPTURE_MAGIC)?;
+        }
+
+        let (sender, receiver) = mpsc::channel(RECORDER_QUEUE_SIZE);
+        let handle = tokio::spawn(Self::run_writer(path, writer, receiver));
+
+        Ok((Self { sender, dropped: Arc::new(AtomicU64::new(0)) }, handle))
+    }
+
+    pub fn record(&self, event: CaptureEvent) {
+        if self.sender.try_send(CaptureRecord::now(event)).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Events discarded because the writer could not keep up
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    async fn run_writer(path: PathBuf, mut writer: BufWriter<File>, mut receiver: mpsc::Receiver<CaptureRecord>) {
+        info!("Recording market data to {:?}", path);
+
+        let mut flush_timer = tokio::time::interval(Duration::from_millis(RECORDER_FLUSH_INTERVAL_MS));
+        let mut buf = Vec::with_capacity(4096);
+        let mut written: u64 = 0;
+
+        loop {
+            tokio::select! {
+                record = receiver.recv() => {
+                    let Some(record) = record else { break };
+                    buf.clear();
+                    record.encode(&mut buf);
+                    if let Err(e) = writer.write_all(&buf) {
+                        error!("Failed to write capture record: {}", e);
+                        return;
+                    }
+                    written += 1;
+                }
+                _ = flush_timer.tick() => {
+                    if let Err(e) = writer.flush() {
+                        error!("Failed to flush capture file: {}", e);
+                    }
+                }
+            }
+        }
+
+        if let Err(e) = writer.flush() {
+            error!("Failed to flush capture file: {}", e);
+        }
+        info!("Capture writer stopped after {} records", written);
+    }
+}
+
+/// Sequential reader over a capture file
+pub struct CaptureReader {
+    reader: BufReader<File>,
+}
+
+impl CaptureReader {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).with_context(|| format!("Failed to open capture file {:?}", path))?;
+        let mut reader = BufReader::with_capacity(1 << 20, file);
+
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != CAPTURE_MAGIC {
+            return Err(anyhow!("{:?} is not a capture file", path));
+        }
+
+        Ok(Self { reader })
+    }
+
+    /// Next record, or `None` at the end. A record cut short by a crash ends the stream.
+    pub fn next_record(&mut self) -> Result<Option<CaptureRecord>> {
+        match CaptureRecord::decode(&mut self.reader) {
+            Ok(record) => Ok(record),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                warn!("Capture file ends with a truncated record, stopping replay");
+                Ok(None)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// How fast a capture is played back relative to the original session
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    RealTime,
+    Multiplier(f64),
+    AsFastAsPossible,
+}
+
+impl ReplaySpeed {
+    /// Parse "1x", "10x", "0.5x" or "max"
+    pub fn parse(value: &str) -> Result<Self> {
+        let value = value.trim().to_lowercase();
+        if value == "max" {
+            return Ok(Self::AsFastAsPossible);
+        }
+
+        let factor: f64 = value.trim_end_matches('x').parse()
+            .map_err(|_| anyhow!("Invalid replay speed '{}', expected e.g. 1x, 10x or max", value))?;
+        if factor <= 0.0 {
+            return Err(anyhow!("Replay speed must be positive"));
+        }
+
+        Ok(if factor == 1.0 { Self::RealTime } else { Self::Multiplier(factor) })
+    }
+
+    fn factor(&self) -> Option<f64> {
+        match self {
+            Self::RealTime => Some(1.0),
+            Self::Multiplier(factor) => Some(*factor),
+            Self::AsFastAsPossible => None,
+        }
+    }
+}
+
+/// Feeds a capture file back into the live pipeline channels
+pub struct ReplaySource {
+    path: PathBuf,
+    speed: ReplaySpeed,
+    orderflow_sender: mpsc::Sender<OrderflowEvent>,
+    depth_sender: Option<mpsc::Sender<DepthUpdate>>,
+    liquidation_sender: Option<mpsc::Sender<LiquidationEvent>>,
+    depth_snapshot_injector: Option<mpsc::Sender<(String, Result<DepthSnapshotResponse>)>>,
+}
+
+impl ReplaySource {
+    pub fn new(path: impl AsRef<Path>, speed: ReplaySpeed, orderflow_sender: mpsc::Sender<OrderflowEvent>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            speed,
+            orderflow_sender,
+            depth_sender: None,
+            liquidation_sender: None,
+            depth_snapshot_injector: None,
+        }
+    }
+
+    pub fn set_depth_sender(&mut self, sender: mpsc::Sender<DepthUpdate>) {
+        self.depth_sender = Some(sender);
+    }
+
+    pub fn set_liquidation_sender(&mut self, sender: mpsc::Sender<LiquidationEvent>) {
+        self.liquidation_sender = Some(sender);
+    }
+
+    /// Route recorded REST snapshots straight into an `OrderBookManager`
+    pub fn set_depth_snapshot_injector(&mut self, sender: mpsc::Sender<(String, Result<DepthSnapshotResponse>)>) {
+        self.depth_snapshot_injector = Some(sender);
+    }
+
+    /// Play the whole file, returning the number of records replayed
+    pub async fn run(self) -> Result<u64> {
+        let mut reader = CaptureReader::open(&self.path)?;
+        info!("Replaying {:?} at {:?}", self.path, self.speed);
+
+        let started = Instant::now();
+        let mut first_recv_time = None;
+        let mut replayed = 0;
+
+        while let Some(record) = reader.next_record()? {
+            if let Some(factor) = self.speed.factor() {
+                let first = *first_recv_time.get_or_insert(record.recv_time);
+                let offset_ms = record.recv_time.saturating_sub(first) as f64 / factor;
+                sleep_until(started + Duration::from_secs_f64(offset_ms / 1000.0)).await;
+            }
+
+            // Blocking sends: replay must not lose events the way live try_send can
+            let delivered = match record.event {
+                CaptureEvent::Trade(trade) => self.orderflow_sender.send(trade).await.is_ok(),
+                CaptureEvent::Depth(update) => match &self.depth_sender {
+                    Some(sender) => sender.send(update).await.is_ok(),
+                    None => true,
+                },
+                CaptureEvent::Liquidation(liquidation) => match &self.liquidation_sender {
+                    Some(sender) => sender.send(liquidation).await.is_ok(),
+                    None => true,
+                },
+                CaptureEvent::DepthSnapshot { symbol, snapshot } => match &self.depth_snapshot_injector {
+                    Some(sender) => sender.send((symbol, Ok(snapshot))).await.is_ok(),
+                    None => true,
+                },
+            };
+
+            if !delivered {
+                debug!("Replay consumer closed, stopping");
+                break;
+            }
+            replayed += 1;
+        }
+
+        info!("Replay of {:?} finished: {} records", self.path, replayed);
+        Ok(replayed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("binance-screener-{}-{}.cap", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    fn trade(trade_id: u64, price: f64) -> OrderflowEvent {
+        OrderflowEvent {
+            symbol: "BTCUSDT".to_string(),
+            timestamp: 1_700_000_000_000 + trade_id,
+            price,
+            quantity: 0.5,
+            is_buyer_maker: trade_id.is_multiple_of(2),
+            trade_id,
+        }
+    }
+
+    #[test]
+    fn test_record_round_trip() {
+        let records = vec![
+            CaptureRecord { recv_time: 10, event: CaptureEvent::Trade(trade(1, 100.5)) },
+            CaptureRecord { recv_time: 11, event: CaptureEvent::Depth(DepthUpdate {
+                event_type: "depthUpdate".to_string(),
+                event_time: 5,
+                symbol: "ETHUSDT".to_string(),
+                first_update_id: 7,
+                last_update_id: 9,
+                prev_final_update_id: 6,
+                bids: vec![(2000.0, 1.0), (1999.5, 0.0)],
+                asks: vec![(2000.5, 3.25)],
+            })},
+            CaptureRecord { recv_time: 12, event: CaptureEvent::Liquidation(LiquidationEvent {
+                symbol: "SOLUSDT".to_string(),
+                timestamp: 3,
+                side: "SELL".to_string(),
+                price: 150.0,
+                quantity: 2.0,
+                is_forced: true,
+                notional_value: 300.0,
+            })},
+            CaptureRecord { recv_time: 13, event: CaptureEvent::DepthSnapshot {
+                symbol: "ETHUSDT".to_string(),
+                snapshot: DepthSnapshotResponse { last_update_id: 8, event_time: 4, bids: vec![(1.0, 2.0)], asks: vec![] },
+            }},
+        ];
+
+        let mut buf = Vec::new();
+        for record in &records {
+            record.encode(&mut buf);
+        }
+
+        let mut cursor = io::Cursor::new(buf);
+        let mut decoded = Vec::new();
+        while let Some(record) = CaptureRecord::decode(&mut cursor).unwrap() {
+            decoded.push(record);
+        }
+
+        assert_eq!(decoded.len(), 4);
+        assert_eq!(format!("{:?}", decoded), format!("{:?}", records));
+    }
+
+    #[test]
+    fn test_replay_speed_parse() {
+        assert_eq!(ReplaySpeed::parse("1x").unwrap(), ReplaySpeed::RealTime);
+        assert_eq!(ReplaySpeed::parse("10x").unwrap(), ReplaySpeed::Multiplier(10.0));
+        assert_eq!(ReplaySpeed::parse("MAX").unwrap(), ReplaySpeed::AsFastAsPossible);
+        assert!(ReplaySpeed::parse("fast").is_err());
+        assert!(ReplaySpeed::parse("0x").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay() {
+        let path = temp_path("replay");
+
+        let (recorder, writer) = CaptureRecorder::start(&path).unwrap();
+        for i in 0..100 {
+            recorder.record(CaptureEvent::Trade(trade(i, 100.0 + i as f64)));
+        }
+        drop(recorder);
+        writer.await.unwrap();
+
+        // Appending to an existing capture must not rewrite the header
+        let (recorder, writer) = CaptureRecorder::start(&path).unwrap();
+        recorder.record(CaptureEvent::Trade(trade(100, 200.0)));
+        drop(recorder);
+        writer.await.unwrap();
+
+        let (orderflow_tx, mut orderflow_rx) = mpsc::channel(10);
+        let replay = ReplaySource::new(&path, ReplaySpeed::AsFastAsPossible, orderflow_tx);
+        let handle = tokio::spawn(replay.run());
+
+        let mut received = Vec::new();
+        while let Some(event) = orderflow_rx.recv().await {
+            received.push(event);
+        }
+
+        assert_eq!(handle.await.unwrap().unwrap(), 101);
+        assert_eq!(received.len(), 101);
+        assert!(received.windows(2).all(|w| w[0].trade_id + 1 == w[1].trade_id));
+        assert_eq!(received[100].price, 200.0);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_truncated_tail_is_ignored() {
+        let path = temp_path("truncated");
+        let mut bytes = CAPTURE_MAGIC.to_vec();
+        CaptureRecord { recv_time: 1, event: CaptureEvent::Trade(trade(1, 1.0)) }.encode(&mut bytes);
+        let mut partial = Vec::new();
+        CaptureRecord { recv_time: 2, event: CaptureEvent::Trade(trade(2, 2.0)) }.encode(&mut partial);
+        bytes.extend_from_slice(&partial[..partial.len() / 2]);
+        std::fs::write(&path, bytes).unwrap();
+
+        let mut reader = CaptureReader::open(&path).unwrap();
+        assert!(reader.next_record().unwrap().is_some());
+        assert!(reader.next_record().unwrap().is_none());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,374 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::{mpsc, Notify};
+use tokio::time::Duration;
+use tracing::{info, warn};
+
+use crate::config::settings::BinanceConfig;
+use super::GuiUpdate;
+
+/// Shortest window a message rate is measured over
+const RATE_WINDOW_MS: u64 = 1_000;
+/// Spacing of the health summary log line
+const HEALTH_LOG_INTERVAL_SECS: u64 = 60;
+
+#[derive(Debug, Clone)]
+pub struct HealthConfig {
+    pub ping_interval: Duration,
+    pub stale_timeout: Duration,
+}
+
+impl HealthConfig {
+    /// How often the supervisor looks for stale connections
+    pub fn check_interval(&self) -> Duration {
+        (self.stale_timeout / 4).max(Duration::from_millis(10))
+    }
+}
+
+impl From<&BinanceConfig> for HealthConfig {
+    fn from(config: &BinanceConfig) -> Self {
+        Self {
+            ping_interval: Duration::from_secs(config.ping_interval_secs.max(1)),
+            stale_timeout: Duration::from_secs(config.stale_stream_timeout_secs.max(1)),
+        }
+    }
+}
+
+/// Health of one WebSocket connection at the time of a report
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionHealth {
+    pub name: String,
+    pub connected: bool,
+    /// Wall clock time (ms) of the last data frame
+    pub last_message_time: Option<u64>,
+    /// Time since the last frame of any kind, pongs included
+    pub silent_ms: u64,
+    /// Data frames per second over the last rate window
+    pub messages_per_second: f64,
+    pub total_messages: u64,
+    /// Data frames that could not be decoded
+    pub parse_errors: u64,
+    /// Decoded events lost because the downstream channel was full
+    pub dropped_messages: u64,
+    /// Connections established after the first one
+    pub reconnects: u32,
+    /// Reconnects the supervisor forced on a silent connection
+    pub forced_reconnects: u32,
+    /// Round trip of the latest answered ping
+    pub ping_latency_ms: Option<u64>,
+}
+
+impl ConnectionHealth {
+    pub fn is_stale(&self, stale_timeout: Duration) -> bool {
+        self.connected && self.silent_ms >= stale_timeout.as_millis() as u64
+    }
+}
+
+/// One line summary of a health report, for logs and the status bar
+pub fn health_summary(report: &[ConnectionHealth]) -> String {
+    let connected = report.iter().filter(|health| health.connected).count();
+    let rate: f64 = report.iter().map(|health| health.messages_per_second).sum();
+    let reconnects: u32 = report.iter().map(|health| health.reconnects).sum();
+    let latency = report.iter().filter_map(|health| health.ping_latency_ms).max();
+
+    format!(
+        "{}/{} connections up, {:.0} msg/s, {} reconnects, max ping {}",
+        connected,
+        report.len(),
+        rate,
+        reconnects,
+        latency.map_or("N/A".to_string(), |latency| format!("{}ms", latency))
+    )
+}
+
+struct TrackedConnection {
+    health: ConnectionHealth,
+    ever_connected: bool,
+    last_frame: Instant,
+    window_start: Instant,
+    window_messages: u64,
+    reconnect: Arc<Notify>,
+}
+
+/// Shared, cheaply cloned registry of every stream connection's health.
+/// Connection tasks report through a `ConnectionProbe`; a `ConnectionSupervisor`
+/// reads the reports and forces silent connections to reconnect.
+#[derive(Clone)]
+pub struct ConnectionMonitor {
+    config: HealthConfig,
+    connections: Arc<Mutex<BTreeMap<String, TrackedConnection>>>,
+}
+
+impl ConnectionMonitor {
+    pub fn new(config: HealthConfig) -> Self {
+        Self {
+            config,
+            connections: Arc::new(Mutex::new(BTreeMap::new())),
+        }
+    }
+
+    /// Start tracking a connection; registering a known name reuses its entry
+    pub fn register(&self, name: &str) -> ConnectionProbe {
+        let now = Instant::now();
+        let reconnect = self.connections
+            .lock()
+            .unwrap()
+            .entry(name.to_string())
+            .or_insert_with(|| TrackedConnection {
+                health: ConnectionHealth {
+                    name: name.to_string(),
+                    connected: false,
+                    last_message_time: None,
+                    silent_ms: 0,
+                    messages_per_second: 0.0,
+                    total_messages: 0,
+                    parse_errors: 0,
+                    dropped_messages: 0,
+                    reconnects: 0,
+                    forced_reconnects: 0,
+                    ping_latency_ms: None,
+                },
+                ever_connected: false,
+                last_frame: now,
+                window_start: now,
+                window_messages: 0,
+                reconnect: Arc::new(Notify::new()),
+            })
+            .reconnect
+            .clone();
+
+        ConnectionProbe {
+            monitor: self.clone(),
+            name: name.to_string(),
+            reconnect,
+        }
+    }
+
+    fn update(&self, name: &str, apply: impl FnOnce(&mut TrackedConnection)) {
+        if let Some(connection) = self.connections.lock().unwrap().get_mut(name) {
+            apply(connection);
+        }
+    }
+
+    /// Ask a connection task to drop its connection and resubscribe on a new one
+    pub fn force_reconnect(&self, name: &str) {
+        self.update(name, |connection| {
+            connection.health.forced_reconnects += 1;
+            connection.reconnect.notify_waiters();
+        });
+    }
+
+    /// Health of every connection, sorted by name. Rate windows of at least
+    /// `RATE_WINDOW_MS` are closed, so the rate is that of the latest full window.
+    pub fn report(&self) -> Vec<ConnectionHealth> {
+        let now = Instant::now();
+        let mut connections = self.connections.lock().unwrap();
+        connections
+            .values_mut()
+            .map(|connection| {
+                let window_ms = now.duration_since(connection.window_start).as_millis() as u64;
+                if window_ms >= RATE_WINDOW_MS {
+                    connection.health.messages_per_second = connection.window_messages as f64 * 1000.0 / window_ms as f64;
+                    connection.window_start = now;
+                    connection.window_messages = 0;
+                }
+                connection.health.silent_ms = now.duration_since(connection.last_frame).as_millis() as u64;
+                connection.health.clone()
+            })
+            .collect()
+    }
+
+    /// Whether every tracked connection is up and none has gone silent
+    pub fn check_health(&self) -> bool {
+        self.report()
+            .iter()
+            .all(|health| health.connected && !health.is_stale(self.config.stale_timeout))
+    }
+
+    pub fn get_status(&self) -> String {
+        health_summary(&self.report())
+    }
+}
+
+/// A connection task's handle on its entry in the `ConnectionMonitor`
+pub struct ConnectionProbe {
+    monitor: ConnectionMonitor,
+    name: String,
+    reconnect: Arc<Notify>,
+}
+
+impl ConnectionProbe {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn ping_interval(&self) -> Duration {
+        self.monitor.config.ping_interval
+    }
+
+    pub fn connected(&self) {
+        self.monitor.update(&self.name, |connection| {
+            if connection.ever_connected {
+                connection.health.reconnects += 1;
+            }
+            connection.ever_connected = true;
+            connection.health.connected = true;
+            connection.last_frame = Instant::now();
+        });
+    }
+
+    pub fn disconnected(&self) {
+        self.monitor.update(&self.name, |connection| connection.health.connected = false);
+    }
+
+    /// A data frame arrived
+    pub fn message(&self) {
+        self.monitor.update(&self.name, |connection| {
+            connection.last_frame = Instant::now();
+            connection.window_messages += 1;
+            connection.health.total_messages += 1;
+            connection.health.last_message_time = Some(chrono::Utc::now().timestamp_millis() as u64);
+        });
+    }
+
+    /// A data frame could not be decoded
+    pub fn parse_error(&self) {
+        self.monitor.update(&self.name, |connection| connection.health.parse_errors += 1);
+    }
+
+    /// A decoded event was dropped because its channel was full
+    pub fn dropped(&self) {
+        self.monitor.update(&self.name, |connection| connection.health.dropped_messages += 1);
+    }
+
+    /// A control frame arrived; proves the connection alive without counting as data
+    pub fn frame(&self) {
+        self.monitor.update(&self.name, |connection| connection.last_frame = Instant::now());
+    }
+
+    /// Payload of the next ping: the send time, echoed back in the pong
+    pub fn ping_payload(&self) -> Vec<u8> {
+        (chrono::Utc::now().timestamp_millis() as u64).to_be_bytes().to_vec()
+    }
+
+    /// A pong arrived; payloads other than our own pings only count as a frame
+    pub fn pong(&self, payload: &[u8]) {
+        let sent = <[u8; 8]>::try_from(payload).ok().map(u64::from_be_bytes);
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        self.monitor.update(&self.name, |connection| {
+            connection.last_frame = Instant::now();
+            if let Some(sent) = sent.filter(|sent| *sent <= now) {
+                connection.health.ping_latency_ms = Some(now - sent);
+            }
+        });
+    }
+
+    /// Resolves when the supervisor wants this connection replaced
+    pub async fn reconnect_requested(&self) {
+        self.reconnect.notified().await;
+    }
+}
+
+/// Periodically checks the `ConnectionMonitor`: forces silent connections to
+/// reconnect, logs a summary and publishes the report to the status bar.
+pub struct ConnectionSupervisor {
+    monitor: ConnectionMonitor,
+    gui_sender: Option<mpsc::Sender<GuiUpdate>>,
+}
+
+impl ConnectionSupervisor {
+    pub fn new(monitor: ConnectionMonitor) -> Self {
+        Self {
+            monitor,
+            gui_sender: None,
+        }
+    }
+
+    pub fn set_gui_sender(&mut self, sender: mpsc::Sender<GuiUpdate>) {
+        self.gui_sender = Some(sender);
+    }
+
+    pub async fn run(self) {
+        info!(
+            "Connection supervisor started (ping every {:?}, reconnect after {:?} of silence)",
+            self.monitor.config.ping_interval, self.monitor.config.stale_timeout
+        );
+
+        let mut check = tokio::time::interval(self.monitor.config.check_interval());
+        let mut last_log = Instant::now();
+        loop {
+            check.tick().await;
+            let report = self.check();
+
+            if last_log.elapsed() >= Duration::from_secs(HEALTH_LOG_INTERVAL_SECS) && !report.is_empty() {
+                if self.monitor.check_health() {
+                    info!("Stream health: {}", self.monitor.get_status());
+                } else {
+                    warn!("Stream health degraded: {}", self.monitor.get_status());
+                }
+                last_log = Instant::now();
+            }
+            if let Some(gui_sender) = &self.gui_sender {
+                let _ = gui_sender.try_send(GuiUpdate::ConnectionHealth(report));
+            }
+        }
+    }
+
+    /// Report every connection's health, forcing stale ones to reconnect
+    pub fn check(&self) -> Vec<ConnectionHealth> {
+        let mut report = self.monitor.report();
+        for health in report.iter_mut().filter(|health| health.is_stale(self.monitor.config.stale_timeout)) {
+            warn!("{} silent for {}ms, forcing a reconnect", health.name, health.silent_ms);
+            self.monitor.force_reconnect(&health.name);
+            health.forced_reconnects += 1;
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_supervisor_forces_silent_connections_to_reconnect() {
+        let monitor = ConnectionMonitor::new(HealthConfig {
+            ping_interval: Duration::from_millis(10),
+            stale_timeout: Duration::from_millis(50),
+        });
+        let supervisor = ConnectionSupervisor::new(monitor.clone());
+        let quiet = monitor.register("quiet");
+        let busy = monitor.register("busy");
+        assert!(!monitor.check_health());
+
+        quiet.connected();
+        busy.connected();
+        busy.message();
+        busy.parse_error();
+        busy.pong(&busy.ping_payload());
+        assert!(monitor.check_health());
+
+        let reconnect = tokio::spawn(async move {
+            quiet.reconnect_requested().await;
+            quiet
+        });
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        busy.frame();
+
+        let report = supervisor.check();
+        assert_eq!(report.iter().map(|health| health.name.as_str()).collect::<Vec<_>>(), vec!["busy", "quiet"]);
+        assert_eq!((report[0].total_messages, report[0].parse_errors, report[0].forced_reconnects), (1, 1, 0));
+        assert!(report[0].last_message_time.is_some() && report[0].ping_latency_ms.is_some());
+        assert_eq!(report[1].forced_reconnects, 1);
+
+        // The quiet task is told to reconnect, and counts the new connection
+        let quiet = tokio::time::timeout(Duration::from_secs(1), reconnect).await.unwrap().unwrap();
+        quiet.disconnected();
+        quiet.connected();
+        let report = monitor.report();
+        assert_eq!((report[1].reconnects, report[1].connected), (1, true));
+        assert!(!report[1].is_stale(Duration::from_millis(50)));
+        assert!(health_summary(&report).starts_with("2/2 connections up"));
+    }
+}
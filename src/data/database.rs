@@ -1,9 +1,242 @@
 use rusqlite::{Connection, Result as SqliteResult, params};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use anyhow::{Result, Context};
-use chrono::{DateTime, Utc};
-use super::{OrderflowEvent, VolumeProfile, OrderImbalance, LiquidationEvent, Candle, DailyStats};
+use tracing::info;
+use chrono::Utc;
+use super::{OrderflowEvent, VolumeProfile, OrderImbalance, LiquidationEvent, OpenInterestData, FundingRateData, Candle, DailyStats, PersistRecord, BookAnalytics, BandImbalance, SlippageEstimate, LiquidityWall, SpreadStats};
+use super::footprint_store::{StoredFootprintCandle, decode_levels};
+use super::heatmap_store::{HeatmapColumn, encode_heatmap_levels, decode_heatmap_levels};
+
+/// One forward-only schema change, tracked through `PRAGMA user_version`
+struct Migration {
+    version: u32,
+    description: &'static str,
+    sql: &'static str,
+}
+
+/// Ordered migrations; append new steps, never edit applied ones
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "baseline tables",
+        // IF NOT EXISTS so databases created before versioning (user_version 0) adopt it as-is
+        sql: r#"
+        CREATE TABLE IF NOT EXISTS candles (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            symbol TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            open_price REAL NOT NULL,
+            high_price REAL NOT NULL,
+            low_price REAL NOT NULL,
+            close_price REAL NOT NULL,
+            volume REAL NOT NULL,
+            buy_volume REAL NOT NULL,
+            sell_volume REAL NOT NULL,
+            timeframe TEXT NOT NULL,
+            trade_count INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER,
+            UNIQUE(symbol, timestamp, timeframe)
+        );
+
+        CREATE TABLE IF NOT EXISTS volume_profile (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            symbol TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            price_level REAL NOT NULL,
+            buy_volume REAL NOT NULL,
+            sell_volume REAL NOT NULL,
+            total_volume REAL NOT NULL,
+            trade_count INTEGER NOT NULL DEFAULT 0,
+            timeframe TEXT NOT NULL,
+            created_at INTEGER
+        );
+
+        CREATE TABLE IF NOT EXISTS order_imbalances (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            symbol TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            bid_volume REAL NOT NULL,
+            ask_volume REAL NOT NULL,
+            imbalance_ratio REAL NOT NULL,
+            window_duration_seconds INTEGER NOT NULL,
+            created_at INTEGER
+        );
+
+        CREATE TABLE IF NOT EXISTS liquidations (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            symbol TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            side TEXT NOT NULL,
+            price REAL NOT NULL,
+            quantity REAL NOT NULL,
+            is_forced INTEGER NOT NULL,
+            notional_value REAL NOT NULL,
+            created_at INTEGER
+        );
+
+        CREATE TABLE IF NOT EXISTS daily_stats (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            symbol TEXT NOT NULL,
+            date TEXT NOT NULL,
+            avg_volume REAL NOT NULL,
+            total_volume REAL NOT NULL,
+            avg_price REAL NOT NULL,
+            high_price REAL NOT NULL,
+            low_price REAL NOT NULL,
+            trade_count INTEGER NOT NULL,
+            created_at INTEGER,
+            updated_at INTEGER,
+            UNIQUE(symbol, date)
+        );
+
+        CREATE TABLE IF NOT EXISTS raw_trades (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            symbol TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            price REAL NOT NULL,
+            quantity REAL NOT NULL,
+            is_buyer_maker INTEGER NOT NULL,
+            trade_id INTEGER NOT NULL,
+            created_at INTEGER
+        );
+"#,
+    },
+    Migration {
+        version: 2,
+        description: "symbol/time indexes on stream tables",
+        sql: r#"
+        CREATE INDEX IF NOT EXISTS idx_raw_trades_symbol_timestamp ON raw_trades(symbol, timestamp);
+        CREATE INDEX IF NOT EXISTS idx_order_imbalances_symbol_timestamp ON order_imbalances(symbol, timestamp);
+        CREATE INDEX IF NOT EXISTS idx_liquidations_symbol_timestamp ON liquidations(symbol, timestamp);
+        CREATE INDEX IF NOT EXISTS idx_volume_profile_symbol_timestamp ON volume_profile(symbol, timestamp, timeframe);
+"#,
+    },
+    Migration {
+        version: 3,
+        description: "footprint candles with packed price levels",
+        sql: r#"
+        CREATE TABLE footprint_candles (
+            symbol TEXT NOT NULL,
+            timeframe TEXT NOT NULL,
+            open_time INTEGER NOT NULL,
+            tick_size REAL NOT NULL,
+            open_price REAL NOT NULL,
+            high_price REAL NOT NULL,
+            low_price REAL NOT NULL,
+            close_price REAL NOT NULL,
+            levels BLOB NOT NULL,
+            created_at INTEGER,
+            PRIMARY KEY (symbol, timeframe, open_time)
+        ) WITHOUT ROWID;
+"#,
+    },
+    Migration {
+        version: 4,
+        description: "open interest series",
+        sql: r#"
+        CREATE TABLE open_interest (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            symbol TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            open_interest REAL NOT NULL,
+            oi_change REAL NOT NULL,
+            oi_change_percentage REAL NOT NULL,
+            created_at INTEGER,
+            UNIQUE(symbol, timestamp)
+        );
+"#,
+    },
+    Migration {
+        version: 5,
+        description: "funding rate and mark price samples",
+        sql: r#"
+        CREATE TABLE funding_rates (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            symbol TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            funding_rate REAL NOT NULL,
+            mark_price REAL NOT NULL,
+            index_price REAL NOT NULL,
+            next_funding_time INTEGER NOT NULL,
+            created_at INTEGER,
+            UNIQUE(symbol, timestamp)
+        );
+"#,
+    },
+    Migration {
+        version: 6,
+        description: "order book analytics samples",
+        // Bands, slippage estimates and walls get one row per entry, `position`
+        // keeping the analyzer's order
+        sql: r#"
+        CREATE TABLE book_analytics (
+            symbol TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            mid_price REAL NOT NULL,
+            spread REAL NOT NULL,
+            spread_bps REAL NOT NULL,
+            mean_spread_bps REAL NOT NULL,
+            min_spread_bps REAL NOT NULL,
+            max_spread_bps REAL NOT NULL,
+            PRIMARY KEY (symbol, timestamp)
+        ) WITHOUT ROWID;
+
+        CREATE TABLE book_analytics_bands (
+            symbol TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            position INTEGER NOT NULL,
+            band_pct REAL NOT NULL,
+            bid_quantity REAL NOT NULL,
+            ask_quantity REAL NOT NULL,
+            imbalance REAL NOT NULL,
+            PRIMARY KEY (symbol, timestamp, position)
+        ) WITHOUT ROWID;
+
+        CREATE TABLE book_analytics_slippage (
+            symbol TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            position INTEGER NOT NULL,
+            notional REAL NOT NULL,
+            buy_bps REAL,
+            sell_bps REAL,
+            PRIMARY KEY (symbol, timestamp, position)
+        ) WITHOUT ROWID;
+
+        CREATE TABLE book_analytics_walls (
+            symbol TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            position INTEGER NOT NULL,
+            side TEXT NOT NULL,
+            price REAL NOT NULL,
+            quantity REAL NOT NULL,
+            distance_pct REAL NOT NULL,
+            size_multiple REAL NOT NULL,
+            PRIMARY KEY (symbol, timestamp, position)
+        ) WITHOUT ROWID;
+"#,
+    },
+    Migration {
+        version: 7,
+        description: "downsampled order book heatmap columns",
+        sql: r#"
+        CREATE TABLE heatmap_columns (
+            symbol TEXT NOT NULL,
+            resolution_ms INTEGER NOT NULL,
+            start_time INTEGER NOT NULL,
+            granularity REAL NOT NULL,
+            bids BLOB NOT NULL,
+            asks BLOB NOT NULL,
+            created_at INTEGER,
+            PRIMARY KEY (resolution_ms, start_time, symbol)
+        ) WITHOUT ROWID;
+"#,
+    },
+];
+
+/// Schema version written by this build
+pub const SCHEMA_VERSION: u32 = 7;
 
 pub struct DatabaseManager {
     connection: Arc<Mutex<Connection>>,
@@ -26,143 +259,55 @@ impl DatabaseManager {
         }))
     }
 
+    /// Bring the schema up to `SCHEMA_VERSION`, applying each pending migration in its own transaction
     pub async fn initialize_schema(&self) -> Result<()> {
-        let conn = self.connection.lock().await;
-        
-        println!("Creating candles table...");
-        // Create candles table
-        conn.execute(
-            r#"
-            CREATE TABLE IF NOT EXISTS candles (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                symbol TEXT NOT NULL,
-                timestamp INTEGER NOT NULL,
-                open_price REAL NOT NULL,
-                high_price REAL NOT NULL,
-                low_price REAL NOT NULL,
-                close_price REAL NOT NULL,
-                volume REAL NOT NULL,
-                buy_volume REAL NOT NULL,
-                sell_volume REAL NOT NULL,
-                timeframe TEXT NOT NULL,
-                trade_count INTEGER NOT NULL DEFAULT 0,
-                created_at INTEGER,
-                UNIQUE(symbol, timestamp, timeframe)
-            )
-            "#,
-            [],
-        )?;
-
-        println!("Creating volume profile table...");
-        // Create volume profile table
-        conn.execute(
-            r#"
-            CREATE TABLE IF NOT EXISTS volume_profile (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                symbol TEXT NOT NULL,
-                timestamp INTEGER NOT NULL,
-                price_level REAL NOT NULL,
-                buy_volume REAL NOT NULL,
-                sell_volume REAL NOT NULL,
-                total_volume REAL NOT NULL,
-                trade_count INTEGER NOT NULL DEFAULT 0,
-                timeframe TEXT NOT NULL,
-                created_at INTEGER
-            )
-            "#,
-            [],
-        )?;
+        let mut conn = self.connection.lock().await;
+        Self::migrate(&mut conn)
+    }
 
-        println!("Creating order imbalances table...");
-        // Create order imbalances table
-        conn.execute(
-            r#"
-            CREATE TABLE IF NOT EXISTS order_imbalances (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                symbol TEXT NOT NULL,
-                timestamp INTEGER NOT NULL,
-                bid_volume REAL NOT NULL,
-                ask_volume REAL NOT NULL,
-                imbalance_ratio REAL NOT NULL,
-                window_duration_seconds INTEGER NOT NULL,
-                created_at INTEGER
-            )
-            "#,
-            [],
-        )?;
+    fn migrate(conn: &mut Connection) -> Result<()> {
+        let current: u32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if current > SCHEMA_VERSION {
+            return Err(anyhow::anyhow!(
+                "Database schema version {} is newer than this build supports ({}); refusing to open it",
+                current, SCHEMA_VERSION
+            ));
+        }
 
-        println!("Creating liquidations table...");
-        // Create liquidations table
-        conn.execute(
-            r#"
-            CREATE TABLE IF NOT EXISTS liquidations (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                symbol TEXT NOT NULL,
-                timestamp INTEGER NOT NULL,
-                side TEXT NOT NULL,
-                price REAL NOT NULL,
-                quantity REAL NOT NULL,
-                is_forced INTEGER NOT NULL,
-                notional_value REAL NOT NULL,
-                created_at INTEGER
-            )
-            "#,
-            [],
-        )?;
+        for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+            info!("Applying database migration {}: {}", migration.version, migration.description);
 
-        println!("Creating daily stats table...");
-        // Create daily stats table
-        conn.execute(
-            r#"
-            CREATE TABLE IF NOT EXISTS daily_stats (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                symbol TEXT NOT NULL,
-                date TEXT NOT NULL,
-                avg_volume REAL NOT NULL,
-                total_volume REAL NOT NULL,
-                avg_price REAL NOT NULL,
-                high_price REAL NOT NULL,
-                low_price REAL NOT NULL,
-                trade_count INTEGER NOT NULL,
-                created_at INTEGER,
-                updated_at INTEGER,
-                UNIQUE(symbol, date)
-            )
-            "#,
-            [],
-        )?;
+            let tx = conn.transaction()?;
+            tx.execute_batch(migration.sql)
+                .with_context(|| format!("Migration {} failed", migration.version))?;
+            tx.pragma_update(None, "user_version", migration.version)?;
+            tx.commit()?;
+        }
 
-        println!("Creating raw trades table...");
-        // Create raw trades table for temporary storage
-        conn.execute(
-            r#"
-            CREATE TABLE IF NOT EXISTS raw_trades (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                symbol TEXT NOT NULL,
-                timestamp INTEGER NOT NULL,
-                price REAL NOT NULL,
-                quantity REAL NOT NULL,
-                is_buyer_maker INTEGER NOT NULL,
-                trade_id INTEGER NOT NULL,
-                created_at INTEGER
-            )
-            "#,
-            [],
-        )?;
-        
-        println!("Database schema initialized successfully!");
         Ok(())
     }
 
+    /// Schema version recorded in the database file
+    pub async fn schema_version(&self) -> Result<u32> {
+        let conn = self.connection.lock().await;
+        Ok(conn.pragma_query_value(None, "user_version", |row| row.get(0))?)
+    }
+
     pub async fn insert_orderflow_event(&self, event: &OrderflowEvent) -> Result<()> {
         let conn = self.connection.lock().await;
-        
-        conn.execute(
+        Self::insert_orderflow_row(&conn, event)?;
+        Ok(())
+    }
+
+    fn insert_orderflow_row(conn: &Connection, event: &OrderflowEvent) -> SqliteResult<()> {
+        let mut stmt = conn.prepare_cached(
             r#"
             INSERT OR REPLACE INTO raw_trades 
             (symbol, timestamp, price, quantity, is_buyer_maker, trade_id)
             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
             "#,
+        )?;
+        stmt.execute(
             params![
                 event.symbol,
                 event.timestamp,
@@ -206,21 +351,26 @@ impl DatabaseManager {
 
     pub async fn insert_volume_profile(&self, profile: &VolumeProfile) -> Result<()> {
         let conn = self.connection.lock().await;
-        
+        Self::insert_volume_profile_rows(&conn, profile)?;
+        Ok(())
+    }
+
+    fn insert_volume_profile_rows(conn: &Connection, profile: &VolumeProfile) -> SqliteResult<()> {
         // Clear existing profile data for this timestamp and symbol
-        conn.execute(
+        conn.prepare_cached(
             "DELETE FROM volume_profile WHERE symbol = ?1 AND timestamp = ?2 AND timeframe = ?3",
-            params![profile.symbol, profile.timestamp, profile.timeframe],
-        )?;
+        )?.execute(params![profile.symbol, profile.timestamp, profile.timeframe])?;
 
         // Insert new profile data
+        let mut stmt = conn.prepare_cached(
+            r#"
+            INSERT INTO volume_profile 
+            (symbol, timestamp, price_level, buy_volume, sell_volume, total_volume, trade_count, timeframe)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            "#,
+        )?;
         for (price, volume_data) in &profile.price_levels {
-            conn.execute(
-                r#"
-                INSERT INTO volume_profile 
-                (symbol, timestamp, price_level, buy_volume, sell_volume, total_volume, trade_count, timeframe)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
-                "#,
+            stmt.execute(
                 params![
                     profile.symbol,
                     profile.timestamp,
@@ -239,13 +389,19 @@ impl DatabaseManager {
 
     pub async fn insert_order_imbalance(&self, imbalance: &OrderImbalance) -> Result<()> {
         let conn = self.connection.lock().await;
-        
-        conn.execute(
+        Self::insert_order_imbalance_row(&conn, imbalance)?;
+        Ok(())
+    }
+
+    fn insert_order_imbalance_row(conn: &Connection, imbalance: &OrderImbalance) -> SqliteResult<()> {
+        let mut stmt = conn.prepare_cached(
             r#"
             INSERT INTO order_imbalances 
             (symbol, timestamp, bid_volume, ask_volume, imbalance_ratio, window_duration_seconds)
             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
             "#,
+        )?;
+        stmt.execute(
             params![
                 imbalance.symbol,
                 imbalance.timestamp,
@@ -261,13 +417,19 @@ impl DatabaseManager {
 
     pub async fn insert_liquidation(&self, liquidation: &LiquidationEvent) -> Result<()> {
         let conn = self.connection.lock().await;
-        
-        conn.execute(
+        Self::insert_liquidation_row(&conn, liquidation)?;
+        Ok(())
+    }
+
+    fn insert_liquidation_row(conn: &Connection, liquidation: &LiquidationEvent) -> SqliteResult<()> {
+        let mut stmt = conn.prepare_cached(
             r#"
             INSERT INTO liquidations 
             (symbol, timestamp, side, price, quantity, is_forced, notional_value)
             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
             "#,
+        )?;
+        stmt.execute(
             params![
                 liquidation.symbol,
                 liquidation.timestamp,
@@ -282,6 +444,307 @@ impl DatabaseManager {
         Ok(())
     }
 
+    /// Readings repeat when polled history overlaps stored rows; the latest write wins
+    fn insert_open_interest_row(conn: &Connection, reading: &OpenInterestData) -> SqliteResult<()> {
+        let mut stmt = conn.prepare_cached(
+            r#"
+            INSERT OR REPLACE INTO open_interest
+            (symbol, timestamp, open_interest, oi_change, oi_change_percentage)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+        )?;
+        stmt.execute(
+            params![
+                reading.symbol,
+                reading.timestamp,
+                reading.open_interest,
+                reading.oi_change_24h,
+                reading.oi_change_percentage
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn insert_book_analytics_row(conn: &Connection, analytics: &BookAnalytics) -> SqliteResult<()> {
+        let key = params![analytics.symbol, analytics.timestamp];
+        for table in ["book_analytics_bands", "book_analytics_slippage", "book_analytics_walls"] {
+            conn.prepare_cached(&format!("DELETE FROM {} WHERE symbol = ?1 AND timestamp = ?2", table))?
+                .execute(key)?;
+        }
+
+        let spread = &analytics.spread;
+        conn.prepare_cached(
+            r#"
+            INSERT OR REPLACE INTO book_analytics
+            (symbol, timestamp, mid_price, spread, spread_bps, mean_spread_bps, min_spread_bps, max_spread_bps)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            "#,
+        )?.execute(params![
+            analytics.symbol,
+            analytics.timestamp,
+            analytics.mid_price,
+            spread.spread,
+            spread.spread_bps,
+            spread.mean_bps,
+            spread.min_bps,
+            spread.max_bps
+        ])?;
+
+        let mut stmt = conn.prepare_cached(
+            r#"
+            INSERT INTO book_analytics_bands
+            (symbol, timestamp, position, band_pct, bid_quantity, ask_quantity, imbalance)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#,
+        )?;
+        for (position, band) in analytics.bands.iter().enumerate() {
+            stmt.execute(params![
+                analytics.symbol,
+                analytics.timestamp,
+                position,
+                band.band_pct,
+                band.bid_quantity,
+                band.ask_quantity,
+                band.imbalance
+            ])?;
+        }
+
+        let mut stmt = conn.prepare_cached(
+            r#"
+            INSERT INTO book_analytics_slippage
+            (symbol, timestamp, position, notional, buy_bps, sell_bps)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+        )?;
+        for (position, estimate) in analytics.slippage.iter().enumerate() {
+            stmt.execute(params![
+                analytics.symbol,
+                analytics.timestamp,
+                position,
+                estimate.notional,
+                estimate.buy_bps,
+                estimate.sell_bps
+            ])?;
+        }
+
+        let mut stmt = conn.prepare_cached(
+            r#"
+            INSERT INTO book_analytics_walls
+            (symbol, timestamp, position, side, price, quantity, distance_pct, size_multiple)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            "#,
+        )?;
+        for (position, wall) in analytics.walls.iter().enumerate() {
+            stmt.execute(params![
+                analytics.symbol,
+                analytics.timestamp,
+                position,
+                wall.side,
+                wall.price,
+                wall.quantity,
+                wall.distance_pct,
+                wall.size_multiple
+            ])?;
+        }
+
+        Ok(())
+    }
+
+    fn insert_footprint_candle_row(conn: &Connection, candle: &StoredFootprintCandle) -> SqliteResult<()> {
+        let mut stmt = conn.prepare_cached(
+            r#"
+            INSERT OR REPLACE INTO footprint_candles
+            (symbol, timeframe, open_time, tick_size, open_price, high_price, low_price, close_price, levels, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+            "#,
+        )?;
+        stmt.execute(
+            params![
+                candle.symbol,
+                candle.timeframe,
+                candle.open_time,
+                candle.tick_size,
+                candle.open,
+                candle.high,
+                candle.low,
+                candle.close,
+                candle.encode_levels(),
+                Utc::now().timestamp()
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// A column closed again after a price step change replaces the earlier part
+    fn insert_heatmap_column_row(conn: &Connection, column: &HeatmapColumn) -> SqliteResult<()> {
+        let mut stmt = conn.prepare_cached(
+            r#"
+            INSERT OR REPLACE INTO heatmap_columns
+            (symbol, resolution_ms, start_time, granularity, bids, asks)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+        )?;
+        stmt.execute(
+            params![
+                column.symbol,
+                column.resolution_ms,
+                column.start,
+                column.granularity,
+                encode_heatmap_levels(&column.bids),
+                encode_heatmap_levels(&column.asks)
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    fn insert_funding_rate_row(conn: &Connection, reading: &FundingRateData) -> SqliteResult<()> {
+        let mut stmt = conn.prepare_cached(
+            r#"
+            INSERT OR REPLACE INTO funding_rates
+            (symbol, timestamp, funding_rate, mark_price, index_price, next_funding_time)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+        )?;
+        stmt.execute(
+            params![
+                reading.symbol,
+                reading.timestamp,
+                reading.funding_rate,
+                reading.mark_price,
+                reading.index_price,
+                reading.next_funding_time()
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Insert many trades in one transaction, reusing the cached insert statement
+    pub async fn insert_orderflow_batch(&self, events: &[OrderflowEvent]) -> Result<usize> {
+        let mut conn = self.connection.lock().await;
+        let tx = conn.transaction()?;
+
+        for event in events {
+            Self::insert_orderflow_row(&tx, event)?;
+        }
+
+        tx.commit()?;
+        Ok(events.len())
+    }
+
+    /// Insert many liquidations in one transaction, reusing the cached insert statement
+    pub async fn insert_liquidations_batch(&self, liquidations: &[LiquidationEvent]) -> Result<usize> {
+        let mut conn = self.connection.lock().await;
+        let tx = conn.transaction()?;
+
+        for liquidation in liquidations {
+            Self::insert_liquidation_row(&tx, liquidation)?;
+        }
+
+        tx.commit()?;
+        Ok(liquidations.len())
+    }
+
+    /// Insert many imbalances in one transaction, reusing the cached insert statement
+    pub async fn insert_imbalances_batch(&self, imbalances: &[OrderImbalance]) -> Result<usize> {
+        let mut conn = self.connection.lock().await;
+        let tx = conn.transaction()?;
+
+        for imbalance in imbalances {
+            Self::insert_order_imbalance_row(&tx, imbalance)?;
+        }
+
+        tx.commit()?;
+        Ok(imbalances.len())
+    }
+
+    /// Insert or replace footprint candles in one transaction; a candle rewritten
+    /// with the same (symbol, timeframe, open time) replaces the stored one
+    pub async fn insert_footprint_candles(&self, candles: &[StoredFootprintCandle]) -> Result<usize> {
+        let mut conn = self.connection.lock().await;
+        let tx = conn.transaction()?;
+        for candle in candles {
+            Self::insert_footprint_candle_row(&tx, candle)?;
+        }
+        tx.commit()?;
+        Ok(candles.len())
+    }
+
+    /// Footprint candles for `symbol` and `timeframe` opening in `[start_ms, end_ms)`, oldest first
+    pub async fn get_footprint_candles(
+        &self,
+        symbol: &str,
+        timeframe: &str,
+        start_ms: u64,
+        end_ms: u64,
+    ) -> Result<Vec<StoredFootprintCandle>> {
+        let conn = self.connection.lock().await;
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT open_time, tick_size, open_price, high_price, low_price, close_price, levels
+            FROM footprint_candles
+            WHERE symbol = ?1 AND timeframe = ?2 AND open_time >= ?3 AND open_time < ?4
+            ORDER BY open_time
+            "#
+        )?;
+
+        let rows = stmt.query_map(params![symbol, timeframe, start_ms, end_ms], |row| {
+            Ok((
+                row.get::<_, u64>(0)?,
+                row.get::<_, f64>(1)?,
+                [row.get::<_, f64>(2)?, row.get(3)?, row.get(4)?, row.get(5)?],
+                row.get::<_, Vec<u8>>(6)?,
+            ))
+        })?;
+
+        let mut candles = Vec::new();
+        for row in rows {
+            let (open_time, tick_size, [open, high, low, close], blob) = row?;
+            let levels = decode_levels(&blob)
+                .with_context(|| format!("Corrupt footprint levels for {} {} at {}", symbol, timeframe, open_time))?;
+            candles.push(StoredFootprintCandle {
+                symbol: symbol.to_string(),
+                timeframe: timeframe.to_string(),
+                open_time,
+                tick_size,
+                open,
+                high,
+                low,
+                close,
+                levels,
+            });
+        }
+
+        Ok(candles)
+    }
+
+    /// Write a mixed batch of stream rows in a single transaction
+    pub async fn write_batch(&self, records: &[PersistRecord]) -> Result<()> {
+        let mut conn = self.connection.lock().await;
+        let tx = conn.transaction()?;
+
+        for record in records {
+            match record {
+                PersistRecord::Trade(event) => Self::insert_orderflow_row(&tx, event)?,
+                PersistRecord::Imbalance(imbalance) => Self::insert_order_imbalance_row(&tx, imbalance)?,
+                PersistRecord::Liquidation(liquidation) => Self::insert_liquidation_row(&tx, liquidation)?,
+                PersistRecord::VolumeProfile(profile) => Self::insert_volume_profile_rows(&tx, profile)?,
+                PersistRecord::OpenInterest(reading) => Self::insert_open_interest_row(&tx, reading)?,
+                PersistRecord::FundingRate(reading) => Self::insert_funding_rate_row(&tx, reading)?,
+                PersistRecord::BookAnalytics(analytics) => Self::insert_book_analytics_row(&tx, analytics)?,
+                PersistRecord::HeatmapColumn(column) => Self::insert_heatmap_column_row(&tx, column)?,
+                PersistRecord::FootprintCandle(candle) => Self::insert_footprint_candle_row(&tx, candle)?,
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
     pub async fn insert_or_update_daily_stats(&self, stats: &DailyStats) -> Result<()> {
         let conn = self.connection.lock().await;
         
@@ -402,6 +865,346 @@ impl DatabaseManager {
         Ok(())
     }
 
+    /// Highest row id in one of the append-only stream tables, 0 when empty
+    pub async fn latest_row_id(&self, table: &str) -> Result<i64> {
+        if !["raw_trades", "order_imbalances", "liquidations"].contains(&table) {
+            return Err(anyhow::anyhow!("Unknown stream table {}", table));
+        }
+
+        let conn = self.connection.lock().await;
+        let id: i64 = conn.query_row(&format!("SELECT COALESCE(MAX(id), 0) FROM {}", table), [], |row| row.get(0))?;
+        Ok(id)
+    }
+
+    /// Trades inserted after `after_id`, oldest first, with their row ids
+    pub async fn get_trades_after(&self, after_id: i64, limit: usize) -> Result<Vec<(i64, OrderflowEvent)>> {
+        let conn = self.connection.lock().await;
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, symbol, timestamp, price, quantity, is_buyer_maker, trade_id
+            FROM raw_trades
+            WHERE id > ?1
+            ORDER BY id
+            LIMIT ?2
+            "#
+        )?;
+
+        let rows = stmt.query_map(params![after_id, limit], |row| {
+            Ok((row.get(0)?, OrderflowEvent {
+                symbol: row.get(1)?,
+                timestamp: row.get(2)?,
+                price: row.get(3)?,
+                quantity: row.get(4)?,
+                is_buyer_maker: row.get::<_, i64>(5)? != 0,
+                trade_id: row.get(6)?,
+            }))
+        })?;
+
+        Ok(rows.collect::<SqliteResult<Vec<_>>>()?)
+    }
+
+    /// Trades for `symbol` with `start_ms <= timestamp < end_ms`, ordered by aggregate
+    /// trade id. Rows written twice are returned twice.
+    pub async fn get_trades_between(&self, symbol: &str, start_ms: u64, end_ms: u64) -> Result<Vec<OrderflowEvent>> {
+        let conn = self.connection.lock().await;
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT symbol, timestamp, price, quantity, is_buyer_maker, trade_id
+            FROM raw_trades
+            WHERE symbol = ?1 AND timestamp >= ?2 AND timestamp < ?3
+            ORDER BY trade_id
+            "#
+        )?;
+
+        let rows = stmt.query_map(params![symbol, start_ms, end_ms], |row| {
+            Ok(OrderflowEvent {
+                symbol: row.get(0)?,
+                timestamp: row.get(1)?,
+                price: row.get(2)?,
+                quantity: row.get(3)?,
+                is_buyer_maker: row.get::<_, i64>(4)? != 0,
+                trade_id: row.get(5)?,
+            })
+        })?;
+
+        Ok(rows.collect::<SqliteResult<Vec<_>>>()?)
+    }
+
+    /// Open interest readings for `symbol` with `start_ms <= timestamp < end_ms`, oldest first
+    pub async fn get_open_interest(&self, symbol: &str, start_ms: u64, end_ms: u64) -> Result<Vec<OpenInterestData>> {
+        let conn = self.connection.lock().await;
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT symbol, timestamp, open_interest, oi_change, oi_change_percentage
+            FROM open_interest
+            WHERE symbol = ?1 AND timestamp >= ?2 AND timestamp < ?3
+            ORDER BY timestamp
+            "#
+        )?;
+
+        let rows = stmt.query_map(params![symbol, start_ms, end_ms], |row| {
+            Ok(OpenInterestData {
+                symbol: row.get(0)?,
+                timestamp: row.get(1)?,
+                open_interest: row.get(2)?,
+                oi_change_24h: row.get(3)?,
+                oi_change_percentage: row.get(4)?,
+            })
+        })?;
+
+        Ok(rows.collect::<SqliteResult<Vec<_>>>()?)
+    }
+
+    /// Funding samples for `symbol` with `start_ms <= timestamp < end_ms`, oldest first
+    pub async fn get_funding_rates(&self, symbol: &str, start_ms: u64, end_ms: u64) -> Result<Vec<FundingRateData>> {
+        let conn = self.connection.lock().await;
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT symbol, timestamp, funding_rate, mark_price, index_price, next_funding_time
+            FROM funding_rates
+            WHERE symbol = ?1 AND timestamp >= ?2 AND timestamp < ?3
+            ORDER BY timestamp
+            "#
+        )?;
+
+        let rows = stmt.query_map(params![symbol, start_ms, end_ms], |row| {
+            let timestamp: u64 = row.get(1)?;
+            let next_funding_time: u64 = row.get(5)?;
+            let mut reading = FundingRateData::new(row.get(0)?, timestamp, row.get(2)?);
+            reading.mark_price = row.get(3)?;
+            reading.index_price = row.get(4)?;
+            reading.funding_interval = next_funding_time.saturating_sub(timestamp) / 1000;
+            Ok(reading)
+        })?;
+
+        Ok(rows.collect::<SqliteResult<Vec<_>>>()?)
+    }
+
+    /// Book analytics samples for `symbol` with `start_ms <= timestamp < end_ms`, oldest first
+    pub async fn get_book_analytics(&self, symbol: &str, start_ms: u64, end_ms: u64) -> Result<Vec<BookAnalytics>> {
+        let conn = self.connection.lock().await;
+        let range = params![symbol, start_ms, end_ms];
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT timestamp, mid_price, spread, spread_bps, mean_spread_bps, min_spread_bps, max_spread_bps
+            FROM book_analytics
+            WHERE symbol = ?1 AND timestamp >= ?2 AND timestamp < ?3
+            ORDER BY timestamp
+            "#
+        )?;
+        let mut samples = stmt.query_map(range, |row| {
+            Ok(BookAnalytics {
+                symbol: symbol.to_string(),
+                timestamp: row.get(0)?,
+                mid_price: row.get(1)?,
+                spread: SpreadStats {
+                    spread: row.get(2)?,
+                    spread_bps: row.get(3)?,
+                    mean_bps: row.get(4)?,
+                    min_bps: row.get(5)?,
+                    max_bps: row.get(6)?,
+                },
+                bands: Vec::new(),
+                slippage: Vec::new(),
+                walls: Vec::new(),
+            })
+        })?.collect::<SqliteResult<Vec<_>>>()?;
+        let index: HashMap<u64, usize> = samples.iter().enumerate().map(|(i, sample)| (sample.timestamp, i)).collect();
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT timestamp, band_pct, bid_quantity, ask_quantity, imbalance
+            FROM book_analytics_bands
+            WHERE symbol = ?1 AND timestamp >= ?2 AND timestamp < ?3
+            ORDER BY timestamp, position
+            "#
+        )?;
+        let bands = stmt.query_map(range, |row| {
+            Ok((row.get::<_, u64>(0)?, BandImbalance {
+                band_pct: row.get(1)?,
+                bid_quantity: row.get(2)?,
+                ask_quantity: row.get(3)?,
+                imbalance: row.get(4)?,
+            }))
+        })?;
+        for band in bands {
+            let (timestamp, band) = band?;
+            if let Some(&i) = index.get(&timestamp) {
+                samples[i].bands.push(band);
+            }
+        }
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT timestamp, notional, buy_bps, sell_bps
+            FROM book_analytics_slippage
+            WHERE symbol = ?1 AND timestamp >= ?2 AND timestamp < ?3
+            ORDER BY timestamp, position
+            "#
+        )?;
+        let estimates = stmt.query_map(range, |row| {
+            Ok((row.get::<_, u64>(0)?, SlippageEstimate {
+                notional: row.get(1)?,
+                buy_bps: row.get(2)?,
+                sell_bps: row.get(3)?,
+            }))
+        })?;
+        for estimate in estimates {
+            let (timestamp, estimate) = estimate?;
+            if let Some(&i) = index.get(&timestamp) {
+                samples[i].slippage.push(estimate);
+            }
+        }
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT timestamp, side, price, quantity, distance_pct, size_multiple
+            FROM book_analytics_walls
+            WHERE symbol = ?1 AND timestamp >= ?2 AND timestamp < ?3
+            ORDER BY timestamp, position
+            "#
+        )?;
+        let walls = stmt.query_map(range, |row| {
+            Ok((row.get::<_, u64>(0)?, LiquidityWall {
+                side: row.get(1)?,
+                price: row.get(2)?,
+                quantity: row.get(3)?,
+                distance_pct: row.get(4)?,
+                size_multiple: row.get(5)?,
+            }))
+        })?;
+        for wall in walls {
+            let (timestamp, wall) = wall?;
+            if let Some(&i) = index.get(&timestamp) {
+                samples[i].walls.push(wall);
+            }
+        }
+
+        Ok(samples)
+    }
+
+    /// Delete the book analytics samples of every symbol taken before `before_ms`
+    pub async fn delete_book_analytics_before(&self, before_ms: u64) -> Result<usize> {
+        let mut conn = self.connection.lock().await;
+        let tx = conn.transaction()?;
+        for table in ["book_analytics_bands", "book_analytics_slippage", "book_analytics_walls"] {
+            tx.execute(&format!("DELETE FROM {} WHERE timestamp < ?1", table), params![before_ms])?;
+        }
+        let deleted = tx.execute("DELETE FROM book_analytics WHERE timestamp < ?1", params![before_ms])?;
+        tx.commit()?;
+        Ok(deleted)
+    }
+
+    /// Heatmap columns of `symbol` at `resolution_ms` starting at or after `start_ms`, oldest first
+    pub async fn get_heatmap_columns(&self, symbol: &str, resolution_ms: u64, start_ms: u64) -> Result<Vec<HeatmapColumn>> {
+        let conn = self.connection.lock().await;
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT start_time, granularity, bids, asks
+            FROM heatmap_columns
+            WHERE resolution_ms = ?1 AND start_time >= ?2 AND symbol = ?3
+            ORDER BY start_time
+            "#
+        )?;
+
+        let rows = stmt.query_map(params![resolution_ms, start_ms, symbol], |row| {
+            Ok((
+                row.get::<_, u64>(0)?,
+                row.get::<_, f64>(1)?,
+                row.get::<_, Vec<u8>>(2)?,
+                row.get::<_, Vec<u8>>(3)?,
+            ))
+        })?;
+
+        let mut columns = Vec::new();
+        for row in rows {
+            let (start, granularity, bids, asks) = row?;
+            let corrupt = || format!("Corrupt heatmap column for {} at {}", symbol, start);
+            columns.push(HeatmapColumn {
+                bids: decode_heatmap_levels(&bids).with_context(corrupt)?,
+                asks: decode_heatmap_levels(&asks).with_context(corrupt)?,
+                symbol: symbol.to_string(),
+                start,
+                resolution_ms,
+                granularity,
+            });
+        }
+
+        Ok(columns)
+    }
+
+    /// Delete heatmap columns at `resolution_ms` that start before `before_ms`
+    pub async fn delete_heatmap_columns_before(&self, resolution_ms: u64, before_ms: u64) -> Result<usize> {
+        let conn = self.connection.lock().await;
+        Ok(conn.execute(
+            "DELETE FROM heatmap_columns WHERE resolution_ms = ?1 AND start_time < ?2",
+            params![resolution_ms, before_ms],
+        )?)
+    }
+
+    /// Imbalances inserted after `after_id`, oldest first, with their row ids
+    pub async fn get_imbalances_after(&self, after_id: i64, limit: usize) -> Result<Vec<(i64, OrderImbalance)>> {
+        let conn = self.connection.lock().await;
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, symbol, timestamp, bid_volume, ask_volume, imbalance_ratio, window_duration_seconds
+            FROM order_imbalances
+            WHERE id > ?1
+            ORDER BY id
+            LIMIT ?2
+            "#
+        )?;
+
+        let rows = stmt.query_map(params![after_id, limit], |row| {
+            Ok((row.get(0)?, OrderImbalance {
+                symbol: row.get(1)?,
+                timestamp: row.get(2)?,
+                bid_volume: row.get(3)?,
+                ask_volume: row.get(4)?,
+                imbalance_ratio: row.get(5)?,
+                window_duration_seconds: row.get(6)?,
+            }))
+        })?;
+
+        Ok(rows.collect::<SqliteResult<Vec<_>>>()?)
+    }
+
+    /// Liquidations inserted after `after_id`, oldest first, with their row ids
+    pub async fn get_liquidations_after(&self, after_id: i64, limit: usize) -> Result<Vec<(i64, LiquidationEvent)>> {
+        let conn = self.connection.lock().await;
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT id, symbol, timestamp, side, price, quantity, is_forced, notional_value
+            FROM liquidations
+            WHERE id > ?1
+            ORDER BY id
+            LIMIT ?2
+            "#
+        )?;
+
+        let rows = stmt.query_map(params![after_id, limit], |row| {
+            Ok((row.get(0)?, LiquidationEvent {
+                symbol: row.get(1)?,
+                timestamp: row.get(2)?,
+                side: row.get(3)?,
+                price: row.get(4)?,
+                quantity: row.get(5)?,
+                is_forced: row.get::<_, i64>(6)? != 0,
+                notional_value: row.get(7)?,
+            }))
+        })?;
+
+        Ok(rows.collect::<SqliteResult<Vec<_>>>()?)
+    }
+
     pub async fn get_connection_stats(&self) -> Result<String> {
         let conn = self.connection.lock().await;
         
@@ -418,4 +1221,330 @@ impl DatabaseManager {
 
         Ok(stats)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+impl DatabaseManager {
+    /// Hold the connection lock, e.g. to simulate a stalled writer
+    pub async fn connection_for_tests(&self) -> tokio::sync::MutexGuard<'_, Connection> {
+        self.connection.lock().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{BandImbalance, LiquidityWall, SlippageEstimate, SpreadStats};
+
+    fn liquidation(symbol: &str, timestamp: u64) -> LiquidationEvent {
+        LiquidationEvent {
+            symbol: symbol.to_string(),
+            timestamp,
+            side: "SELL".to_string(),
+            price: 100.0,
+            quantity: 2.0,
+            is_forced: true,
+            notional_value: 200.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_rows_after_id() {
+        let db = DatabaseManager::new(":memory:").await.unwrap();
+        db.initialize_schema().await.unwrap();
+        assert_eq!(db.latest_row_id("liquidations").await.unwrap(), 0);
+
+        for i in 0..5 {
+            db.insert_liquidation(&liquidation("BTCUSDT", i)).await.unwrap();
+        }
+        assert_eq!(db.latest_row_id("liquidations").await.unwrap(), 5);
+        assert!(db.latest_row_id("daily_stats; DROP TABLE candles").await.is_err());
+
+        let rows = db.get_liquidations_after(2, 10).await.unwrap();
+        assert_eq!(rows.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![3, 4, 5]);
+        assert_eq!(rows[0].1.timestamp, 2);
+        assert!(rows[0].1.is_forced);
+
+        assert_eq!(db.get_liquidations_after(0, 2).await.unwrap().len(), 2);
+        assert!(db.get_liquidations_after(5, 10).await.unwrap().is_empty());
+    }
+
+    fn synthetic_trade(i: u64) -> OrderflowEvent {
+        OrderflowEvent {
+            symbol: ["BTCUSDT", "ETHUSDT", "SOLUSDT", "BNBUSDT"][(i % 4) as usize].to_string(),
+            timestamp: 1_700_000_000_000 + i,
+            price: 100.0 + (i % 1000) as f64 * 0.01,
+            quantity: 0.001 * (1 + i % 50) as f64,
+            is_buyer_maker: i.is_multiple_of(3),
+            trade_id: i,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_inserts() {
+        let db = DatabaseManager::new(":memory:").await.unwrap();
+        db.initialize_schema().await.unwrap();
+
+        let trades: Vec<_> = (0..1000).map(synthetic_trade).collect();
+        assert_eq!(db.insert_orderflow_batch(&trades).await.unwrap(), 1000);
+        assert_eq!(db.insert_liquidations_batch(&[liquidation("ETHUSDT", 7)]).await.unwrap(), 1);
+
+        let stored = db.get_trades_after(998, 10).await.unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[1].1.trade_id, 999);
+        assert_eq!(stored[1].1.symbol, "BNBUSDT");
+        assert_eq!(db.get_liquidations_after(0, 10).await.unwrap()[0].1.symbol, "ETHUSDT");
+
+        let window = db.get_trades_between("BTCUSDT", 1_700_000_000_100, 1_700_000_000_200).await.unwrap();
+        assert_eq!(window.len(), 25);
+        assert_eq!((window[0].trade_id, window[24].trade_id), (100, 196));
+    }
+
+    /// Throughput benchmark: `cargo test --release bench_insert_orderflow_batch -- --ignored --nocapture`
+    #[tokio::test]
+    #[ignore]
+    async fn bench_insert_orderflow_batch() {
+        const EVENTS: u64 = 1_000_000;
+        const BATCH: u64 = 10_000;
+
+        let path = std::env::temp_dir().join(format!("binance-screener-bench-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let db = DatabaseManager::new(path.to_str().unwrap()).await.unwrap();
+        db.initialize_schema().await.unwrap();
+
+        let started = std::time::Instant::now();
+        for chunk_start in (0..EVENTS).step_by(BATCH as usize) {
+            let batch: Vec<_> = (chunk_start..chunk_start + BATCH).map(synthetic_trade).collect();
+            db.insert_orderflow_batch(&batch).await.unwrap();
+        }
+        let elapsed = started.elapsed();
+
+        assert_eq!(db.latest_row_id("raw_trades").await.unwrap(), EVENTS as i64);
+        println!(
+            "Inserted {} trades in {:.2?} ({:.0} rows/s, batches of {})",
+            EVENTS, elapsed, EVENTS as f64 / elapsed.as_secs_f64(), BATCH
+        );
+
+        drop(db);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+
+    fn temp_db_path(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("binance-screener-{}-{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[tokio::test]
+    async fn test_migrates_unversioned_fixture_forward() {
+        let path = temp_db_path("migrate-v0");
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(include_str!("../../tests/fixtures/schema_v0.sql")).unwrap();
+        }
+
+        let db = DatabaseManager::new(path.to_str().unwrap()).await.unwrap();
+        assert_eq!(db.schema_version().await.unwrap(), 0);
+        db.initialize_schema().await.unwrap();
+        assert_eq!(db.schema_version().await.unwrap(), SCHEMA_VERSION);
+
+        // Existing rows survive and the new indexes exist
+        let trades = db.get_trades_after(0, 10).await.unwrap();
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].1.trade_id, 1001);
+        assert_eq!(db.get_liquidations_after(0, 10).await.unwrap()[0].1.symbol, "ETHUSDT");
+
+        let index_count: i64 = db.connection_for_tests().await.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'index' AND name = 'idx_raw_trades_symbol_timestamp'",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+        assert_eq!(index_count, 1);
+
+        // Re-running is a no-op
+        db.initialize_schema().await.unwrap();
+        assert_eq!(db.schema_version().await.unwrap(), SCHEMA_VERSION);
+
+        drop(db);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_migrates_from_intermediate_version() {
+        let db = DatabaseManager::new(":memory:").await.unwrap();
+        {
+            let mut conn = db.connection_for_tests().await;
+            let tx = conn.transaction().unwrap();
+            tx.execute_batch(MIGRATIONS[0].sql).unwrap();
+            tx.pragma_update(None, "user_version", 1).unwrap();
+            tx.commit().unwrap();
+        }
+
+        db.initialize_schema().await.unwrap();
+        assert_eq!(db.schema_version().await.unwrap(), SCHEMA_VERSION);
+    }
+
+    #[tokio::test]
+    async fn test_refuses_newer_schema() {
+        let db = DatabaseManager::new(":memory:").await.unwrap();
+        db.connection_for_tests().await.pragma_update(None, "user_version", SCHEMA_VERSION + 1).unwrap();
+
+        let err = db.initialize_schema().await.unwrap_err();
+        assert!(err.to_string().contains("newer"));
+        assert_eq!(db.schema_version().await.unwrap(), SCHEMA_VERSION + 1);
+    }
+
+    #[tokio::test]
+    async fn test_footprint_candles_round_trip() {
+        use crate::data::FootprintLevel;
+
+        let db = DatabaseManager::new(":memory:").await.unwrap();
+        db.initialize_schema().await.unwrap();
+
+        let candle = |timeframe: &str, open_time: u64, ask_volume: f64| StoredFootprintCandle {
+            symbol: "BTCUSDT".to_string(),
+            timeframe: timeframe.to_string(),
+            open_time,
+            tick_size: 0.1,
+            open: 37000.1,
+            high: 37002.5,
+            low: 36999.9,
+            close: 37001.0,
+            levels: vec![
+                FootprintLevel { tick: 369_999, bid_volume: 4.0, ask_volume: 0.0 },
+                FootprintLevel { tick: 370_001, bid_volume: 0.0, ask_volume },
+                FootprintLevel { tick: 370_025, bid_volume: 9.5, ask_volume: 0.002 },
+            ],
+        };
+
+        let first = candle("1m", 1_700_000_040_000, 3.0);
+        let second = candle("1m", 1_700_000_100_000, 8.25);
+        db.insert_footprint_candles(&[second.clone(), first.clone(), candle("5m", 1_700_000_100_000, 1.0)]).await.unwrap();
+        let loaded = db.get_footprint_candles("BTCUSDT", "1m", 0, u64::MAX / 2).await.unwrap();
+        assert_eq!(loaded, vec![first.clone(), second]);
+
+        // Rewriting an open candle replaces it
+        let updated = candle("1m", 1_700_000_100_000, 11.0);
+        db.insert_footprint_candles(std::slice::from_ref(&updated)).await.unwrap();
+        let loaded = db.get_footprint_candles("BTCUSDT", "1m", 1_700_000_100_000, 1_700_000_160_000).await.unwrap();
+        assert_eq!(loaded, vec![updated]);
+
+        assert!(db.get_footprint_candles("ETHUSDT", "1m", 0, u64::MAX / 2).await.unwrap().is_empty());
+        assert_eq!(db.get_footprint_candles("BTCUSDT", "5m", 0, u64::MAX / 2).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_open_interest_series() {
+        let db = DatabaseManager::new(":memory:").await.unwrap();
+        db.initialize_schema().await.unwrap();
+
+        let reading = |timestamp: u64, open_interest: f64, previous: f64| {
+            let mut reading = OpenInterestData::new("BTCUSDT".to_string(), timestamp, open_interest);
+            reading.calculate_change(previous);
+            PersistRecord::OpenInterest(reading)
+        };
+        db.write_batch(&[reading(300_000, 100.0, 0.0), reading(600_000, 104.0, 100.0), reading(900_000, 99.0, 104.0)]).await.unwrap();
+        // A polled reading landing on a stored history timestamp replaces it
+        db.write_batch(&[reading(600_000, 105.0, 100.0)]).await.unwrap();
+
+        let series = db.get_open_interest("BTCUSDT", 300_000, 900_000).await.unwrap();
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[1].open_interest, 105.0);
+        assert!((series[1].oi_change_percentage - 5.0).abs() < 1e-9);
+        assert!(db.get_open_interest("ETHUSDT", 0, 1_000_000).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_funding_rate_series() {
+        let db = DatabaseManager::new(":memory:").await.unwrap();
+        db.initialize_schema().await.unwrap();
+
+        let sample = |timestamp: u64, funding_rate: f64| {
+            let mut reading = FundingRateData::new("BTCUSDT".to_string(), timestamp, funding_rate);
+            reading.mark_price = 65_010.0;
+            reading.index_price = 65_000.0;
+            reading.funding_interval = 3_600;
+            PersistRecord::FundingRate(reading)
+        };
+        db.write_batch(&[sample(60_000, 0.0001), sample(120_000, 0.0003)]).await.unwrap();
+
+        let series = db.get_funding_rates("BTCUSDT", 0, 1_000_000).await.unwrap();
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[1].funding_rate, 0.0003);
+        assert_eq!(series[1].mark_price, 65_010.0);
+        assert_eq!(series[1].next_funding_time(), 120_000 + 3_600_000);
+        assert!(db.get_funding_rates("BTCUSDT", 120_001, 1_000_000).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_book_analytics_round_trip() {
+        let db = DatabaseManager::new(":memory:").await.unwrap();
+        db.initialize_schema().await.unwrap();
+
+        let analytics = BookAnalytics {
+            symbol: "BTCUSDT".to_string(),
+            timestamp: 60_000,
+            mid_price: 65_000.05,
+            spread: SpreadStats { spread: 0.1, spread_bps: 0.015, mean_bps: 0.02, min_bps: 0.015, max_bps: 0.04 },
+            bands: vec![BandImbalance { band_pct: 0.1, bid_quantity: 12.0, ask_quantity: 4.0, imbalance: 0.5 }],
+            slippage: vec![SlippageEstimate { notional: 1_000_000.0, buy_bps: Some(1.2), sell_bps: None }],
+            walls: vec![LiquidityWall { side: "BID".to_string(), price: 64_900.0, quantity: 80.0, distance_pct: 0.15, size_multiple: 9.0 }],
+        };
+        let mut later = analytics.clone();
+        later.timestamp = 120_000;
+        later.bands.push(BandImbalance { band_pct: 1.0, bid_quantity: 30.0, ask_quantity: 50.0, imbalance: -0.25 });
+        later.walls.clear();
+        db.write_batch(&[PersistRecord::BookAnalytics(analytics.clone()), PersistRecord::BookAnalytics(later.clone())]).await.unwrap();
+        // Rewriting a sample replaces its entries instead of adding to them
+        db.write_batch(&[PersistRecord::BookAnalytics(later.clone())]).await.unwrap();
+
+        assert_eq!(db.get_book_analytics("BTCUSDT", 0, 180_000).await.unwrap(), vec![analytics.clone(), later.clone()]);
+        assert!(db.get_book_analytics("BTCUSDT", 60_001, 120_000).await.unwrap().is_empty());
+
+        assert_eq!(db.delete_book_analytics_before(120_000).await.unwrap(), 1);
+        assert_eq!(db.get_book_analytics("BTCUSDT", 0, 180_000).await.unwrap(), vec![later]);
+        let walls: u32 = db.connection_for_tests().await
+            .query_row("SELECT COUNT(*) FROM book_analytics_walls", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(walls, 0);
+    }
+
+    #[tokio::test]
+    async fn test_heatmap_columns_round_trip() {
+        let db = DatabaseManager::new(":memory:").await.unwrap();
+        db.initialize_schema().await.unwrap();
+
+        let column = |symbol: &str, resolution_ms: u64, start: u64| HeatmapColumn {
+            symbol: symbol.to_string(),
+            start,
+            resolution_ms,
+            granularity: 0.5,
+            bids: vec![(129_998, 12.5), (130_000, 3.0)],
+            asks: vec![(130_002, 40.25)],
+        };
+        let records: Vec<_> = [column("BTCUSDT", 1_000, 2_000), column("ETHUSDT", 1_000, 1_000), column("BTCUSDT", 10_000, 0)]
+            .into_iter()
+            .map(PersistRecord::HeatmapColumn)
+            .collect();
+        db.write_batch(&records).await.unwrap();
+
+        assert_eq!(db.get_heatmap_columns("BTCUSDT", 1_000, 0).await.unwrap(), vec![column("BTCUSDT", 1_000, 2_000)]);
+        assert_eq!(db.get_heatmap_columns("ETHUSDT", 1_000, 0).await.unwrap(), vec![column("ETHUSDT", 1_000, 1_000)]);
+        assert!(db.get_heatmap_columns("ETHUSDT", 1_000, 1_001).await.unwrap().is_empty());
+
+        assert_eq!(db.delete_heatmap_columns_before(1_000, 2_000).await.unwrap(), 1);
+        assert!(db.get_heatmap_columns("ETHUSDT", 1_000, 0).await.unwrap().is_empty());
+        assert_eq!(db.get_heatmap_columns("BTCUSDT", 1_000, 0).await.unwrap(), vec![column("BTCUSDT", 1_000, 2_000)]);
+        assert_eq!(db.get_heatmap_columns("BTCUSDT", 10_000, 0).await.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_migrations_are_ordered() {
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].version + 1 == w[1].version));
+        assert_eq!(MIGRATIONS.last().unwrap().version, SCHEMA_VERSION);
+    }
+}
@@ -0,0 +1,83 @@
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{interval, Duration};
+use anyhow::Result;
+use tracing::{info, warn};
+
+use super::{DatabaseManager, OrderflowEvent, OrderImbalance, LiquidationEvent, EventBus, Topic};
+
+const FOLLOW_POLL_INTERVAL_MS: u64 = 500;
+/// Rows read per table per poll
+const FOLLOW_BATCH_SIZE: usize = 5000;
+/// Rows of history loaded from each table when attaching
+const FOLLOW_INITIAL_BACKLOG: i64 = 1000;
+
+/// Tails the stream tables of a database written by a headless daemon and
+/// publishes new rows to the GUI, as the live pipeline would
+pub struct DatabaseFollower {
+    db: Arc<DatabaseManager>,
+    orderflow: Topic<OrderflowEvent>,
+    imbalance_sender: mpsc::Sender<OrderImbalance>,
+    liquidations: Topic<LiquidationEvent>,
+}
+
+impl DatabaseFollower {
+    pub fn new(
+        db: Arc<DatabaseManager>,
+        bus: &EventBus,
+        imbalance_sender: mpsc::Sender<OrderImbalance>,
+    ) -> Self {
+        Self {
+            db,
+            orderflow: bus.orderflow.clone(),
+            imbalance_sender,
+            liquidations: bus.liquidations.clone(),
+        }
+    }
+
+    pub async fn start(self) -> Result<()> {
+        let mut last_trade_id = (self.db.latest_row_id("raw_trades").await? - FOLLOW_INITIAL_BACKLOG).max(0);
+        let mut last_imbalance_id = (self.db.latest_row_id("order_imbalances").await? - FOLLOW_INITIAL_BACKLOG).max(0);
+        let mut last_liquidation_id = (self.db.latest_row_id("liquidations").await? - FOLLOW_INITIAL_BACKLOG).max(0);
+        info!("Following daemon database from trade row {}", last_trade_id);
+
+        let mut poll = interval(Duration::from_millis(FOLLOW_POLL_INTERVAL_MS));
+        loop {
+            poll.tick().await;
+
+            match self.db.get_trades_after(last_trade_id, FOLLOW_BATCH_SIZE).await {
+                Ok(rows) => {
+                    for (id, event) in rows {
+                        last_trade_id = id;
+                        self.orderflow.publish(event).await;
+                    }
+                }
+                Err(e) => warn!("Failed to read trades from database: {}", e),
+            }
+
+            match self.db.get_imbalances_after(last_imbalance_id, FOLLOW_BATCH_SIZE).await {
+                Ok(rows) => {
+                    for (id, imbalance) in rows {
+                        last_imbalance_id = id;
+                        let _ = self.imbalance_sender.try_send(imbalance);
+                    }
+                }
+                Err(e) => warn!("Failed to read imbalances from database: {}", e),
+            }
+
+            match self.db.get_liquidations_after(last_liquidation_id, FOLLOW_BATCH_SIZE).await {
+                Ok(rows) => {
+                    for (id, liquidation) in rows {
+                        last_liquidation_id = id;
+                        self.liquidations.publish(liquidation).await;
+                    }
+                }
+                Err(e) => warn!("Failed to read liquidations from database: {}", e),
+            }
+
+            if self.imbalance_sender.is_closed() {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,338 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::{TryRecvError, TrySendError};
+use tokio::time::Duration;
+use tracing::warn;
+
+use crate::config::settings::EventBusConfig;
+pub use crate::config::settings::OverflowPolicy;
+use super::{BigOrderflowAlert, BookAnalytics, BookDelta, BookFlowBucket, DepthSnapshot, GuiUpdate, LiquidationEvent, OrderflowEvent};
+
+/// Spacing of the subscriber stats published to the status bar
+const BUS_REPORT_INTERVAL_SECS: u64 = 5;
+
+/// Counters of one subscriber, shared by its topic and its `Subscription`
+#[derive(Debug)]
+struct SubscriberMetrics {
+    topic: &'static str,
+    name: String,
+    policy: OverflowPolicy,
+    sent: AtomicU64,
+    received: AtomicU64,
+    dropped: AtomicU64,
+    max_lag: AtomicU64,
+}
+
+impl SubscriberMetrics {
+    fn record_sent(&self) {
+        let sent = self.sent.fetch_add(1, Ordering::Relaxed) + 1;
+        let lag = sent.saturating_sub(self.received.load(Ordering::Relaxed));
+        self.max_lag.fetch_max(lag, Ordering::Relaxed);
+    }
+
+    fn stats(&self) -> SubscriberStats {
+        let sent = self.sent.load(Ordering::Relaxed);
+        let received = self.received.load(Ordering::Relaxed);
+        SubscriberStats {
+            topic: self.topic,
+            name: self.name.clone(),
+            policy: self.policy,
+            sent,
+            received,
+            lag: sent.saturating_sub(received),
+            max_lag: self.max_lag.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Point-in-time copy of one subscriber's counters
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubscriberStats {
+    pub topic: &'static str,
+    pub name: String,
+    pub policy: OverflowPolicy,
+    /// Events queued for the subscriber
+    pub sent: u64,
+    pub received: u64,
+    /// Events queued but not yet received
+    pub lag: u64,
+    /// Highest lag seen
+    pub max_lag: u64,
+    /// Events lost because the subscriber's queue was full
+    pub dropped: u64,
+}
+
+/// One line summary of the bus stats, for logs and the status bar
+pub fn bus_summary(stats: &[SubscriberStats]) -> String {
+    let dropped: u64 = stats.iter().map(|stats| stats.dropped).sum();
+    let slowest = stats.iter().max_by_key(|stats| stats.lag);
+    format!(
+        "{} subscribers, {} events dropped, largest lag {}",
+        stats.len(),
+        dropped,
+        slowest.map_or("0".to_string(), |stats| format!("{} ({} {})", stats.lag, stats.topic, stats.name))
+    )
+}
+
+struct Subscriber<T> {
+    sender: mpsc::Sender<Arc<T>>,
+    metrics: Arc<SubscriberMetrics>,
+}
+
+/// One typed stream of the bus. Every subscriber gets its own bounded queue of
+/// `Arc`-shared events, so a slow subscriber only loses its own events (drop
+/// policy) or holds up the publisher (block policy), never the others.
+pub struct Topic<T> {
+    name: &'static str,
+    capacity: usize,
+    subscribers: Arc<Mutex<Vec<Subscriber<T>>>>,
+}
+
+impl<T> Clone for Topic<T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name,
+            capacity: self.capacity,
+            subscribers: self.subscribers.clone(),
+        }
+    }
+}
+
+impl<T> Topic<T> {
+    fn new(name: &'static str, capacity: usize) -> Self {
+        Self {
+            name,
+            capacity: capacity.max(1),
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Receive every event published from now on
+    pub fn subscribe(&self, name: &str, policy: OverflowPolicy) -> Subscription<T> {
+        let (sender, receiver) = mpsc::channel(self.capacity);
+        let metrics = Arc::new(SubscriberMetrics {
+            topic: self.name,
+            name: name.to_string(),
+            policy,
+            sent: AtomicU64::new(0),
+            received: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            max_lag: AtomicU64::new(0),
+        });
+        self.subscribers.lock().unwrap().push(Subscriber { sender, metrics: metrics.clone() });
+        Subscription { receiver, metrics }
+    }
+
+    /// Deliver an event to every subscriber. Full drop-policy subscribers lose it;
+    /// full block-policy subscribers are waited for.
+    pub async fn publish(&self, event: T) {
+        let event = Arc::new(event);
+        for (sender, metrics) in self.offer(&event, true) {
+            if sender.send(event.clone()).await.is_ok() {
+                metrics.record_sent();
+            }
+        }
+    }
+
+    /// Deliver an event without waiting, for publishers outside async code.
+    /// Full subscribers lose it whatever their policy.
+    pub fn try_publish(&self, event: T) {
+        let event = Arc::new(event);
+        self.offer(&event, false);
+    }
+
+    /// Queue `event` for every subscriber with room, dropping closed subscribers.
+    /// Returns the full block-policy subscribers when `block` is set.
+    fn offer(&self, event: &Arc<T>, block: bool) -> Vec<(mpsc::Sender<Arc<T>>, Arc<SubscriberMetrics>)> {
+        let mut blocked = Vec::new();
+        self.subscribers.lock().unwrap().retain(|subscriber| {
+            match subscriber.sender.try_send(event.clone()) {
+                Ok(()) => subscriber.metrics.record_sent(),
+                Err(TrySendError::Full(_)) if block && subscriber.metrics.policy == OverflowPolicy::Block => {
+                    blocked.push((subscriber.sender.clone(), subscriber.metrics.clone()));
+                }
+                Err(TrySendError::Full(_)) => {
+                    subscriber.metrics.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(TrySendError::Closed(_)) => return false,
+            }
+            true
+        });
+        blocked
+    }
+
+    pub fn stats(&self) -> Vec<SubscriberStats> {
+        self.subscribers.lock().unwrap().iter().map(|subscriber| subscriber.metrics.stats()).collect()
+    }
+}
+
+/// A subscriber's end of a `Topic`
+pub struct Subscription<T> {
+    receiver: mpsc::Receiver<Arc<T>>,
+    metrics: Arc<SubscriberMetrics>,
+}
+
+impl<T> Subscription<T> {
+    /// Next event; `None` once every publisher is gone
+    pub async fn recv(&mut self) -> Option<Arc<T>> {
+        let event = self.receiver.recv().await?;
+        self.metrics.received.fetch_add(1, Ordering::Relaxed);
+        Some(event)
+    }
+
+    pub fn try_recv(&mut self) -> Result<Arc<T>, TryRecvError> {
+        let event = self.receiver.try_recv()?;
+        self.metrics.received.fetch_add(1, Ordering::Relaxed);
+        Ok(event)
+    }
+}
+
+/// Typed fan-out between the data sources, the analyzers and the front ends.
+/// Cheap to clone; every clone publishes to the same subscribers.
+#[derive(Clone)]
+pub struct EventBus {
+    pub orderflow: Topic<OrderflowEvent>,
+    /// Book snapshots of synced order books, by symbol
+    pub depth: Topic<(String, DepthSnapshot)>,
+    /// Full-depth snapshots of the wide heatmap focus symbols
+    pub wide_depth: Topic<(String, DepthSnapshot)>,
+    pub book_analytics: Topic<BookAnalytics>,
+    /// Level changes of live order books, diff by diff
+    pub book_deltas: Topic<BookDelta>,
+    /// Adds, cancels and executions per level, one closed bucket at a time
+    pub book_flow: Topic<BookFlowBucket>,
+    /// forceOrder liquidations and the ones inferred from trades
+    pub liquidations: Topic<LiquidationEvent>,
+    pub alerts: Topic<BigOrderflowAlert>,
+    /// Policy of subscribers that should follow the configuration
+    policy: OverflowPolicy,
+}
+
+impl EventBus {
+    pub fn new(config: &EventBusConfig) -> Self {
+        let capacity = config.subscriber_capacity;
+        Self {
+            orderflow: Topic::new("orderflow", capacity),
+            depth: Topic::new("depth", capacity),
+            wide_depth: Topic::new("wide_depth", capacity),
+            book_analytics: Topic::new("book_analytics", capacity),
+            book_deltas: Topic::new("book_deltas", capacity),
+            book_flow: Topic::new("book_flow", capacity),
+            liquidations: Topic::new("liquidations", capacity),
+            alerts: Topic::new("alerts", capacity),
+            policy: config.overflow_policy,
+        }
+    }
+
+    /// Configured policy for pipeline subscribers; front ends always drop, as a
+    /// frozen window must not stall the analysis
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// Every subscriber of every topic
+    pub fn stats(&self) -> Vec<SubscriberStats> {
+        let mut stats = self.orderflow.stats();
+        stats.extend(self.depth.stats());
+        stats.extend(self.wide_depth.stats());
+        stats.extend(self.book_analytics.stats());
+        stats.extend(self.book_deltas.stats());
+        stats.extend(self.book_flow.stats());
+        stats.extend(self.liquidations.stats());
+        stats.extend(self.alerts.stats());
+        stats
+    }
+
+    /// Publish subscriber stats to the status bar and log when events were dropped
+    pub async fn report(self, gui_sender: Option<mpsc::Sender<GuiUpdate>>) {
+        let mut tick = tokio::time::interval(Duration::from_secs(BUS_REPORT_INTERVAL_SECS));
+        let mut last_dropped = 0;
+        loop {
+            tick.tick().await;
+            let stats = self.stats();
+
+            let dropped: u64 = stats.iter().map(|stats| stats.dropped).sum();
+            if dropped > last_dropped {
+                warn!("Event bus dropped {} events since the last report: {}", dropped - last_dropped, bus_summary(&stats));
+                last_dropped = dropped;
+            }
+            if let Some(gui_sender) = &gui_sender {
+                let _ = gui_sender.try_send(GuiUpdate::EventBus(stats));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bus(capacity: usize) -> EventBus {
+        EventBus::new(&EventBusConfig { subscriber_capacity: capacity, overflow_policy: OverflowPolicy::Drop })
+    }
+
+    fn alert(quantity: f64) -> BigOrderflowAlert {
+        BigOrderflowAlert {
+            symbol: "BTCUSDT".to_string(),
+            timestamp: 1,
+            side: "BUY".to_string(),
+            price: 100.0,
+            quantity,
+            percentage_of_daily: 1.0,
+            notional_value: 100.0 * quantity,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_drop_subscriber_loses_only_its_own_events() {
+        let bus = bus(2);
+        let mut fast = bus.alerts.subscribe("fast", OverflowPolicy::Drop);
+        let mut slow = bus.alerts.subscribe("slow", OverflowPolicy::Drop);
+
+        let mut received = Vec::new();
+        for quantity in 1..=5 {
+            bus.alerts.publish(alert(quantity as f64)).await;
+            received.push(fast.recv().await.unwrap().quantity);
+        }
+        assert_eq!(received, vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+
+        // The slow subscriber kept the first two and counts the rest as dropped
+        assert_eq!(bus.stats()[1].lag, 2);
+        assert_eq!(slow.try_recv().unwrap().quantity, 1.0);
+        let stats = bus.stats();
+        assert_eq!((stats[0].name.as_str(), stats[0].dropped, stats[0].max_lag), ("fast", 0, 1));
+        assert_eq!((stats[1].name.as_str(), stats[1].dropped, stats[1].lag, stats[1].max_lag), ("slow", 3, 1, 2));
+        assert!(bus_summary(&stats).starts_with("2 subscribers, 3 events dropped"));
+
+        // A dropped subscription is forgotten on the next publish
+        drop(slow);
+        bus.alerts.try_publish(alert(6.0));
+        assert_eq!(bus.stats().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_block_subscriber_holds_up_the_publisher() {
+        let bus = bus(1);
+        let mut blocking = bus.alerts.subscribe("persistence", OverflowPolicy::Block);
+
+        let publisher = bus.clone();
+        let handle = tokio::spawn(async move {
+            for quantity in 1..=3 {
+                publisher.alerts.publish(alert(quantity as f64)).await;
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!handle.is_finished());
+
+        // Every event arrives once the subscriber catches up
+        let mut quantities = Vec::new();
+        for _ in 0..3 {
+            quantities.push(blocking.recv().await.unwrap().quantity);
+        }
+        handle.await.unwrap();
+        assert_eq!(quantities, vec![1.0, 2.0, 3.0]);
+        assert_eq!(bus.stats()[0].dropped, 0);
+    }
+}
@@ -0,0 +1,312 @@
+use std::collections::{BTreeMap, HashMap};
+use anyhow::{Result, anyhow};
+use tracing::info;
+
+use super::capture::{get_f64, get_u8, get_varint, put_f64, put_varint, unzigzag, zigzag};
+use super::{OrderflowEvent, PersistRecord, PersistenceHandle, Subscription, SymbolRegistry};
+
+/// Timeframe of the candles the recorder persists and the footprint panel reloads
+pub const FOOTPRINT_BASE_TIMEFRAME: &str = "1m";
+/// Length of a `FOOTPRINT_BASE_TIMEFRAME` candle
+const BASE_TIMEFRAME_MS: u64 = 60_000;
+/// Tick for symbols missing from the registry
+const DEFAULT_TICK_SIZE: f64 = 0.01;
+
+/// Leading byte of a packed level blob, bumped if the layout changes
+const LEVELS_FORMAT_VERSION: u8 = 2;
+
+/// Bid/ask volume traded at one price tick
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FootprintLevel {
+    /// Price divided by the candle's tick size
+    pub tick: i64,
+    /// Market sells at the bid
+    pub bid_volume: f64,
+    /// Market buys at the ask
+    pub ask_volume: f64,
+}
+
+/// A footprint candle as stored in `footprint_candles`, keyed by
+/// (symbol, timeframe, open time)
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredFootprintCandle {
+    pub symbol: String,
+    pub timeframe: String,
+    pub open_time: u64,
+    pub tick_size: f64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// Ordered by tick
+    pub levels: Vec<FootprintLevel>,
+}
+
+impl StoredFootprintCandle {
+    /// Pack the level map for the `levels` blob column
+    pub fn encode_levels(&self) -> Vec<u8> {
+        encode_levels(&self.levels)
+    }
+}
+
+/// Pack levels as a version byte, a varint count, then per level the zigzag
+/// varint tick delta from the previous level and the bid and ask volumes as
+/// `f64`, so fractional contract quantities survive the round trip exactly.
+pub fn encode_levels(levels: &[FootprintLevel]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(2 + levels.len() * 18);
+    buf.push(LEVELS_FORMAT_VERSION);
+    put_varint(&mut buf, levels.len() as u64);
+
+    let mut previous_tick = 0i64;
+    for level in levels {
+        put_varint(&mut buf, zigzag(level.tick.wrapping_sub(previous_tick)));
+        put_f64(&mut buf, level.bid_volume);
+        put_f64(&mut buf, level.ask_volume);
+        previous_tick = level.tick;
+    }
+
+    buf
+}
+
+/// Inverse of `encode_levels`
+pub fn decode_levels(mut blob: &[u8]) -> Result<Vec<FootprintLevel>> {
+    let reader = &mut blob;
+    let version = get_u8(reader)?;
+    if version != LEVELS_FORMAT_VERSION {
+        return Err(anyhow!("Unsupported footprint level format {}", version));
+    }
+
+    let count = get_varint(reader)? as usize;
+    let mut levels = Vec::with_capacity(count.min(100_000));
+    let mut tick = 0i64;
+    for _ in 0..count {
+        tick = tick.wrapping_add(unzigzag(get_varint(reader)?));
+        levels.push(FootprintLevel {
+            tick,
+            bid_volume: get_f64(reader)?,
+            ask_volume: get_f64(reader)?,
+        });
+    }
+
+    if !reader.is_empty() {
+        return Err(anyhow!("{} trailing bytes after footprint levels", reader.len()));
+    }
+    Ok(levels)
+}
+
+/// Base candle of one symbol still being filled by the recorder
+#[derive(Debug)]
+struct OpenCandle {
+    open_time: u64,
+    tick_size: f64,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    /// Tick -> (bid volume, ask volume)
+    levels: BTreeMap<i64, (f64, f64)>,
+    last_trade_id: u64,
+    /// Every trade of the minute went through this candle
+    complete: bool,
+}
+
+impl OpenCandle {
+    fn new(trade: &OrderflowEvent, tick_size: f64, complete: bool) -> Self {
+        Self {
+            open_time: trade.timestamp / BASE_TIMEFRAME_MS * BASE_TIMEFRAME_MS,
+            tick_size,
+            open: trade.price,
+            high: trade.price,
+            low: trade.price,
+            close: trade.price,
+            levels: BTreeMap::new(),
+            last_trade_id: trade.trade_id,
+            complete,
+        }
+    }
+
+    fn add_trade(&mut self, trade: &OrderflowEvent) {
+        self.high = self.high.max(trade.price);
+        self.low = self.low.min(trade.price);
+        self.close = trade.price;
+        self.last_trade_id = trade.trade_id;
+
+        let (bid_volume, ask_volume) = self.levels.entry((trade.price / self.tick_size).round() as i64).or_default();
+        if trade.is_buyer_maker {
+            *bid_volume += trade.quantity;
+        } else {
+            *ask_volume += trade.quantity;
+        }
+    }
+
+    fn into_stored(self, symbol: &str) -> StoredFootprintCandle {
+        StoredFootprintCandle {
+            symbol: symbol.to_string(),
+            timeframe: FOOTPRINT_BASE_TIMEFRAME.to_string(),
+            open_time: self.open_time,
+            tick_size: self.tick_size,
+            open: self.open,
+            high: self.high,
+            low: self.low,
+            close: self.close,
+            levels: self.levels
+                .into_iter()
+                .map(|(tick, (bid_volume, ask_volume))| FootprintLevel { tick, bid_volume, ask_volume })
+                .collect(),
+        }
+    }
+}
+
+/// Builds base footprint candles from the trades leaving the gap filler, at
+/// each symbol's contract tick, and persists every complete one as it closes.
+/// A symbol's first minute of the session began before its first trade
+/// arrived, and a jump in its aggregate trade ids means trades were lost, so
+/// those minutes are left for the backfill to rebuild from stored trades.
+pub struct FootprintRecorder {
+    registry: SymbolRegistry,
+    persistence: PersistenceHandle,
+    candles: HashMap<String, OpenCandle>,
+}
+
+impl FootprintRecorder {
+    pub fn new(registry: SymbolRegistry, persistence: PersistenceHandle) -> Self {
+        Self { registry, persistence, candles: HashMap::new() }
+    }
+
+    fn record(&mut self, trade: &OrderflowEvent) {
+        let Some(candle) = self.candles.get_mut(&trade.symbol) else {
+            let tick_size = self.registry.tick_size(&trade.symbol).unwrap_or(DEFAULT_TICK_SIZE);
+            let mut candle = OpenCandle::new(trade, tick_size, false);
+            candle.add_trade(trade);
+            self.candles.insert(trade.symbol.clone(), candle);
+            return;
+        };
+        // Backfilled trades can overlap ones already seen
+        if trade.trade_id <= candle.last_trade_id {
+            return;
+        }
+
+        let contiguous = trade.trade_id == candle.last_trade_id + 1;
+        if trade.timestamp >= candle.open_time + BASE_TIMEFRAME_MS {
+            let tick_size = self.registry.tick_size(&trade.symbol).unwrap_or(DEFAULT_TICK_SIZE);
+            let closed = std::mem::replace(candle, OpenCandle::new(trade, tick_size, contiguous));
+            if closed.complete && contiguous {
+                self.persistence.persist(PersistRecord::FootprintCandle(closed.into_stored(&trade.symbol)));
+            }
+        } else if !contiguous {
+            candle.complete = false;
+        }
+        candle.add_trade(trade);
+    }
+
+    pub async fn start(mut self, mut orderflow: Subscription<OrderflowEvent>) {
+        info!("Starting footprint recorder");
+        while let Some(trade) = orderflow.recv().await {
+            self.record(&trade);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(tick: i64, bid_volume: f64, ask_volume: f64) -> FootprintLevel {
+        FootprintLevel { tick, bid_volume, ask_volume }
+    }
+
+    #[test]
+    fn test_levels_round_trip() {
+        let levels = vec![
+            level(-5, 0.0, 1.0),
+            level(3_700_010, 12.0, 0.0),
+            level(3_700_011, 1e12, 127.0),
+            level(3_700_013, 128.0, 16_384.0),
+            level(i64::MAX, 1.0, 1.0),
+        ];
+        assert_eq!(decode_levels(&encode_levels(&levels)).unwrap(), levels);
+        assert!(decode_levels(&encode_levels(&[])).unwrap().is_empty());
+
+        // Adjacent ticks take one byte of delta each
+        let dense: Vec<_> = (0..100).map(|i| level(3_700_000 + i, 5.0, 7.0)).collect();
+        assert!(encode_levels(&dense).len() < 4 + 100 * 17 + 4);
+        assert_eq!(decode_levels(&encode_levels(&dense)).unwrap(), dense);
+    }
+
+    #[test]
+    fn test_fractional_volumes_round_trip() {
+        // Sub-contract quantities, as traded on BTCUSDT, must not be truncated
+        let levels = vec![
+            level(370_001, 0.003, 1.25),
+            level(370_002, 0.1 + 0.2, 0.0),
+            level(370_005, 12_345.678_9, 1e-8),
+        ];
+        assert_eq!(decode_levels(&encode_levels(&levels)).unwrap(), levels);
+    }
+
+    #[test]
+    fn test_rejects_corrupt_blobs() {
+        let blob = encode_levels(&[level(100, 1.0, 2.0), level(101, 3.0, 4.0)]);
+
+        assert!(decode_levels(&blob[..blob.len() - 1]).is_err());
+        assert!(decode_levels(&[]).is_err());
+
+        let mut wrong_version = blob.clone();
+        wrong_version[0] = 9;
+        assert!(decode_levels(&wrong_version).is_err());
+
+        let mut trailing = blob;
+        trailing.push(0);
+        assert!(decode_levels(&trailing).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_recorder_skips_the_partial_first_minute_and_gap_minutes() {
+        use crate::data::{DatabaseManager, PersistenceConfig};
+
+        const MINUTE: u64 = 60_000;
+        let base = 1_700_000_040_000;
+        let trade = |trade_id: u64, timestamp: u64, quantity: f64| OrderflowEvent {
+            symbol: "BTCUSDT".to_string(),
+            timestamp,
+            price: 37000.0 + (trade_id % 3) as f64 * 0.1,
+            quantity,
+            is_buyer_maker: trade_id.is_multiple_of(2),
+            trade_id,
+        };
+
+        let db = DatabaseManager::new(":memory:").await.unwrap();
+        db.initialize_schema().await.unwrap();
+        let config = PersistenceConfig { queue_capacity: 100, batch_size: 10, flush_interval_ms: 10 };
+        let (persistence, writer) = PersistenceHandle::start(db.clone(), config);
+        let mut recorder = FootprintRecorder::new(SymbolRegistry::new(), persistence);
+
+        let trades = [
+            // Joined mid-minute: the first minute is partial
+            trade(10, base + 30_000, 1.0),
+            trade(11, base + MINUTE, 0.004),
+            trade(12, base + MINUTE + 10, 0.25),
+            trade(12, base + MINUTE + 10, 0.25),
+            trade(13, base + 2 * MINUTE, 1.5),
+            // Ids 14 and 15 never arrived: minutes 2 and 3 are partial
+            trade(16, base + 3 * MINUTE, 2.0),
+            trade(17, base + 4 * MINUTE, 3.0),
+            trade(18, base + 5 * MINUTE, 1.0),
+        ];
+        for trade in &trades {
+            recorder.record(trade);
+        }
+
+        drop(recorder);
+        writer.await.unwrap();
+        let saved = db.get_footprint_candles("BTCUSDT", FOOTPRINT_BASE_TIMEFRAME, 0, u64::MAX / 2).await.unwrap();
+        let starts: Vec<u64> = saved.iter().map(|candle| candle.open_time).collect();
+        assert_eq!(starts, vec![base + MINUTE, base + 4 * MINUTE]);
+
+        // Fractional volumes are stored as traded, at the default tick
+        let minute = &saved[0];
+        assert_eq!(minute.tick_size, DEFAULT_TICK_SIZE);
+        assert_eq!((minute.open, minute.high, minute.close), (37000.2, 37000.2, 37000.0));
+        assert_eq!(minute.levels, vec![level(3_700_000, 0.25, 0.0), level(3_700_020, 0.0, 0.004)]);
+    }
+}
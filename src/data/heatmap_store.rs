@@ -0,0 +1,545 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::{Arc, RwLock};
+use anyhow::{Result, anyhow};
+use tokio::sync::Notify;
+use tokio::time::Duration;
+use tracing::{info, warn};
+
+use crate::config::settings::{HeatmapConfig, HeatmapTierConfig};
+use super::capture::{get_u8, get_varint, put_varint, unzigzag, zigzag};
+use super::{DatabaseManager, DepthAggregation, DepthSnapshot, PersistRecord, PersistenceHandle, Subscription, SymbolRegistry};
+
+/// Leading byte of a packed heatmap level blob, bumped if the layout changes
+const HEATMAP_FORMAT_VERSION: u8 = 1;
+/// Tick for symbols missing from the registry
+const DEFAULT_TICK_SIZE: f64 = 0.01;
+/// Spacing of the deletes applying tier retention to the stored columns
+const HEATMAP_PRUNE_INTERVAL_SECS: u64 = 600;
+
+/// Resting size per price bucket of one symbol, averaged over the depth
+/// snapshots taken within `[start, start + resolution_ms)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeatmapColumn {
+    pub symbol: String,
+    pub start: u64,
+    pub resolution_ms: u64,
+    /// Price step of the buckets
+    pub granularity: f64,
+    /// (price / granularity, average quantity), ordered by bucket
+    pub bids: Vec<(i64, f64)>,
+    pub asks: Vec<(i64, f64)>,
+}
+
+impl HeatmapColumn {
+    pub fn end(&self) -> u64 {
+        self.start + self.resolution_ms
+    }
+
+    /// Largest average size on either side
+    pub fn max_quantity(&self) -> f64 {
+        self.bids.iter().chain(&self.asks).map(|(_, quantity)| *quantity).fold(0.0, f64::max)
+    }
+}
+
+/// Pack levels as a version byte, a varint count, then per level the zigzag
+/// varint bucket delta from the previous level and the quantity as an `f32`
+pub fn encode_heatmap_levels(levels: &[(i64, f64)]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(2 + levels.len() * 6);
+    buf.push(HEATMAP_FORMAT_VERSION);
+    put_varint(&mut buf, levels.len() as u64);
+
+    let mut previous_bucket = 0i64;
+    for (bucket, quantity) in levels {
+        put_varint(&mut buf, zigzag(bucket.wrapping_sub(previous_bucket)));
+        buf.extend_from_slice(&(*quantity as f32).to_le_bytes());
+        previous_bucket = *bucket;
+    }
+
+    buf
+}
+
+/// Inverse of `encode_heatmap_levels`
+pub fn decode_heatmap_levels(mut blob: &[u8]) -> Result<Vec<(i64, f64)>> {
+    let reader = &mut blob;
+    let version = get_u8(reader)?;
+    if version != HEATMAP_FORMAT_VERSION {
+        return Err(anyhow!("Unsupported heatmap level format {}", version));
+    }
+
+    let count = get_varint(reader)? as usize;
+    let mut levels = Vec::with_capacity(count.min(100_000));
+    let mut bucket = 0i64;
+    for _ in 0..count {
+        bucket = bucket.wrapping_add(unzigzag(get_varint(reader)?));
+        let mut quantity = [0u8; 4];
+        std::io::Read::read_exact(reader, &mut quantity)?;
+        levels.push((bucket, f32::from_le_bytes(quantity) as f64));
+    }
+
+    if !reader.is_empty() {
+        return Err(anyhow!("{} trailing bytes after heatmap levels", reader.len()));
+    }
+    Ok(levels)
+}
+
+/// Summed snapshots of a column still being filled
+#[derive(Debug, Clone)]
+struct ColumnAccumulator {
+    start: u64,
+    granularity: f64,
+    snapshots: u32,
+    bids: BTreeMap<i64, f64>,
+    asks: BTreeMap<i64, f64>,
+}
+
+impl ColumnAccumulator {
+    fn new(start: u64, granularity: f64) -> Self {
+        Self { start, granularity, snapshots: 0, bids: BTreeMap::new(), asks: BTreeMap::new() }
+    }
+
+    fn add(&mut self, depth: &DepthSnapshot) {
+        for (price, quantity) in &depth.bids {
+            *self.bids.entry((price / self.granularity).round() as i64).or_insert(0.0) += quantity;
+        }
+        for (price, quantity) in &depth.asks {
+            *self.asks.entry((price / self.granularity).round() as i64).or_insert(0.0) += quantity;
+        }
+        self.snapshots += 1;
+    }
+
+    fn to_column(&self, symbol: &str, resolution_ms: u64) -> HeatmapColumn {
+        let snapshots = self.snapshots.max(1) as f64;
+        let average = |levels: &BTreeMap<i64, f64>| levels.iter().map(|(bucket, sum)| (*bucket, sum / snapshots)).collect();
+        HeatmapColumn {
+            symbol: symbol.to_string(),
+            start: self.start,
+            resolution_ms,
+            granularity: self.granularity,
+            bids: average(&self.bids),
+            asks: average(&self.asks),
+        }
+    }
+}
+
+/// One tier of one symbol: closed columns oldest first, and the open one
+#[derive(Debug, Default)]
+struct TierColumns {
+    closed: VecDeque<HeatmapColumn>,
+    open: Option<ColumnAccumulator>,
+}
+
+impl TierColumns {
+    fn oldest_start(&self) -> Option<u64> {
+        self.closed.front().map(|column| column.start).or(self.open.as_ref().map(|open| open.start))
+    }
+}
+
+#[derive(Debug, Default)]
+struct StoreInner {
+    /// Finest resolution first
+    tiers: Vec<HeatmapTierConfig>,
+    symbols: HashMap<String, Vec<TierColumns>>,
+    /// Symbols whose snapshots are folded in: the configured ones and every opened one
+    recorded: HashSet<String>,
+    /// Symbols opened in the panel, and those of them still waiting for their stored history
+    opened: HashSet<String>,
+    pending_history: Vec<String>,
+    /// Start of each symbol's newest open column, in any tier; every column ending before it is closed
+    closed_through: HashMap<String, u64>,
+    /// Stored columns inserted so far
+    restored: u64,
+}
+
+/// Depth snapshots downsampled into time × price columns at a few resolutions,
+/// e.g. 1s columns for the last hour and 10s columns for the day. Clones share
+/// the columns: the recorder fills them and the footprint heatmap reads them.
+#[derive(Clone, Default)]
+pub struct HeatmapStore {
+    inner: Arc<RwLock<StoreInner>>,
+    history_requested: Arc<Notify>,
+}
+
+impl HeatmapStore {
+    pub fn new(config: &HeatmapConfig) -> Self {
+        let mut tiers: Vec<HeatmapTierConfig> = config.tiers.iter().filter(|tier| tier.resolution_ms > 0).copied().collect();
+        tiers.sort_by_key(|tier| tier.resolution_ms);
+        Self {
+            inner: Arc::new(RwLock::new(StoreInner {
+                tiers,
+                recorded: config.symbols.iter().cloned().collect(),
+                ..Default::default()
+            })),
+            history_requested: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Configured tiers, finest first
+    pub fn tiers(&self) -> Vec<HeatmapTierConfig> {
+        self.inner.read().unwrap().tiers.clone()
+    }
+
+    /// Start recording `symbol` and ask the recorder for its stored history,
+    /// once per symbol; the footprint panel calls this for the symbol it shows
+    pub fn open(&self, symbol: &str) {
+        if self.inner.read().unwrap().opened.contains(symbol) {
+            return;
+        }
+        let mut inner = self.inner.write().unwrap();
+        if inner.opened.insert(symbol.to_string()) {
+            inner.recorded.insert(symbol.to_string());
+            inner.pending_history.push(symbol.to_string());
+            self.history_requested.notify_one();
+        }
+    }
+
+    pub fn is_recorded(&self, symbol: &str) -> bool {
+        self.inner.read().unwrap().recorded.contains(symbol)
+    }
+
+    fn take_pending_history(&self) -> Vec<String> {
+        std::mem::take(&mut self.inner.write().unwrap().pending_history)
+    }
+
+    /// Fold a snapshot bucketed at `granularity` into every tier, returning the
+    /// columns it closed. A change of price step closes the open column early.
+    pub fn add_snapshot(&self, symbol: &str, depth: &DepthSnapshot, granularity: f64) -> Vec<HeatmapColumn> {
+        let mut inner = self.inner.write().unwrap();
+        let StoreInner { tiers, symbols, closed_through, .. } = &mut *inner;
+        let columns = symbols
+            .entry(symbol.to_string())
+            .or_insert_with(|| tiers.iter().map(|_| TierColumns::default()).collect());
+
+        let mut closed = Vec::new();
+        for (tier, columns) in tiers.iter().zip(columns.iter_mut()) {
+            let start = depth.timestamp / tier.resolution_ms * tier.resolution_ms;
+            if columns.open.as_ref().is_some_and(|open| open.start < start || open.granularity != granularity) {
+                if let Some(open) = columns.open.take() {
+                    let column = open.to_column(symbol, tier.resolution_ms);
+                    columns.closed.push_back(column.clone());
+                    closed.push(column);
+                }
+            }
+            columns.open.get_or_insert_with(|| ColumnAccumulator::new(start, granularity)).add(depth);
+
+            let horizon = depth.timestamp.saturating_sub(tier.retention_secs * 1000);
+            while columns.closed.front().is_some_and(|column| column.start < horizon) {
+                columns.closed.pop_front();
+            }
+        }
+        if let Some(open) = columns.iter().filter_map(|tier| tier.open.as_ref()).map(|open| open.start).max() {
+            let newest = closed_through.entry(symbol.to_string()).or_default();
+            *newest = (*newest).max(open);
+        }
+        closed
+    }
+
+    /// Time before which `symbol`'s columns are all closed, and the number of
+    /// restored columns. A cached `column_between` range is stale once this time
+    /// moves inside it, or once stored columns were restored.
+    pub fn revision(&self, symbol: &str) -> (u64, u64) {
+        let inner = self.inner.read().unwrap();
+        (inner.closed_through.get(symbol).copied().unwrap_or(0), inner.restored)
+    }
+
+    /// Restore a stored column; columns of a resolution no tier uses are ignored
+    pub fn insert_column(&self, column: HeatmapColumn) {
+        let mut inner = self.inner.write().unwrap();
+        let StoreInner { tiers, symbols, restored, .. } = &mut *inner;
+        let Some(index) = tiers.iter().position(|tier| tier.resolution_ms == column.resolution_ms) else {
+            return;
+        };
+        *restored += 1;
+        let closed = &mut symbols
+            .entry(column.symbol.clone())
+            .or_insert_with(|| tiers.iter().map(|_| TierColumns::default()).collect())[index]
+            .closed;
+
+        let position = closed.partition_point(|stored| stored.start < column.start);
+        if closed.get(position).is_some_and(|stored| stored.start == column.start) {
+            closed[position] = column;
+        } else {
+            closed.insert(position, column);
+        }
+    }
+
+    /// Average resting size over `[start, end)`, re-bucketed at `granularity`.
+    /// Reads the finest tier whose history reaches back to `start`, or the one
+    /// reaching furthest back, including its open column.
+    pub fn column_between(&self, symbol: &str, start: u64, end: u64, granularity: f64) -> Option<HeatmapColumn> {
+        let inner = self.inner.read().unwrap();
+        let columns = inner.symbols.get(symbol)?;
+        let (index, tier) = columns
+            .iter()
+            .enumerate()
+            .find(|(_, tier)| tier.oldest_start().is_some_and(|oldest| oldest <= start))
+            .or_else(|| {
+                columns.iter().enumerate()
+                    .filter(|(_, tier)| tier.oldest_start().is_some())
+                    .min_by_key(|(_, tier)| tier.oldest_start())
+            })?;
+        let resolution_ms = inner.tiers[index].resolution_ms;
+
+        let open = tier.open
+            .as_ref()
+            .filter(|open| open.start < end && open.start + resolution_ms > start)
+            .map(|open| open.to_column(symbol, resolution_ms));
+        let first = tier.closed.partition_point(|column| column.end() <= start);
+        let overlapping = tier.closed.range(first..).take_while(|column| column.start < end).chain(open.as_ref());
+
+        let mut bids = BTreeMap::new();
+        let mut asks = BTreeMap::new();
+        let mut count = 0;
+        for column in overlapping {
+            rebucket(&column.bids, column.granularity, granularity, &mut bids);
+            rebucket(&column.asks, column.granularity, granularity, &mut asks);
+            count += 1;
+        }
+        if count == 0 {
+            return None;
+        }
+
+        let average = |levels: BTreeMap<i64, f64>| levels.into_iter().map(|(bucket, sum)| (bucket, sum / count as f64)).collect();
+        Some(HeatmapColumn {
+            symbol: symbol.to_string(),
+            start,
+            resolution_ms: end.saturating_sub(start),
+            granularity,
+            bids: average(bids),
+            asks: average(asks),
+        })
+    }
+}
+
+/// Add levels bucketed at `from` into buckets of `to`
+fn rebucket(levels: &[(i64, f64)], from: f64, to: f64, into: &mut BTreeMap<i64, f64>) {
+    for (bucket, quantity) in levels {
+        *into.entry((*bucket as f64 * from / to).round() as i64).or_insert(0.0) += quantity;
+    }
+}
+
+/// Feeds the recorded symbols' book snapshots into a `HeatmapStore` at each
+/// symbol's depth history step, persists the columns it closes and seeds each
+/// opened symbol from the database
+pub struct HeatmapRecorder {
+    store: HeatmapStore,
+    registry: SymbolRegistry,
+    depth_aggregation: DepthAggregation,
+    database: Option<Arc<DatabaseManager>>,
+    persistence: Option<PersistenceHandle>,
+}
+
+impl HeatmapRecorder {
+    pub fn new(store: HeatmapStore, registry: SymbolRegistry, depth_aggregation: DepthAggregation) -> Self {
+        Self { store, registry, depth_aggregation, database: None, persistence: None }
+    }
+
+    /// Load stored columns of opened symbols, and prune them when also persisting
+    pub fn set_database(&mut self, database: Arc<DatabaseManager>) {
+        self.database = Some(database);
+    }
+
+    pub fn set_persistence(&mut self, persistence: PersistenceHandle) {
+        self.persistence = Some(persistence);
+    }
+
+    fn granularity(&self, symbol: &str) -> f64 {
+        let tick_size = self.registry.tick_size(symbol).unwrap_or(DEFAULT_TICK_SIZE);
+        tick_size * self.depth_aggregation.tick_multiple(symbol) as f64
+    }
+
+    /// Seed the store with the stored history of the symbols opened since the last call
+    async fn load_requested_history(&self) {
+        let symbols = self.store.take_pending_history();
+        let Some(database) = &self.database else {
+            return;
+        };
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        for symbol in symbols {
+            let mut loaded = 0;
+            for tier in self.store.tiers() {
+                let start = now.saturating_sub(tier.retention_secs * 1000);
+                match database.get_heatmap_columns(&symbol, tier.resolution_ms, start).await {
+                    Ok(columns) => {
+                        loaded += columns.len();
+                        for column in columns {
+                            self.store.insert_column(column);
+                        }
+                    }
+                    Err(e) => warn!("Heatmap history of {} at {}ms unavailable: {}", symbol, tier.resolution_ms, e),
+                }
+            }
+            info!("Loaded {} heatmap columns of {} from the database", loaded, symbol);
+        }
+    }
+
+    /// Drop stored columns past their tier's retention; attached GUIs leave this to the daemon
+    async fn prune(&self) {
+        let (Some(database), Some(_)) = (&self.database, &self.persistence) else {
+            return;
+        };
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        for tier in self.store.tiers() {
+            let before = now.saturating_sub(tier.retention_secs * 1000);
+            if let Err(e) = database.delete_heatmap_columns_before(tier.resolution_ms, before).await {
+                warn!("Cannot prune heatmap columns at {}ms: {}", tier.resolution_ms, e);
+            }
+        }
+    }
+
+    pub async fn start(self, mut depth: Subscription<(String, DepthSnapshot)>) {
+        info!("Starting heatmap recorder");
+        let history_requested = self.store.history_requested.clone();
+        let mut prune_timer = tokio::time::interval(Duration::from_secs(HEATMAP_PRUNE_INTERVAL_SECS));
+        loop {
+            tokio::select! {
+                published = depth.recv() => {
+                    let Some(published) = published else {
+                        break;
+                    };
+                    let (symbol, snapshot) = &*published;
+                    if !self.store.is_recorded(symbol) {
+                        continue;
+                    }
+                    let closed = self.store.add_snapshot(symbol, snapshot, self.granularity(symbol));
+                    if let Some(persistence) = &self.persistence {
+                        for column in closed {
+                            persistence.persist(PersistRecord::HeatmapColumn(column));
+                        }
+                    }
+                }
+                _ = history_requested.notified() => self.load_requested_history().await,
+                _ = prune_timer.tick() => self.prune().await,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> HeatmapStore {
+        HeatmapStore::new(&HeatmapConfig {
+            enabled: true,
+            tiers: vec![
+                HeatmapTierConfig { resolution_ms: 10_000, retention_secs: 600 },
+                HeatmapTierConfig { resolution_ms: 1_000, retention_secs: 60 },
+            ],
+            symbols: vec!["BTCUSDT".to_string()],
+            wide: Default::default(),
+        })
+    }
+
+    fn snapshot(timestamp: u64, bid: f64, ask: f64) -> DepthSnapshot {
+        DepthSnapshot { bids: vec![(99.0, bid), (98.9, 1.0)], asks: vec![(100.0, ask)], timestamp }
+    }
+
+    #[test]
+    fn test_levels_round_trip() {
+        let levels = vec![(-3, 0.5), (995, 12.25), (996, 1e6), (1_000, 0.0)];
+        assert_eq!(decode_heatmap_levels(&encode_heatmap_levels(&levels)).unwrap(), levels);
+        assert!(decode_heatmap_levels(&encode_heatmap_levels(&[])).unwrap().is_empty());
+
+        let blob = encode_heatmap_levels(&levels);
+        assert!(decode_heatmap_levels(&blob[..blob.len() - 1]).is_err());
+        let mut wrong_version = blob;
+        wrong_version[0] = 9;
+        assert!(decode_heatmap_levels(&wrong_version).is_err());
+    }
+
+    #[test]
+    fn test_snapshots_average_into_tier_columns() {
+        let store = store();
+        assert_eq!(store.tiers()[0].resolution_ms, 1_000);
+
+        assert!(store.add_snapshot("BTCUSDT", &snapshot(1_000, 2.0, 4.0), 0.5).is_empty());
+        assert!(store.add_snapshot("BTCUSDT", &snapshot(1_500, 4.0, 0.0), 0.5).is_empty());
+        assert_eq!(store.revision("BTCUSDT"), (1_000, 0));
+        let closed = store.add_snapshot("BTCUSDT", &snapshot(2_100, 6.0, 6.0), 0.5);
+        assert_eq!(closed.len(), 1);
+        assert_eq!(store.revision("BTCUSDT"), (2_000, 0));
+        assert_eq!((closed[0].start, closed[0].resolution_ms), (1_000, 1_000));
+        // 99.0 and 98.9 share the 99.0 bucket at a 0.5 step
+        assert_eq!(closed[0].bids, vec![(198, 4.0)]);
+        assert_eq!(closed[0].asks, vec![(200, 2.0)]);
+
+        // The 10s tier averages all three snapshots, the open 1s column included
+        let column = store.column_between("BTCUSDT", 0, 10_000, 0.5).unwrap();
+        assert_eq!(column.bids, vec![(198, 5.0)]);
+
+        // Ranges inside the 1s history read 1s columns and re-bucket them
+        let column = store.column_between("BTCUSDT", 1_000, 3_000, 1.0).unwrap();
+        assert_eq!(column.bids, vec![(99, 5.5)]);
+        assert_eq!(column.asks, vec![(100, 4.0)]);
+        assert!(store.column_between("ETHUSDT", 0, 10_000, 0.5).is_none());
+    }
+
+    #[test]
+    fn test_retention_and_restored_history() {
+        let store = store();
+        store.insert_column(HeatmapColumn {
+            symbol: "BTCUSDT".to_string(),
+            start: 0,
+            resolution_ms: 10_000,
+            granularity: 1.0,
+            bids: vec![(99, 8.0)],
+            asks: vec![],
+        });
+        // Unknown resolutions are not kept
+        store.insert_column(HeatmapColumn {
+            symbol: "BTCUSDT".to_string(),
+            start: 0,
+            resolution_ms: 5_000,
+            granularity: 1.0,
+            bids: vec![(99, 100.0)],
+            asks: vec![],
+        });
+        assert_eq!(store.column_between("BTCUSDT", 0, 10_000, 1.0).unwrap().bids, vec![(99, 8.0)]);
+        assert_eq!(store.revision("BTCUSDT"), (0, 1));
+
+        for second in 10..=80 {
+            store.add_snapshot("BTCUSDT", &snapshot(second * 1_000, 1.0, 1.0), 1.0);
+        }
+        // 1s columns past their 60s retention are gone, so older ranges fall back to 10s columns
+        assert_eq!(store.column_between("BTCUSDT", 15_000, 16_000, 1.0).unwrap().bids, vec![(99, 2.0)]);
+        let inner = store.inner.read().unwrap();
+        assert_eq!(inner.symbols["BTCUSDT"][0].closed.front().unwrap().start, 20_000);
+    }
+
+    #[tokio::test]
+    async fn test_opened_symbols_are_recorded_and_seeded_once() {
+        use crate::data::DatabaseManager;
+
+        let db = DatabaseManager::new(":memory:").await.unwrap();
+        db.initialize_schema().await.unwrap();
+        let now = chrono::Utc::now().timestamp_millis() as u64 / 10_000 * 10_000;
+        let stored = |symbol: &str| PersistRecord::HeatmapColumn(HeatmapColumn {
+            symbol: symbol.to_string(),
+            start: now - 20_000,
+            resolution_ms: 10_000,
+            granularity: 1.0,
+            bids: vec![(99, 3.0)],
+            asks: vec![],
+        });
+        db.write_batch(&[stored("BTCUSDT"), stored("ETHUSDT")]).await.unwrap();
+
+        let store = store();
+        let mut recorder = HeatmapRecorder::new(store.clone(), SymbolRegistry::default(), DepthAggregation::new(1));
+        recorder.set_database(db);
+        assert!(store.is_recorded("BTCUSDT"));
+        assert!(!store.is_recorded("ETHUSDT"));
+
+        // Nothing is read from the database until a symbol is opened
+        recorder.load_requested_history().await;
+        assert!(store.column_between("BTCUSDT", 0, now, 1.0).is_none());
+
+        store.open("ETHUSDT");
+        store.open("ETHUSDT");
+        assert!(store.is_recorded("ETHUSDT"));
+        assert_eq!(store.take_pending_history(), vec!["ETHUSDT".to_string()]);
+        store.open("BTCUSDT");
+        recorder.load_requested_history().await;
+        assert_eq!(store.column_between("BTCUSDT", now - 20_000, now - 10_000, 1.0).unwrap().bids, vec![(99, 3.0)]);
+        assert!(store.column_between("ETHUSDT", 0, now, 1.0).is_none());
+    }
+}
@@ -93,6 +93,31 @@ pub struct BigOrderflowAlert {
     pub notional_value: f64,
 }
 
+/// Resting-order behaviour flagged by the book pattern detector
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookPattern {
+    /// Large order repeatedly placed and pulled untraded as price came near
+    Spoof,
+    /// Level that keeps refilling after being traded through
+    Iceberg,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookPatternAlert {
+    pub symbol: String,
+    pub timestamp: u64,
+    pub pattern: BookPattern,
+    /// "BID" or "ASK"
+    pub side: String,
+    pub price: f64,
+    /// Largest size shown at the level
+    pub displayed_quantity: f64,
+    /// Spoof cycles or refills within the detection window
+    pub occurrences: usize,
+    /// Volume printed at the level over those occurrences
+    pub traded_quantity: f64,
+}
+
 #[derive(Debug, Clone)]
 pub enum GuiUpdate {
     BigOrderflow(BigOrderflowAlert),
@@ -100,6 +125,14 @@ pub enum GuiUpdate {
     Liquidation(LiquidationEvent),
     VolumeProfile(VolumeProfile),
     DailyStats(DailyStats),
+    OpenInterest(OpenInterestData),
+    /// Latest funding, mark and index price of every symbol in one mark price frame
+    FundingRates(Vec<FundingRateData>),
+    SymbolUniverse(super::UniverseChange),
+    /// Health of every stream connection, from the connection supervisor
+    ConnectionHealth(Vec<super::ConnectionHealth>),
+    EventBus(Vec<super::SubscriberStats>),
+    Alert(ScreenerAlert),
 }
 
 impl VolumeProfile {
@@ -304,6 +337,7 @@ pub enum ScreenerAlert {
         volume_causing_impact: f64,
         timeframe_seconds: u64,
     },
+    BookPattern(BookPatternAlert),
 }
 
 // Enhanced GuiUpdate enum to include new data types
@@ -366,6 +400,20 @@ impl FundingRateData {
         self.funding_rate.abs() > threshold
     }
 
+    /// Next settlement, from the sample time and the seconds left until it
+    pub fn next_funding_time(&self) -> u64 {
+        self.timestamp + self.funding_interval * 1000
+    }
+
+    /// Mark price premium over `last_price` in basis points
+    pub fn basis_bps(&self, last_price: f64) -> f64 {
+        if last_price > 0.0 {
+            (self.mark_price - last_price) / last_price * 10_000.0
+        } else {
+            0.0
+        }
+    }
+
     pub fn funding_cost_annual(&self) -> f64 {
         // Approximate annual funding cost as percentage
         self.funding_rate * 365.0 * 3.0 * 100.0 // 3 times per day
@@ -0,0 +1,119 @@
+use anyhow::{Result, anyhow};
+use std::sync::Arc;
+
+use crate::config::Settings;
+use super::{OrderflowEvent, LiquidationEvent, DepthUpdate, DepthSnapshotResponse, OpenInterestData, FundingRateData, SymbolInfo, BinanceUsdmSource, BinanceCoinmSource};
+
+/// Normalized event decoded from an exchange frame
+#[derive(Debug, Clone)]
+pub enum MarketEvent {
+    Trade(OrderflowEvent),
+    Depth(DepthUpdate),
+    Liquidation(LiquidationEvent),
+    /// Funding, mark and index price per symbol from one mark price frame
+    MarkPrices(Vec<FundingRateData>),
+}
+
+/// Exchange adapter: knows the endpoints and wire format of one market and turns
+/// its raw frames into the normalized events consumed by the analyzers and panels.
+///
+/// Adapters hold no connections; `WebSocketManager` and `OrderBookManager` drive the
+/// network side, so every adapter can be tested by feeding it recorded frames.
+pub trait MarketDataSource: Send + Sync {
+    /// Short identifier used in logs, e.g. "binance-usdm"
+    fn name(&self) -> &str;
+
+    fn exchange_info_url(&self) -> String;
+
+    fn depth_snapshot_url(&self, symbol: &str, limit: usize) -> String;
+
+    /// REST aggregate trades page: `limit` trades starting at `from_id`, or the most
+    /// recent ones when `from_id` is `None`
+    fn agg_trades_url(&self, symbol: &str, from_id: Option<u64>, limit: usize) -> String;
+
+    /// REST current open interest for one symbol
+    fn open_interest_url(&self, symbol: &str) -> String;
+
+    /// REST open interest history; `period` is one of Binance's "5m", "15m", ... "1d"
+    fn open_interest_history_url(&self, symbol: &str, period: &str, limit: usize) -> String;
+
+    /// Combined-stream endpoint opened with `streams` already subscribed; more can be
+    /// added on the connection with SUBSCRIBE requests
+    fn combined_stream_url(&self, streams: &[String]) -> String;
+
+    fn liquidation_stream_url(&self) -> String;
+
+    /// All-market mark price and funding stream, if the market has one
+    fn mark_price_stream_url(&self) -> Option<String>;
+
+    fn trade_stream(&self, symbol: &str) -> String;
+
+    fn depth_stream(&self, symbol: &str) -> String;
+
+    /// Select tradable perpetual symbols from an exchangeInfo response
+    fn parse_symbols(&self, exchange_info: &serde_json::Value) -> Vec<String>;
+
+    /// Decode tick size, lot size and contract metadata of every symbol in an exchangeInfo response
+    fn parse_symbol_info(&self, exchange_info: &serde_json::Value) -> Vec<SymbolInfo>;
+
+    /// Decode one WebSocket text frame. Control frames (subscription acks) yield `None`.
+    fn parse_frame(&self, text: &str) -> Result<Option<MarketEvent>>;
+
+    /// Decode a REST depth snapshot body
+    fn parse_depth_snapshot(&self, symbol: &str, body: &str) -> Result<DepthSnapshotResponse>;
+
+    /// Decode a REST aggTrades body, oldest trade first
+    fn parse_agg_trades(&self, symbol: &str, body: &str) -> Result<Vec<OrderflowEvent>>;
+
+    /// Decode a REST openInterest body. Open interest stays in the market's own unit:
+    /// base asset on USD-M, contracts on COIN-M.
+    fn parse_open_interest(&self, symbol: &str, body: &str) -> Result<OpenInterestData>;
+
+    /// Decode a REST openInterestHist body, oldest reading first
+    fn parse_open_interest_history(&self, symbol: &str, body: &str) -> Result<Vec<OpenInterestData>>;
+}
+
+/// Fetch the adapter's raw exchangeInfo response
+pub async fn fetch_exchange_info(source: &dyn MarketDataSource) -> Result<serde_json::Value> {
+    let client = reqwest::Client::new();
+    Ok(client
+        .get(source.exchange_info_url())
+        .send()
+        .await?
+        .json()
+        .await?)
+}
+
+/// Fetch the adapter's exchangeInfo and return the tradable perpetual symbols
+pub async fn fetch_active_symbols(source: &dyn MarketDataSource) -> Result<Vec<String>> {
+    Ok(source.parse_symbols(&fetch_exchange_info(source).await?))
+}
+
+/// Keep the `symbols` this adapter lists, so per-symbol REST calls are not sent to
+/// the wrong market. Keeps them all when exchangeInfo cannot be fetched.
+pub async fn retain_listed_symbols(source: &dyn MarketDataSource, symbols: &mut Vec<String>) {
+    match fetch_active_symbols(source).await {
+        Ok(active) => symbols.retain(|symbol| active.contains(symbol)),
+        Err(e) => tracing::warn!("Cannot list {} symbols: {}", source.name(), e),
+    }
+}
+
+/// Build the adapters listed in `binance.markets`
+pub fn create_sources(settings: &Settings) -> Result<Vec<Arc<dyn MarketDataSource>>> {
+    settings.binance.markets
+        .iter()
+        .map(|market| -> Result<Arc<dyn MarketDataSource>> {
+            match market.as_str() {
+                "usdm" => Ok(Arc::new(BinanceUsdmSource::new(
+                    settings.binance.websocket_base_url.clone(),
+                    settings.binance.api_base_url.clone(),
+                ))),
+                "coinm" => Ok(Arc::new(BinanceCoinmSource::new(
+                    settings.binance.coinm_websocket_base_url.clone(),
+                    settings.binance.coinm_api_base_url.clone(),
+                ))),
+                other => Err(anyhow!("Unknown market '{}', expected \"usdm\" or \"coinm\"", other)),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,402 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use anyhow::Result;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::Duration;
+use tracing::{debug, info};
+
+use super::{ConnectionHealth, ConnectionMonitor, EventBus, PersistenceHandle, PersistenceMonitor, PersistenceStats,
+            SubscriberStats, TradeGapMetrics, TradeGapStats};
+
+/// Upper bounds, in seconds, of the latency histogram buckets
+pub const LATENCY_BUCKETS: [f64; 12] = [0.00001, 0.00005, 0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0];
+/// Longest a scrape request may take to arrive
+const SCRAPE_READ_TIMEOUT_SECS: u64 = 5;
+
+/// Monotonic counter; clones count into the same total
+#[derive(Debug, Clone, Default)]
+pub struct Counter(Arc<AtomicU64>);
+
+impl Counter {
+    pub fn inc(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Default)]
+struct HistogramCells {
+    /// One cell per bucket of `LATENCY_BUCKETS`, plus one for slower observations
+    buckets: [AtomicU64; LATENCY_BUCKETS.len() + 1],
+    count: AtomicU64,
+    sum_ns: AtomicU64,
+}
+
+/// Lock-free latency histogram over `LATENCY_BUCKETS`; clones observe into the same cells
+#[derive(Debug, Clone, Default)]
+pub struct LatencyHistogram(Arc<HistogramCells>);
+
+impl LatencyHistogram {
+    pub fn observe(&self, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        let bucket = LATENCY_BUCKETS.iter().position(|bound| seconds <= *bound).unwrap_or(LATENCY_BUCKETS.len());
+        self.0.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.0.count.fetch_add(1, Ordering::Relaxed);
+        self.0.sum_ns.fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> LatencyStats {
+        LatencyStats {
+            buckets: self.0.buckets.iter().map(|bucket| bucket.load(Ordering::Relaxed)).collect(),
+            count: self.0.count.load(Ordering::Relaxed),
+            sum_seconds: self.0.sum_ns.load(Ordering::Relaxed) as f64 / 1e9,
+        }
+    }
+}
+
+/// Point-in-time copy of a `LatencyHistogram`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LatencyStats {
+    /// Observations per bucket (not cumulative), the last one past every bound
+    pub buckets: Vec<u64>,
+    pub count: u64,
+    pub sum_seconds: f64,
+}
+
+impl LatencyStats {
+    pub fn mean_seconds(&self) -> f64 {
+        if self.count == 0 { 0.0 } else { self.sum_seconds / self.count as f64 }
+    }
+
+    /// Upper bound of the bucket holding the `q` quantile; observations past
+    /// every bound report the largest bound
+    pub fn quantile_seconds(&self, q: f64) -> f64 {
+        let rank = (q.clamp(0.0, 1.0) * self.count as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (bucket, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return LATENCY_BUCKETS[bucket.min(LATENCY_BUCKETS.len() - 1)];
+            }
+        }
+        0.0
+    }
+}
+
+#[derive(Default)]
+struct Registry {
+    connections: Option<ConnectionMonitor>,
+    event_bus: Option<EventBus>,
+    persistence: Option<PersistenceMonitor>,
+    trade_gaps: Vec<(String, Arc<TradeGapMetrics>)>,
+    orderbook_resyncs: BTreeMap<String, Counter>,
+    book_level_events: BTreeMap<String, Counter>,
+    analyzer_latency: BTreeMap<String, LatencyHistogram>,
+    gui_frame_time: Option<LatencyHistogram>,
+}
+
+/// Shared, cheaply cloned registry of the pipeline's counters. Components either
+/// get a counter or histogram to update, or register the stats they already keep;
+/// `snapshot` reads everything for the `/metrics` endpoint and the diagnostics panel.
+#[derive(Clone, Default)]
+pub struct Metrics {
+    registry: Arc<Mutex<Registry>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_connection_monitor(&self, monitor: ConnectionMonitor) {
+        self.registry.lock().unwrap().connections = Some(monitor);
+    }
+
+    pub fn set_event_bus(&self, bus: EventBus) {
+        self.registry.lock().unwrap().event_bus = Some(bus);
+    }
+
+    /// Report the persistence queue without keeping its writer alive
+    pub fn set_persistence(&self, persistence: &PersistenceHandle) {
+        self.registry.lock().unwrap().persistence = Some(persistence.monitor());
+    }
+
+    pub fn add_trade_gaps(&self, market: &str, metrics: Arc<TradeGapMetrics>) {
+        self.registry.lock().unwrap().trade_gaps.push((market.to_string(), metrics));
+    }
+
+    /// Order book resyncs of `market`
+    pub fn orderbook_resyncs(&self, market: &str) -> Counter {
+        self.registry.lock().unwrap().orderbook_resyncs.entry(market.to_string()).or_default().clone()
+    }
+
+    /// Level changes classified as `kind` (add, cancel or execution)
+    pub fn book_level_events(&self, kind: &str) -> Counter {
+        self.registry.lock().unwrap().book_level_events.entry(kind.to_string()).or_default().clone()
+    }
+
+    /// Time `analyzer` spends on one orderflow event
+    pub fn analyzer_latency(&self, analyzer: &str) -> LatencyHistogram {
+        self.registry.lock().unwrap().analyzer_latency.entry(analyzer.to_string()).or_default().clone()
+    }
+
+    pub fn gui_frame_time(&self) -> LatencyHistogram {
+        self.registry.lock().unwrap().gui_frame_time.get_or_insert_with(LatencyHistogram::default).clone()
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let registry = self.registry.lock().unwrap();
+        MetricsSnapshot {
+            connections: registry.connections.as_ref().map(ConnectionMonitor::report).unwrap_or_default(),
+            event_bus: registry.event_bus.as_ref().map(EventBus::stats).unwrap_or_default(),
+            persistence: registry.persistence.as_ref().map(PersistenceMonitor::stats),
+            trade_gaps: registry.trade_gaps.iter().map(|(market, metrics)| (market.clone(), metrics.stats())).collect(),
+            orderbook_resyncs: registry.orderbook_resyncs.iter().map(|(market, counter)| (market.clone(), counter.get())).collect(),
+            book_level_events: registry.book_level_events.iter().map(|(kind, counter)| (kind.clone(), counter.get())).collect(),
+            analyzer_latency: registry.analyzer_latency.iter().map(|(name, histogram)| (name.clone(), histogram.stats())).collect(),
+            gui_frame_time: registry.gui_frame_time.as_ref().map(LatencyHistogram::stats),
+        }
+    }
+}
+
+/// Everything the `Metrics` registry knows at one point in time
+#[derive(Debug, Clone, Default)]
+pub struct MetricsSnapshot {
+    /// Per stream connection: message, parse error and drop counts among others
+    pub connections: Vec<ConnectionHealth>,
+    pub event_bus: Vec<SubscriberStats>,
+    pub persistence: Option<PersistenceStats>,
+    pub trade_gaps: Vec<(String, TradeGapStats)>,
+    pub orderbook_resyncs: Vec<(String, u64)>,
+    pub book_level_events: Vec<(String, u64)>,
+    pub analyzer_latency: Vec<(String, LatencyStats)>,
+    pub gui_frame_time: Option<LatencyStats>,
+}
+
+impl MetricsSnapshot {
+    /// Render in the Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        let mut out = PrometheusWriter::default();
+
+        let connections = &self.connections;
+        out.family("screener_stream_messages_total", "counter", "Data frames received per stream connection");
+        for health in connections {
+            out.sample("screener_stream_messages_total", &[("connection", &health.name)], health.total_messages as f64);
+        }
+        out.family("screener_stream_parse_errors_total", "counter", "Frames that could not be decoded");
+        for health in connections {
+            out.sample("screener_stream_parse_errors_total", &[("connection", &health.name)], health.parse_errors as f64);
+        }
+        out.family("screener_stream_dropped_total", "counter", "Decoded events dropped because the downstream channel was full");
+        for health in connections {
+            out.sample("screener_stream_dropped_total", &[("connection", &health.name)], health.dropped_messages as f64);
+        }
+        out.family("screener_stream_reconnects_total", "counter", "Connections established after the first one");
+        for health in connections {
+            out.sample("screener_stream_reconnects_total", &[("connection", &health.name)], health.reconnects as f64);
+        }
+        out.family("screener_stream_connected", "gauge", "Whether the connection is up");
+        for health in connections {
+            out.sample("screener_stream_connected", &[("connection", &health.name)], if health.connected { 1.0 } else { 0.0 });
+        }
+        out.family("screener_stream_ping_seconds", "gauge", "Round trip of the latest answered ping");
+        for health in connections {
+            if let Some(latency) = health.ping_latency_ms {
+                out.sample("screener_stream_ping_seconds", &[("connection", &health.name)], latency as f64 / 1000.0);
+            }
+        }
+
+        out.family("screener_event_bus_dropped_total", "counter", "Events lost because the subscriber's queue was full");
+        for stats in &self.event_bus {
+            out.sample("screener_event_bus_dropped_total", &[("topic", stats.topic), ("subscriber", &stats.name)], stats.dropped as f64);
+        }
+        out.family("screener_event_bus_lag", "gauge", "Events queued but not yet received by the subscriber");
+        for stats in &self.event_bus {
+            out.sample("screener_event_bus_lag", &[("topic", stats.topic), ("subscriber", &stats.name)], stats.lag as f64);
+        }
+
+        if let Some(persistence) = &self.persistence {
+            out.family("screener_persistence_rows_total", "counter", "Rows offered to the database writer");
+            out.sample("screener_persistence_rows_total", &[("state", "enqueued")], persistence.enqueued as f64);
+            out.sample("screener_persistence_rows_total", &[("state", "written")], persistence.written as f64);
+            out.sample("screener_persistence_rows_total", &[("state", "dropped")], persistence.dropped as f64);
+            out.sample("screener_persistence_rows_total", &[("state", "failed")], persistence.failed_rows as f64);
+            out.family("screener_persistence_failed_batches_total", "counter", "Batches the database rejected");
+            out.sample("screener_persistence_failed_batches_total", &[], persistence.failed_batches as f64);
+            out.family("screener_persistence_queue_depth", "gauge", "Rows waiting for the database writer");
+            out.sample("screener_persistence_queue_depth", &[], persistence.queue_depth as f64);
+            out.histogram("screener_db_write_seconds", "Time to commit one batch", &[], &persistence.write_latency);
+        }
+
+        out.family("screener_trade_gap_missing_trades_total", "counter", "Trades skipped by jumps in aggregate trade ids");
+        for (market, stats) in &self.trade_gaps {
+            out.sample("screener_trade_gap_missing_trades_total", &[("market", market)], stats.missing_trades as f64);
+        }
+        out.family("screener_trade_gap_backfilled_trades_total", "counter", "Missing trades fetched over REST");
+        for (market, stats) in &self.trade_gaps {
+            out.sample("screener_trade_gap_backfilled_trades_total", &[("market", market)], stats.backfilled_trades as f64);
+        }
+
+        out.family("screener_orderbook_resyncs_total", "counter", "Order books re-seeded after a gap in depth updates");
+        for (market, resyncs) in &self.orderbook_resyncs {
+            out.sample("screener_orderbook_resyncs_total", &[("market", market)], *resyncs as f64);
+        }
+
+        out.family("screener_book_level_events_total", "counter", "Order book level changes by classification");
+        for (kind, events) in &self.book_level_events {
+            out.sample("screener_book_level_events_total", &[("kind", kind)], *events as f64);
+        }
+
+        out.family("screener_analyzer_seconds", "histogram", "Time an analyzer spends on one orderflow event");
+        for (analyzer, stats) in &self.analyzer_latency {
+            out.buckets("screener_analyzer_seconds", &[("analyzer", analyzer)], stats);
+        }
+
+        if let Some(frame_time) = &self.gui_frame_time {
+            out.histogram("screener_gui_frame_seconds", "Time to build one GUI frame", &[], frame_time);
+        }
+
+        out.text
+    }
+}
+
+#[derive(Default)]
+struct PrometheusWriter {
+    text: String,
+}
+
+impl PrometheusWriter {
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.text, "# HELP {} {}\n# TYPE {} {}", name, help, name, kind);
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        self.text.push_str(name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(key, value)| format!("{}=\"{}\"", key, value.replace('\\', "\\\\").replace('"', "\\\"")))
+                .collect();
+            let _ = write!(self.text, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(self.text, " {}", value);
+    }
+
+    fn histogram(&mut self, name: &str, help: &str, labels: &[(&str, &str)], stats: &LatencyStats) {
+        self.family(name, "histogram", help);
+        self.buckets(name, labels, stats);
+    }
+
+    /// Cumulative `_bucket` samples plus `_sum` and `_count`
+    fn buckets(&mut self, name: &str, labels: &[(&str, &str)], stats: &LatencyStats) {
+        let bucket_name = format!("{}_bucket", name);
+        let mut cumulative = 0;
+        for (bucket, count) in stats.buckets.iter().enumerate() {
+            cumulative += count;
+            let bound = LATENCY_BUCKETS.get(bucket).map_or("+Inf".to_string(), |bound| bound.to_string());
+            let mut bucket_labels = labels.to_vec();
+            bucket_labels.push(("le", &bound));
+            self.sample(&bucket_name, &bucket_labels, cumulative as f64);
+        }
+        self.sample(&format!("{}_sum", name), labels, stats.sum_seconds);
+        self.sample(&format!("{}_count", name), labels, stats.count as f64);
+    }
+}
+
+/// Serve `GET /metrics` in Prometheus text format until the task is aborted
+pub async fn serve_metrics(listener: TcpListener, metrics: Metrics) {
+    if let Ok(address) = listener.local_addr() {
+        info!("Serving metrics on http://{}/metrics", address);
+    }
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let metrics = metrics.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = answer_scrape(stream, &metrics).await {
+                        debug!("Metrics request failed: {}", e);
+                    }
+                });
+            }
+            Err(e) => debug!("Cannot accept metrics connection: {}", e),
+        }
+    }
+}
+
+async fn answer_scrape(mut stream: TcpStream, metrics: &Metrics) -> Result<()> {
+    // Only the request line matters; headers and body are ignored
+    let mut request = vec![0u8; 1024];
+    let read = tokio::time::timeout(Duration::from_secs(SCRAPE_READ_TIMEOUT_SECS), stream.read(&mut request)).await??;
+    let request = String::from_utf8_lossy(&request[..read]);
+    let mut request_line = request.lines().next().unwrap_or_default().split_whitespace();
+
+    let response = match (request_line.next(), request_line.next()) {
+        (Some("GET"), Some("/metrics")) => {
+            let body = metrics.snapshot().to_prometheus();
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(), body
+            )
+        }
+        _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+    };
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn get(address: std::net::SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream.write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint_exports_registered_metrics() {
+        let metrics = Metrics::new();
+        let resyncs = metrics.orderbook_resyncs("binance-usdm");
+        resyncs.inc();
+        resyncs.inc();
+        metrics.book_level_events("cancel").inc();
+        let latency = metrics.analyzer_latency("imbalance");
+        latency.observe(Duration::from_micros(20));
+        latency.observe(Duration::from_micros(30));
+        latency.observe(Duration::from_millis(3));
+        latency.observe(Duration::from_secs(10));
+
+        let stats = latency.stats();
+        assert_eq!(stats.count, 4);
+        assert_eq!(stats.quantile_seconds(0.5), 0.00005);
+        assert_eq!(stats.quantile_seconds(0.75), 0.005);
+        assert_eq!(stats.quantile_seconds(1.0), 5.0);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = tokio::spawn(serve_metrics(listener, metrics.clone()));
+
+        let response = get(address, "/metrics").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("# TYPE screener_orderbook_resyncs_total counter"));
+        assert!(response.contains("screener_orderbook_resyncs_total{market=\"binance-usdm\"} 2\n"));
+        assert!(response.contains("screener_book_level_events_total{kind=\"cancel\"} 1\n"));
+        assert!(response.contains("screener_analyzer_seconds_bucket{analyzer=\"imbalance\",le=\"0.00005\"} 2\n"));
+        assert!(response.contains("screener_analyzer_seconds_bucket{analyzer=\"imbalance\",le=\"5\"} 3\n"));
+        assert!(response.contains("screener_analyzer_seconds_bucket{analyzer=\"imbalance\",le=\"+Inf\"} 4\n"));
+        assert!(response.contains("screener_analyzer_seconds_count{analyzer=\"imbalance\"} 4\n"));
+
+        assert!(get(address, "/").await.starts_with("HTTP/1.1 404"));
+        server.abort();
+    }
+}
@@ -5,6 +5,23 @@ pub mod database;
 pub mod symbols;
 pub mod orderbook;
 pub mod orderbook_manager;
+pub mod market_source;
+pub mod binance_source;
+pub mod binance_trades;
+pub mod capture;
+pub mod db_follower;
+pub mod persistence;
+pub mod backfill;
+pub mod footprint_store;
+pub mod symbol_universe;
+pub mod symbol_registry;
+pub mod connection_health;
+pub mod trade_gaps;
+pub mod event_bus;
+pub mod metrics;
+pub mod book_analytics;
+pub mod book_events;
+pub mod heatmap_store;
 
 pub use websocket::*;
 pub use market_data::*;
@@ -12,4 +29,20 @@ pub use orderflow::*;
 pub use database::*;
 pub use symbols::*;
 pub use orderbook::*;
-pub use orderbook_manager::*;
\ No newline at end of file
+pub use orderbook_manager::*;
+pub use market_source::*;
+pub use binance_source::*;
+pub use capture::*;
+pub use db_follower::*;
+pub use persistence::*;
+pub use backfill::*;
+pub use footprint_store::*;
+pub use symbol_universe::*;
+pub use symbol_registry::*;
+pub use connection_health::*;
+pub use trade_gaps::*;
+pub use event_bus::*;
+pub use metrics::*;
+pub use book_analytics::*;
+pub use book_events::*;
+pub use heatmap_store::*;
\ No newline at end of file
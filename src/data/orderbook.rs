@@ -19,6 +19,56 @@ pub struct OrderBook {
     pub asks: BTreeMap<OrderedFloat<f64>, f64>,
     pub last_update_id: u64,
     pub timestamp: u64,
+    pub sync_state: BookSyncState,
+}
+
+/// Where the local book stands relative to the exchange's update-id sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookSyncState {
+    /// No REST snapshot applied yet, diffs cannot be used
+    Unseeded,
+    /// Snapshot applied, waiting for the first diff that straddles its lastUpdateId
+    Seeded,
+    /// Diffs are chained by `pu` and applied continuously
+    Live,
+}
+
+/// One price level of a diff event, with the quantity it replaced
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelChange {
+    /// "BID" or "ASK"
+    pub side: String,
+    pub price: f64,
+    pub previous: f64,
+    pub quantity: f64,
+}
+
+/// The levels a diff event changed in a live book, and the touch it left
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookDelta {
+    pub symbol: String,
+    pub timestamp: u64,
+    pub best_bid: Option<f64>,
+    pub best_ask: Option<f64>,
+    pub changes: Vec<LevelChange>,
+}
+
+impl BookDelta {
+    pub fn mid_price(&self) -> Option<f64> {
+        Some((self.best_bid? + self.best_ask?) / 2.0)
+    }
+}
+
+/// Result of feeding a diff event into the book
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateOutcome {
+    Applied,
+    /// Event is older than the book and was ignored
+    Stale,
+    /// Book has no snapshot yet, event was not applied
+    Unseeded,
+    /// Sequence broken, the book must be re-seeded from a fresh snapshot
+    Gap { expected: u64, first_update_id: u64, prev_final_update_id: u64 },
 }
 
 impl OrderBook {
@@ -29,13 +79,96 @@ impl OrderBook {
             asks: BTreeMap::new(),
             last_update_id: 0,
             timestamp: 0,
+            sync_state: BookSyncState::Unseeded,
+        }
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.sync_state != BookSyncState::Unseeded
+    }
+
+    /// Replace the book contents with a REST depth snapshot
+    pub fn apply_snapshot(&mut self, snapshot: &DepthSnapshotResponse) {
+        self.bids = snapshot.bids.iter()
+            .filter(|(_, q)| *q > 0.0)
+            .map(|(p, q)| (OrderedFloat(*p), *q))
+            .collect();
+        self.asks = snapshot.asks.iter()
+            .filter(|(_, q)| *q > 0.0)
+            .map(|(p, q)| (OrderedFloat(*p), *q))
+            .collect();
+        self.last_update_id = snapshot.last_update_id;
+        self.timestamp = snapshot.event_time;
+        self.sync_state = BookSyncState::Seeded;
+    }
+
+    /// Drop all levels and wait for a new snapshot
+    pub fn reset(&mut self) {
+        self.bids.clear();
+        self.asks.clear();
+        self.last_update_id = 0;
+        self.sync_state = BookSyncState::Unseeded;
+    }
+
+    /// Apply depth update from WebSocket, validating the U/u/pu sequence
+    ///
+    /// Follows Binance's futures book-sync rules: events with `u` below the
+    /// snapshot's lastUpdateId are dropped, the first applied event must satisfy
+    /// `U <= lastUpdateId <= u`, and every later event's `pu` must equal the
+    /// previous event's `u`.
+    pub fn apply_update(&mut self, update: &DepthUpdate) -> UpdateOutcome {
+        match self.sync_state {
+            BookSyncState::Unseeded => return UpdateOutcome::Unseeded,
+            BookSyncState::Seeded => {
+                if update.last_update_id < self.last_update_id {
+                    return UpdateOutcome::Stale;
+                }
+                if update.first_update_id > self.last_update_id {
+                    return UpdateOutcome::Gap {
+                        expected: self.last_update_id,
+                        first_update_id: update.first_update_id,
+                        prev_final_update_id: update.prev_final_update_id,
+                    };
+                }
+            }
+            BookSyncState::Live => {
+                if update.last_update_id <= self.last_update_id {
+                    return UpdateOutcome::Stale;
+                }
+                if update.prev_final_update_id != self.last_update_id {
+                    return UpdateOutcome::Gap {
+                        expected: self.last_update_id,
+                        first_update_id: update.first_update_id,
+                        prev_final_update_id: update.prev_final_update_id,
+                    };
+                }
+            }
         }
+
+        self.apply_levels(update);
+        self.sync_state = BookSyncState::Live;
+        UpdateOutcome::Applied
+    }
+
+    /// Levels `update` would change, against the current contents of the book
+    pub fn level_changes(&self, update: &DepthUpdate) -> Vec<LevelChange> {
+        let side_changes = |side: &str, book: &BTreeMap<OrderedFloat<f64>, f64>, levels: &[(f64, f64)]| {
+            levels
+                .iter()
+                .filter_map(|&(price, quantity)| {
+                    let previous = book.get(&OrderedFloat(price)).copied().unwrap_or(0.0);
+                    (previous != quantity).then(|| LevelChange { side: side.to_string(), price, previous, quantity })
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut changes = side_changes("BID", &self.bids, &update.bids);
+        changes.extend(side_changes("ASK", &self.asks, &update.asks));
+        changes
     }
 
-    /// Apply depth update from WebSocket
-    pub fn apply_update(&mut self, update: DepthUpdate) {
+    fn apply_levels(&mut self, update: &DepthUpdate) {
         // Update bids
-        for bid in update.bids {
+        for bid in &update.bids {
             let price = OrderedFloat(bid.0);
             if bid.1 == 0.0 {
                 self.bids.remove(&price);
@@ -45,7 +178,7 @@ impl OrderBook {
         }
 
         // Update asks
-        for ask in update.asks {
+        for ask in &update.asks {
             let price = OrderedFloat(ask.0);
             if ask.1 == 0.0 {
                 self.asks.remove(&price);
@@ -143,6 +276,10 @@ pub struct DepthUpdate {
     #[serde(rename = "u")]
     pub last_update_id: u64,
 
+    /// Final update id of the previous event on this stream
+    #[serde(rename = "pu", default)]
+    pub prev_final_update_id: u64,
+
     #[serde(rename = "b")]
     #[serde(deserialize_with = "deserialize_levels")]
     pub bids: Vec<(f64, f64)>,
@@ -152,6 +289,22 @@ pub struct DepthUpdate {
     pub asks: Vec<(f64, f64)>,
 }
 
+/// REST depth snapshot from `/fapi/v1/depth`, used to seed the local book
+#[derive(Debug, Clone, Deserialize)]
+pub struct DepthSnapshotResponse {
+    #[serde(rename = "lastUpdateId")]
+    pub last_update_id: u64,
+
+    #[serde(rename = "E", default)]
+    pub event_time: u64,
+
+    #[serde(deserialize_with = "deserialize_levels")]
+    pub bids: Vec<(f64, f64)>,
+
+    #[serde(deserialize_with = "deserialize_levels")]
+    pub asks: Vec<(f64, f64)>,
+}
+
 /// Deserialize price levels from string arrays to f64 tuples
 fn deserialize_levels<'de, D>(deserializer: D) -> Result<Vec<(f64, f64)>, D::Error>
 where
@@ -189,26 +342,37 @@ pub struct DepthHistory {
     pub symbol: String,
     pub snapshots: Vec<TimedDepthSnapshot>,
     pub max_history: usize,  // Number of snapshots to keep
+    pub granularity: f64,  // Price step of the aggregated levels
 }
 
 #[derive(Debug, Clone)]
 pub struct TimedDepthSnapshot {
     pub timestamp: u64,
     pub depth: DepthSnapshot,
-    pub aggregated_bids: BTreeMap<i64, f64>,  // price_tick -> cumulative quantity
+    pub granularity: f64,
+    pub aggregated_bids: BTreeMap<i64, f64>,  // price / granularity -> cumulative quantity
     pub aggregated_asks: BTreeMap<i64, f64>,
 }
 
 impl DepthHistory {
-    pub fn new(symbol: String, max_history: usize) -> Self {
+    pub fn new(symbol: String, max_history: usize, granularity: f64) -> Self {
         Self {
             symbol,
             snapshots: Vec::with_capacity(max_history),
             max_history,
+            granularity,
         }
     }
 
-    pub fn add_snapshot(&mut self, snapshot: TimedDepthSnapshot) {
+    /// Aggregate `depth` at the history's granularity and store it
+    pub fn add_depth(&mut self, timestamp: u64, depth: DepthSnapshot) {
+        self.add_snapshot(TimedDepthSnapshot::new(timestamp, depth, self.granularity));
+    }
+
+    pub fn add_snapshot(&mut self, mut snapshot: TimedDepthSnapshot) {
+        if snapshot.granularity != self.granularity {
+            snapshot.rebucket(self.granularity);
+        }
         self.snapshots.push(snapshot);
         if self.snapshots.len() > self.max_history {
             self.snapshots.remove(0);
@@ -227,6 +391,17 @@ impl DepthHistory {
         Some((bid_qty, ask_qty))
     }
 
+    /// Re-aggregate the stored snapshots at a new price step
+    pub fn set_granularity(&mut self, granularity: f64) {
+        if granularity == self.granularity {
+            return;
+        }
+        self.granularity = granularity;
+        for snapshot in &mut self.snapshots {
+            snapshot.rebucket(granularity);
+        }
+    }
+
     /// Get all snapshots within a time range
     pub fn get_snapshots_in_range(&self, start_time: u64, end_time: u64) -> Vec<&TimedDepthSnapshot> {
         self.snapshots.iter()
@@ -236,29 +411,32 @@ impl DepthHistory {
 }
 
 impl TimedDepthSnapshot {
-    pub fn new(timestamp: u64, depth: DepthSnapshot, tick_size: f64) -> Self {
-        let mut aggregated_bids = BTreeMap::new();
-        let mut aggregated_asks = BTreeMap::new();
-
-        // Aggregate bids to price ticks
-        for (price, qty) in &depth.bids {
-            let price_tick = (*price / tick_size).round() as i64;
-            *aggregated_bids.entry(price_tick).or_insert(0.0) += qty;
-        }
-
-        // Aggregate asks to price ticks
-        for (price, qty) in &depth.asks {
-            let price_tick = (*price / tick_size).round() as i64;
-            *aggregated_asks.entry(price_tick).or_insert(0.0) += qty;
-        }
-
+    pub fn new(timestamp: u64, depth: DepthSnapshot, granularity: f64) -> Self {
         Self {
             timestamp,
+            aggregated_bids: aggregate_levels(&depth.bids, granularity),
+            aggregated_asks: aggregate_levels(&depth.asks, granularity),
             depth,
-            aggregated_bids,
-            aggregated_asks,
+            granularity,
         }
     }
+
+    /// Re-aggregate the full depth this snapshot keeps at a new price step
+    pub fn rebucket(&mut self, granularity: f64) {
+        self.granularity = granularity;
+        self.aggregated_bids = aggregate_levels(&self.depth.bids, granularity);
+        self.aggregated_asks = aggregate_levels(&self.depth.asks, granularity);
+    }
+}
+
+/// Sum (price, quantity) levels into buckets of `granularity`
+fn aggregate_levels(levels: &[(f64, f64)], granularity: f64) -> BTreeMap<i64, f64> {
+    let mut aggregated = BTreeMap::new();
+    for (price, qty) in levels {
+        let price_tick = (*price / granularity).round() as i64;
+        *aggregated.entry(price_tick).or_insert(0.0) += qty;
+    }
+    aggregated
 }
 
 #[cfg(test)]
@@ -287,6 +465,29 @@ mod tests {
         assert_eq!(snapshot.bids[0].0, 50000.0);  // Highest bid first
     }
 
+    #[test]
+    fn test_depth_history_rebuckets() {
+        let depth = DepthSnapshot {
+            bids: vec![(100.2, 1.0), (100.1, 2.0), (99.4, 3.0)],
+            asks: vec![(100.3, 1.5)],
+            timestamp: 1,
+        };
+        let mut history = DepthHistory::new("BTCUSDT".to_string(), 10, 0.1);
+        history.add_depth(1, depth.clone());
+        assert_eq!(history.snapshots[0].aggregated_bids.len(), 3);
+
+        history.set_granularity(1.0);
+        let snapshot = &history.snapshots[0];
+        assert_eq!(snapshot.aggregated_bids.get(&100), Some(&3.0));
+        assert_eq!(snapshot.aggregated_bids.get(&99), Some(&3.0));
+        assert_eq!(snapshot.aggregated_asks.get(&100), Some(&1.5));
+
+        // Snapshots built at another step join at the history's
+        history.add_snapshot(TimedDepthSnapshot::new(2, depth, 0.1));
+        assert_eq!(history.snapshots[1].granularity, 1.0);
+        assert_eq!(history.get_intensity_at(2, 100), Some((3.0, 1.5)));
+    }
+
     #[test]
     fn test_cumulative_depth() {
         let mut ob = OrderBook::new("BTCUSDT".to_string());
@@ -297,4 +498,75 @@ mod tests {
         let cumulative = ob.get_bid_depth_at_price(50000.0);
         assert_eq!(cumulative, 6.0);  // Sum of all bids <= 50000
     }
+
+    fn diff(first: u64, last: u64, prev: u64, bids: Vec<(f64, f64)>) -> DepthUpdate {
+        DepthUpdate {
+            event_type: "depthUpdate".to_string(),
+            event_time: last,
+            symbol: "BTCUSDT".to_string(),
+            first_update_id: first,
+            last_update_id: last,
+            prev_final_update_id: prev,
+            bids,
+            asks: vec![],
+        }
+    }
+
+    fn seeded_book() -> OrderBook {
+        let mut ob = OrderBook::new("BTCUSDT".to_string());
+        ob.apply_snapshot(&DepthSnapshotResponse {
+            last_update_id: 100,
+            event_time: 1,
+            bids: vec![(50000.0, 1.0), (49999.0, 2.0)],
+            asks: vec![(50001.0, 1.5)],
+        });
+        ob
+    }
+
+    #[test]
+    fn test_update_requires_snapshot() {
+        let mut ob = OrderBook::new("BTCUSDT".to_string());
+        assert_eq!(ob.apply_update(&diff(1, 2, 0, vec![(1.0, 1.0)])), UpdateOutcome::Unseeded);
+        assert!(ob.bids.is_empty());
+    }
+
+    #[test]
+    fn test_sequenced_updates_after_snapshot() {
+        let mut ob = seeded_book();
+
+        // Entirely before the snapshot
+        assert_eq!(ob.apply_update(&diff(90, 95, 89, vec![(1.0, 1.0)])), UpdateOutcome::Stale);
+
+        // First event straddles lastUpdateId
+        assert_eq!(ob.apply_update(&diff(98, 103, 97, vec![(50000.0, 0.0)])), UpdateOutcome::Applied);
+        assert_eq!(ob.sync_state, BookSyncState::Live);
+        assert!(!ob.bids.contains_key(&OrderedFloat(50000.0)));
+
+        // Chained by pu
+        assert_eq!(ob.apply_update(&diff(104, 110, 103, vec![(49998.0, 4.0)])), UpdateOutcome::Applied);
+        assert_eq!(ob.last_update_id, 110);
+        assert_eq!(ob.bids.get(&OrderedFloat(49998.0)), Some(&4.0));
+    }
+
+    #[test]
+    fn test_gap_detection() {
+        let mut ob = seeded_book();
+
+        // First event starts after the snapshot
+        let outcome = ob.apply_update(&diff(105, 110, 104, vec![]));
+        assert!(matches!(outcome, UpdateOutcome::Gap { expected: 100, .. }));
+
+        ob.apply_update(&diff(99, 101, 98, vec![]));
+        let outcome = ob.apply_update(&diff(120, 125, 119, vec![(1.0, 1.0)]));
+        assert!(matches!(outcome, UpdateOutcome::Gap { expected: 101, prev_final_update_id: 119, .. }));
+        assert!(!ob.bids.contains_key(&OrderedFloat(1.0)));
+    }
+
+    #[test]
+    fn test_depth_update_parses_pu() {
+        let json = r#"{"e":"depthUpdate","E":1,"T":1,"s":"BTCUSDT","U":5,"u":9,"pu":4,"b":[["100.0","1.5"]],"a":[]}"#;
+        let update: DepthUpdate = serde_json::from_str(json).unwrap();
+        assert_eq!(update.prev_final_update_id, 4);
+        assert_eq!(update.bids, vec![(100.0, 1.5)]);
+    }
 }
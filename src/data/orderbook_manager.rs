@@ -1,77 +1,255 @@
 use tokio::sync::mpsc;
-use std::collections::HashMap;
+use tokio::time::{sleep, Duration};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, RwLock};
 use anyhow::Result;
 use tracing::{info, debug, warn};
 
-use crate::data::orderbook::{OrderBook, DepthUpdate, DepthSnapshot, DepthHistory, TimedDepthSnapshot};
+use crate::data::orderbook::{OrderBook, BookDelta, BookSyncState, DepthUpdate, DepthSnapshot, DepthSnapshotResponse, DepthHistory, UpdateOutcome};
+use crate::data::market_source::MarketDataSource;
+use crate::data::capture::{CaptureRecorder, CaptureEvent};
+use crate::data::symbol_registry::SymbolRegistry;
+use crate::data::event_bus::Topic;
+use crate::data::metrics::{Counter, Metrics};
+use crate::data::book_analytics::{BookAnalytics, BookAnalyzer};
+use crate::config::settings::{BookAnalyticsConfig, LobConfig, WideHeatmapConfig};
+
+/// Depth levels requested from the REST snapshot endpoint (weight 10 on Binance futures)
+const SNAPSHOT_DEPTH_LIMIT: usize = 500;
+/// Deepest REST snapshot, requested for wide heatmap focus symbols (weight 20)
+const SNAPSHOT_MAX_DEPTH_LIMIT: usize = 1000;
+/// Spacing between snapshot requests to stay well inside the REST weight budget
+const SNAPSHOT_REQUEST_INTERVAL_MS: u64 = 400;
+const SNAPSHOT_MAX_ATTEMPTS: u32 = 3;
+/// Diffs kept per symbol while waiting for a snapshot
+const MAX_BUFFERED_UPDATES: usize = 5000;
+
+/// Depth history price step per symbol, in multiples of the contract tick.
+/// Clones share overrides, so the GUI can re-bucket a symbol's history in the
+/// order book managers when the footprint price scale changes.
+#[derive(Debug, Clone)]
+pub struct DepthAggregation {
+    default_multiple: u32,
+    multiples: Arc<RwLock<HashMap<String, u32>>>,
+}
+
+impl DepthAggregation {
+    pub fn new(default_multiple: u32) -> Self {
+        Self {
+            default_multiple: default_multiple.max(1),
+            multiples: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub fn tick_multiple(&self, symbol: &str) -> u32 {
+        self.multiples.read().unwrap().get(symbol).copied().unwrap_or(self.default_multiple)
+    }
+
+    pub fn set_tick_multiple(&self, symbol: &str, multiple: u32) {
+        self.multiples.write().unwrap().insert(symbol.to_string(), multiple.max(1));
+    }
+}
+
+impl Default for DepthAggregation {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl From<&LobConfig> for DepthAggregation {
+    fn from(config: &LobConfig) -> Self {
+        let aggregation = Self::new(config.tick_multiple);
+        for (symbol, multiple) in &config.tick_multiples {
+            aggregation.set_tick_multiple(symbol, *multiple);
+        }
+        aggregation
+    }
+}
 
 pub struct OrderBookManager {
     orderbooks: HashMap<String, OrderBook>,
     depth_histories: HashMap<String, DepthHistory>,
     depth_receiver: mpsc::Receiver<DepthUpdate>,
 
-    // Channels to send processed data to GUI
-    snapshot_sender: mpsc::Sender<(String, DepthSnapshot)>,
+    // Book snapshots published to the GUI
+    depth_topic: Topic<(String, DepthSnapshot)>,
+    // Periodic analytics of the synced books, when enabled
+    book_analytics: Option<(BookAnalyzer, Topic<BookAnalytics>)>,
+    // Level changes of live books, when enabled
+    book_deltas: Option<Topic<BookDelta>>,
+    // Full-depth snapshots of the wide heatmap focus symbols, when enabled
+    wide_depth: Option<(WideHeatmapConfig, Topic<(String, DepthSnapshot)>)>,
+
+    // Book seeding: diffs are buffered until the REST snapshot arrives
+    snapshot_fetcher: DepthSnapshotFetcher,
+    pending_updates: HashMap<String, VecDeque<DepthUpdate>>,
+    snapshot_requests_in_flight: HashSet<String>,
+    snapshot_request_sender: mpsc::UnboundedSender<String>,
+    snapshot_request_receiver: Option<mpsc::UnboundedReceiver<String>>,
+    snapshot_result_sender: mpsc::Sender<(String, Result<DepthSnapshotResponse>)>,
+    snapshot_result_receiver: Option<mpsc::Receiver<(String, Result<DepthSnapshotResponse>)>>,
+    fetch_snapshots: bool,
+    resyncs: Counter,
+
+    recorder: Option<CaptureRecorder>,
+    registry: SymbolRegistry,
+    depth_aggregation: DepthAggregation,
 
     max_levels: usize,  // Max depth levels to maintain
     snapshot_interval_ms: u64,  // How often to snapshot for history
-    tick_size: f64,  // Default tick size for aggregation
+    tick_size: f64,  // Aggregation tick for symbols missing from the registry
 }
 
 impl OrderBookManager {
     pub fn new(
         depth_receiver: mpsc::Receiver<DepthUpdate>,
-        snapshot_sender: mpsc::Sender<(String, DepthSnapshot)>,
+        depth_topic: Topic<(String, DepthSnapshot)>,
+        source: Arc<dyn MarketDataSource>,
     ) -> Self {
-        Self {
-            orderbooks: HashMap::new(),
-            depth_histories: HashMap::new(),
-            depth_receiver,
-            snapshot_sender,
-            max_levels: 100,
-            snapshot_interval_ms: 100,
-            tick_size: 0.01,
-        }
+        Self::with_config(depth_receiver, depth_topic, source, 100, 100, 0.01)
     }
 
     pub fn with_config(
         depth_receiver: mpsc::Receiver<DepthUpdate>,
-        snapshot_sender: mpsc::Sender<(String, DepthSnapshot)>,
+        depth_topic: Topic<(String, DepthSnapshot)>,
+        source: Arc<dyn MarketDataSource>,
         max_levels: usize,
         snapshot_interval_ms: u64,
         tick_size: f64,
     ) -> Self {
+        let (snapshot_request_sender, snapshot_request_receiver) = mpsc::unbounded_channel();
+        let (snapshot_result_sender, snapshot_result_receiver) = mpsc::channel(100);
+
         Self {
             orderbooks: HashMap::new(),
             depth_histories: HashMap::new(),
             depth_receiver,
-            snapshot_sender,
+            depth_topic,
+            book_analytics: None,
+            book_deltas: None,
+            wide_depth: None,
+            snapshot_fetcher: DepthSnapshotFetcher::new(source),
+            pending_updates: HashMap::new(),
+            snapshot_requests_in_flight: HashSet::new(),
+            snapshot_request_sender,
+            snapshot_request_receiver: Some(snapshot_request_receiver),
+            snapshot_result_sender,
+            snapshot_result_receiver: Some(snapshot_result_receiver),
+            fetch_snapshots: true,
+            resyncs: Counter::default(),
+            recorder: None,
+            registry: SymbolRegistry::new(),
+            depth_aggregation: DepthAggregation::default(),
             max_levels,
             snapshot_interval_ms,
             tick_size,
         }
     }
 
+    /// Record incoming diffs and fetched REST snapshots to a capture file
+    pub fn set_recorder(&mut self, recorder: CaptureRecorder) {
+        self.recorder = Some(recorder);
+    }
+
+    /// Aggregate each symbol's depth history at its contract tick
+    pub fn set_symbol_registry(&mut self, registry: SymbolRegistry) {
+        self.registry = registry;
+    }
+
+    /// Per-symbol depth history step, in contract ticks; one tick by default
+    pub fn set_depth_aggregation(&mut self, aggregation: DepthAggregation) {
+        self.depth_aggregation = aggregation;
+    }
+
+    /// Count resyncs in the shared metrics registry, under the source's market
+    pub fn set_metrics(&mut self, metrics: &Metrics) {
+        self.resyncs = metrics.orderbook_resyncs(self.snapshot_fetcher.source.name());
+    }
+
+    /// Analyze every synced book each `config.interval_ms` and publish the results
+    pub fn set_book_analytics(&mut self, topic: Topic<BookAnalytics>, config: BookAnalyticsConfig) {
+        self.book_analytics = Some((BookAnalyzer::new(config), topic));
+    }
+
+    /// Publish the levels each applied diff changed in a live book
+    pub fn set_book_deltas(&mut self, topic: Topic<BookDelta>) {
+        self.book_deltas = Some(topic);
+    }
+
+    /// Publish `config.levels`-deep snapshots of the focus symbols every
+    /// `config.interval_ms`, seeding their books from the deepest REST snapshot
+    pub fn set_wide_depth(&mut self, topic: Topic<(String, DepthSnapshot)>, config: WideHeatmapConfig) {
+        if config.levels > SNAPSHOT_DEPTH_LIMIT {
+            self.snapshot_fetcher.set_deep_symbols(config.symbols.iter().cloned().collect());
+        }
+        self.wide_depth = Some((config, topic));
+    }
+
+    /// Price step of `symbol`'s depth history
+    pub fn depth_granularity(&self, symbol: &str) -> f64 {
+        let tick_size = self.registry.tick_size(symbol).unwrap_or(self.tick_size);
+        tick_size * self.depth_aggregation.tick_multiple(symbol) as f64
+    }
+
+    /// Stop fetching REST snapshots and return a sender for supplying them instead,
+    /// used when replaying a capture
+    pub fn snapshot_injector(&mut self) -> mpsc::Sender<(String, Result<DepthSnapshotResponse>)> {
+        self.fetch_snapshots = false;
+        self.snapshot_result_sender.clone()
+    }
+
     pub async fn start(mut self) {
         info!("OrderBookManager started");
 
+        let Some(mut snapshot_result_rx) = self.snapshot_result_receiver.take() else {
+            warn!("OrderBookManager already started");
+            return;
+        };
+        if let Some(request_rx) = self.snapshot_request_receiver.take() {
+            if self.fetch_snapshots {
+                let fetcher = self.snapshot_fetcher.clone();
+                tokio::spawn(fetcher.run(request_rx, self.snapshot_result_sender.clone()));
+            }
+        }
+
         let mut snapshot_timer = tokio::time::interval(
             std::time::Duration::from_millis(self.snapshot_interval_ms)
         );
+        let analytics_interval_ms = self.book_analytics.as_ref().map_or(1_000, |(analyzer, _)| analyzer.interval_ms());
+        let mut analytics_timer = tokio::time::interval(
+            std::time::Duration::from_millis(analytics_interval_ms.max(1))
+        );
+        let wide_interval_ms = self.wide_depth.as_ref().map_or(1_000, |(config, _)| config.interval_ms);
+        let mut wide_timer = tokio::time::interval(
+            std::time::Duration::from_millis(wide_interval_ms.max(1))
+        );
 
         loop {
             tokio::select! {
                 Some(update) = self.depth_receiver.recv() => {
                     self.process_depth_update(update);
                 }
+                Some((symbol, result)) = snapshot_result_rx.recv() => {
+                    self.handle_snapshot_result(symbol, result);
+                }
                 _ = snapshot_timer.tick() => {
                     self.capture_snapshots();
                 }
+                _ = analytics_timer.tick(), if self.book_analytics.is_some() => {
+                    self.publish_book_analytics();
+                }
+                _ = wide_timer.tick(), if self.wide_depth.is_some() => {
+                    self.publish_wide_depth();
+                }
             }
         }
     }
 
     fn process_depth_update(&mut self, update: DepthUpdate) {
+        if let Some(recorder) = &self.recorder {
+            recorder.record(CaptureEvent::Depth(update.clone()));
+        }
+
         let symbol = update.symbol.clone();
 
         let orderbook = self.orderbooks
@@ -81,33 +259,163 @@ impl OrderBookManager {
                 OrderBook::new(symbol.clone())
             });
 
-        orderbook.apply_update(update);
+        // Only a live book knows what each level held before the diff
+        let changes = match &self.book_deltas {
+            Some(_) if orderbook.sync_state == BookSyncState::Live => orderbook.level_changes(&update),
+            _ => Vec::new(),
+        };
+
+        match orderbook.apply_update(&update) {
+            UpdateOutcome::Applied => {
+                if let Some(topic) = self.book_deltas.as_ref().filter(|_| !changes.is_empty()) {
+                    topic.try_publish(BookDelta {
+                        symbol,
+                        timestamp: update.event_time,
+                        best_bid: orderbook.bids.keys().next_back().map(|price| price.0),
+                        best_ask: orderbook.asks.keys().next().map(|price| price.0),
+                        changes,
+                    });
+                }
+            }
+            UpdateOutcome::Stale => {}
+            UpdateOutcome::Unseeded => {
+                self.buffer_update(update);
+                self.request_snapshot(&symbol);
+            }
+            UpdateOutcome::Gap { expected, first_update_id, prev_final_update_id } => {
+                warn!(
+                    "Depth sequence gap for {}: expected pu={}, got U={} pu={}. Resyncing",
+                    symbol, expected, first_update_id, prev_final_update_id
+                );
+                orderbook.reset();
+                self.resyncs.inc();
+                self.pending_updates.remove(&symbol);
+                self.buffer_update(update);
+                self.request_snapshot(&symbol);
+            }
+        }
+    }
+
+    fn buffer_update(&mut self, update: DepthUpdate) {
+        let buffer = self.pending_updates
+            .entry(update.symbol.clone())
+            .or_default();
+
+        buffer.push_back(update);
+        while buffer.len() > MAX_BUFFERED_UPDATES {
+            buffer.pop_front();
+        }
+    }
+
+    fn request_snapshot(&mut self, symbol: &str) {
+        // Injected snapshots arrive on their own; just keep buffering until then
+        if !self.fetch_snapshots {
+            return;
+        }
+
+        if self.snapshot_requests_in_flight.insert(symbol.to_string()) {
+            debug!("Requesting depth snapshot for {}", symbol);
+            if self.snapshot_request_sender.send(symbol.to_string()).is_err() {
+                warn!("Depth snapshot fetcher is not running, cannot seed {}", symbol);
+            }
+        }
+    }
+
+    fn handle_snapshot_result(&mut self, symbol: String, result: Result<DepthSnapshotResponse>) {
+        self.snapshot_requests_in_flight.remove(&symbol);
+
+        match result {
+            Ok(snapshot) => {
+                if let Some(recorder) = &self.recorder {
+                    recorder.record(CaptureEvent::DepthSnapshot { symbol: symbol.clone(), snapshot: snapshot.clone() });
+                }
+                self.apply_snapshot(&symbol, snapshot);
+            }
+            Err(e) => {
+                // The next diff for this symbol triggers a fresh request
+                warn!("Failed to fetch depth snapshot for {}: {}", symbol, e);
+            }
+        }
+    }
+
+    fn apply_snapshot(&mut self, symbol: &str, snapshot: DepthSnapshotResponse) {
+        let orderbook = self.orderbooks
+            .entry(symbol.to_string())
+            .or_insert_with(|| OrderBook::new(symbol.to_string()));
+
+        orderbook.apply_snapshot(&snapshot);
+        info!("Seeded {} order book from snapshot (lastUpdateId={})", symbol, snapshot.last_update_id);
+
+        // Replay everything buffered while the snapshot was in flight
+        let mut buffered = self.pending_updates.remove(symbol).unwrap_or_default();
+        while let Some(update) = buffered.pop_front() {
+            if let UpdateOutcome::Gap { expected, first_update_id, .. } = orderbook.apply_update(&update) {
+                warn!(
+                    "Buffered depth for {} does not continue snapshot (expected {}, got U={}). Resyncing",
+                    symbol, expected, first_update_id
+                );
+                orderbook.reset();
+                self.resyncs.inc();
+                buffered.push_front(update);
+                self.pending_updates.insert(symbol.to_string(), buffered);
+                self.request_snapshot(symbol);
+                return;
+            }
+        }
     }
 
     fn capture_snapshots(&mut self) {
         let timestamp = chrono::Utc::now().timestamp_millis() as u64;
 
         for (symbol, orderbook) in &self.orderbooks {
+            // Never publish a book that has not been seeded or is resyncing
+            if !orderbook.is_synced() {
+                continue;
+            }
+
             let snapshot = orderbook.get_depth_snapshot(self.max_levels);
 
-            // Store in history
+            // Store in history, re-bucketed first if the symbol's step changed
+            let granularity = self.depth_granularity(symbol);
             let history = self.depth_histories
                 .entry(symbol.clone())
                 .or_insert_with(|| {
-                    DepthHistory::new(symbol.clone(), 500)  // 50 seconds at 100ms
+                    DepthHistory::new(symbol.clone(), 500, granularity)  // 50 seconds at 100ms
                 });
+            history.set_granularity(granularity);
+            history.add_depth(timestamp, snapshot.clone());
 
-            // Create timed snapshot with aggregation
-            let timed_snapshot = TimedDepthSnapshot::new(timestamp, snapshot.clone(), self.tick_size);
-            history.add_snapshot(timed_snapshot);
+            // Publish to GUI
+            self.depth_topic.try_publish((symbol.clone(), snapshot));
+        }
+    }
 
-            // Send to GUI
-            if let Err(e) = self.snapshot_sender.try_send((symbol.clone(), snapshot)) {
-                debug!("Failed to send depth snapshot for {}: {}", symbol, e);
+    fn publish_book_analytics(&mut self) {
+        let Some((analyzer, topic)) = &mut self.book_analytics else {
+            return;
+        };
+        for orderbook in self.orderbooks.values() {
+            if !orderbook.is_synced() {
+                continue;
+            }
+            if let Some(analytics) = analyzer.analyze(orderbook) {
+                topic.try_publish(analytics);
             }
         }
     }
 
+    fn publish_wide_depth(&self) {
+        let Some((config, topic)) = &self.wide_depth else {
+            return;
+        };
+        for symbol in &config.symbols {
+            let Some(orderbook) = self.orderbooks.get(symbol).filter(|book| book.is_synced()) else {
+                continue;
+            };
+            topic.try_publish((symbol.clone(), orderbook.get_depth_snapshot(config.levels)));
+        }
+    }
+
     /// Get current orderbook for a symbol
     pub fn get_orderbook(&self, symbol: &str) -> Option<&OrderBook> {
         self.orderbooks.get(symbol)
@@ -122,18 +430,317 @@ impl OrderBookManager {
     pub fn symbol_count(&self) -> usize {
         self.orderbooks.len()
     }
+
+    /// Number of times a book was dropped and re-seeded after a sequence gap
+    pub fn resync_count(&self) -> u64 {
+        self.resyncs.get()
+    }
+}
+
+/// Fetches REST depth snapshots used to seed local order books
+#[derive(Clone)]
+pub struct DepthSnapshotFetcher {
+    client: reqwest::Client,
+    source: Arc<dyn MarketDataSource>,
+    limit: usize,
+    deep_symbols: Arc<HashSet<String>>, // seeded with SNAPSHOT_MAX_DEPTH_LIMIT levels
+}
+
+impl DepthSnapshotFetcher {
+    pub fn new(source: Arc<dyn MarketDataSource>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            source,
+            limit: SNAPSHOT_DEPTH_LIMIT,
+            deep_symbols: Arc::default(),
+        }
+    }
+
+    /// Seed these symbols from the deepest snapshot the endpoint serves
+    pub fn set_deep_symbols(&mut self, symbols: HashSet<String>) {
+        self.deep_symbols = Arc::new(symbols);
+    }
+
+    fn limit_for(&self, symbol: &str) -> usize {
+        if self.deep_symbols.contains(symbol) {
+            SNAPSHOT_MAX_DEPTH_LIMIT
+        } else {
+            self.limit
+        }
+    }
+
+    pub async fn fetch(&self, symbol: &str) -> Result<DepthSnapshotResponse> {
+        let url = self.source.depth_snapshot_url(symbol, self.limit_for(symbol));
+        let body = self.client.get(&url).send().await?.error_for_status()?.text().await?;
+        self.source.parse_depth_snapshot(symbol, &body)
+    }
+
+    /// Serve snapshot requests one at a time, spaced to respect REST rate limits
+    async fn run(
+        self,
+        mut requests: mpsc::UnboundedReceiver<String>,
+        results: mpsc::Sender<(String, Result<DepthSnapshotResponse>)>,
+    ) {
+        while let Some(symbol) = requests.recv().await {
+            let mut attempt = 0;
+            let result = loop {
+                attempt += 1;
+                match self.fetch(&symbol).await {
+                    Ok(snapshot) => break Ok(snapshot),
+                    Err(e) if attempt >= SNAPSHOT_MAX_ATTEMPTS => break Err(e),
+                    Err(e) => {
+                        debug!("Depth snapshot for {} failed (attempt {}): {}", symbol, attempt, e);
+                        sleep(Duration::from_millis(SNAPSHOT_REQUEST_INTERVAL_MS * attempt as u64)).await;
+                    }
+                }
+            };
+
+            if results.send((symbol, result)).await.is_err() {
+                break;
+            }
+
+            sleep(Duration::from_millis(SNAPSHOT_REQUEST_INTERVAL_MS)).await;
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::settings::{EventBusConfig, OverflowPolicy};
+    use crate::data::{BinanceUsdmSource, EventBus, LevelChange, Subscription};
+    use ordered_float::OrderedFloat;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn diff(first: u64, last: u64, prev: u64, bids: Vec<(f64, f64)>) -> DepthUpdate {
+        DepthUpdate {
+            event_type: "depthUpdate".to_string(),
+            event_time: last,
+            symbol: "BTCUSDT".to_string(),
+            first_update_id: first,
+            last_update_id: last,
+            prev_final_update_id: prev,
+            bids,
+            asks: vec![],
+        }
+    }
+
+    /// Minimal HTTP stand-in that serves the given snapshot bodies in order
+    async fn serve_snapshots(bodies: Vec<&'static str>) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let hits_clone = hits.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 2048];
+                let _ = socket.read(&mut buf).await;
+                let n = hits_clone.fetch_add(1, Ordering::SeqCst);
+                let body = bodies[n.min(bodies.len() - 1)];
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+
+        (format!("http://{}", addr), hits)
+    }
+
+    /// Read published snapshots until one carries the given bid level
+    async fn wait_for_bid(rx: &mut Subscription<(String, DepthSnapshot)>, price: f64, qty: f64) -> DepthSnapshot {
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        loop {
+            let published = tokio::time::timeout_at(deadline, rx.recv()).await
+                .expect("timed out waiting for depth snapshot")
+                .unwrap();
+            let snapshot = &published.1;
+            if snapshot.bids.iter().any(|&(p, q)| p == price && q == qty) {
+                return snapshot.clone();
+            }
+        }
+    }
+
+    fn depth_topic() -> Topic<(String, DepthSnapshot)> {
+        EventBus::new(&EventBusConfig::default()).depth
+    }
+
+    fn source(api_base_url: &str) -> Arc<dyn MarketDataSource> {
+        Arc::new(BinanceUsdmSource::new("ws://127.0.0.1:1".to_string(), api_base_url.to_string()))
+    }
 
     #[tokio::test]
     async fn test_orderbook_manager_creation() {
-        let (depth_tx, depth_rx) = mpsc::channel(100);
-        let (snapshot_tx, _snapshot_rx) = mpsc::channel(100);
+        let (_depth_tx, depth_rx) = mpsc::channel(100);
 
-        let manager = OrderBookManager::new(depth_rx, snapshot_tx);
+        let manager = OrderBookManager::new(depth_rx, depth_topic(), source("http://127.0.0.1:1"));
         assert_eq!(manager.symbol_count(), 0);
     }
+
+    #[tokio::test]
+    async fn test_buffers_until_snapshot_and_resyncs_on_gap() {
+        let (_depth_tx, depth_rx) = mpsc::channel(100);
+        let mut manager = OrderBookManager::new(depth_rx, depth_topic(), source("http://127.0.0.1:1"));
+
+        manager.process_depth_update(diff(90, 95, 89, vec![(10.0, 1.0)]));
+        manager.process_depth_update(diff(96, 102, 95, vec![(11.0, 2.0)]));
+        assert!(!manager.get_orderbook("BTCUSDT").unwrap().is_synced());
+        assert_eq!(manager.pending_updates["BTCUSDT"].len(), 2);
+
+        manager.handle_snapshot_result("BTCUSDT".to_string(), Ok(DepthSnapshotResponse {
+            last_update_id: 100,
+            event_time: 0,
+            bids: vec![(9.0, 3.0)],
+            asks: vec![],
+        }));
+
+        let book = manager.get_orderbook("BTCUSDT").unwrap();
+        assert!(book.is_synced());
+        assert_eq!(book.last_update_id, 102);
+        assert!(!book.bids.contains_key(&OrderedFloat(10.0)));  // stale diff dropped
+        assert_eq!(book.bids.get(&OrderedFloat(11.0)), Some(&2.0));
+
+        // pu does not chain onto 102
+        manager.process_depth_update(diff(110, 112, 109, vec![]));
+        assert!(!manager.get_orderbook("BTCUSDT").unwrap().is_synced());
+        assert_eq!(manager.resync_count(), 1);
+        assert!(manager.snapshot_requests_in_flight.contains("BTCUSDT"));
+    }
+
+    #[tokio::test]
+    async fn test_live_diffs_publish_level_changes() {
+        let (_depth_tx, depth_rx) = mpsc::channel(100);
+        let bus = EventBus::new(&EventBusConfig::default());
+        let mut deltas = bus.book_deltas.subscribe("test", OverflowPolicy::Drop);
+        let mut manager = OrderBookManager::new(depth_rx, bus.depth.clone(), source("http://127.0.0.1:1"));
+        manager.set_book_deltas(bus.book_deltas.clone());
+
+        manager.process_depth_update(diff(96, 102, 95, vec![(11.0, 2.0)]));
+        manager.handle_snapshot_result("BTCUSDT".to_string(), Ok(DepthSnapshotResponse {
+            last_update_id: 100,
+            event_time: 0,
+            bids: vec![(9.0, 3.0)],
+            asks: vec![(12.0, 1.0)],
+        }));
+        // The diff straddling the snapshot only brings the book live
+        assert!(deltas.try_recv().is_err());
+
+        manager.process_depth_update(diff(103, 105, 102, vec![(11.0, 0.5), (9.0, 3.0), (8.0, 0.0)]));
+        let delta = deltas.try_recv().unwrap();
+        assert_eq!((delta.timestamp, delta.best_bid, delta.best_ask), (105, Some(11.0), Some(12.0)));
+        assert_eq!(delta.changes, vec![LevelChange { side: "BID".to_string(), price: 11.0, previous: 2.0, quantity: 0.5 }]);
+    }
+
+    #[tokio::test]
+    async fn test_depth_history_granularity_per_symbol() {
+        let (_depth_tx, depth_rx) = mpsc::channel(100);
+        let mut manager = OrderBookManager::new(depth_rx, depth_topic(), source("http://127.0.0.1:1"));
+
+        let registry = SymbolRegistry::new();
+        let info = |symbol: &str, tick: &str| crate::data::SymbolInfo::from_exchange_info("binance-usdm", &serde_json::json!({
+            "symbol": symbol,
+            "filters": [{"filterType": "PRICE_FILTER", "tickSize": tick}, {"filterType": "LOT_SIZE", "stepSize": "1"}]
+        })).unwrap();
+        registry.update("binance-usdm", vec![info("BTCUSDT", "0.10"), info("1000SHIBUSDT", "0.000001")]);
+        manager.set_symbol_registry(registry);
+
+        let aggregation = DepthAggregation::from(&LobConfig {
+            tick_multiple: 10,
+            tick_multiples: HashMap::from([("BTCUSDT".to_string(), 5)]),
+        });
+        manager.set_depth_aggregation(aggregation.clone());
+
+        assert!((manager.depth_granularity("BTCUSDT") - 0.5).abs() < 1e-12);
+        assert!((manager.depth_granularity("1000SHIBUSDT") - 0.00001).abs() < 1e-12);
+        // Unknown symbols fall back to the manager's default tick
+        assert!((manager.depth_granularity("ETHUSDT") - 0.1).abs() < 1e-12);
+
+        manager.process_depth_update(diff(99, 101, 98, vec![(100.2, 1.0)]));
+        manager.handle_snapshot_result("BTCUSDT".to_string(), Ok(DepthSnapshotResponse {
+            last_update_id: 100,
+            event_time: 0,
+            bids: vec![(100.4, 2.0), (99.9, 3.0)],
+            asks: vec![],
+        }));
+        manager.capture_snapshots();
+        let history = manager.get_depth_history("BTCUSDT").unwrap();
+        assert_eq!(history.snapshots[0].aggregated_bids.get(&201), Some(&2.0));  // 100.4
+        assert_eq!(history.snapshots[0].aggregated_bids.get(&200), Some(&4.0));  // 100.2 + 99.9
+
+        // A scale change from the GUI re-buckets what is already stored
+        aggregation.set_tick_multiple("BTCUSDT", 10);
+        manager.capture_snapshots();
+        let history = manager.get_depth_history("BTCUSDT").unwrap();
+        assert_eq!(history.granularity, 1.0);
+        assert!(history.snapshots.iter().all(|snapshot| snapshot.aggregated_bids.get(&100) == Some(&6.0)));
+    }
+
+    #[tokio::test]
+    async fn test_wide_depth_publishes_focus_symbols_at_full_depth() {
+        let (_depth_tx, depth_rx) = mpsc::channel(100);
+        let bus = EventBus::new(&EventBusConfig::default());
+        let mut wide = bus.wide_depth.subscribe("test", OverflowPolicy::Drop);
+        let mut manager = OrderBookManager::new(depth_rx, bus.depth.clone(), source("http://127.0.0.1:1"));
+        manager.set_wide_depth(bus.wide_depth.clone(), WideHeatmapConfig {
+            enabled: true,
+            symbols: vec!["BTCUSDT".to_string()],
+            levels: 1000,
+            ..Default::default()
+        });
+        assert_eq!(manager.snapshot_fetcher.limit_for("BTCUSDT"), 1000);
+        assert_eq!(manager.snapshot_fetcher.limit_for("ETHUSDT"), 500);
+
+        // Unseeded books are skipped
+        manager.process_depth_update(diff(99, 101, 98, vec![]));
+        manager.publish_wide_depth();
+        assert!(wide.try_recv().is_err());
+
+        manager.handle_snapshot_result("BTCUSDT".to_string(), Ok(DepthSnapshotResponse {
+            last_update_id: 100,
+            event_time: 0,
+            bids: (0..400).map(|i| (100.0 - i as f64 * 0.1, 1.0)).collect(),
+            asks: vec![(100.1, 1.0)],
+        }));
+        manager.publish_wide_depth();
+        let (symbol, snapshot) = &*wide.try_recv().unwrap();
+        assert_eq!(symbol, "BTCUSDT");
+        assert_eq!(snapshot.bids.len(), 400);
+        assert!(wide.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_end_to_end_with_local_snapshot_server() {
+        let (api_url, hits) = serve_snapshots(vec![
+            r#"{"lastUpdateId":100,"E":1,"T":1,"bids":[["100.0","1.0"]],"asks":[["101.0","1.0"]]}"#,
+            r#"{"lastUpdateId":200,"E":2,"T":2,"bids":[["100.0","5.0"]],"asks":[["101.0","1.0"]]}"#,
+        ]).await;
+
+        let (depth_tx, depth_rx) = mpsc::channel(100);
+        let bus = EventBus::new(&EventBusConfig::default());
+        let mut snapshot_rx = bus.depth.subscribe("test", OverflowPolicy::Drop);
+        let manager = OrderBookManager::with_config(depth_rx, bus.depth.clone(), source(&api_url), 100, 10, 0.01);
+        tokio::spawn(manager.start());
+
+        depth_tx.send(diff(99, 101, 98, vec![(99.0, 2.0)])).await.unwrap();
+        depth_tx.send(diff(102, 104, 101, vec![(98.0, 3.0)])).await.unwrap();
+        let snapshot = wait_for_bid(&mut snapshot_rx, 98.0, 3.0).await;
+        assert!(snapshot.bids.contains(&(100.0, 1.0)));
+        assert!(snapshot.bids.contains(&(99.0, 2.0)));
+
+        // Deliberate gap: 105..109 never delivered
+        depth_tx.send(diff(110, 112, 109, vec![(97.0, 1.0)])).await.unwrap();
+        depth_tx.send(diff(199, 201, 198, vec![(96.0, 4.0)])).await.unwrap();
+
+        let snapshot = wait_for_bid(&mut snapshot_rx, 96.0, 4.0).await;
+        assert!(snapshot.bids.contains(&(100.0, 5.0)));
+        assert!(!snapshot.bids.iter().any(|&(p, _)| p == 97.0 || p == 99.0));
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
 }
@@ -0,0 +1,380 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{Duration, Instant};
+use tracing::{info, warn, error};
+
+use crate::config::settings::DatabaseConfig;
+use super::{DatabaseManager, LatencyHistogram, LatencyStats, OrderflowEvent, OrderImbalance, LiquidationEvent, VolumeProfile, OpenInterestData, FundingRateData, BookAnalytics, HeatmapColumn, StoredFootprintCandle};
+
+/// How often the writer logs its throughput and backpressure counters
+const PERSISTENCE_REPORT_INTERVAL_SECS: u64 = 60;
+
+/// A row (or row group) waiting to be written by the persistence writer
+#[derive(Debug, Clone)]
+pub enum PersistRecord {
+    Trade(OrderflowEvent),
+    Imbalance(OrderImbalance),
+    Liquidation(LiquidationEvent),
+    VolumeProfile(VolumeProfile),
+    OpenInterest(OpenInterestData),
+    FundingRate(FundingRateData),
+    BookAnalytics(BookAnalytics),
+    HeatmapColumn(HeatmapColumn),
+    FootprintCandle(StoredFootprintCandle),
+}
+
+#[derive(Debug, Clone)]
+pub struct PersistenceConfig {
+    pub queue_capacity: usize,
+    pub batch_size: usize,
+    pub flush_interval_ms: u64,
+}
+
+impl From<&DatabaseConfig> for PersistenceConfig {
+    fn from(config: &DatabaseConfig) -> Self {
+        Self {
+            queue_capacity: config.persistence_queue_capacity,
+            batch_size: config.persistence_batch_size,
+            flush_interval_ms: config.persistence_flush_interval_ms,
+        }
+    }
+}
+
+/// Counters shared between the producers and the writer
+#[derive(Debug, Default)]
+pub struct PersistenceMetrics {
+    enqueued: AtomicU64,
+    dropped: AtomicU64,
+    written: AtomicU64,
+    batches: AtomicU64,
+    failed_batches: AtomicU64,
+    failed_rows: AtomicU64,
+    last_batch_ms: AtomicU64,
+    max_queue_depth: AtomicU64,
+    write_latency: LatencyHistogram,
+}
+
+/// Point-in-time copy of `PersistenceMetrics`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PersistenceStats {
+    pub enqueued: u64,
+    /// Rows rejected because the queue was full
+    pub dropped: u64,
+    pub written: u64,
+    pub batches: u64,
+    pub failed_batches: u64,
+    /// Rows the database rejected when a failed batch was retried row by row
+    pub failed_rows: u64,
+    pub last_batch_ms: u64,
+    pub queue_depth: usize,
+    pub max_queue_depth: u64,
+    /// Commit time per batch
+    pub write_latency: LatencyStats,
+}
+
+/// Cloneable producer side of the persistence queue. Never blocks: when the
+/// writer falls behind, rows are dropped and counted instead of stalling the
+/// analyzers.
+#[derive(Clone)]
+pub struct PersistenceHandle {
+    sender: mpsc::Sender<PersistRecord>,
+    capacity: usize,
+    metrics: Arc<PersistenceMetrics>,
+}
+
+impl PersistenceHandle {
+    /// Spawn the background writer for `db`
+    pub fn start(db: Arc<DatabaseManager>, config: PersistenceConfig) -> (Self, tokio::task::JoinHandle<()>) {
+        let capacity = config.queue_capacity.max(1);
+        let (sender, receiver) = mpsc::channel(capacity);
+        let metrics = Arc::new(PersistenceMetrics::default());
+
+        let writer = PersistenceWriter {
+            db,
+            receiver,
+            batch_size: config.batch_size.max(1),
+            flush_interval: Duration::from_millis(config.flush_interval_ms),
+            metrics: metrics.clone(),
+        };
+        let handle = tokio::spawn(writer.run());
+
+        (Self { sender, capacity, metrics }, handle)
+    }
+
+    pub fn persist(&self, record: PersistRecord) {
+        match self.sender.try_send(record) {
+            Ok(()) => {
+                self.metrics.enqueued.fetch_add(1, Ordering::Relaxed);
+                let depth = self.queue_depth() as u64;
+                self.metrics.max_queue_depth.fetch_max(depth, Ordering::Relaxed);
+            }
+            Err(_) => {
+                self.metrics.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn queue_depth(&self) -> usize {
+        self.capacity - self.sender.capacity()
+    }
+
+    pub fn stats(&self) -> PersistenceStats {
+        self.monitor().stats()
+    }
+
+    /// Read-only view of the counters that does not keep the writer running
+    pub fn monitor(&self) -> PersistenceMonitor {
+        PersistenceMonitor {
+            sender: self.sender.downgrade(),
+            capacity: self.capacity,
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+/// Reports a persistence queue's counters, including after its handles are gone
+#[derive(Clone)]
+pub struct PersistenceMonitor {
+    sender: mpsc::WeakSender<PersistRecord>,
+    capacity: usize,
+    metrics: Arc<PersistenceMetrics>,
+}
+
+impl PersistenceMonitor {
+    pub fn stats(&self) -> PersistenceStats {
+        PersistenceStats {
+            enqueued: self.metrics.enqueued.load(Ordering::Relaxed),
+            dropped: self.metrics.dropped.load(Ordering::Relaxed),
+            written: self.metrics.written.load(Ordering::Relaxed),
+            batches: self.metrics.batches.load(Ordering::Relaxed),
+            failed_batches: self.metrics.failed_batches.load(Ordering::Relaxed),
+            failed_rows: self.metrics.failed_rows.load(Ordering::Relaxed),
+            last_batch_ms: self.metrics.last_batch_ms.load(Ordering::Relaxed),
+            queue_depth: self.sender.upgrade().map_or(0, |sender| self.capacity - sender.capacity()),
+            max_queue_depth: self.metrics.max_queue_depth.load(Ordering::Relaxed),
+            write_latency: self.metrics.write_latency.stats(),
+        }
+    }
+}
+
+/// Drains the persistence queue into the database, one transaction per batch.
+/// Exits after writing everything still queued once every handle is dropped.
+struct PersistenceWriter {
+    db: Arc<DatabaseManager>,
+    receiver: mpsc::Receiver<PersistRecord>,
+    batch_size: usize,
+    flush_interval: Duration,
+    metrics: Arc<PersistenceMetrics>,
+}
+
+impl PersistenceWriter {
+    async fn run(mut self) {
+        info!("Persistence writer started (batch {}, flush every {:?})", self.batch_size, self.flush_interval);
+
+        let mut batch = Vec::with_capacity(self.batch_size);
+        let mut report_timer = tokio::time::interval(Duration::from_secs(PERSISTENCE_REPORT_INTERVAL_SECS));
+        report_timer.tick().await;
+        let mut last_dropped = 0;
+
+        loop {
+            // Wait for the first row, then keep collecting until the batch is full
+            // or the flush interval has passed
+            let first = tokio::select! {
+                record = self.receiver.recv() => record,
+                _ = report_timer.tick() => {
+                    last_dropped = self.report(last_dropped);
+                    continue;
+                }
+            };
+            let Some(first) = first else { break };
+            batch.push(first);
+
+            let deadline = Instant::now() + self.flush_interval;
+            while batch.len() < self.batch_size {
+                match tokio::time::timeout_at(deadline, self.receiver.recv()).await {
+                    Ok(Some(record)) => batch.push(record),
+                    Ok(None) | Err(_) => break,
+                }
+            }
+
+            self.write(&mut batch).await;
+        }
+
+        self.write(&mut batch).await;
+        let written = self.metrics.written.load(Ordering::Relaxed);
+        info!("Persistence writer stopped after writing {} rows", written);
+    }
+
+    async fn write(&self, batch: &mut Vec<PersistRecord>) {
+        if batch.is_empty() {
+            return;
+        }
+
+        let started = Instant::now();
+        match self.db.write_batch(batch).await {
+            Ok(()) => {
+                self.metrics.written.fetch_add(batch.len() as u64, Ordering::Relaxed);
+                self.metrics.batches.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                self.metrics.failed_batches.fetch_add(1, Ordering::Relaxed);
+                warn!("Failed to persist batch of {} rows, retrying row by row: {}", batch.len(), e);
+                self.write_rows(batch).await;
+            }
+        }
+        self.metrics.last_batch_ms.store(started.elapsed().as_millis() as u64, Ordering::Relaxed);
+        self.metrics.write_latency.observe(started.elapsed());
+        batch.clear();
+    }
+
+    /// Write a rejected batch one row per transaction so a bad row only loses itself
+    async fn write_rows(&self, batch: &[PersistRecord]) {
+        let mut failed = 0;
+        for record in batch {
+            match self.db.write_batch(std::slice::from_ref(record)).await {
+                Ok(()) => {
+                    self.metrics.written.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(e) => {
+                    if failed == 0 {
+                        error!("Failed to persist {:?}: {}", record, e);
+                    }
+                    failed += 1;
+                }
+            }
+        }
+        if failed > 0 {
+            self.metrics.failed_rows.fetch_add(failed, Ordering::Relaxed);
+            error!("Dropped {} of {} rows the database rejected", failed, batch.len());
+        }
+    }
+
+    fn report(&self, last_dropped: u64) -> u64 {
+        let dropped = self.metrics.dropped.load(Ordering::Relaxed);
+        let written = self.metrics.written.load(Ordering::Relaxed);
+        let queued = self.receiver.len();
+
+        if dropped > last_dropped {
+            warn!(
+                "Persistence queue full: dropped {} rows in the last {}s ({} queued, {} written)",
+                dropped - last_dropped, PERSISTENCE_REPORT_INTERVAL_SECS, queued, written
+            );
+        } else {
+            info!(
+                "Persistence: {} rows written in {} batches, {} queued, last batch {}ms",
+                written, self.metrics.batches.load(Ordering::Relaxed), queued,
+                self.metrics.last_batch_ms.load(Ordering::Relaxed)
+            );
+        }
+
+        dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(trade_id: u64) -> OrderflowEvent {
+        OrderflowEvent {
+            symbol: "BTCUSDT".to_string(),
+            timestamp: 1_700_000_000_000 + trade_id,
+            price: 100.0,
+            quantity: 1.0,
+            is_buyer_maker: false,
+            trade_id,
+        }
+    }
+
+    async fn memory_db() -> Arc<DatabaseManager> {
+        let db = DatabaseManager::new(":memory:").await.unwrap();
+        db.initialize_schema().await.unwrap();
+        db
+    }
+
+    #[tokio::test]
+    async fn test_writer_flushes_everything_on_shutdown() {
+        let db = memory_db().await;
+        let config = PersistenceConfig { queue_capacity: 1000, batch_size: 64, flush_interval_ms: 10_000 };
+        let (handle, writer) = PersistenceHandle::start(db.clone(), config);
+
+        for trade_id in 0..500 {
+            handle.persist(PersistRecord::Trade(trade(trade_id)));
+        }
+        handle.persist(PersistRecord::Imbalance(OrderImbalance {
+            symbol: "BTCUSDT".to_string(),
+            timestamp: 1,
+            bid_volume: 3.0,
+            ask_volume: 1.0,
+            imbalance_ratio: 0.5,
+            window_duration_seconds: 60,
+        }));
+
+        // The monitor outlives the handle without holding the writer open
+        let monitor = handle.monitor();
+        drop(handle);
+        writer.await.unwrap();
+
+        assert_eq!(db.latest_row_id("raw_trades").await.unwrap(), 500);
+        assert_eq!(db.latest_row_id("order_imbalances").await.unwrap(), 1);
+
+        let stats = monitor.stats();
+        assert_eq!(stats.enqueued, 501);
+        assert_eq!(stats.written, 501);
+        assert_eq!(stats.dropped, 0);
+        assert_eq!(stats.queue_depth, 0);
+        assert!(stats.batches >= 501 / 64);
+        assert_eq!(stats.write_latency.count, stats.batches);
+    }
+
+    #[tokio::test]
+    async fn test_failed_batch_only_drops_rejected_rows() {
+        let db = memory_db().await;
+        let config = PersistenceConfig { queue_capacity: 100, batch_size: 100, flush_interval_ms: 10_000 };
+        let (handle, writer) = PersistenceHandle::start(db.clone(), config);
+
+        for trade_id in 0..10 {
+            handle.persist(PersistRecord::Trade(trade(trade_id)));
+        }
+        // NaN binds as NULL and violates the NOT NULL constraint
+        handle.persist(PersistRecord::Imbalance(OrderImbalance {
+            symbol: "BTCUSDT".to_string(),
+            timestamp: 1,
+            bid_volume: 3.0,
+            ask_volume: 1.0,
+            imbalance_ratio: f64::NAN,
+            window_duration_seconds: 60,
+        }));
+        let monitor = handle.monitor();
+        drop(handle);
+        writer.await.unwrap();
+
+        assert_eq!(db.latest_row_id("raw_trades").await.unwrap(), 10);
+        let stats = monitor.stats();
+        assert_eq!((stats.written, stats.failed_rows, stats.failed_batches), (10, 1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_drops_and_counts() {
+        let db = memory_db().await;
+        // Hold the connection so the writer cannot make progress
+        let config = PersistenceConfig { queue_capacity: 10, batch_size: 1, flush_interval_ms: 0 };
+        let (handle, writer) = PersistenceHandle::start(db.clone(), config);
+        let guard = db.connection_for_tests().await;
+
+        for trade_id in 0..50 {
+            handle.persist(PersistRecord::Trade(trade(trade_id)));
+        }
+
+        let stats = handle.stats();
+        assert!(stats.dropped > 0);
+        assert_eq!(stats.enqueued + stats.dropped, 50);
+        assert!(stats.max_queue_depth <= 10);
+
+        drop(guard);
+        drop(handle);
+        writer.await.unwrap();
+        assert_eq!(db.latest_row_id("raw_trades").await.unwrap() as u64, stats.enqueued);
+    }
+}
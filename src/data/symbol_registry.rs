@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// Trading rules and contract metadata of one symbol, read from exchangeInfo
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbolInfo {
+    pub symbol: String,
+    /// `MarketDataSource::name` of the market listing the symbol
+    pub market: String,
+    pub status: String,
+    pub contract_type: String,
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub margin_asset: String,
+    /// PRICE_FILTER tickSize
+    pub tick_size: f64,
+    /// LOT_SIZE stepSize
+    pub step_size: f64,
+    /// LOT_SIZE minQty
+    pub min_quantity: f64,
+    /// MIN_NOTIONAL notional; COIN-M contracts have none
+    pub min_notional: Option<f64>,
+    pub price_precision: u32,
+    pub quantity_precision: u32,
+    /// Contract value in USD on COIN-M; `None` on linear contracts
+    pub contract_size: Option<f64>,
+    pub onboard_date: Option<u64>,
+}
+
+impl SymbolInfo {
+    /// Decode one entry of an exchangeInfo `symbols` array. Entries without a
+    /// PRICE_FILTER tick or LOT_SIZE step are skipped, as nothing can be binned on them.
+    pub fn from_exchange_info(market: &str, entry: &serde_json::Value) -> Option<Self> {
+        let symbol = entry["symbol"].as_str()?;
+        let filter = |filter_type: &str, field: &str| -> Option<f64> {
+            entry["filters"]
+                .as_array()?
+                .iter()
+                .find(|filter| filter["filterType"].as_str() == Some(filter_type))
+                .and_then(|filter| json_f64(&filter[field]))
+        };
+        let text = |field: &str| entry[field].as_str().unwrap_or_default().to_string();
+
+        let tick_size = filter("PRICE_FILTER", "tickSize").filter(|tick| *tick > 0.0)?;
+        let step_size = filter("LOT_SIZE", "stepSize").filter(|step| *step > 0.0)?;
+
+        Some(Self {
+            symbol: symbol.to_string(),
+            market: market.to_string(),
+            // USD-M calls it `status`, COIN-M `contractStatus`
+            status: entry["status"].as_str().or(entry["contractStatus"].as_str()).unwrap_or_default().to_string(),
+            contract_type: text("contractType"),
+            base_asset: text("baseAsset"),
+            quote_asset: text("quoteAsset"),
+            margin_asset: text("marginAsset"),
+            tick_size,
+            step_size,
+            min_quantity: filter("LOT_SIZE", "minQty").unwrap_or(step_size),
+            min_notional: filter("MIN_NOTIONAL", "notional"),
+            price_precision: entry["pricePrecision"].as_u64().unwrap_or_else(|| decimals(tick_size) as u64) as u32,
+            quantity_precision: entry["quantityPrecision"].as_u64().unwrap_or_else(|| decimals(step_size) as u64) as u32,
+            contract_size: json_f64(&entry["contractSize"]),
+            onboard_date: entry["onboardDate"].as_u64(),
+        })
+    }
+
+    /// Decimals needed to show a price at tick resolution
+    pub fn price_decimals(&self) -> usize {
+        decimals(self.tick_size)
+    }
+}
+
+/// Decode every usable entry of an exchangeInfo response
+pub fn parse_symbol_infos(market: &str, exchange_info: &serde_json::Value) -> Vec<SymbolInfo> {
+    exchange_info["symbols"]
+        .as_array()
+        .map(|entries| entries.iter().filter_map(|entry| SymbolInfo::from_exchange_info(market, entry)).collect())
+        .unwrap_or_default()
+}
+
+/// Binance sends filter values as strings and a few fields as numbers
+fn json_f64(value: &serde_json::Value) -> Option<f64> {
+    value.as_f64().or_else(|| value.as_str()?.parse().ok())
+}
+
+/// Significant decimals of a tick or step size, e.g. 0.0010 -> 3
+fn decimals(increment: f64) -> usize {
+    let formatted = format!("{:.10}", increment);
+    formatted.trim_end_matches('0').split('.').nth(1).map_or(0, str::len)
+}
+
+/// Shared, cheaply cloned lookup of symbol trading rules for every market.
+/// Filled from exchangeInfo by `SymbolUniverse` and mirrored to a JSON file,
+/// so tick sizes are known at startup even when the exchange is unreachable.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolRegistry {
+    symbols: Arc<RwLock<HashMap<String, SymbolInfo>>>,
+    cache_path: Option<PathBuf>,
+}
+
+impl SymbolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry backed by a cache file, pre-filled from it when it exists
+    pub fn with_cache(path: impl AsRef<Path>) -> Self {
+        let mut registry = Self::new();
+        registry.cache_path = Some(path.as_ref().to_path_buf());
+        if path.as_ref().exists() {
+            match registry.load_cache(path.as_ref()) {
+                Ok(count) => info!("Loaded {} cached symbols from {}", count, path.as_ref().display()),
+                Err(e) => warn!("Ignoring symbol cache {}: {}", path.as_ref().display(), e),
+            }
+        }
+        registry
+    }
+
+    /// Replace `market`'s symbols with a fresh exchangeInfo listing and rewrite the cache.
+    /// An empty listing is ignored, like in `SymbolUniverse::apply`.
+    pub fn update(&self, market: &str, infos: Vec<SymbolInfo>) {
+        if infos.is_empty() {
+            return;
+        }
+
+        {
+            let mut symbols = self.symbols.write().unwrap();
+            symbols.retain(|_, info| info.market != market);
+            for info in infos {
+                symbols.insert(info.symbol.clone(), info);
+            }
+        }
+
+        if let Some(path) = &self.cache_path {
+            if let Err(e) = self.save_cache(path) {
+                warn!("Cannot write symbol cache {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<SymbolInfo> {
+        self.symbols.read().unwrap().get(symbol).cloned()
+    }
+
+    pub fn tick_size(&self, symbol: &str) -> Option<f64> {
+        self.symbols.read().unwrap().get(symbol).map(|info| info.tick_size)
+    }
+
+    /// Tradable perpetual symbols of every market, sorted
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self.symbols
+            .read()
+            .unwrap()
+            .values()
+            .filter(|info| info.status == "TRADING" && info.contract_type == "PERPETUAL")
+            .map(|info| info.symbol.clone())
+            .collect();
+        symbols.sort();
+        symbols
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn load_cache(&self, path: &Path) -> Result<usize> {
+        let infos: Vec<SymbolInfo> = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        let count = infos.len();
+
+        let mut symbols = self.symbols.write().unwrap();
+        for info in infos {
+            symbols.insert(info.symbol.clone(), info);
+        }
+        Ok(count)
+    }
+
+    pub fn save_cache(&self, path: &Path) -> Result<()> {
+        let mut infos: Vec<SymbolInfo> = self.symbols.read().unwrap().values().cloned().collect();
+        infos.sort_by(|a, b| (&a.market, &a.symbol).cmp(&(&b.market, &b.symbol)));
+
+        // Write then rename, so a crash never leaves a truncated cache behind
+        let temp_path = path.with_extension("tmp");
+        std::fs::write(&temp_path, serde_json::to_string_pretty(&infos)?)?;
+        std::fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usdm_entry() -> serde_json::Value {
+        serde_json::json!({
+            "symbol": "BTCUSDT", "status": "TRADING", "contractType": "PERPETUAL",
+            "baseAsset": "BTC", "quoteAsset": "USDT", "marginAsset": "USDT",
+            "pricePrecision": 2, "quantityPrecision": 3, "onboardDate": 1569398400000u64,
+            "filters": [
+                {"filterType": "PRICE_FILTER", "minPrice": "556.80", "maxPrice": "4529764", "tickSize": "0.10"},
+                {"filterType": "LOT_SIZE", "stepSize": "0.001", "maxQty": "1000", "minQty": "0.001"},
+                {"filterType": "MIN_NOTIONAL", "notional": "100"}
+            ]
+        })
+    }
+
+    #[test]
+    fn test_exchange_info_filters() {
+        let info = SymbolInfo::from_exchange_info("binance-usdm", &usdm_entry()).unwrap();
+        assert_eq!(info.tick_size, 0.1);
+        assert_eq!((info.step_size, info.min_quantity), (0.001, 0.001));
+        assert_eq!(info.min_notional, Some(100.0));
+        assert_eq!((info.price_precision, info.quantity_precision), (2, 3));
+        assert_eq!(info.price_decimals(), 1);
+        assert_eq!(info.contract_size, None);
+
+        let coinm = serde_json::json!({"symbols": [
+            {"symbol": "ETHUSD_PERP", "contractStatus": "TRADING", "contractType": "PERPETUAL", "contractSize": 10,
+             "pricePrecision": 2, "quantityPrecision": 0,
+             "filters": [{"filterType": "PRICE_FILTER", "tickSize": "0.01"}, {"filterType": "LOT_SIZE", "stepSize": "1", "minQty": "1"}]},
+            {"symbol": "BROKEN_PERP", "contractStatus": "TRADING", "filters": []}
+        ]});
+        let infos = parse_symbol_infos("binance-coinm", &coinm);
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos[0].status, "TRADING");
+        assert_eq!((infos[0].contract_size, infos[0].min_notional), (Some(10.0), None));
+    }
+
+    #[test]
+    fn test_cache_round_trip_and_market_refresh() {
+        let path = std::env::temp_dir().join(format!("symbol_registry_test_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let registry = SymbolRegistry::with_cache(&path);
+        assert!(registry.is_empty());
+        let btc = SymbolInfo::from_exchange_info("binance-usdm", &usdm_entry()).unwrap();
+        let mut eth = btc.clone();
+        eth.symbol = "ETHUSDT".to_string();
+        eth.tick_size = 0.01;
+        registry.update("binance-usdm", vec![btc.clone(), eth]);
+
+        // A fresh start reads the rules back without the exchange
+        let offline = SymbolRegistry::with_cache(&path);
+        assert_eq!(offline.tick_size("ETHUSDT"), Some(0.01));
+        assert_eq!(offline.get("BTCUSDT"), Some(btc.clone()));
+        assert_eq!(offline.symbols(), vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()]);
+
+        // A refresh drops the market's delisted symbols; an empty one is ignored
+        offline.update("binance-usdm", vec![btc]);
+        offline.update("binance-usdm", Vec::new());
+        assert_eq!(offline.tick_size("ETHUSDT"), None);
+        assert_eq!(offline.len(), 1);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
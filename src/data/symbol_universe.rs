@@ -0,0 +1,174 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::{mpsc, watch};
+use tokio::time::{sleep, Duration};
+use tracing::{info, warn};
+
+use super::{GuiUpdate, MarketDataSource, SymbolRegistry, fetch_exchange_info};
+
+/// Retry spacing while the first exchangeInfo request keeps failing
+const INITIAL_FETCH_RETRY_SECS: u64 = 5;
+
+/// Listings and delistings found by one exchangeInfo refresh
+#[derive(Debug, Clone, PartialEq)]
+pub struct UniverseChange {
+    /// `MarketDataSource::name` of the market that changed
+    pub market: String,
+    /// The full listed universe after the change
+    pub symbols: Vec<String>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Symbols in `new` but not `old`, and in `old` but not `new`, each in input order
+pub fn diff_symbols(old: &[String], new: &[String]) -> (Vec<String>, Vec<String>) {
+    let old_set: HashSet<&String> = old.iter().collect();
+    let new_set: HashSet<&String> = new.iter().collect();
+
+    let added = new.iter().filter(|symbol| !old_set.contains(symbol)).cloned().collect();
+    let removed = old.iter().filter(|symbol| !new_set.contains(symbol)).cloned().collect();
+    (added, removed)
+}
+
+/// Keeps one market's tradable perpetuals current by refreshing exchangeInfo.
+/// Stream managers follow the list through `subscribe`; listings and delistings
+/// are also published to the GUI, and the symbols' trading rules to the registry.
+pub struct SymbolUniverse {
+    source: Arc<dyn MarketDataSource>,
+    refresh_interval: Duration,
+    sender: watch::Sender<Vec<String>>,
+    gui_sender: Option<mpsc::Sender<GuiUpdate>>,
+    registry: Option<SymbolRegistry>,
+}
+
+impl SymbolUniverse {
+    pub fn new(source: Arc<dyn MarketDataSource>, refresh_interval_secs: u64) -> Self {
+        let (sender, _) = watch::channel(Vec::new());
+        Self {
+            source,
+            refresh_interval: Duration::from_secs(refresh_interval_secs.max(1)),
+            sender,
+            gui_sender: None,
+            registry: None,
+        }
+    }
+
+    pub fn set_gui_sender(&mut self, sender: mpsc::Sender<GuiUpdate>) {
+        self.gui_sender = Some(sender);
+    }
+
+    pub fn set_symbol_registry(&mut self, registry: SymbolRegistry) {
+        self.registry = Some(registry);
+    }
+
+    /// Current universe; empty until the first successful refresh
+    pub fn subscribe(&self) -> watch::Receiver<Vec<String>> {
+        self.sender.subscribe()
+    }
+
+    pub async fn run(self) {
+        loop {
+            let delay = match fetch_exchange_info(self.source.as_ref()).await {
+                Ok(exchange_info) => {
+                    if let Some(registry) = &self.registry {
+                        registry.update(self.source.name(), self.source.parse_symbol_info(&exchange_info));
+                    }
+                    self.apply(self.source.parse_symbols(&exchange_info));
+                    self.refresh_interval
+                }
+                Err(e) => {
+                    warn!("Cannot refresh {} symbol universe: {}", self.source.name(), e);
+                    if self.sender.borrow().is_empty() {
+                        Duration::from_secs(INITIAL_FETCH_RETRY_SECS)
+                    } else {
+                        self.refresh_interval
+                    }
+                }
+            };
+
+            if self.sender.is_closed() && self.gui_sender.as_ref().is_none_or(|sender| sender.is_closed()) {
+                return;
+            }
+            sleep(delay).await;
+        }
+    }
+
+    /// Publish `symbols` if they differ from the current universe; returns the change
+    pub fn apply(&self, symbols: Vec<String>) -> Option<UniverseChange> {
+        // An empty listing is an exchange hiccup, not a mass delisting
+        if symbols.is_empty() {
+            return None;
+        }
+
+        let (added, removed) = diff_symbols(&self.sender.borrow(), &symbols);
+        if added.is_empty() && removed.is_empty() {
+            return None;
+        }
+
+        let initial = self.sender.borrow().is_empty();
+        if initial {
+            info!("{} lists {} perpetual symbols", self.source.name(), symbols.len());
+        } else {
+            info!(
+                "{} symbol universe changed: listed {:?}, delisted {:?}",
+                self.source.name(), added, removed
+            );
+        }
+
+        let change = UniverseChange {
+            market: self.source.name().to_string(),
+            symbols: symbols.clone(),
+            added,
+            removed,
+        };
+        self.sender.send_replace(symbols);
+        if let Some(gui_sender) = &self.gui_sender {
+            let _ = gui_sender.try_send(GuiUpdate::SymbolUniverse(change.clone()));
+        }
+        Some(change)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::BinanceUsdmSource;
+
+    fn symbols(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_diff_symbols() {
+        let (added, removed) = diff_symbols(&symbols(&["BTCUSDT", "ETHUSDT", "XRPUSDT"]), &symbols(&["ETHUSDT", "NEWUSDT", "BTCUSDT"]));
+        assert_eq!(added, symbols(&["NEWUSDT"]));
+        assert_eq!(removed, symbols(&["XRPUSDT"]));
+    }
+
+    #[tokio::test]
+    async fn test_apply_publishes_changes_only() {
+        let source = Arc::new(BinanceUsdmSource::new("ws://127.0.0.1:1".to_string(), "http://127.0.0.1:1".to_string()));
+        let mut universe = SymbolUniverse::new(source, 300);
+        let (gui_sender, mut gui_receiver) = mpsc::channel(10);
+        universe.set_gui_sender(gui_sender);
+        let mut receiver = universe.subscribe();
+
+        let initial = universe.apply(symbols(&["BTCUSDT", "ETHUSDT"])).unwrap();
+        assert_eq!(initial.added.len(), 2);
+        assert!(receiver.has_changed().unwrap());
+        assert_eq!(*receiver.borrow_and_update(), symbols(&["BTCUSDT", "ETHUSDT"]));
+
+        // Same listing in another order, and an empty listing, change nothing
+        assert!(universe.apply(symbols(&["ETHUSDT", "BTCUSDT"])).is_none());
+        assert!(universe.apply(Vec::new()).is_none());
+        assert!(!receiver.has_changed().unwrap());
+
+        let change = universe.apply(symbols(&["BTCUSDT", "SOLUSDT"])).unwrap();
+        assert_eq!((change.added, change.removed), (symbols(&["SOLUSDT"]), symbols(&["ETHUSDT"])));
+        assert_eq!(*receiver.borrow_and_update(), symbols(&["BTCUSDT", "SOLUSDT"]));
+
+        assert!(matches!(gui_receiver.try_recv(), Ok(GuiUpdate::SymbolUniverse(change)) if change.market == "binance-usdm"));
+        assert!(matches!(gui_receiver.try_recv(), Ok(GuiUpdate::SymbolUniverse(change)) if change.removed == symbols(&["ETHUSDT"])));
+        assert!(gui_receiver.try_recv().is_err());
+    }
+}
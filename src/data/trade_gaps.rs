@@ -0,0 +1,415 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use anyhow::Result;
+use tokio::sync::mpsc;
+use tokio::time::{sleep_until, Duration, Instant};
+use tracing::{debug, info, warn};
+
+use crate::config::settings::BinanceConfig;
+use super::{MarketDataSource, OrderflowEvent, AGG_TRADES_PAGE_LIMIT};
+
+/// Spacing between gap backfill requests; aggTrades weighs 20 of the 2400 per minute
+const GAP_REQUEST_INTERVAL_MS: u64 = 500;
+/// Live trades held back for one symbol before its backfill is abandoned
+const MAX_HELD_TRADES: usize = 100_000;
+
+#[derive(Debug, Clone)]
+pub struct TradeGapConfig {
+    /// aggTrades pages fetched per gap; 0 only counts gaps
+    pub max_pages: usize,
+    pub request_interval_ms: u64,
+}
+
+impl From<&BinanceConfig> for TradeGapConfig {
+    fn from(config: &BinanceConfig) -> Self {
+        Self {
+            max_pages: config.gap_backfill_max_pages,
+            request_interval_ms: GAP_REQUEST_INTERVAL_MS,
+        }
+    }
+}
+
+/// Counters shared between the gap filler and whoever reports on it
+#[derive(Debug, Default)]
+pub struct TradeGapMetrics {
+    gaps: AtomicU64,
+    missing_trades: AtomicU64,
+    backfilled_trades: AtomicU64,
+    unrecovered_trades: AtomicU64,
+    requests: AtomicU64,
+}
+
+/// Point-in-time copy of `TradeGapMetrics`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TradeGapStats {
+    /// Jumps in a symbol's aggregate trade ids
+    pub gaps: u64,
+    /// Trades skipped by those jumps
+    pub missing_trades: u64,
+    pub backfilled_trades: u64,
+    /// Missing trades the page budget or a failed request left out
+    pub unrecovered_trades: u64,
+    /// aggTrades requests made
+    pub requests: u64,
+}
+
+impl TradeGapMetrics {
+    pub fn stats(&self) -> TradeGapStats {
+        TradeGapStats {
+            gaps: self.gaps.load(Ordering::Relaxed),
+            missing_trades: self.missing_trades.load(Ordering::Relaxed),
+            backfilled_trades: self.backfilled_trades.load(Ordering::Relaxed),
+            unrecovered_trades: self.unrecovered_trades.load(Ordering::Relaxed),
+            requests: self.requests.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Aggregate trade ids `from_id..until_id` missing for `symbol`
+#[derive(Debug, Clone, PartialEq)]
+struct GapRequest {
+    symbol: String,
+    from_id: u64,
+    until_id: u64,
+}
+
+/// Live trades of a symbol waiting for its gap to be filled
+struct HeldTrades {
+    request: GapRequest,
+    trades: Vec<OrderflowEvent>,
+}
+
+/// Sits between the stream connections and the analysis engines. Aggregate
+/// trade ids are contiguous per symbol, so a jump means trades were lost, usually
+/// across a reconnect. The missing ids are fetched from the REST aggTrades
+/// endpoint while the symbol's live trades are held back, then both are passed
+/// on in id order. Other symbols keep flowing meanwhile.
+pub struct TradeGapFiller {
+    source: Arc<dyn MarketDataSource>,
+    receiver: mpsc::Receiver<OrderflowEvent>,
+    sender: mpsc::Sender<OrderflowEvent>,
+    config: TradeGapConfig,
+    metrics: Arc<TradeGapMetrics>,
+    /// Last aggregate trade id passed on per symbol
+    last_ids: HashMap<String, u64>,
+    held: HashMap<String, HeldTrades>,
+}
+
+impl TradeGapFiller {
+    pub fn new(
+        source: Arc<dyn MarketDataSource>,
+        receiver: mpsc::Receiver<OrderflowEvent>,
+        sender: mpsc::Sender<OrderflowEvent>,
+        config: TradeGapConfig,
+    ) -> Self {
+        Self {
+            source,
+            receiver,
+            sender,
+            config,
+            metrics: Arc::new(TradeGapMetrics::default()),
+            last_ids: HashMap::new(),
+            held: HashMap::new(),
+        }
+    }
+
+    pub fn metrics(&self) -> Arc<TradeGapMetrics> {
+        self.metrics.clone()
+    }
+
+    /// Run until every stream connection has dropped its sender
+    pub async fn run(mut self) {
+        let (requests, request_receiver) = mpsc::unbounded_channel();
+        let (fills, mut fill_receiver) = mpsc::unbounded_channel();
+        let fetcher = GapFetcher {
+            source: self.source.clone(),
+            client: reqwest::Client::new(),
+            config: self.config.clone(),
+            metrics: self.metrics.clone(),
+        };
+        let fetcher_handle = tokio::spawn(fetcher.run(request_receiver, fills));
+
+        loop {
+            tokio::select! {
+                event = self.receiver.recv() => {
+                    let Some(event) = event else { break };
+                    if !self.handle_trade(event, &requests).await {
+                        break;
+                    }
+                }
+                Some((request, trades)) = fill_receiver.recv() => {
+                    if !self.complete(request, trades, &requests).await {
+                        break;
+                    }
+                }
+            }
+        }
+
+        fetcher_handle.abort();
+    }
+
+    /// Pass a live trade on, hold it behind a pending backfill, or start one.
+    /// Returns false once the downstream receiver is gone.
+    async fn handle_trade(&mut self, event: OrderflowEvent, requests: &mpsc::UnboundedSender<GapRequest>) -> bool {
+        if let Some(held) = self.held.get_mut(&event.symbol) {
+            if held.trades.last().is_none_or(|last| event.trade_id > last.trade_id) {
+                held.trades.push(event);
+            }
+            if held.trades.len() >= MAX_HELD_TRADES {
+                let symbol = held.request.symbol.clone();
+                warn!("Giving up the {} trade backfill, {} live trades waiting", symbol, MAX_HELD_TRADES);
+                return self.complete_held(&symbol, Vec::new(), requests).await;
+            }
+            return true;
+        }
+
+        let last_id = self.last_ids.get(&event.symbol).copied();
+        match last_id {
+            // Repeated by an overlapping connection
+            Some(last) if event.trade_id <= last => true,
+            Some(last) if event.trade_id > last + 1 && self.config.max_pages > 0 => {
+                let request = GapRequest {
+                    symbol: event.symbol.clone(),
+                    from_id: last + 1,
+                    until_id: event.trade_id,
+                };
+                self.count_gap(&request);
+                let _ = requests.send(request.clone());
+                self.held.insert(event.symbol.clone(), HeldTrades { request, trades: vec![event] });
+                true
+            }
+            _ => {
+                if let Some(last) = last_id.filter(|last| event.trade_id > last + 1) {
+                    // Backfill disabled: count the loss and move on
+                    let request = GapRequest { symbol: event.symbol.clone(), from_id: last + 1, until_id: event.trade_id };
+                    self.count_gap(&request);
+                    self.metrics.unrecovered_trades.fetch_add(request.until_id - request.from_id, Ordering::Relaxed);
+                }
+                self.forward(event).await
+            }
+        }
+    }
+
+    fn count_gap(&self, request: &GapRequest) {
+        let missing = request.until_id - request.from_id;
+        self.metrics.gaps.fetch_add(1, Ordering::Relaxed);
+        self.metrics.missing_trades.fetch_add(missing, Ordering::Relaxed);
+        info!("{} trade gap: {} aggregate trades missing from id {}", request.symbol, missing, request.from_id);
+    }
+
+    async fn forward(&mut self, event: OrderflowEvent) -> bool {
+        self.last_ids.insert(event.symbol.clone(), event.trade_id);
+        self.sender.send(event).await.is_ok()
+    }
+
+    /// A backfill finished; fills for a symbol no longer waiting on that gap are stale
+    async fn complete(&mut self, request: GapRequest, trades: Vec<OrderflowEvent>, requests: &mpsc::UnboundedSender<GapRequest>) -> bool {
+        if self.held.get(&request.symbol).is_none_or(|held| held.request != request) {
+            return true;
+        }
+        self.complete_held(&request.symbol, trades, requests).await
+    }
+
+    /// Pass on the backfilled trades, then the held live ones. The live ones go
+    /// through `handle_trade` again, so a new gap among them is caught as well.
+    async fn complete_held(&mut self, symbol: &str, mut trades: Vec<OrderflowEvent>, requests: &mpsc::UnboundedSender<GapRequest>) -> bool {
+        let Some(held) = self.held.remove(symbol) else {
+            return true;
+        };
+        let GapRequest { from_id, until_id, .. } = held.request;
+
+        trades.retain(|trade| trade.trade_id >= from_id && trade.trade_id < until_id);
+        trades.sort_by_key(|trade| trade.trade_id);
+        trades.dedup_by_key(|trade| trade.trade_id);
+        let backfilled = trades.len() as u64;
+        let unrecovered = until_id - from_id - backfilled;
+        self.metrics.backfilled_trades.fetch_add(backfilled, Ordering::Relaxed);
+        self.metrics.unrecovered_trades.fetch_add(unrecovered, Ordering::Relaxed);
+
+        let stats = self.metrics.stats();
+        info!(
+            "Backfilled {} of {} missing {} trades ({} gaps, {} backfilled, {} unrecovered so far)",
+            backfilled, until_id - from_id, symbol, stats.gaps, stats.backfilled_trades, stats.unrecovered_trades
+        );
+
+        // Later live trades count from the end of the gap, filled or not
+        self.last_ids.insert(symbol.to_string(), until_id - 1);
+        for trade in trades {
+            if !self.forward(trade).await {
+                return false;
+            }
+        }
+        for trade in held.trades {
+            if !Box::pin(self.handle_trade(trade, requests)).await {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Fetches gap requests one after another, spaced to respect the REST weight limit
+struct GapFetcher {
+    source: Arc<dyn MarketDataSource>,
+    client: reqwest::Client,
+    config: TradeGapConfig,
+    metrics: Arc<TradeGapMetrics>,
+}
+
+impl GapFetcher {
+    async fn run(
+        self,
+        mut requests: mpsc::UnboundedReceiver<GapRequest>,
+        fills: mpsc::UnboundedSender<(GapRequest, Vec<OrderflowEvent>)>,
+    ) {
+        let mut next_request_at = Instant::now();
+        while let Some(request) = requests.recv().await {
+            let trades = self.fetch(&request, &mut next_request_at).await;
+            if fills.send((request, trades)).is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Trades of `request` in id order, walking forward page by page until the
+    /// gap is closed, the page budget is spent or a request fails
+    async fn fetch(&self, request: &GapRequest, next_request_at: &mut Instant) -> Vec<OrderflowEvent> {
+        let mut trades = Vec::new();
+        let mut from_id = request.from_id;
+
+        for _ in 0..self.config.max_pages {
+            if from_id >= request.until_id {
+                break;
+            }
+            sleep_until(*next_request_at).await;
+            *next_request_at = Instant::now() + Duration::from_millis(self.config.request_interval_ms);
+            self.metrics.requests.fetch_add(1, Ordering::Relaxed);
+
+            let limit = ((request.until_id - from_id) as usize).min(AGG_TRADES_PAGE_LIMIT);
+            let page = match self.fetch_page(&request.symbol, from_id, limit).await {
+                Ok(page) => page,
+                Err(e) => {
+                    warn!("aggTrades gap backfill for {} failed: {}", request.symbol, e);
+                    break;
+                }
+            };
+
+            let page: Vec<_> = page
+                .into_iter()
+                .filter(|trade| trade.trade_id >= from_id && trade.trade_id < request.until_id)
+                .collect();
+            let Some(newest) = page.last() else {
+                break;
+            };
+            from_id = newest.trade_id + 1;
+            trades.extend(page);
+        }
+
+        debug!("Fetched {} trades for the {} gap {}..{}", trades.len(), request.symbol, request.from_id, request.until_id);
+        trades
+    }
+
+    async fn fetch_page(&self, symbol: &str, from_id: u64, limit: usize) -> Result<Vec<OrderflowEvent>> {
+        let url = self.source.agg_trades_url(symbol, Some(from_id), limit);
+        let body = self.client.get(&url).send().await?.error_for_status()?.text().await?;
+        self.source.parse_agg_trades(symbol, &body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::BinanceUsdmSource;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn trade(symbol: &str, id: u64) -> OrderflowEvent {
+        OrderflowEvent {
+            symbol: symbol.to_string(),
+            timestamp: 1_700_000_000_000 + id,
+            price: 100.0,
+            quantity: 1.0,
+            is_buyer_maker: id.is_multiple_of(2),
+            trade_id: id,
+        }
+    }
+
+    /// aggTrades endpoint serving BTCUSDT ids 1..=100 from `fromId`
+    async fn serve_agg_trades() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 2048];
+                let n = socket.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let query = request.split_whitespace().nth(1).unwrap_or_default().split_once('?').map_or("", |(_, query)| query);
+                let param = |name: &str| query.split('&').find_map(|pair| pair.strip_prefix(name)?.strip_prefix('=')?.parse::<u64>().ok());
+                let (from_id, limit) = (param("fromId").unwrap_or(1), param("limit").unwrap_or(500));
+
+                let body = serde_json::to_string(&(from_id..=100).take(limit as usize).map(|id| serde_json::json!({
+                    "a": id, "p": "100.0", "q": "1", "f": id, "l": id, "T": 1_700_000_000_000u64 + id, "m": id % 2 == 0,
+                })).collect::<Vec<_>>()).unwrap();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    async fn run_filler(api_base_url: String, max_pages: usize, input: Vec<OrderflowEvent>) -> (Vec<OrderflowEvent>, TradeGapStats) {
+        let source = Arc::new(BinanceUsdmSource::new("ws://127.0.0.1:1".to_string(), api_base_url));
+        let (input_sender, input_receiver) = mpsc::channel(100);
+        let (output_sender, mut output_receiver) = mpsc::channel(100);
+        let filler = TradeGapFiller::new(source, input_receiver, output_sender, TradeGapConfig { max_pages, request_interval_ms: 0 });
+        let metrics = filler.metrics();
+        let handle = tokio::spawn(filler.run());
+
+        for event in input {
+            input_sender.send(event).await.unwrap();
+        }
+        // Everything is out once the output stays quiet
+        let mut output = Vec::new();
+        while let Ok(Some(event)) = tokio::time::timeout(Duration::from_millis(500), output_receiver.recv()).await {
+            output.push(event);
+        }
+        handle.abort();
+        (output, metrics.stats())
+    }
+
+    fn ids(trades: &[OrderflowEvent], symbol: &str) -> Vec<u64> {
+        trades.iter().filter(|trade| trade.symbol == symbol).map(|trade| trade.trade_id).collect()
+    }
+
+    #[tokio::test]
+    async fn test_gap_is_backfilled_in_order() {
+        let input = vec![
+            trade("BTCUSDT", 1), trade("BTCUSDT", 2), trade("BTCUSDT", 3),
+            // Reconnect: 4..=9 lost
+            trade("BTCUSDT", 10), trade("ETHUSDT", 7), trade("BTCUSDT", 11),
+            // Late repeat from the old connection
+            trade("BTCUSDT", 3), trade("ETHUSDT", 8),
+        ];
+        let (output, stats) = run_filler(serve_agg_trades().await, 10, input).await;
+
+        assert_eq!(ids(&output, "BTCUSDT"), (1..=11).collect::<Vec<_>>());
+        assert_eq!(ids(&output, "ETHUSDT"), vec![7, 8]);
+        assert_eq!(stats, TradeGapStats { gaps: 1, missing_trades: 6, backfilled_trades: 6, unrecovered_trades: 0, requests: 1 });
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_rest_releases_held_trades() {
+        let input = vec![trade("BTCUSDT", 1), trade("BTCUSDT", 5), trade("BTCUSDT", 6), trade("BTCUSDT", 9)];
+        let (output, stats) = run_filler("http://127.0.0.1:1".to_string(), 10, input).await;
+
+        // Both gaps are counted and given up, the live trades still arrive in order
+        assert_eq!(ids(&output, "BTCUSDT"), vec![1, 5, 6, 9]);
+        assert_eq!((stats.gaps, stats.missing_trades, stats.backfilled_trades, stats.unrecovered_trades), (2, 5, 0, 5));
+        assert_eq!(stats.requests, 2);
+    }
+}
@@ -1,77 +1,32 @@
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio_tungstenite::{connect_async, tungstenite, tungstenite::Message, MaybeTlsStream, WebSocketStream};
 use futures_util::{SinkExt, StreamExt};
-use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc;
-use tokio::time::{sleep, Duration};
+use futures_util::stream::{SplitSink, SplitStream};
+use serde::Serialize;
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, watch};
+use tokio::time::{sleep, sleep_until, Duration, Instant};
 use anyhow::{Result, anyhow};
 use tracing::{info, warn, error, debug};
 use std::collections::HashMap;
-use url::Url;
+use std::sync::{Arc, Mutex};
 
 use crate::config::Settings;
-use super::{OrderflowEvent, LiquidationEvent, DepthUpdate};
-
-#[derive(Debug, Deserialize)]
-struct BinanceAggTradeMessage {
-    #[serde(rename = "e")]
-    event_type: String,
-    #[serde(rename = "E")]
-    event_time: u64,
-    #[serde(rename = "s")]
-    symbol: String,
-    #[serde(rename = "a")]
-    aggregate_trade_id: u64,
-    #[serde(rename = "p")]
-    price: String,
-    #[serde(rename = "q")]
-    quantity: String,
-    #[serde(rename = "f")]
-    first_trade_id: u64,
-    #[serde(rename = "l")]
-    last_trade_id: u64,
-    #[serde(rename = "T")]
-    trade_time: u64,
-    #[serde(rename = "m")]
-    is_buyer_maker: bool,
-    #[serde(rename = "M")]
-    ignore: Option<bool>,
-}
-
-#[derive(Debug, Deserialize)]
-struct BinanceLiquidationMessage {
-    #[serde(rename = "e")]
-    event_type: String,
-    #[serde(rename = "E")]
-    event_time: u64,
-    #[serde(rename = "o")]
-    order: LiquidationOrder,
-}
-
-#[derive(Debug, Deserialize)]
-struct LiquidationOrder {
-    #[serde(rename = "s")]
-    symbol: String,
-    #[serde(rename = "S")]
-    side: String,
-    #[serde(rename = "o")]
-    order_type: String,
-    #[serde(rename = "f")]
-    time_in_force: String,
-    #[serde(rename = "q")]
-    original_quantity: String,
-    #[serde(rename = "p")]
-    price: String,
-    #[serde(rename = "ap")]
-    average_price: String,
-    #[serde(rename = "X")]
-    order_status: String,
-    #[serde(rename = "l")]
-    last_filled_quantity: String,
-    #[serde(rename = "z")]
-    filled_accumulated_quantity: String,
-    #[serde(rename = "T")]
-    trade_time: u64,
-}
+use crate::config::settings::BinanceConfig;
+use super::{OrderflowEvent, LiquidationEvent, DepthUpdate, FundingRateData, MarketDataSource, MarketEvent, fetch_active_symbols, diff_symbols};
+use super::{ConnectionMonitor, ConnectionProbe, HealthConfig};
+
+/// Streams carried by one connection; Binance allows 1024, stay well below
+const MAX_STREAMS_PER_CONNECTION: usize = 200;
+/// How often the manager checks whether its stream connections stopped
+const CONNECTION_CHECK_INTERVAL_SECS: u64 = 5;
+/// Uptime after which a dropped connection reconnects from the base delay again
+const STABLE_CONNECTION_SECS: u64 = 30;
+/// Wait before retrying a rotation whose replacement connection failed
+const ROTATION_RETRY_SECS: u64 = 30;
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+type WsSink = SplitSink<WsStream, Message>;
+type WsSource = SplitStream<WsStream>;
 
 #[derive(Debug, Serialize)]
 struct StreamSubscription {
@@ -80,22 +35,138 @@ struct StreamSubscription {
     id: u64,
 }
 
+/// Per-symbol streams opened for every listed symbol
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StreamKind {
+    Trade,
+    Depth,
+}
+
+impl StreamKind {
+    fn label(self) -> &'static str {
+        match self {
+            StreamKind::Trade => "Trade",
+            StreamKind::Depth => "Depth",
+        }
+    }
+
+    fn stream_name(self, source: &dyn MarketDataSource, symbol: &str) -> String {
+        match self {
+            StreamKind::Trade => source.trade_stream(symbol),
+            StreamKind::Depth => source.depth_stream(symbol),
+        }
+    }
+
+    /// Name of the connection in health reports, e.g. "binance-usdm trade #0"
+    fn connection_name(self, source: &dyn MarketDataSource, index: usize) -> String {
+        format!("{} {} #{}", source.name(), self.label().to_lowercase(), index)
+    }
+
+    /// Messages between throughput log lines
+    fn log_interval(self) -> u64 {
+        match self {
+            StreamKind::Trade => 100,
+            StreamKind::Depth => 1000,
+        }
+    }
+}
+
+/// Live subscription change for one connection
+#[derive(Debug)]
+enum StreamCommand {
+    Subscribe(Vec<String>),
+    Unsubscribe(Vec<String>),
+}
+
+/// One multiplexed stream connection. The symbol list is shared with the
+/// connection task so a reconnect subscribes to the current set; commands carry
+/// the changes made while it is connected.
+struct StreamConnection {
+    kind: StreamKind,
+    symbols: Arc<Mutex<Vec<String>>>,
+    commands: mpsc::UnboundedSender<StreamCommand>,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+/// Destinations for events decoded on trade and depth connections
+#[derive(Clone)]
+struct StreamSenders {
+    orderflow: mpsc::Sender<OrderflowEvent>,
+    depth: Option<mpsc::Sender<DepthUpdate>>,
+}
+
+/// Capped exponential reconnect delay with jitter, so connections dropped
+/// together do not hammer the exchange in lockstep. It never runs out.
+struct ReconnectBackoff {
+    base: Duration,
+    max: Duration,
+    attempt: u32,
+}
+
+impl ReconnectBackoff {
+    fn new(config: &BinanceConfig) -> Self {
+        let base = Duration::from_millis(config.reconnect_delay_ms.max(1));
+        Self {
+            base,
+            max: Duration::from_millis(config.max_reconnect_delay_ms).max(base),
+            attempt: 0,
+        }
+    }
+
+    fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// Delay before reconnecting a connection that was up for `uptime`: a random
+    /// point in the upper half of `base * 2^attempt`, capped at `max`
+    fn after_connection(&mut self, uptime: Duration) -> Duration {
+        if uptime >= Duration::from_secs(STABLE_CONNECTION_SECS) {
+            self.attempt = 0;
+        }
+        let ceiling = self.base.saturating_mul(2u32.saturating_pow(self.attempt)).min(self.max);
+        self.attempt = self.attempt.saturating_add(1);
+        ceiling / 2 + ceiling.mul_f64(random_fraction() / 2.0)
+    }
+}
+
+/// Uniform in [0, 1), from the randomly keyed std hasher
+fn random_fraction() -> f64 {
+    use std::hash::{BuildHasher, Hasher};
+    let hash = std::collections::hash_map::RandomState::new().build_hasher().finish();
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
 pub struct WebSocketManager {
     settings: Settings,
+    source: Arc<dyn MarketDataSource>,
     orderflow_sender: mpsc::Sender<OrderflowEvent>,
     liquidation_sender: Option<mpsc::Sender<LiquidationEvent>>,
     depth_sender: Option<mpsc::Sender<DepthUpdate>>,
+    mark_price_sender: Option<mpsc::Sender<Vec<FundingRateData>>>,
+    universe: Option<watch::Receiver<Vec<String>>>,
     active_symbols: Vec<String>,
+    connections: Vec<StreamConnection>,
+    monitor: ConnectionMonitor,
 }
 
 impl WebSocketManager {
-    pub fn new(settings: Settings, orderflow_sender: mpsc::Sender<OrderflowEvent>) -> Self {
+    pub fn new(
+        settings: Settings,
+        source: Arc<dyn MarketDataSource>,
+        orderflow_sender: mpsc::Sender<OrderflowEvent>,
+    ) -> Self {
+        let monitor = ConnectionMonitor::new(HealthConfig::from(&settings.binance));
         Self {
             settings,
+            source,
             orderflow_sender,
             liquidation_sender: None,
             depth_sender: None,
+            mark_price_sender: None,
+            universe: None,
             active_symbols: Vec::new(),
+            connections: Vec::new(),
+            monitor,
         }
     }
 
@@ -107,40 +178,87 @@ impl WebSocketManager {
         self.depth_sender = Some(sender);
     }
 
-    pub async fn start(&mut self) -> Result<()> {
-        // Get ALL active symbols from Binance API for orderflow (like liquidations)
-        self.active_symbols = self.settings.get_active_symbols().await?;
-        info!("Found {} active USDT perpetual symbols for orderflow streams", self.active_symbols.len());
+    /// Receive funding and mark price samples, one batch per mark price frame
+    pub fn set_mark_price_sender(&mut self, sender: mpsc::Sender<Vec<FundingRateData>>) {
+        self.mark_price_sender = Some(sender);
+    }
 
-        // Start trade streams
-        let trade_handle = self.start_trade_streams().await?;
+    /// Follow a `SymbolUniverse` instead of reading exchangeInfo once at startup
+    pub fn set_symbol_universe(&mut self, universe: watch::Receiver<Vec<String>>) {
+        self.universe = Some(universe);
+    }
 
-        // Start liquidation stream
-        let liquidation_handle = self.start_liquidation_stream().await?;
+    /// Report connection health into a monitor shared with a `ConnectionSupervisor`
+    pub fn set_connection_monitor(&mut self, monitor: ConnectionMonitor) {
+        self.monitor = monitor;
+    }
 
-        // Start depth streams if enabled
-        let depth_handle = if self.depth_sender.is_some() {
-            Some(self.start_depth_streams().await?)
+    pub async fn start(&mut self) -> Result<()> {
+        // Stream ALL listed perpetuals for orderflow (like liquidations)
+        let mut universe = self.universe.take();
+        self.active_symbols = match &mut universe {
+            Some(universe) => universe.wait_for(|symbols| !symbols.is_empty()).await?.clone(),
+            None => fetch_active_symbols(self.source.as_ref()).await?,
+        };
+        info!("Found {} active perpetual symbols on {} for orderflow streams",
+              self.active_symbols.len(), self.source.name());
+
+        // Start trade streams, and depth streams if enabled
+        let symbols = self.active_symbols.clone();
+        self.open_connections(StreamKind::Trade, &symbols);
+        if self.depth_sender.is_some() {
+            self.open_connections(StreamKind::Depth, &symbols);
         } else {
             info!("Depth streams disabled (no depth sender configured)");
-            None
+        }
+
+        // Start liquidation stream
+        let mut liquidation_handle = self.start_liquidation_stream().await?;
+
+        // Start the mark price stream if requested and the market has one
+        let mut mark_price_handle = match (&self.mark_price_sender, self.source.mark_price_stream_url()) {
+            (Some(sender), Some(url)) => Some(self.start_mark_price_stream(url, sender.clone())),
+            (Some(_), None) => {
+                info!("No all-market mark price stream on {}", self.source.name());
+                None
+            }
+            (None, _) => None,
         };
 
-        // Wait for all streams
-        tokio::select! {
-            result = trade_handle => {
-                if let Err(e) = result {
-                    error!("Trade stream error: {}", e);
+        // Follow the symbol universe until a stream gives up
+        let mut connection_check = tokio::time::interval(Duration::from_secs(CONNECTION_CHECK_INTERVAL_SECS));
+        loop {
+            tokio::select! {
+                result = &mut liquidation_handle => {
+                    if let Err(e) = result {
+                        error!("Liquidation stream error: {}", e);
+                    }
+                    break;
                 }
-            }
-            result = liquidation_handle => {
-                if let Err(e) = result {
-                    error!("Liquidation stream error: {}", e);
+                result = async { if let Some(h) = &mut mark_price_handle { h.await } else { std::future::pending().await } } => {
+                    if let Err(e) = result {
+                        error!("Mark price stream error: {:?}", e);
+                    }
+                    break;
                 }
-            }
-            result = async { if let Some(h) = depth_handle { h.await } else { Ok(Ok(())) } } => {
-                if let Err(e) = result {
-                    error!("Depth stream error: {:?}", e);
+                changed = async { if let Some(u) = &mut universe { u.changed().await } else { std::future::pending().await } } => {
+                    match changed {
+                        Ok(()) => {
+                            let symbols = universe.as_mut().map(|u| u.borrow_and_update().clone()).unwrap_or_default();
+                            self.apply_universe(symbols);
+                        }
+                        Err(_) => universe = None,
+                    }
+                }
+                _ = connection_check.tick() => {
+                    let trade_connections_gone = self.connections
+                        .iter()
+                        .filter(|connection| connection.kind == StreamKind::Trade)
+                        .all(|connection| connection.handle.is_finished());
+                    if trade_connections_gone {
+                        error!("All trade stream connections on {} stopped", self.source.name());
+                        break;
+                    }
                 }
             }
         }
@@ -148,209 +266,115 @@ impl WebSocketManager {
         Ok(())
     }
 
-    async fn start_trade_streams(&self) -> Result<tokio::task::JoinHandle<Result<()>>> {
-        let settings = self.settings.clone();
-        let orderflow_sender = self.orderflow_sender.clone();
-        let symbols = self.active_symbols.clone();
-
-        let handle = tokio::spawn(async move {
-            // Split symbols into chunks to avoid WebSocket limits
-            const MAX_STREAMS_PER_CONNECTION: usize = 200; // Conservative limit
-            let symbol_chunks: Vec<Vec<String>> = symbols
-                .chunks(MAX_STREAMS_PER_CONNECTION)
-                .map(|chunk| chunk.to_vec())
-                .collect();
-
-            info!("Splitting {} symbols into {} WebSocket connections", symbols.len(), symbol_chunks.len());
-
-            let mut connection_handles = Vec::new();
-
-            for (i, chunk) in symbol_chunks.into_iter().enumerate() {
-                let settings_clone = settings.clone();
-                let sender_clone = orderflow_sender.clone();
-
-                let handle: tokio::task::JoinHandle<Result<()>> = tokio::spawn(async move {
-                    let mut retry_count = 0;
-                    let max_retries = settings_clone.binance.max_reconnect_attempts;
-
-                    loop {
-                        match Self::connect_trade_streams(&settings_clone, &sender_clone, &chunk).await {
-                            Ok(_) => {
-                                info!("Trade stream connection {} connected successfully with {} symbols", i, chunk.len());
-                                retry_count = 0;
-                            }
-                            Err(e) => {
-                                retry_count += 1;
-                                error!("Trade stream connection {} failed (attempt {}/{}): {}",
-                                       i, retry_count, max_retries, e);
-
-                                if retry_count >= max_retries {
-                                    return Err(anyhow!("Max retry attempts reached for trade stream connection {}", i));
-                                }
-
-                                let delay = Duration::from_millis(
-                                    settings_clone.binance.reconnect_delay_ms * retry_count as u64
-                                );
-                                warn!("Reconnecting trade stream connection {} in {:?}", i, delay);
-                                sleep(delay).await;
-                            }
-                        }
-                    }
-                });
+    /// Subscribe newly listed symbols and unsubscribe delisted ones on the open
+    /// connections, opening more connections when the existing ones are full
+    fn apply_universe(&mut self, symbols: Vec<String>) {
+        if symbols.is_empty() {
+            return;
+        }
+        let (added, removed) = diff_symbols(&self.active_symbols, &symbols);
+        if added.is_empty() && removed.is_empty() {
+            return;
+        }
+        info!("{}: subscribing {} listed and unsubscribing {} delisted symbols",
+              self.source.name(), added.len(), removed.len());
 
-                connection_handles.push(handle);
-            }
+        let mut kinds = vec![StreamKind::Trade];
+        if self.depth_sender.is_some() {
+            kinds.push(StreamKind::Depth);
+        }
+        for kind in kinds {
+            self.unsubscribe(kind, &removed);
+            self.subscribe(kind, &added);
+        }
+        self.active_symbols = symbols;
+    }
 
-            // Wait for all connections
-            for (i, handle) in connection_handles.into_iter().enumerate() {
-                if let Err(e) = handle.await {
-                    error!("Trade stream connection {} failed: {:?}", i, e);
-                }
+    fn unsubscribe(&mut self, kind: StreamKind, symbols: &[String]) {
+        for connection in self.connections.iter().filter(|connection| connection.kind == kind) {
+            let mut subscribed = connection.symbols.lock().unwrap();
+            let dropped: Vec<String> = subscribed.iter().filter(|symbol| symbols.contains(symbol)).cloned().collect();
+            if !dropped.is_empty() {
+                subscribed.retain(|symbol| !dropped.contains(symbol));
+                let _ = connection.commands.send(StreamCommand::Unsubscribe(dropped));
             }
-
-            Ok(())
-        });
-
-        Ok(handle)
+        }
     }
 
-    async fn connect_trade_streams(
-        settings: &Settings,
-        orderflow_sender: &mpsc::Sender<OrderflowEvent>,
-        symbols: &[String],
-    ) -> Result<()> {
-        let stream_names: Vec<String> = symbols
-            .iter()
-            .map(|s| format!("{}@aggTrade", s.to_lowercase()))
-            .collect();
-
-        let url = format!("{}/ws", settings.binance.websocket_base_url);
-        let (ws_stream, _) = connect_async(&url).await?;
-        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+    fn subscribe(&mut self, kind: StreamKind, symbols: &[String]) {
+        let mut pending = symbols.to_vec();
 
-        // Subscribe to streams
-        let subscription = StreamSubscription {
-            method: "SUBSCRIBE".to_string(),
-            params: stream_names.clone(),
-            id: 1,
-        };
-
-        let subscribe_msg = Message::Text(serde_json::to_string(&subscription)?);
-        ws_sender.send(subscribe_msg).await?;
-
-        info!("Subscribed to {} trade streams", symbols.len());
-        info!("First 10 symbols: {:?}", &symbols[..symbols.len().min(10)]);
-        info!("Subscription message size: {} characters", serde_json::to_string(&subscription)?.len());
-        debug!("First few stream names: {:?}", &stream_names[..stream_names.len().min(5)]);
-
-        // Process incoming messages
-        let mut message_count = 0;
-        while let Some(msg) = ws_receiver.next().await {
-            match msg {
-                Ok(Message::Text(text)) => {
-                    message_count += 1;
-                    if message_count % 100 == 0 {
-                        info!("WebSocket received {} messages. Latest size: {} chars", message_count, text.len());
-                    }
-                    if text.contains("error") {
-                        error!("WebSocket error message: {}", text);
-                    }
-                    if let Err(e) = Self::process_trade_message(&text, orderflow_sender).await {
-                        debug!("Failed to process trade message: {}", e);
-                    }
-                }
-                Ok(Message::Ping(ping)) => {
-                    ws_sender.send(Message::Pong(ping)).await?;
-                }
-                Ok(Message::Close(_)) => {
-                    warn!("WebSocket connection closed by server");
-                    break;
-                }
-                Err(e) => {
-                    error!("WebSocket error: {}", e);
-                    break;
-                }
-                _ => {}
+        // Fill free slots on the existing connections first
+        for connection in self.connections.iter().filter(|connection| connection.kind == kind) {
+            if pending.is_empty() {
+                break;
+            }
+            let mut subscribed = connection.symbols.lock().unwrap();
+            let room = MAX_STREAMS_PER_CONNECTION.saturating_sub(subscribed.len()).min(pending.len());
+            if room > 0 {
+                let batch: Vec<String> = pending.drain(..room).collect();
+                subscribed.extend(batch.iter().cloned());
+                let _ = connection.commands.send(StreamCommand::Subscribe(batch));
             }
         }
 
-        Err(anyhow!("Trade stream connection lost"))
+        self.open_connections(kind, &pending);
     }
 
-    async fn process_trade_message(
-        text: &str,
-        orderflow_sender: &mpsc::Sender<OrderflowEvent>,
-    ) -> Result<()> {
-        // Handle subscription confirmation messages
-        if text.contains("\"result\":null") || text.contains("\"id\":") {
-            info!("WebSocket subscription confirmed: {}", text);
-            return Ok(());
+    /// Spread `symbols` over new connections of `kind`
+    fn open_connections(&mut self, kind: StreamKind, symbols: &[String]) {
+        if symbols.is_empty() {
+            return;
         }
+        info!("Opening {} {} WebSocket connections for {} symbols",
+              symbols.len().div_ceil(MAX_STREAMS_PER_CONNECTION), kind.label().to_lowercase(), symbols.len());
 
-        let trade_msg: BinanceAggTradeMessage = serde_json::from_str(text)
-            .map_err(|e| {
-                error!("Failed to parse aggTrade message: {}. Message: {}", e, text);
-                e
-            })?;
-
-        if trade_msg.event_type == "aggTrade" {
-            let price = trade_msg.price.parse::<f64>()?;
-            let quantity = trade_msg.quantity.parse::<f64>()?;
-
-            let event = OrderflowEvent {
-                symbol: trade_msg.symbol.clone(),
-                timestamp: trade_msg.trade_time,
-                price,
-                quantity,
-                is_buyer_maker: trade_msg.is_buyer_maker,
-                trade_id: trade_msg.aggregate_trade_id,
+        let senders = StreamSenders {
+            orderflow: self.orderflow_sender.clone(),
+            depth: self.depth_sender.clone(),
+        };
+        for chunk in symbols.chunks(MAX_STREAMS_PER_CONNECTION) {
+            let index = self.connections.iter().filter(|connection| connection.kind == kind).count();
+            let subscribed = Arc::new(Mutex::new(chunk.to_vec()));
+            let (commands, command_receiver) = mpsc::unbounded_channel();
+            let task = StreamTask {
+                kind,
+                probe: self.monitor.register(&kind.connection_name(self.source.as_ref(), index)),
+                source: self.source.clone(),
+                senders: senders.clone(),
+                symbols: subscribed.clone(),
+                commands: command_receiver,
+                backoff: ReconnectBackoff::new(&self.settings.binance),
+                max_age: Duration::from_secs(self.settings.binance.connection_max_age_secs.max(1)),
+                rotation_overlap: Duration::from_millis(self.settings.binance.rotation_overlap_ms),
+                last_trade_ids: HashMap::new(),
+                message_count: 0,
             };
-
-            debug!("Parsed aggTrade for {}: price={}, qty={}, buyer_maker={}",
-                   event.symbol, event.price, event.quantity, event.is_buyer_maker);
-
-            if let Err(e) = orderflow_sender.try_send(event.clone()) {
-                error!("Failed to send orderflow event: {}", e);
-            } else {
-                debug!("Successfully sent orderflow event to channel");
-            }
-        } else {
-            debug!("Received non-aggTrade event: {}", trade_msg.event_type);
+            let handle = tokio::spawn(task.run());
+            self.connections.push(StreamConnection { kind, symbols: subscribed, commands, handle });
         }
-
-        Ok(())
     }
 
-    async fn start_liquidation_stream(&self) -> Result<tokio::task::JoinHandle<Result<()>>> {
+    async fn start_liquidation_stream(&self) -> Result<tokio::task::JoinHandle<()>> {
         let settings = self.settings.clone();
+        let source = self.source.clone();
         let liquidation_sender = self.liquidation_sender.clone();
+        let probe = self.monitor.register(&format!("{} liquidations", self.source.name()));
 
         let handle = tokio::spawn(async move {
-            let mut retry_count = 0;
-            let max_retries = settings.binance.max_reconnect_attempts;
+            let mut backoff = ReconnectBackoff::new(&settings.binance);
 
             loop {
-                match Self::connect_liquidation_stream(&settings, &liquidation_sender).await {
-                    Ok(_) => {
-                        info!("Liquidation stream connected successfully");
-                        retry_count = 0;
-                    }
-                    Err(e) => {
-                        retry_count += 1;
-                        error!("Liquidation stream connection failed (attempt {}/{}): {}", 
-                               retry_count, max_retries, e);
-
-                        if retry_count >= max_retries {
-                            return Err(anyhow!("Max retry attempts reached for liquidation stream"));
-                        }
+                let connected_at = Instant::now();
+                let result = Self::connect_liquidation_stream(source.as_ref(), &liquidation_sender, &probe).await;
+                probe.disconnected();
 
-                        let delay = Duration::from_millis(
-                            settings.binance.reconnect_delay_ms * retry_count as u64
-                        );
-                        warn!("Reconnecting liquidation stream in {:?}", delay);
-                        sleep(delay).await;
-                    }
+                let delay = backoff.after_connection(connected_at.elapsed());
+                if let Err(e) = result {
+                    error!("Liquidation stream connection failed (attempt {}): {}", backoff.attempt(), e);
                 }
+                warn!("Reconnecting liquidation stream in {:?}", delay);
+                sleep(delay).await;
             }
         });
 
@@ -358,275 +382,593 @@ impl WebSocketManager {
     }
 
     async fn connect_liquidation_stream(
-        settings: &Settings,
+        source: &dyn MarketDataSource,
         liquidation_sender: &Option<mpsc::Sender<LiquidationEvent>>,
+        probe: &ConnectionProbe,
     ) -> Result<()> {
-        let url = format!("{}/ws/!forceOrder@arr", settings.binance.websocket_base_url);
+        let url = source.liquidation_stream_url();
         let (ws_stream, _) = connect_async(&url).await?;
         let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+        probe.connected();
 
         info!("Connected to liquidation stream");
 
-        // Process incoming messages
-        while let Some(msg) = ws_receiver.next().await {
-            match msg {
-                Ok(Message::Text(text)) => {
-                    if let Some(sender) = liquidation_sender {
-                        if let Err(e) = Self::process_liquidation_message(&text, sender).await {
-                            debug!("Failed to process liquidation message: {}", e);
+        // Process incoming messages; pings keep the quiet stream verifiably alive
+        let mut ping = tokio::time::interval(probe.ping_interval());
+        loop {
+            tokio::select! {
+                msg = ws_receiver.next() => {
+                    let Some(msg) = msg else { break };
+                    match msg {
+                        Ok(Message::Text(text)) => {
+                            probe.message();
+                            if let Some(sender) = liquidation_sender {
+                                match source.parse_frame(&text) {
+                                    Ok(Some(MarketEvent::Liquidation(event))) => {
+                                        if let Err(e) = sender.try_send(event) {
+                                            probe.dropped();
+                                            debug!("Failed to send liquidation event: {}", e);
+                                        }
+                                    }
+                                    Ok(_) => {}
+                                    Err(e) => {
+                                        probe.parse_error();
+                                        debug!("Failed to process liquidation message: {}", e);
+                                    }
+                                }
+                            }
+                        }
+                        Ok(Message::Ping(ping)) => {
+                            probe.frame();
+                            ws_sender.send(Message::Pong(ping)).await?;
+                        }
+                        Ok(Message::Pong(pong)) => probe.pong(&pong),
+                        Ok(Message::Close(_)) => {
+                            warn!("Liquidation WebSocket connection closed by server");
+                            break;
+                        }
+                        Err(e) => {
+                            error!("Liquidation WebSocket error: {}", e);
+                            break;
                         }
+                        _ => {}
                     }
                 }
-                Ok(Message::Ping(ping)) => {
-                    ws_sender.send(Message::Pong(ping)).await?;
+                _ = ping.tick() => {
+                    ws_sender.send(Message::Ping(probe.ping_payload())).await?;
                 }
-                Ok(Message::Close(_)) => {
-                    warn!("Liquidation WebSocket connection closed by server");
-                    break;
-                }
-                Err(e) => {
-                    error!("Liquidation WebSocket error: {}", e);
-                    break;
+                _ = probe.reconnect_requested() => {
+                    return Err(anyhow!("{} went silent, reconnecting", probe.name()));
                 }
-                _ => {}
             }
         }
 
         Err(anyhow!("Liquidation stream connection lost"))
     }
 
-    async fn process_liquidation_message(
-        text: &str,
-        liquidation_sender: &mpsc::Sender<LiquidationEvent>,
-    ) -> Result<()> {
-        let liquidation_msg: BinanceLiquidationMessage = serde_json::from_str(text)?;
-
-        if liquidation_msg.event_type == "forceOrder" {
-            let order = liquidation_msg.order;
-            
-            let event = LiquidationEvent {
-                symbol: order.symbol,
-                timestamp: liquidation_msg.event_time,
-                side: order.side,
-                price: order.price.parse()?,
-                quantity: order.original_quantity.parse()?,
-                is_forced: true,
-                notional_value: order.price.parse::<f64>()? * order.original_quantity.parse::<f64>()?,
-            };
-
-            if let Err(e) = liquidation_sender.try_send(event) {
-                debug!("Failed to send liquidation event: {}", e);
-            }
-        }
+    fn start_mark_price_stream(
+        &self,
+        url: String,
+        mark_price_sender: mpsc::Sender<Vec<FundingRateData>>,
+    ) -> tokio::task::JoinHandle<()> {
+        let settings = self.settings.clone();
+        let source = self.source.clone();
+        let probe = self.monitor.register(&format!("{} mark prices", self.source.name()));
 
-        Ok(())
-    }
+        tokio::spawn(async move {
+            let mut backoff = ReconnectBackoff::new(&settings.binance);
 
-    async fn start_depth_streams(&self) -> Result<tokio::task::JoinHandle<Result<()>>> {
-        let settings = self.settings.clone();
-        let depth_sender = self.depth_sender.clone();
-        let symbols = self.active_symbols.clone();
+            loop {
+                let connected_at = Instant::now();
+                let result = Self::connect_mark_price_stream(source.as_ref(), &url, &mark_price_sender, &probe).await;
+                probe.disconnected();
 
-        let handle = tokio::spawn(async move {
-            // Split symbols into chunks to avoid WebSocket limits
-            const MAX_STREAMS_PER_CONNECTION: usize = 200;
-            let symbol_chunks: Vec<Vec<String>> = symbols
-                .chunks(MAX_STREAMS_PER_CONNECTION)
-                .map(|chunk| chunk.to_vec())
-                .collect();
-
-            info!("Splitting {} symbols into {} depth WebSocket connections", symbols.len(), symbol_chunks.len());
-
-            let mut connection_handles = Vec::new();
-
-            for (i, chunk) in symbol_chunks.into_iter().enumerate() {
-                let settings_clone = settings.clone();
-                let sender_clone = depth_sender.clone();
-
-                let handle: tokio::task::JoinHandle<Result<()>> = tokio::spawn(async move {
-                    let mut retry_count = 0;
-                    let max_retries = settings_clone.binance.max_reconnect_attempts;
-
-                    loop {
-                        match Self::connect_depth_streams(&settings_clone, &sender_clone, &chunk).await {
-                            Ok(_) => {
-                                info!("Depth stream connection {} connected successfully with {} symbols", i, chunk.len());
-                                retry_count = 0;
-                            }
-                            Err(e) => {
-                                retry_count += 1;
-                                error!("Depth stream connection {} failed (attempt {}/{}): {}",
-                                       i, retry_count, max_retries, e);
+                let delay = backoff.after_connection(connected_at.elapsed());
+                if let Err(e) = result {
+                    error!("Mark price stream connection failed (attempt {}): {}", backoff.attempt(), e);
+                }
+                warn!("Reconnecting mark price stream in {:?}", delay);
+                sleep(delay).await;
+            }
+        })
+    }
 
-                                if retry_count >= max_retries {
-                                    return Err(anyhow!("Max retry attempts reached for depth stream connection {}", i));
+    async fn connect_mark_price_stream(
+        source: &dyn MarketDataSource,
+        url: &str,
+        mark_price_sender: &mpsc::Sender<Vec<FundingRateData>>,
+        probe: &ConnectionProbe,
+    ) -> Result<()> {
+        let (ws_stream, _) = connect_async(url).await?;
+        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+        probe.connected();
+
+        info!("Connected to mark price stream");
+
+        let mut ping = tokio::time::interval(probe.ping_interval());
+        loop {
+            tokio::select! {
+                msg = ws_receiver.next() => {
+                    let Some(msg) = msg else { break };
+                    match msg {
+                        Ok(Message::Text(text)) => {
+                            probe.message();
+                            match source.parse_frame(&text) {
+                                Ok(Some(MarketEvent::MarkPrices(readings))) => {
+                                    if let Err(e) = mark_price_sender.try_send(readings) {
+                                        probe.dropped();
+                                        debug!("Failed to send mark prices: {}", e);
+                                    }
+                                }
+                                Ok(_) => {}
+                                Err(e) => {
+                                    probe.parse_error();
+                                    debug!("Failed to process mark price message: {}", e);
                                 }
-
-                                let delay = Duration::from_millis(
-                                    settings_clone.binance.reconnect_delay_ms * retry_count as u64
-                                );
-                                warn!("Reconnecting depth stream connection {} in {:?}", i, delay);
-                                sleep(delay).await;
                             }
                         }
+                        Ok(Message::Ping(ping)) => {
+                            probe.frame();
+                            ws_sender.send(Message::Pong(ping)).await?;
+                        }
+                        Ok(Message::Pong(pong)) => probe.pong(&pong),
+                        Ok(Message::Close(_)) => {
+                            warn!("Mark price WebSocket connection closed by server");
+                            break;
+                        }
+                        Err(e) => {
+                            error!("Mark price WebSocket error: {}", e);
+                            break;
+                        }
+                        _ => {}
                     }
-                });
-
-                connection_handles.push(handle);
-            }
-
-            // Wait for all connections
-            for (i, handle) in connection_handles.into_iter().enumerate() {
-                if let Err(e) = handle.await {
-                    error!("Depth stream connection {} failed: {:?}", i, e);
+                }
+                _ = ping.tick() => {
+                    ws_sender.send(Message::Ping(probe.ping_payload())).await?;
+                }
+                _ = probe.reconnect_requested() => {
+                    return Err(anyhow!("{} went silent, reconnecting", probe.name()));
                 }
             }
+        }
 
-            Ok(())
-        });
+        Err(anyhow!("Mark price stream connection lost"))
+    }
 
-        Ok(handle)
+}
+
+/// One trade or depth connection's task: keeps it up for good, reconnecting
+/// with backoff and rotating onto a fresh connection before Binance's 24h limit.
+struct StreamTask {
+    kind: StreamKind,
+    probe: ConnectionProbe,
+    source: Arc<dyn MarketDataSource>,
+    senders: StreamSenders,
+    symbols: Arc<Mutex<Vec<String>>>,
+    commands: mpsc::UnboundedReceiver<StreamCommand>,
+    backoff: ReconnectBackoff,
+    /// Age at which the connection is replaced
+    max_age: Duration,
+    /// How long the old connection keeps being read after its replacement is up
+    rotation_overlap: Duration,
+    /// Highest aggregate trade id forwarded per symbol
+    last_trade_ids: HashMap<String, u64>,
+    message_count: u64,
+}
+
+impl StreamTask {
+    async fn run(mut self) {
+        loop {
+            let connected_at = Instant::now();
+            let result = self.connect().await;
+            self.probe.disconnected();
+
+            let delay = self.backoff.after_connection(connected_at.elapsed());
+            if let Err(e) = result {
+                error!("{} failed (attempt {}): {}", self.probe.name(), self.backoff.attempt(), e);
+            }
+            warn!("Reconnecting {} in {:?}", self.probe.name(), delay);
+            sleep(delay).await;
+        }
     }
 
-    async fn connect_depth_streams(
-        settings: &Settings,
-        depth_sender: &Option<mpsc::Sender<DepthUpdate>>,
-        symbols: &[String],
-    ) -> Result<()> {
-        let stream_names: Vec<String> = symbols
+    /// Connect to the combined stream endpoint with the connection's current symbols
+    async fn open(&mut self) -> Result<(WsSink, WsSource)> {
+        // Changes queued while disconnected are already in `symbols`
+        while self.commands.try_recv().is_ok() {}
+        let stream_names: Vec<String> = self.symbols
+            .lock()
+            .unwrap()
             .iter()
-            .map(|s| format!("{}@depth@100ms", s.to_lowercase()))
+            .map(|s| self.kind.stream_name(self.source.as_ref(), s))
             .collect();
 
-        let url = format!("{}/ws", settings.binance.websocket_base_url);
+        let url = self.source.combined_stream_url(&stream_names);
         let (ws_stream, _) = connect_async(&url).await?;
-        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
-
-        // Subscribe to streams
-        let subscription = StreamSubscription {
-            method: "SUBSCRIBE".to_string(),
-            params: stream_names.clone(),
-            id: 2,
-        };
 
-        let subscribe_msg = Message::Text(serde_json::to_string(&subscription)?);
-        ws_sender.send(subscribe_msg).await?;
-
-        info!("Subscribed to {} depth streams", symbols.len());
-        debug!("First few depth stream names: {:?}", &stream_names[..stream_names.len().min(5)]);
+        info!("Subscribed to {} {} streams", stream_names.len(), self.kind.label().to_lowercase());
+        debug!("First few stream names: {:?}", &stream_names[..stream_names.len().min(5)]);
+        Ok(ws_stream.split())
+    }
 
-        // Process incoming messages
-        let mut message_count = 0;
-        while let Some(msg) = ws_receiver.next().await {
-            match msg {
-                Ok(Message::Text(text)) => {
-                    message_count += 1;
-                    if message_count % 1000 == 0 {
-                        info!("Depth WebSocket received {} messages", message_count);
+    async fn connect(&mut self) -> Result<()> {
+        let (mut ws_sender, mut ws_receiver) = self.open().await?;
+        self.probe.connected();
+
+        // Process incoming messages, subscription changes, liveness pings and rotations
+        let mut request_id = 1;
+        let mut ping = tokio::time::interval(self.probe.ping_interval());
+        let mut rotate_at = Instant::now() + self.max_age;
+        // The replaced connection, read until `retire_at` so no trade falls in between
+        let mut retiring: Option<(WsSink, WsSource)> = None;
+        let mut retire_at = Instant::now();
+        loop {
+            tokio::select! {
+                msg = ws_receiver.next() => {
+                    if !self.handle_message(msg, &mut ws_sender).await? {
+                        break;
                     }
-                    if text.contains("error") {
-                        error!("Depth WebSocket error message: {}", text);
+                }
+                msg = async { if let Some((_, receiver)) = &mut retiring { receiver.next().await } else { std::future::pending().await } } => {
+                    if let Some((sender, _)) = &mut retiring {
+                        if !self.handle_message(msg, sender).await? {
+                            retiring = None;
+                        }
+                    }
+                }
+                _ = sleep_until(retire_at), if retiring.is_some() => {
+                    if let Some((mut sender, _)) = retiring.take() {
+                        let _ = sender.close().await;
                     }
-                    if let Some(sender) = depth_sender {
-                        if let Err(e) = Self::process_depth_message(&text, sender).await {
-                            debug!("Failed to process depth message: {}", e);
+                    info!("{} rotated onto a new connection", self.probe.name());
+                }
+                _ = sleep_until(rotate_at) => {
+                    match self.open().await {
+                        Ok((sender, receiver)) => {
+                            info!("{} is {:?} old, replacing it", self.probe.name(), self.max_age);
+                            retiring = Some((
+                                std::mem::replace(&mut ws_sender, sender),
+                                std::mem::replace(&mut ws_receiver, receiver),
+                            ));
+                            retire_at = Instant::now() + self.rotation_overlap;
+                            rotate_at = Instant::now() + self.max_age;
+                        }
+                        Err(e) => {
+                            warn!("Cannot open a replacement for {}: {}", self.probe.name(), e);
+                            rotate_at = Instant::now() + Duration::from_secs(ROTATION_RETRY_SECS);
                         }
                     }
                 }
-                Ok(Message::Ping(ping)) => {
-                    ws_sender.send(Message::Pong(ping)).await?;
+                Some(command) = self.commands.recv() => {
+                    let (method, changed) = match command {
+                        StreamCommand::Subscribe(changed) => ("SUBSCRIBE", changed),
+                        StreamCommand::Unsubscribe(changed) => ("UNSUBSCRIBE", changed),
+                    };
+                    request_id += 1;
+                    let request = StreamSubscription {
+                        method: method.to_string(),
+                        params: changed.iter().map(|s| self.kind.stream_name(self.source.as_ref(), s)).collect(),
+                        id: request_id,
+                    };
+                    ws_sender.send(Message::Text(serde_json::to_string(&request)?)).await?;
+                    info!("{} {} {} streams: {:?}", method, changed.len(), self.kind.label().to_lowercase(), changed);
                 }
-                Ok(Message::Close(_)) => {
-                    warn!("Depth WebSocket connection closed by server");
-                    break;
+                _ = ping.tick() => {
+                    ws_sender.send(Message::Ping(self.probe.ping_payload())).await?;
                 }
-                Err(e) => {
-                    error!("Depth WebSocket error: {}", e);
-                    break;
+                _ = self.probe.reconnect_requested() => {
+                    return Err(anyhow!("{} went silent, reconnecting", self.probe.name()));
                 }
-                _ => {}
             }
         }
 
-        Err(anyhow!("Depth stream connection lost"))
+        Err(anyhow!("{} stream connection lost", self.kind.label()))
     }
 
-    async fn process_depth_message(
-        text: &str,
-        depth_sender: &mpsc::Sender<DepthUpdate>,
-    ) -> Result<()> {
-        // Handle subscription confirmation messages
-        if text.contains("\"result\":null") || text.contains("\"id\":") {
-            debug!("Depth WebSocket subscription confirmed");
-            return Ok(());
+    /// Forward one received frame; returns whether the connection is still open
+    async fn handle_message(
+        &mut self,
+        msg: Option<Result<Message, tungstenite::Error>>,
+        ws_sender: &mut WsSink,
+    ) -> Result<bool> {
+        let kind = self.kind;
+        match msg {
+            Some(Ok(Message::Text(text))) => {
+                self.probe.message();
+                self.message_count += 1;
+                if self.message_count.is_multiple_of(kind.log_interval()) {
+                    info!("{} WebSocket received {} messages. Latest size: {} chars",
+                          kind.label(), self.message_count, text.len());
+                }
+                if text.contains("error") {
+                    error!("{} WebSocket error message: {}", kind.label(), text);
+                }
+                match self.source.parse_frame(&text) {
+                    Ok(Some(MarketEvent::Trade(event))) => {
+                        if self.is_new_trade(&event) {
+                            if let Err(e) = self.senders.orderflow.try_send(event) {
+                                self.probe.dropped();
+                                error!("Failed to send orderflow event: {}", e);
+                            }
+                        }
+                    }
+                    Ok(Some(MarketEvent::Depth(update))) => {
+                        // Depth repeated across a rotation is dropped as stale by the order book
+                        if let Some(sender) = &self.senders.depth {
+                            if let Err(e) = sender.try_send(update) {
+                                self.probe.dropped();
+                                debug!("Failed to send depth update: {}", e);
+                            }
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        self.probe.parse_error();
+                        debug!("Failed to process {} message: {}. Message snippet: {}",
+                               kind.label().to_lowercase(), e, &text[..text.len().min(100)]);
+                    }
+                }
+            }
+            Some(Ok(Message::Ping(ping))) => {
+                self.probe.frame();
+                ws_sender.send(Message::Pong(ping)).await?;
+            }
+            Some(Ok(Message::Pong(pong))) => self.probe.pong(&pong),
+            Some(Ok(Message::Close(_))) => {
+                warn!("{} WebSocket connection closed by server", kind.label());
+                return Ok(false);
+            }
+            Some(Err(e)) => {
+                error!("{} WebSocket error: {}", kind.label(), e);
+                return Ok(false);
+            }
+            None => return Ok(false),
+            _ => {}
         }
+        Ok(true)
+    }
 
-        let depth_update: DepthUpdate = serde_json::from_str(text)
-            .map_err(|e| {
-                debug!("Failed to parse depth message: {}. Message snippet: {}", e, &text[..text.len().min(100)]);
-                e
-            })?;
-
-        if depth_update.event_type == "depthUpdate" {
-            debug!("Parsed depthUpdate for {}: {} bids, {} asks",
-                   depth_update.symbol, depth_update.bids.len(), depth_update.asks.len());
-
-            if let Err(e) = depth_sender.try_send(depth_update) {
-                debug!("Failed to send depth update: {}", e);
+    /// Whether `event` is past the last aggregate trade forwarded for its symbol.
+    /// Both connections deliver the same trades during a rotation's overlap.
+    fn is_new_trade(&mut self, event: &OrderflowEvent) -> bool {
+        match self.last_trade_ids.get_mut(&event.symbol) {
+            Some(last) if event.trade_id <= *last => false,
+            Some(last) => {
+                *last = event.trade_id;
+                true
+            }
+            None => {
+                self.last_trade_ids.insert(event.symbol.clone(), event.trade_id);
+                true
             }
         }
-
-        Ok(())
     }
 }
 
-// Connection health monitoring
-pub struct ConnectionMonitor {
-    trade_stream_active: bool,
-    liquidation_stream_active: bool,
-    last_trade_time: Option<u64>,
-    last_liquidation_time: Option<u64>,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{BinanceUsdmSource, ConnectionSupervisor};
 
-impl ConnectionMonitor {
-    pub fn new() -> Self {
-        Self {
-            trade_stream_active: false,
-            liquidation_stream_active: false,
-            last_trade_time: None,
-            last_liquidation_time: None,
+    fn names(range: std::ops::Range<usize>) -> Vec<String> {
+        range.map(|i| format!("SYM{}USDT", i)).collect()
+    }
+
+    fn connection_sizes(manager: &WebSocketManager) -> Vec<usize> {
+        manager.connections.iter().map(|connection| connection.symbols.lock().unwrap().len()).collect()
+    }
+
+    fn subscribed(manager: &WebSocketManager) -> Vec<String> {
+        let mut symbols: Vec<String> = manager.connections
+            .iter()
+            .flat_map(|connection| connection.symbols.lock().unwrap().clone())
+            .collect();
+        symbols.sort();
+        symbols
+    }
+
+    #[tokio::test]
+    async fn test_universe_changes_rebalance_connections() {
+        // Nothing listens here, so the connection tasks just keep retrying
+        let source = Arc::new(BinanceUsdmSource::new("ws://127.0.0.1:1".to_string(), "http://127.0.0.1:1".to_string()));
+        let (orderflow_sender, _) = mpsc::channel(10);
+        let mut manager = WebSocketManager::new(Settings::default(), source, orderflow_sender);
+
+        let initial = names(0..450);
+        manager.active_symbols = initial.clone();
+        manager.open_connections(StreamKind::Trade, &initial);
+        assert_eq!(connection_sizes(&manager), vec![200, 200, 50]);
+
+        // Ten delistings free slots on the first connection, which the first new listings take
+        let mut universe = initial[10..].to_vec();
+        universe.extend(names(1000..1030));
+        manager.apply_universe(universe.clone());
+        assert_eq!(connection_sizes(&manager), vec![200, 200, 70]);
+        universe.sort();
+        assert_eq!(subscribed(&manager), universe);
+
+        // Listings beyond the free slots open another connection
+        universe.extend(names(2000..2200));
+        manager.apply_universe(universe.clone());
+        assert_eq!(connection_sizes(&manager), vec![200, 200, 200, 70]);
+        assert!(manager.connections.iter().all(|connection| connection.kind == StreamKind::Trade));
+
+        for connection in &manager.connections {
+            connection.handle.abort();
         }
     }
 
-    pub fn update_trade_activity(&mut self, timestamp: u64) {
-        self.trade_stream_active = true;
-        self.last_trade_time = Some(timestamp);
+    /// Streams requested in a combined-stream handshake, read without consuming it
+    async fn requested_streams(stream: &tokio::net::TcpStream) -> Vec<String> {
+        let mut buf = [0u8; 4096];
+        let n = stream.peek(&mut buf).await.unwrap_or(0);
+        let request = String::from_utf8_lossy(&buf[..n]);
+        let path = request.split_whitespace().nth(1).unwrap_or_default();
+        path.split_once("?streams=").map_or_else(Vec::new, |(_, streams)| streams.split('/').map(str::to_string).collect())
     }
 
-    pub fn update_liquidation_activity(&mut self, timestamp: u64) {
-        self.liquidation_stream_active = true;
-        self.last_liquidation_time = Some(timestamp);
+    /// Serves `script.len()` connections: each reports the streams requested in
+    /// its URL, then stalls without reading, drops, or serves normally
+    async fn scripted_server(script: Vec<&'static str>) -> (String, mpsc::UnboundedReceiver<Vec<String>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (subscriptions, subscription_receiver) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            for behaviour in script {
+                let (stream, _) = listener.accept().await.unwrap();
+                let _ = subscriptions.send(requested_streams(&stream).await);
+                let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                match behaviour {
+                    "stall" => {
+                        ws.send(Message::Text(r#"{"result":null,"id":1}"#.to_string())).await.unwrap();
+                        // Hold the socket without reading, so pings go unanswered
+                        tokio::spawn(async move {
+                            sleep(Duration::from_secs(30)).await;
+                            drop(ws);
+                        });
+                    }
+                    "drop" => drop(ws),
+                    _ => {
+                        // Reading answers pings
+                        tokio::spawn(async move { while let Some(Ok(_)) = ws.next().await {} });
+                    }
+                }
+            }
+        });
+
+        (url, subscription_receiver)
     }
 
-    pub fn check_health(&self, max_silence_seconds: u64) -> bool {
-        let current_time = chrono::Utc::now().timestamp() as u64;
-        
-        if let Some(last_trade) = self.last_trade_time {
-            if current_time - last_trade > max_silence_seconds {
-                return false;
+    #[tokio::test]
+    async fn test_stalled_and_dropped_connections_reconnect_and_resubscribe() {
+        let (url, mut subscriptions) = scripted_server(vec!["stall", "drop", "serve"]).await;
+        let source = Arc::new(BinanceUsdmSource::new(url, "http://127.0.0.1:1".to_string()));
+        let (orderflow_sender, _orderflow_receiver) = mpsc::channel(10);
+        let mut settings = Settings::default();
+        settings.binance.reconnect_delay_ms = 10;
+
+        let monitor = ConnectionMonitor::new(HealthConfig {
+            ping_interval: Duration::from_millis(50),
+            stale_timeout: Duration::from_millis(300),
+        });
+        let supervisor = ConnectionSupervisor::new(monitor.clone());
+        let supervisor_handle = tokio::spawn(supervisor.run());
+
+        let mut manager = WebSocketManager::new(settings, source, orderflow_sender);
+        manager.set_connection_monitor(monitor.clone());
+        manager.open_connections(StreamKind::Trade, &names(0..3));
+
+        // The stalled connection is replaced, the dropped one too, and each new one resubscribes
+        let expected: Vec<String> = names(0..3).iter().map(|symbol| format!("{}@aggTrade", symbol.to_lowercase())).collect();
+        for _ in 0..3 {
+            let params = tokio::time::timeout(Duration::from_secs(5), subscriptions.recv()).await.unwrap().unwrap();
+            assert_eq!(params, expected);
+        }
+
+        let health = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let report = monitor.report();
+                if report[0].connected && report[0].ping_latency_ms.is_some() && report[0].reconnects >= 2 {
+                    return report[0].clone();
+                }
+                sleep(Duration::from_millis(20)).await;
             }
+        }).await.unwrap();
+        assert_eq!(health.name, "binance-usdm trade #0");
+        // A stall may be seen by more than one check before the task drops the socket
+        assert!(health.forced_reconnects >= 1);
+        assert_eq!(health.total_messages, 1);
+
+        supervisor_handle.abort();
+        for connection in &manager.connections {
+            connection.handle.abort();
+        }
+    }
+
+    #[test]
+    fn test_backoff_grows_with_jitter_and_resets_after_stable_connection() {
+        let mut config = Settings::default().binance;
+        config.reconnect_delay_ms = 100;
+        config.max_reconnect_delay_ms = 1_000;
+        let mut backoff = ReconnectBackoff::new(&config);
+
+        let quick = Duration::from_millis(10);
+        for ceiling in [100, 200, 400, 800, 1_000, 1_000, 1_000] {
+            let delay = backoff.after_connection(quick);
+            assert!(delay >= Duration::from_millis(ceiling / 2) && delay <= Duration::from_millis(ceiling), "{:?}", delay);
         }
+        assert_eq!(backoff.attempt(), 7);
 
-        true
+        // A connection that stayed up starts over from the base delay
+        assert!(backoff.after_connection(Duration::from_secs(STABLE_CONNECTION_SECS)) <= Duration::from_millis(100));
+        assert_eq!(backoff.attempt(), 1);
     }
 
-    pub fn get_status(&self) -> String {
-        format!(
-            "Trade Stream: {}, Liquidation Stream: {}, Last Trade: {:?}, Last Liquidation: {:?}",
-            if self.trade_stream_active { "Active" } else { "Inactive" },
-            if self.liquidation_stream_active { "Active" } else { "Inactive" },
-            self.last_trade_time,
-            self.last_liquidation_time
-        )
+    /// Streams one aggTrade per tick to every open connection, so connections
+    /// open at the same time deliver the same trades
+    async fn trade_server() -> (String, mpsc::UnboundedReceiver<()>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (connections, connection_receiver) = mpsc::unbounded_channel();
+        let (trades, _) = tokio::sync::broadcast::channel::<u64>(100);
+
+        let ticker = trades.clone();
+        tokio::spawn(async move {
+            for id in 1.. {
+                let _ = ticker.send(id);
+                sleep(Duration::from_millis(10)).await;
+            }
+        });
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                let _ = connections.send(());
+                let mut trades = trades.subscribe();
+                tokio::spawn(async move {
+                    while let Ok(id) = trades.recv().await {
+                        let frame = format!(
+                            r#"{{"stream":"btcusdt@aggTrade","data":{{"e":"aggTrade","E":1,"s":"BTCUSDT","a":{},"p":"100.0","q":"1","f":1,"l":1,"T":1,"m":true}}}}"#, id
+                        );
+                        if ws.send(Message::Text(frame)).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        (url, connection_receiver)
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_rotation_overlaps_connections_without_duplicate_trades() {
+        let (url, mut connections) = trade_server().await;
+        let source = Arc::new(BinanceUsdmSource::new(url, "http://127.0.0.1:1".to_string()));
+        let (orderflow_sender, mut orderflow_receiver) = mpsc::channel(1000);
+        let mut settings = Settings::default();
+        settings.binance.connection_max_age_secs = 1;
+        settings.binance.rotation_overlap_ms = 200;
+
+        let mut manager = WebSocketManager::new(settings, source, orderflow_sender);
+        manager.open_connections(StreamKind::Trade, &names(0..1));
+
+        // The first connection plus one replacement
+        for _ in 0..2 {
+            tokio::time::timeout(Duration::from_secs(5), connections.recv()).await.unwrap().unwrap();
+        }
+        sleep(Duration::from_millis(400)).await;
+        for connection in &manager.connections {
+            connection.handle.abort();
+        }
+
+        // Every trade arrives once, in order and without a hole across the handover
+        let mut ids = Vec::new();
+        while let Ok(event) = orderflow_receiver.try_recv() {
+            ids.push(event.trade_id);
+        }
+        assert!(ids.len() > 50, "{} trades", ids.len());
+        assert!(ids.windows(2).all(|pair| pair[1] == pair[0] + 1), "{:?}", ids);
+    }
+}
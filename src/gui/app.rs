@@ -4,9 +4,10 @@ use std::sync::Arc;
 use std::collections::HashMap;
 use anyhow::Result;
 
-use crate::data::{OrderImbalance, LiquidationEvent, VolumeProfile, GuiUpdate, DatabaseManager, BigOrderflowAlert, OrderflowEvent, BinanceSymbols, DepthSnapshot};
+use crate::data::{OrderImbalance, LiquidationEvent, VolumeProfile, GuiUpdate, DatabaseManager, BigOrderflowAlert, OrderflowEvent, BinanceSymbols, DepthSnapshot, ScreenerAlert, SymbolRegistry, BookAnalytics, BookFlowBucket, DepthAggregation, HeatmapStore, FootprintHistory, ConnectionHealth, health_summary, EventBus, OverflowPolicy, Subscription, SubscriberStats, bus_summary, Metrics, LatencyHistogram};
 use crate::analysis::volume_analysis::VolumeAnalyzer;
-use super::{ScreenerTheme, ScreenerPanel, ImbalancePanel, FootprintPanel, LiquidationPanel, DOMPanel};
+use crate::config::settings::WideHeatmapConfig;
+use super::{ScreenerTheme, ScreenerPanel, ImbalancePanel, FootprintPanel, LiquidationPanel, DOMPanel, DiagnosticsPanel};
 
 #[derive(Debug, PartialEq)]
 enum ActivePanel {
@@ -15,6 +16,7 @@ enum ActivePanel {
     Footprint,
     Liquidation,
     DOM,
+    Diagnostics,
 }
 
 pub struct ScreenerApp {
@@ -24,17 +26,22 @@ pub struct ScreenerApp {
     footprint_panel: FootprintPanel,
     liquidation_panel: LiquidationPanel,
     dom_panel: DOMPanel,
+    diagnostics_panel: DiagnosticsPanel,
 
     // State
     active_panel: ActivePanel,
     
     // Data receivers
     imbalance_receiver: Option<mpsc::Receiver<OrderImbalance>>,
-    liquidation_receiver: Option<mpsc::Receiver<LiquidationEvent>>,
+    liquidation_receiver: Option<Subscription<LiquidationEvent>>,
     volume_receiver: Option<mpsc::Receiver<VolumeProfile>>,
     gui_update_receiver: Option<mpsc::Receiver<GuiUpdate>>,
-    orderflow_receiver: Option<mpsc::Receiver<OrderflowEvent>>,
-    depth_snapshot_receiver: Option<mpsc::Receiver<(String, DepthSnapshot)>>,
+    orderflow_receiver: Option<Subscription<OrderflowEvent>>,
+    depth_snapshot_receiver: Option<Subscription<(String, DepthSnapshot)>>,
+    book_analytics_receiver: Option<Subscription<BookAnalytics>>,
+    book_flow_receiver: Option<Subscription<BookFlowBucket>>,
+    wide_depth_receiver: Option<Subscription<(String, DepthSnapshot)>>,
+    history_receiver: Option<mpsc::Receiver<FootprintHistory>>,
     
     // Database
     database: Arc<DatabaseManager>,
@@ -44,16 +51,20 @@ pub struct ScreenerApp {
     last_update_time: std::time::Instant,
     frame_count: u64,
     fps: f32,
+    frame_time: LatencyHistogram,
 
     // Subscribed symbols
     subscribed_symbols: Vec<String>,
 
     // Volume analyzer for generating alerts
     volume_analyzer: Option<VolumeAnalyzer>,
-    big_orderflow_receiver: Option<mpsc::Receiver<BigOrderflowAlert>>,
+    big_orderflow_receiver: Option<Subscription<BigOrderflowAlert>>,
 
     // Demo data generation
     last_demo_generation: std::time::Instant,
+
+    // Soonest funding settlement across symbols (ms)
+    next_funding_time: Option<u64>,
 }
 
 #[derive(Debug, Clone)]
@@ -63,6 +74,10 @@ struct ConnectionStatus {
     last_trade_time: Option<u64>,
     total_symbols: usize,
     active_symbols: usize,
+    /// Latest report of the connection supervisor
+    connections: Vec<ConnectionHealth>,
+    /// Latest event bus subscriber stats
+    event_bus: Vec<SubscriberStats>,
 }
 
 impl Default for ConnectionStatus {
@@ -73,6 +88,8 @@ impl Default for ConnectionStatus {
             last_trade_time: None,
             total_symbols: 0,
             active_symbols: 0,
+            connections: Vec::new(),
+            event_bus: Vec::new(),
         }
     }
 }
@@ -80,22 +97,18 @@ impl Default for ConnectionStatus {
 impl ScreenerApp {
     pub async fn new(
         imbalance_receiver: mpsc::Receiver<OrderImbalance>,
-        liquidation_receiver: mpsc::Receiver<LiquidationEvent>,
         volume_receiver: mpsc::Receiver<VolumeProfile>,
         gui_update_receiver: mpsc::Receiver<GuiUpdate>,
-        orderflow_receiver: mpsc::Receiver<OrderflowEvent>,
-        depth_snapshot_receiver: mpsc::Receiver<(String, DepthSnapshot)>,
+        event_bus: &EventBus,
         database: Arc<DatabaseManager>,
         subscribed_symbols: Vec<String>,
     ) -> Result<Self> {
         // Create channels for volume analyzer outputs
         let (volume_sender, volume_receiver_new) = mpsc::channel(1000);
-        let (alert_sender, big_orderflow_receiver) = mpsc::channel(1000);
 
         // Create volume analyzer with Binance API URL
         let api_base_url = "https://fapi.binance.com".to_string();
-        let mut volume_analyzer = VolumeAnalyzer::new(volume_sender, api_base_url);
-        volume_analyzer.set_alert_sender(alert_sender);
+        let volume_analyzer = VolumeAnalyzer::new(volume_sender, api_base_url);
 
         let symbols = if subscribed_symbols.is_empty() {
             BinanceSymbols::get_default_symbols()
@@ -109,26 +122,78 @@ impl ScreenerApp {
             footprint_panel: FootprintPanel::new_with_symbols(symbols.clone()),
             liquidation_panel: LiquidationPanel::new(),
             dom_panel: DOMPanel::new(symbols.first().unwrap_or(&"BTCUSDT".to_string()).clone()),
+            diagnostics_panel: DiagnosticsPanel::new(),
             active_panel: ActivePanel::Screener,
             imbalance_receiver: Some(imbalance_receiver),
-            liquidation_receiver: Some(liquidation_receiver),
+            liquidation_receiver: Some(event_bus.liquidations.subscribe("gui", OverflowPolicy::Drop)),
             volume_receiver: Some(volume_receiver_new), // Use new receiver from volume analyzer
             gui_update_receiver: Some(gui_update_receiver),
-            orderflow_receiver: Some(orderflow_receiver),
-            depth_snapshot_receiver: Some(depth_snapshot_receiver),
+            orderflow_receiver: Some(event_bus.orderflow.subscribe("gui", OverflowPolicy::Drop)),
+            depth_snapshot_receiver: Some(event_bus.depth.subscribe("gui", OverflowPolicy::Drop)),
+            book_analytics_receiver: Some(event_bus.book_analytics.subscribe("gui", OverflowPolicy::Drop)),
+            book_flow_receiver: Some(event_bus.book_flow.subscribe("gui", OverflowPolicy::Drop)),
+            wide_depth_receiver: None,
+            history_receiver: None,
             database,
             connection_status: ConnectionStatus::default(),
             last_update_time: std::time::Instant::now(),
             frame_count: 0,
             fps: 0.0,
+            frame_time: LatencyHistogram::default(),
             subscribed_symbols: symbols,
             volume_analyzer: Some(volume_analyzer),
-            big_orderflow_receiver: Some(big_orderflow_receiver),
+            big_orderflow_receiver: Some(event_bus.alerts.subscribe("gui", OverflowPolicy::Drop)),
             last_demo_generation: std::time::Instant::now(),
+            next_funding_time: None,
         })
     }
 
+    /// Receive per-symbol candle and trade history to seed the footprint chart with
+    pub fn set_history_receiver(&mut self, receiver: mpsc::Receiver<FootprintHistory>) {
+        self.history_receiver = Some(receiver);
+    }
+
+    /// Share exchangeInfo tick sizes and listings with the panels that bin prices
+    pub fn set_symbol_registry(&mut self, registry: SymbolRegistry) {
+        self.footprint_panel.set_symbol_registry(registry.clone());
+        self.dom_panel.set_symbol_registry(registry.clone());
+        self.imbalance_panel.set_symbol_registry(registry);
+    }
+
+    /// Shared with the order book managers so footprint scale changes re-bucket their depth history
+    pub fn set_depth_aggregation(&mut self, aggregation: DepthAggregation) {
+        self.footprint_panel.set_depth_aggregation(aggregation);
+    }
+
+    /// Downsampled depth history behind the footprint heatmap
+    pub fn set_heatmap_store(&mut self, store: HeatmapStore) {
+        self.footprint_panel.set_heatmap_store(store);
+    }
+
+    /// Paint the footprint heatmap of the focus symbols from their full-depth snapshots
+    pub fn set_wide_heatmap(&mut self, receiver: Subscription<(String, DepthSnapshot)>, config: &WideHeatmapConfig) {
+        self.footprint_panel.set_wide_heatmap(config.history_secs);
+        self.wide_depth_receiver = Some(receiver);
+    }
+
+    /// Show the pipeline metrics in the diagnostics tab and report frame times into them
+    pub fn set_metrics(&mut self, metrics: Metrics) {
+        self.frame_time = metrics.gui_frame_time();
+        self.diagnostics_panel.set_metrics(metrics);
+    }
+
     fn process_incoming_data(&mut self) {
+        // Seed footprint candles with history loaded at startup
+        if let Some(receiver) = &mut self.history_receiver {
+            while let Ok(history) = receiver.try_recv() {
+                tracing::debug!(
+                    "GUI received {} stored candles and {} historical trades for {}",
+                    history.candles.len(), history.trades.len(), history.symbol
+                );
+                self.footprint_panel.load_history(&history);
+            }
+        }
+
         // Process imbalance updates
         if let Some(receiver) = &mut self.imbalance_receiver {
             let mut count = 0;
@@ -146,7 +211,7 @@ impl ScreenerApp {
             let mut count = 0;
             while let Ok(liquidation) = receiver.try_recv() {
                 count += 1;
-                self.liquidation_panel.add_liquidation(liquidation);
+                self.liquidation_panel.add_liquidation(Arc::unwrap_or_clone(liquidation));
             }
             if count > 0 {
                 tracing::debug!("GUI received {} liquidation events", count);
@@ -170,7 +235,7 @@ impl ScreenerApp {
             let mut count = 0;
             while let Ok(alert) = receiver.try_recv() {
                 count += 1;
-                self.screener_panel.add_orderflow_alert(alert);
+                self.screener_panel.add_orderflow_alert(Arc::unwrap_or_clone(alert));
             }
             if count > 0 {
                 tracing::debug!("GUI received {} big orderflow alerts", count);
@@ -183,6 +248,7 @@ impl ScreenerApp {
             while let Ok(orderflow_event) = receiver.try_recv() {
                 count += 1;
                 self.footprint_panel.add_orderflow_event(&orderflow_event);
+                self.screener_panel.update_last_price(&orderflow_event.symbol, orderflow_event.price);
                 self.dom_panel.process_trade(&orderflow_event);
             }
             if count > 0 {
@@ -193,7 +259,8 @@ impl ScreenerApp {
         // Process depth snapshots for LOB heatmap and DOM
         if let Some(receiver) = &mut self.depth_snapshot_receiver {
             let mut count = 0;
-            while let Ok((symbol, snapshot)) = receiver.try_recv() {
+            while let Ok(published) = receiver.try_recv() {
+                let (symbol, snapshot) = Arc::unwrap_or_clone(published);
                 count += 1;
                 self.footprint_panel.add_depth_snapshot(symbol.clone(), snapshot.clone());
                 self.dom_panel.update_depth(snapshot);
@@ -203,6 +270,27 @@ impl ScreenerApp {
             }
         }
 
+        if let Some(receiver) = &mut self.wide_depth_receiver {
+            while let Ok(published) = receiver.try_recv() {
+                let (symbol, snapshot) = Arc::unwrap_or_clone(published);
+                self.footprint_panel.add_wide_depth_snapshot(symbol, snapshot);
+            }
+        }
+
+        // Process order book analytics
+        if let Some(receiver) = &mut self.book_analytics_receiver {
+            while let Ok(analytics) = receiver.try_recv() {
+                self.imbalance_panel.add_book_analytics(Arc::unwrap_or_clone(analytics));
+            }
+        }
+
+        // Process pulled / stacked liquidity for the DOM
+        if let Some(receiver) = &mut self.book_flow_receiver {
+            while let Ok(bucket) = receiver.try_recv() {
+                self.dom_panel.add_book_flow(Arc::unwrap_or_clone(bucket));
+            }
+        }
+
         // Process GUI updates
         if let Some(receiver) = &mut self.gui_update_receiver {
             while let Ok(update) = receiver.try_recv() {
@@ -222,6 +310,41 @@ impl ScreenerApp {
                     GuiUpdate::DailyStats(_stats) => {
                         // Update connection status or other stats
                     }
+                    GuiUpdate::OpenInterest(reading) => {
+                        self.footprint_panel.add_open_interest(reading);
+                    }
+                    GuiUpdate::FundingRates(readings) => {
+                        self.next_funding_time = readings
+                            .iter()
+                            .filter(|reading| reading.funding_interval > 0)
+                            .map(|reading| reading.next_funding_time())
+                            .min();
+                        self.screener_panel.update_funding(&readings);
+                    }
+                    GuiUpdate::SymbolUniverse(change) => {
+                        self.footprint_panel.apply_universe_change(change);
+                    }
+                    GuiUpdate::ConnectionHealth(report) => {
+                        self.connection_status.websocket_connected =
+                            !report.is_empty() && report.iter().all(|health| health.connected);
+                        self.connection_status.connections = report;
+                    }
+                    GuiUpdate::EventBus(stats) => {
+                        self.connection_status.event_bus = stats;
+                    }
+                    GuiUpdate::Alert(alert) => match alert {
+                        ScreenerAlert::OpenInterestSpike { symbol, oi_change_percentage, timeframe, .. } => {
+                            self.footprint_panel.set_open_interest_spike(symbol, oi_change_percentage, timeframe);
+                        }
+                        ScreenerAlert::FundingExtreme { symbol, .. } => {
+                            self.screener_panel.mark_funding_extreme(symbol);
+                        }
+                        ScreenerAlert::BookPattern(alert) => {
+                            self.dom_panel.add_book_pattern(alert.clone());
+                            self.footprint_panel.add_book_pattern(alert);
+                        }
+                        _ => {}
+                    },
                 }
             }
         }
@@ -353,7 +476,32 @@ impl ScreenerApp {
             };
 
             ui.colored_label(ws_color, "●");
-            ui.label("WebSocket");
+            let connections = &self.connection_status.connections;
+            let ws_label = if connections.is_empty() {
+                ui.label("WebSocket")
+            } else {
+                let connected = connections.iter().filter(|health| health.connected).count();
+                ui.label(format!("WebSocket {}/{}", connected, connections.len()))
+            };
+            if !connections.is_empty() {
+                ws_label.on_hover_ui(|ui| {
+                    ui.label(health_summary(connections));
+                    ui.separator();
+                    let now = chrono::Utc::now().timestamp_millis() as u64;
+                    for health in connections {
+                        let color = if health.connected { ScreenerTheme::BUY_COLOR } else { ScreenerTheme::SELL_COLOR };
+                        ui.colored_label(color, format!(
+                            "{}: {:.0} msg/s, last msg {}, ping {}, {} reconnects ({} forced)",
+                            health.name,
+                            health.messages_per_second,
+                            health.last_message_time.map_or("N/A".to_string(), |time| format!("{}ms ago", now.saturating_sub(time))),
+                            health.ping_latency_ms.map_or("N/A".to_string(), |latency| format!("{}ms", latency)),
+                            health.reconnects,
+                            health.forced_reconnects
+                        ));
+                    }
+                });
+            }
             
             ui.separator();
             
@@ -361,6 +509,24 @@ impl ScreenerApp {
             ui.label("Database");
             
             ui.separator();
+
+            // Events lost to subscribers that fell behind
+            let event_bus = &self.connection_status.event_bus;
+            let dropped: u64 = event_bus.iter().map(|stats| stats.dropped).sum();
+            if dropped > 0 {
+                ui.colored_label(ScreenerTheme::SELL_COLOR, format!("Dropped events: {}", dropped))
+                    .on_hover_ui(|ui| {
+                        ui.label(bus_summary(event_bus));
+                        ui.separator();
+                        for stats in event_bus {
+                            ui.label(format!(
+                                "{} {} ({:?}): {} dropped, lag {} (max {})",
+                                stats.topic, stats.name, stats.policy, stats.dropped, stats.lag, stats.max_lag
+                            ));
+                        }
+                    });
+                ui.separator();
+            }
             
             // Symbol count
             ui.label(format!(
@@ -378,6 +544,22 @@ impl ScreenerApp {
             } else {
                 ui.label("Last trade: N/A");
             }
+
+            ui.separator();
+
+            // Countdown to the next funding settlement
+            match self.next_funding_time {
+                Some(next_funding_time) => {
+                    let remaining = next_funding_time.saturating_sub(chrono::Utc::now().timestamp_millis() as u64) / 1000;
+                    ui.label(format!(
+                        "Funding in {:02}:{:02}:{:02}",
+                        remaining / 3600, remaining / 60 % 60, remaining % 60
+                    ));
+                }
+                None => {
+                    ui.label("Funding: N/A");
+                }
+            }
             
             // Right-aligned content
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -400,6 +582,7 @@ impl ScreenerApp {
             ui.selectable_value(&mut self.active_panel, ActivePanel::Footprint, "📈 Footprint");
             ui.selectable_value(&mut self.active_panel, ActivePanel::Liquidation, "💥 Liquidations");
             ui.selectable_value(&mut self.active_panel, ActivePanel::DOM, "📚 DOM");
+            ui.selectable_value(&mut self.active_panel, ActivePanel::Diagnostics, "🩺 Diagnostics");
         });
     }
 
@@ -420,12 +603,17 @@ impl ScreenerApp {
             ActivePanel::DOM => {
                 self.dom_panel.show(ui);
             }
+            ActivePanel::Diagnostics => {
+                self.diagnostics_panel.show(ui);
+            }
         }
     }
 }
 
 impl eframe::App for ScreenerApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let frame_started = std::time::Instant::now();
+
         // Apply dark theme
         ScreenerTheme::apply_dark_theme(ctx);
         
@@ -501,6 +689,8 @@ impl eframe::App for ScreenerApp {
                     ui.label(format!("  Volume profiles: {}", self.footprint_panel.get_profile_count()));
                 });
         }
+
+        self.frame_time.observe(frame_started.elapsed());
     }
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
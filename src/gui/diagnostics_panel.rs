@@ -0,0 +1,165 @@
+use egui::{Grid, RichText, ScrollArea, Ui};
+use std::time::{Duration, Instant};
+use crate::data::{LatencyStats, Metrics, MetricsSnapshot};
+use super::ScreenerTheme;
+
+/// Spacing between reads of the metrics registry
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Pipeline health at a glance: the same numbers the `/metrics` endpoint exports
+pub struct DiagnosticsPanel {
+    metrics: Option<Metrics>,
+    snapshot: MetricsSnapshot,
+    last_snapshot: Option<Instant>,
+}
+
+impl DiagnosticsPanel {
+    pub fn new() -> Self {
+        Self {
+            metrics: None,
+            snapshot: MetricsSnapshot::default(),
+            last_snapshot: None,
+        }
+    }
+
+    pub fn set_metrics(&mut self, metrics: Metrics) {
+        self.metrics = Some(metrics);
+        self.last_snapshot = None;
+    }
+
+    pub fn show(&mut self, ui: &mut Ui) {
+        let Some(metrics) = &self.metrics else {
+            ui.label("No metrics registry attached");
+            return;
+        };
+        if self.last_snapshot.is_none_or(|last| last.elapsed() >= SNAPSHOT_INTERVAL) {
+            self.snapshot = metrics.snapshot();
+            self.last_snapshot = Some(Instant::now());
+        }
+        let snapshot = &self.snapshot;
+
+        ScrollArea::vertical().show(ui, |ui| {
+            ui.heading("Streams");
+            Grid::new("diagnostics_streams").striped(true).show(ui, |ui| {
+                for header in ["Connection", "Up", "Msg/s", "Messages", "Parse errors", "Dropped", "Reconnects", "Ping"] {
+                    ui.label(RichText::new(header).strong());
+                }
+                ui.end_row();
+                for health in &snapshot.connections {
+                    ui.label(&health.name);
+                    if health.connected {
+                        ui.colored_label(ScreenerTheme::BUY_COLOR, "yes");
+                    } else {
+                        ui.colored_label(ScreenerTheme::SELL_COLOR, "no");
+                    }
+                    ui.label(format!("{:.0}", health.messages_per_second));
+                    ui.label(health.total_messages.to_string());
+                    count_label(ui, health.parse_errors);
+                    count_label(ui, health.dropped_messages);
+                    ui.label(format!("{} ({} forced)", health.reconnects, health.forced_reconnects));
+                    ui.label(health.ping_latency_ms.map_or("N/A".to_string(), |latency| format!("{}ms", latency)));
+                    ui.end_row();
+                }
+            });
+
+            ui.separator();
+            ui.heading("Event bus");
+            Grid::new("diagnostics_event_bus").striped(true).show(ui, |ui| {
+                for header in ["Topic", "Subscriber", "Policy", "Received", "Lag", "Max lag", "Dropped"] {
+                    ui.label(RichText::new(header).strong());
+                }
+                ui.end_row();
+                for stats in &snapshot.event_bus {
+                    ui.label(stats.topic);
+                    ui.label(&stats.name);
+                    ui.label(format!("{:?}", stats.policy));
+                    ui.label(stats.received.to_string());
+                    ui.label(stats.lag.to_string());
+                    ui.label(stats.max_lag.to_string());
+                    count_label(ui, stats.dropped);
+                    ui.end_row();
+                }
+            });
+
+            ui.separator();
+            ui.heading("Latency");
+            Grid::new("diagnostics_latency").striped(true).show(ui, |ui| {
+                for header in ["Stage", "Count", "Mean", "p50", "p99"] {
+                    ui.label(RichText::new(header).strong());
+                }
+                ui.end_row();
+                for (analyzer, stats) in &snapshot.analyzer_latency {
+                    latency_row(ui, analyzer, stats);
+                }
+                if let Some(persistence) = &snapshot.persistence {
+                    latency_row(ui, "database write", &persistence.write_latency);
+                }
+                if let Some(frame_time) = &snapshot.gui_frame_time {
+                    latency_row(ui, "GUI frame", frame_time);
+                }
+            });
+
+            ui.separator();
+            ui.heading("Storage and recovery");
+            if let Some(persistence) = &snapshot.persistence {
+                ui.label(format!(
+                    "Persistence: {} rows written, {} queued (max {}), {} failed batches",
+                    persistence.written, persistence.queue_depth, persistence.max_queue_depth, persistence.failed_batches
+                ));
+                if persistence.dropped > 0 || persistence.failed_rows > 0 {
+                    ui.colored_label(ScreenerTheme::SELL_COLOR, format!(
+                        "Persistence dropped {} rows under backpressure, {} rejected by the database",
+                        persistence.dropped, persistence.failed_rows
+                    ));
+                }
+            }
+            for (market, stats) in &snapshot.trade_gaps {
+                ui.label(format!(
+                    "{} trade gaps: {} gaps, {} missing, {} backfilled, {} unrecovered",
+                    market, stats.gaps, stats.missing_trades, stats.backfilled_trades, stats.unrecovered_trades
+                ));
+            }
+            for (market, resyncs) in &snapshot.orderbook_resyncs {
+                ui.label(format!("{} order book resyncs: {}", market, resyncs));
+            }
+            if !snapshot.book_level_events.is_empty() {
+                let events: Vec<String> = snapshot.book_level_events.iter().map(|(kind, count)| format!("{} {}", count, kind)).collect();
+                ui.label(format!("Book level events: {}", events.join(", ")));
+            }
+        });
+    }
+}
+
+impl Default for DiagnosticsPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Counter of losses, highlighted once non-zero
+fn count_label(ui: &mut Ui, count: u64) {
+    if count > 0 {
+        ui.colored_label(ScreenerTheme::SELL_COLOR, count.to_string());
+    } else {
+        ui.label("0");
+    }
+}
+
+fn latency_row(ui: &mut Ui, stage: &str, stats: &LatencyStats) {
+    ui.label(stage);
+    ui.label(stats.count.to_string());
+    ui.label(format_seconds(stats.mean_seconds()));
+    ui.label(format!("≤{}", format_seconds(stats.quantile_seconds(0.5))));
+    ui.label(format!("≤{}", format_seconds(stats.quantile_seconds(0.99))));
+    ui.end_row();
+}
+
+fn format_seconds(seconds: f64) -> String {
+    if seconds < 0.001 {
+        format!("{:.0}µs", seconds * 1e6)
+    } else if seconds < 1.0 {
+        format!("{:.1}ms", seconds * 1e3)
+    } else {
+        format!("{:.2}s", seconds)
+    }
+}
@@ -1,27 +1,43 @@
 use egui::{Color32, RichText, Ui, Rect, Pos2, Vec2, ScrollArea};
 use std::collections::{HashMap, VecDeque};
-use crate::data::{DepthSnapshot, OrderflowEvent};
+use ordered_float::OrderedFloat;
+use crate::data::{BookFlowBucket, BookPattern, BookPatternAlert, DepthSnapshot, LevelFlow, OrderflowEvent, SymbolRegistry};
 use crate::analysis::TradedVolumeTracker;
 use super::ScreenerTheme;
 
+/// Tick for symbols missing from the registry, the old fixed aggregation step
+const DEFAULT_TICK_SIZE: f64 = 0.01;
+/// How long a spoof or iceberg detection stays marked on its level
+const BOOK_PATTERN_MARKER_TTL_MS: u64 = 10 * 60 * 1000;
+const MAX_BOOK_PATTERN_MARKERS: usize = 200;
+/// Span of recent adds and cancels summed into the pulled / stacked columns
+const BOOK_FLOW_WINDOW_MS: u64 = 60 * 1000;
+const PULLED_COLOR: Color32 = Color32::from_rgb(255, 120, 60);
+const STACKED_COLOR: Color32 = Color32::from_rgb(90, 170, 255);
+
 /// Depth of Market (DOM) panel showing order book and traded volume
 pub struct DOMPanel {
     symbol: String,
 
-    // Aggregation settings
+    // Aggregation settings: rows span a multiple of the contract tick
+    tick_size: f64,
     aggregation_level: f64,
-    available_aggregations: Vec<f64>,
+    available_tick_multiples: Vec<f64>,
     aggregation_index: usize,
+    symbol_registry: SymbolRegistry,
 
     // Display settings
     num_levels_to_show: usize,
     show_traded_volume: bool,
     show_volume_delta: bool,
     show_imbalance_percentage: bool,
+    show_book_flow: bool,
 
     // Data
     current_depth: Option<DepthSnapshot>,
     traded_volume_tracker: HashMap<String, TradedVolumeTracker>,
+    book_patterns: VecDeque<BookPatternAlert>, // newest last
+    book_flow: HashMap<String, VecDeque<BookFlowBucket>>, // newest last
 
     // UI state
     scroll_to_mid: bool,
@@ -31,42 +47,67 @@ pub struct DOMPanel {
 
 impl DOMPanel {
     pub fn new(symbol: String) -> Self {
-        let available_aggregations = vec![0.01, 0.1, 1.0, 10.0, 100.0];
-        let aggregation_index = 0; // Default to 0.01
+        let available_tick_multiples = vec![1.0, 5.0, 10.0, 50.0, 100.0, 500.0, 1000.0];
+        let aggregation_index = 0; // Default to one tick
 
         Self {
             symbol: symbol.clone(),
-            aggregation_level: available_aggregations[aggregation_index],
-            available_aggregations,
+            tick_size: DEFAULT_TICK_SIZE,
+            aggregation_level: DEFAULT_TICK_SIZE * available_tick_multiples[aggregation_index],
+            available_tick_multiples,
             aggregation_index,
+            symbol_registry: SymbolRegistry::new(),
             num_levels_to_show: 20,
             show_traded_volume: true,
             show_volume_delta: true,
             show_imbalance_percentage: true,
+            show_book_flow: true,
             current_depth: None,
             traded_volume_tracker: {
                 let mut map = HashMap::new();
-                map.insert(symbol.clone(), TradedVolumeTracker::new(symbol, 0.01));
+                map.insert(symbol.clone(), TradedVolumeTracker::new(symbol, DEFAULT_TICK_SIZE));
                 map
             },
+            book_patterns: VecDeque::new(),
+            book_flow: HashMap::new(),
             scroll_to_mid: true,
             highlight_large_orders: true,
             large_order_threshold: 0.7, // Highlight orders above 70% of max
         }
     }
 
+    /// Aggregate and track traded volume at each symbol's contract tick
+    pub fn set_symbol_registry(&mut self, registry: SymbolRegistry) {
+        self.symbol_registry = registry;
+        self.apply_tick_size();
+    }
+
     pub fn set_symbol(&mut self, symbol: String) {
         if self.symbol != symbol {
-            self.symbol = symbol.clone();
-            if !self.traded_volume_tracker.contains_key(&symbol) {
-                self.traded_volume_tracker.insert(
-                    symbol.clone(),
-                    TradedVolumeTracker::new(symbol, self.aggregation_level)
-                );
-            }
+            self.symbol = symbol;
+            self.apply_tick_size();
+        }
+    }
+
+    /// Look up the current symbol's tick and make sure its tracker bins at it.
+    /// Runs every frame too, so a registry filled after the DOM opened takes effect.
+    fn apply_tick_size(&mut self) {
+        self.tick_size = self.symbol_registry.tick_size(&self.symbol).unwrap_or(DEFAULT_TICK_SIZE);
+        self.aggregation_level = self.tick_size * self.available_tick_multiples[self.aggregation_index];
+
+        let tick_size = self.tick_size;
+        if self.traded_volume_tracker.get(&self.symbol).is_none_or(|tracker| tracker.tick_size() != tick_size) {
+            self.traded_volume_tracker.insert(
+                self.symbol.clone(),
+                TradedVolumeTracker::for_symbol(self.symbol.clone(), &self.symbol_registry, DEFAULT_TICK_SIZE)
+            );
         }
     }
 
+    fn price_decimals(&self) -> usize {
+        self.symbol_registry.get(&self.symbol).map_or(2, |info| info.price_decimals())
+    }
+
     pub fn update_depth(&mut self, snapshot: DepthSnapshot) {
         // Always update - symbol matching is done at the app level
         self.current_depth = Some(snapshot);
@@ -78,7 +119,62 @@ impl DOMPanel {
         }
     }
 
+    /// Mark a spoof or iceberg candidate on its level
+    pub fn add_book_pattern(&mut self, alert: BookPatternAlert) {
+        self.book_patterns.push_back(alert);
+        while self.book_patterns.len() > MAX_BOOK_PATTERN_MARKERS {
+            self.book_patterns.pop_front();
+        }
+    }
+
+    /// Latest recent detection on the row holding `price`
+    fn book_pattern_at(&self, price: f64, is_ask: bool) -> Option<&BookPatternAlert> {
+        let now = chrono::Utc::now().timestamp_millis() as u64;
+        let side = if is_ask { "ASK" } else { "BID" };
+        let tick = price_to_tick(price, self.aggregation_level);
+        self.book_patterns.iter().rev().find(|alert| {
+            alert.symbol == self.symbol
+                && alert.side == side
+                && price_to_tick(alert.price, self.aggregation_level) == tick
+                && alert.timestamp + BOOK_PATTERN_MARKER_TTL_MS >= now
+        })
+    }
+
+    /// Keep a closed bucket of classified adds, cancels and executions
+    pub fn add_book_flow(&mut self, bucket: BookFlowBucket) {
+        let horizon = bucket.start.saturating_sub(BOOK_FLOW_WINDOW_MS);
+        let buckets = self.book_flow.entry(bucket.symbol.clone()).or_default();
+        buckets.push_back(bucket);
+        while buckets.front().is_some_and(|oldest| oldest.start < horizon) {
+            buckets.pop_front();
+        }
+    }
+
+    fn recent_book_flow(&self) -> impl Iterator<Item = &BookFlowBucket> {
+        let horizon = (chrono::Utc::now().timestamp_millis() as u64).saturating_sub(BOOK_FLOW_WINDOW_MS);
+        self.book_flow
+            .get(&self.symbol)
+            .into_iter()
+            .flatten()
+            .filter(move |bucket| bucket.start + bucket.duration_ms >= horizon)
+    }
+
+    /// Recent flow summed over the row holding `price`
+    fn book_flow_at(&self, price: f64, is_ask: bool) -> LevelFlow {
+        let half_row = OrderedFloat(self.aggregation_level / 2.0);
+        let range = OrderedFloat(price) - half_row..OrderedFloat(price) + half_row;
+        let mut flow = LevelFlow::default();
+        for bucket in self.recent_book_flow() {
+            let levels = if is_ask { &bucket.asks } else { &bucket.bids };
+            for level in levels.range(range.clone()).map(|(_, level)| level) {
+                flow.merge(level);
+            }
+        }
+        flow
+    }
+
     pub fn show(&mut self, ui: &mut Ui) {
+        self.apply_tick_size();
         ui.vertical(|ui| {
             // Header controls
             self.draw_controls(ui);
@@ -99,19 +195,21 @@ impl DOMPanel {
 
             ui.label("Aggregation:");
             let mut agg_changed = false;
+            let decimals = self.price_decimals();
             egui::ComboBox::from_id_source("dom_aggregation_selector")
-                .selected_text(format!("{}", self.aggregation_level))
+                .selected_text(format!("{:.*}", decimals, self.aggregation_level))
                 .width(80.0)
                 .show_ui(ui, |ui| {
-                    for (i, &agg) in self.available_aggregations.iter().enumerate() {
-                        if ui.selectable_value(&mut self.aggregation_index, i, format!("{}", agg)).clicked() {
+                    for (i, &multiple) in self.available_tick_multiples.iter().enumerate() {
+                        let label = format!("{:.*}", decimals, self.tick_size * multiple);
+                        if ui.selectable_value(&mut self.aggregation_index, i, label).clicked() {
                             agg_changed = true;
                         }
                     }
                 });
 
             if agg_changed {
-                self.aggregation_level = self.available_aggregations[self.aggregation_index];
+                self.aggregation_level = self.tick_size * self.available_tick_multiples[self.aggregation_index];
             }
 
             ui.separator();
@@ -130,7 +228,21 @@ impl DOMPanel {
 
             ui.checkbox(&mut self.show_traded_volume, "Traded Vol");
             ui.checkbox(&mut self.show_volume_delta, "Delta");
+            ui.checkbox(&mut self.show_book_flow, "Pulled/Stacked");
             ui.checkbox(&mut self.highlight_large_orders, "Highlight Large");
+
+            ui.separator();
+
+            let mut totals = LevelFlow::default();
+            for bucket in self.recent_book_flow() {
+                totals.merge(&bucket.totals());
+            }
+            let ratio = totals.cancel_to_fill_ratio().map_or("-".to_string(), |ratio| format!("{:.1}", ratio));
+            ui.label(RichText::new(format!("Cancel/Fill (1m): {}", ratio)).color(Color32::LIGHT_GRAY))
+                .on_hover_text(format!(
+                    "{} cancels, {} executions, {} adds over the last minute",
+                    totals.cancels, totals.executions, totals.adds
+                ));
         });
     }
 
@@ -218,6 +330,12 @@ impl DOMPanel {
                     ui.label(RichText::new("Bid Size").size(11.0).color(Color32::GRAY));
                     ui.add_space(5.0);
 
+                    if self.show_book_flow {
+                        ui.label(RichText::new("Pulled").size(11.0).color(Color32::GRAY));
+                        ui.label(RichText::new("Stacked").size(11.0).color(Color32::GRAY));
+                        ui.add_space(5.0);
+                    }
+
                     if self.show_traded_volume {
                         ui.label(RichText::new("Traded").size(11.0).color(Color32::GRAY));
                         ui.add_space(5.0);
@@ -234,6 +352,12 @@ impl DOMPanel {
                         ui.add_space(5.0);
                     }
 
+                    if self.show_book_flow {
+                        ui.label(RichText::new("Stacked").size(11.0).color(Color32::GRAY));
+                        ui.label(RichText::new("Pulled").size(11.0).color(Color32::GRAY));
+                        ui.add_space(5.0);
+                    }
+
                     ui.label(RichText::new("Ask Size").size(11.0).color(Color32::GRAY));
                 },
             );
@@ -246,6 +370,7 @@ impl DOMPanel {
         let best_ask = depth.asks.first().map(|(p, _)| *p).unwrap_or(0.0);
         let mid_price = (best_bid + best_ask) / 2.0;
         let spread = best_ask - best_bid;
+        let decimals = self.price_decimals();
         let spread_pct = if best_bid > 0.0 { (spread / best_bid) * 100.0 } else { 0.0 };
 
         ui.horizontal(|ui| {
@@ -258,8 +383,8 @@ impl DOMPanel {
 
                     ui.label(
                         RichText::new(format!(
-                            "MID: {:.2}  │  SPREAD: {:.2} ({:.3}%)",
-                            mid_price, spread, spread_pct
+                            "MID: {:.*}  │  SPREAD: {:.*} ({:.3}%)",
+                            decimals + 1, mid_price, decimals, spread, spread_pct
                         ))
                         .size(11.0)
                         .strong()
@@ -331,11 +456,27 @@ impl DOMPanel {
                 ui.painter().rect_filled(bar_rect, 0.0, bar_color);
             }
 
+            // Spoof / iceberg tag beside the price
+            if let Some(alert) = self.book_pattern_at(price, is_ask) {
+                let (tag, color) = match alert.pattern {
+                    BookPattern::Spoof => ("SPOOF", Color32::from_rgb(255, 140, 0)),
+                    BookPattern::Iceberg => ("ICEBERG", Color32::from_rgb(0, 200, 255)),
+                };
+                ui.painter().text(
+                    Pos2::new(rect.center().x + 50.0, rect.center().y),
+                    egui::Align2::LEFT_CENTER,
+                    format!("{} ×{}", tag, alert.occurrences),
+                    egui::FontId::proportional(9.0),
+                    color,
+                );
+            }
+
             ui.allocate_ui_with_layout(
                 Vec2::new(width, row_height),
                 egui::Layout::left_to_right(egui::Align::Center),
                 |ui| {
                     let text_color = Color32::WHITE;
+                    let flow = self.show_book_flow.then(|| self.book_flow_at(price, is_ask));
 
                     // Bid side
                     if !is_ask {
@@ -343,6 +484,11 @@ impl DOMPanel {
                             .size(10.0)
                             .color(ScreenerTheme::BUY_COLOR));
 
+                        if let Some(flow) = &flow {
+                            flow_label(ui, flow.cancelled, PULLED_COLOR);
+                            flow_label(ui, flow.added, STACKED_COLOR);
+                        }
+
                         if self.show_traded_volume {
                             if let Some((buy, sell, _total)) = traded_vol {
                                 let delta = buy - sell;
@@ -365,7 +511,7 @@ impl DOMPanel {
                         } else {
                             ScreenerTheme::BUY_COLOR
                         };
-                        ui.label(RichText::new(format!("{:.2}", price))
+                        ui.label(RichText::new(format!("{:.*}", self.price_decimals(), price))
                             .size(11.0)
                             .strong()
                             .color(price_color));
@@ -385,6 +531,11 @@ impl DOMPanel {
                             }
                         }
 
+                        if let Some(flow) = &flow {
+                            flow_label(ui, flow.added, STACKED_COLOR);
+                            flow_label(ui, flow.cancelled, PULLED_COLOR);
+                        }
+
                         ui.label(RichText::new(format!("{:.2}", volume))
                             .size(10.0)
                             .color(ScreenerTheme::SELL_COLOR));
@@ -427,9 +578,9 @@ impl DOMPanel {
     }
 
     fn get_aggregated_traded_volumes(&self, tracker: &TradedVolumeTracker) -> HashMap<i64, (f64, f64, f64)> {
-        // Return map of price_tick -> (buy_volume, sell_volume, total_volume)
-        tracker.volume_at_price.iter()
-            .map(|(tick, vol)| (*tick, (vol.buy_volume, vol.sell_volume, vol.total_volume)))
+        // Return map of aggregated price_tick -> (buy_volume, sell_volume, total_volume)
+        tracker.levels_at_granularity(self.aggregation_level).into_iter()
+            .map(|(tick, vol)| (tick, (vol.buy_volume, vol.sell_volume, vol.total_volume)))
             .collect()
     }
 }
@@ -442,3 +593,12 @@ fn price_to_tick(price: f64, aggregation: f64) -> i64 {
 fn tick_to_price(tick: i64, aggregation: f64) -> f64 {
     tick as f64 * aggregation
 }
+
+/// Pulled or stacked size of one row, a dash when nothing moved
+fn flow_label(ui: &mut Ui, quantity: f64, color: Color32) {
+    if quantity > 0.0 {
+        ui.label(RichText::new(format!("{:.1}", quantity)).size(9.0).color(color));
+    } else {
+        ui.label(RichText::new("-").size(9.0).color(Color32::GRAY));
+    }
+}
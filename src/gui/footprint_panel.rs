@@ -1,10 +1,28 @@
 use egui::{Color32, RichText, Ui, Rect, Pos2, Vec2, Stroke};
 use std::collections::{HashMap, VecDeque, BTreeMap};
-use crate::data::{VolumeProfile, OrderflowEvent, BinanceSymbols, DepthSnapshot, market_data::Candle};
+use std::sync::Arc;
+use crate::data::{VolumeProfile, OrderflowEvent, BookPattern, BookPatternAlert, BinanceSymbols, DepthSnapshot, StoredFootprintCandle, OpenInterestData, UniverseChange, SymbolRegistry, DepthHistory, DepthAggregation, HeatmapColumn, HeatmapStore, FootprintHistory, market_data::Candle};
 use crate::analysis::indicators::{Indicator, SimpleMovingAverage, ExponentialMovingAverage, BollingerBands, RSI};
-use super::{ScreenerTheme, HeatmapColorScheme, DrawingToolsManager, ActiveTool, DrawingTool};
+use super::{ScreenerTheme, HeatmapColorScheme, WideHeatmap, WideHeatmapView, DrawingToolsManager, ActiveTool, DrawingTool};
 use chrono::{DateTime, Utc};
 
+/// Tick for symbols missing from the registry; at the default 10x this keeps the old 0.01 bins
+const DEFAULT_TICK_SIZE: f64 = 0.001;
+/// Price bin in contract ticks until the configured aggregation is shared
+const DEFAULT_TICK_MULTIPLE: u32 = 10;
+/// Spoof and iceberg detections kept per symbol for the heatmap
+const MAX_BOOK_PATTERNS_PER_SYMBOL: usize = 500;
+/// Cached candle heatmap columns; past this the cache starts over
+const MAX_CACHED_HEATMAP_COLUMNS: usize = 2_048;
+
+/// A candle's heatmap column as read from the history store, with the store's
+/// revision at the time so it is only read again once a newer column closes
+struct CachedHeatmapColumn {
+    column: Option<Arc<HeatmapColumn>>,
+    closed_through: u64,
+    restored: u64,
+}
+
 #[derive(Debug, Clone)]
 pub struct FootprintCell {
     pub price: f64,
@@ -89,6 +107,36 @@ impl FootprintCandle {
     pub fn max_volume(&self) -> u64 {
         self.cells.values().map(|cell| cell.total_volume()).max().unwrap_or(0)
     }
+
+    /// Rebuild a candle from storage, re-aggregating its contract-tick levels
+    /// into bins of `tick_size`; volumes are summed before they are truncated
+    pub fn from_stored(stored: &StoredFootprintCandle, tick_size: f64) -> Self {
+        let mut volumes: BTreeMap<i64, (f64, f64)> = BTreeMap::new();
+        for level in &stored.levels {
+            let price_tick = (level.tick as f64 * stored.tick_size / tick_size).round() as i64;
+            let (bid_volume, ask_volume) = volumes.entry(price_tick).or_default();
+            *bid_volume += level.bid_volume;
+            *ask_volume += level.ask_volume;
+        }
+        let cells = volumes
+            .into_iter()
+            .map(|(price_tick, (bid_volume, ask_volume))| (price_tick, FootprintCell {
+                price: price_tick as f64 * tick_size,
+                bid_volume: bid_volume as u64,
+                ask_volume: ask_volume as u64,
+            }))
+            .collect();
+
+        Self {
+            timestamp: stored.open_time,
+            open: stored.open,
+            high: stored.high,
+            low: stored.low,
+            close: stored.close,
+            cells,
+            tick_size,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -121,10 +169,9 @@ pub struct FootprintPanel {
     show_delta: bool,
     show_imbalance: bool,
 
-    // Scale and zoom settings
-    price_scale: f64, // Aggregation scale for price bins
-    available_scales: Vec<f64>,
-    scale_index: usize,
+    // Scale and zoom settings; each symbol's bins span `depth_aggregation.tick_multiple(symbol)` ticks
+    available_tick_multiples: Vec<u32>,
+    symbol_registry: SymbolRegistry,
 
     // Zoom and pan settings
     zoom_level: f32,
@@ -149,18 +196,27 @@ pub struct FootprintPanel {
     // Symbol management
     symbol_category: String,
     show_symbol_selector: bool,
+    listed_symbols: HashMap<String, Vec<String>>, // market -> live exchange listing
+    new_listings: Vec<String>, // Listed while running, newest first
 
     // Cumulative Volume Delta tracking per symbol
     cumulative_cvd: HashMap<String, i64>,
 
     // LOB Heatmap data
-    depth_snapshots: HashMap<String, VecDeque<DepthSnapshot>>,
+    depth_snapshots: HashMap<String, DepthHistory>, // bucketed at the footprint price scale
     max_depth_snapshots: usize,
+    depth_aggregation: DepthAggregation,
+    heatmap_store: Option<HeatmapStore>, // downsampled depth across the session
+    heatmap_columns: HashMap<(String, u64, u64, u32), CachedHeatmapColumn>, // (symbol, candle start, timeframe, tick multiple)
+    latest_heatmap_column: Option<Arc<HeatmapColumn>>, // newest live snapshot, without a history store
+    wide_heatmap: Option<WideHeatmap>, // full-depth books of the focus symbols
 
     // LOB Heatmap rendering settings
     enable_heatmap: bool,
+    show_wide_heatmap: bool,
     heatmap_color_scheme: HeatmapColorScheme,
     heatmap_opacity: f32,
+    book_patterns: HashMap<String, VecDeque<BookPatternAlert>>, // symbol -> detections by time
 
     // Drawing tools
     drawing_tools: DrawingToolsManager,
@@ -184,12 +240,16 @@ pub struct FootprintPanel {
     show_rsi: bool,
     rsi_period: usize,
     show_indicator_panel: bool,
+
+    // Open interest sub-chart
+    open_interest: HashMap<String, VecDeque<OpenInterestData>>, // symbol -> readings by time
+    last_oi_spike: HashMap<String, (f64, String)>, // symbol -> (change %, window)
+    show_open_interest: bool,
 }
 
 impl FootprintPanel {
     pub fn new() -> Self {
-        let available_scales = vec![0.0001, 0.001, 0.01, 0.1, 1.0, 10.0, 100.0];
-        let scale_index = 2; // Default to 0.01
+        let available_tick_multiples = vec![1, 2, 5, 10, 25, 50, 100];
         let symbols = BinanceSymbols::get_high_volume_symbols(); // Use high-volume symbols by default
 
         // Define available timeframes
@@ -227,9 +287,8 @@ impl FootprintPanel {
             show_imbalance: false,
 
             // Scale settings
-            price_scale: available_scales[scale_index],
-            available_scales,
-            scale_index,
+            available_tick_multiples,
+            symbol_registry: SymbolRegistry::new(),
 
             // Zoom and pan settings
             zoom_level: 1.0,
@@ -254,6 +313,8 @@ impl FootprintPanel {
             // Symbol management
             symbol_category: "High Volume".to_string(),
             show_symbol_selector: false,
+            listed_symbols: HashMap::new(),
+            new_listings: Vec::new(),
 
             // Cumulative CVD tracking
             cumulative_cvd: HashMap::new(),
@@ -261,11 +322,18 @@ impl FootprintPanel {
             // LOB Heatmap data
             depth_snapshots: HashMap::new(),
             max_depth_snapshots: 100,  // Keep last 100 snapshots per symbol
+            depth_aggregation: DepthAggregation::new(DEFAULT_TICK_MULTIPLE),
+            heatmap_store: None,
+            heatmap_columns: HashMap::new(),
+            latest_heatmap_column: None,
+            wide_heatmap: None,
 
             // LOB Heatmap rendering settings
             enable_heatmap: true,
+            show_wide_heatmap: true,
             heatmap_color_scheme: HeatmapColorScheme::default(),
             heatmap_opacity: 0.6,
+            book_patterns: HashMap::new(),
 
             // Drawing tools
             drawing_tools: DrawingToolsManager::new(),
@@ -289,6 +357,9 @@ impl FootprintPanel {
             show_rsi: false,
             rsi_period: 14,
             show_indicator_panel: true,
+            open_interest: HashMap::new(),
+            last_oi_spike: HashMap::new(),
+            show_open_interest: true,
         }
     }
 
@@ -301,8 +372,7 @@ impl FootprintPanel {
 
         let selected = default_symbols.first().unwrap_or(&"BTCUSDT".to_string()).clone();
 
-        let available_scales = vec![0.0001, 0.001, 0.01, 0.1, 1.0, 10.0, 100.0];
-        let scale_index = 2; // Default to 0.01
+        let available_tick_multiples = vec![1, 2, 5, 10, 25, 50, 100];
 
         // Define available timeframes
         let available_timeframes = vec![
@@ -339,9 +409,8 @@ impl FootprintPanel {
             show_imbalance: false,
 
             // Scale settings
-            price_scale: available_scales[scale_index],
-            available_scales,
-            scale_index,
+            available_tick_multiples,
+            symbol_registry: SymbolRegistry::new(),
 
             // Zoom and pan settings
             zoom_level: 1.0,
@@ -366,6 +435,8 @@ impl FootprintPanel {
             // Symbol management
             symbol_category: "Default".to_string(),
             show_symbol_selector: false,
+            listed_symbols: HashMap::new(),
+            new_listings: Vec::new(),
 
             // Cumulative CVD tracking
             cumulative_cvd: HashMap::new(),
@@ -373,11 +444,18 @@ impl FootprintPanel {
             // LOB Heatmap data
             depth_snapshots: HashMap::new(),
             max_depth_snapshots: 100,  // Keep last 100 snapshots per symbol
+            depth_aggregation: DepthAggregation::new(DEFAULT_TICK_MULTIPLE),
+            heatmap_store: None,
+            heatmap_columns: HashMap::new(),
+            latest_heatmap_column: None,
+            wide_heatmap: None,
 
             // LOB Heatmap rendering settings
             enable_heatmap: true,
+            show_wide_heatmap: true,
             heatmap_color_scheme: HeatmapColorScheme::default(),
             heatmap_opacity: 0.6,
+            book_patterns: HashMap::new(),
 
             // Drawing tools
             drawing_tools: DrawingToolsManager::new(),
@@ -401,6 +479,9 @@ impl FootprintPanel {
             show_rsi: false,
             rsi_period: 14,
             show_indicator_panel: true,
+            open_interest: HashMap::new(),
+            last_oi_spike: HashMap::new(),
+            show_open_interest: true,
         }
     }
 
@@ -413,6 +494,50 @@ impl FootprintPanel {
         }
     }
 
+    /// Tick sizes come from exchangeInfo; symbols it does not know bin at `DEFAULT_TICK_SIZE`
+    pub fn set_symbol_registry(&mut self, registry: SymbolRegistry) {
+        self.symbol_registry = registry;
+    }
+
+    /// Per-symbol price scale, shared with the order book managers so scale
+    /// changes re-bucket their depth history too
+    pub fn set_depth_aggregation(&mut self, aggregation: DepthAggregation) {
+        self.depth_aggregation = aggregation;
+        self.base_current_candles.clear();
+        self.cache_valid = false;
+        self.rebucket_depth_history();
+    }
+
+    /// Paint candles with the recorded heatmap history instead of the latest book
+    pub fn set_heatmap_store(&mut self, store: HeatmapStore) {
+        self.heatmap_store = Some(store);
+    }
+
+    /// Keep `history_secs` of full-depth snapshots for the wide heatmap focus symbols
+    pub fn set_wide_heatmap(&mut self, history_secs: u64) {
+        self.wide_heatmap = Some(WideHeatmap::new(history_secs));
+    }
+
+    fn tick_size(&self, symbol: &str) -> f64 {
+        self.symbol_registry.tick_size(symbol).unwrap_or(DEFAULT_TICK_SIZE)
+    }
+
+    /// Price bin height for `symbol`: its configured or selected number of contract ticks
+    fn price_scale(&self, symbol: &str) -> f64 {
+        self.tick_size(symbol) * self.depth_aggregation.tick_multiple(symbol) as f64
+    }
+
+    /// Re-bucket the heatmap depth of every symbol at its current price scale
+    fn rebucket_depth_history(&mut self) {
+        let symbols: Vec<String> = self.depth_snapshots.keys().cloned().collect();
+        for symbol in symbols {
+            let granularity = self.price_scale(&symbol);
+            if let Some(history) = self.depth_snapshots.get_mut(&symbol) {
+                history.set_granularity(granularity);
+            }
+        }
+    }
+
     pub fn add_orderflow_event(&mut self, event: &OrderflowEvent) {
         // Always use base timeframe (1m = 60000ms) for incoming events
         const BASE_TIMEFRAME_MS: u64 = 60_000;
@@ -420,8 +545,9 @@ impl FootprintPanel {
         let candle_start = (current_time / BASE_TIMEFRAME_MS) * BASE_TIMEFRAME_MS;
 
         // Get or create current base candle for this symbol
+        let price_scale = self.price_scale(&event.symbol);
         let candle = self.base_current_candles.entry(event.symbol.clone()).or_insert_with(|| {
-            FootprintCandle::new(candle_start, self.price_scale)
+            FootprintCandle::new(candle_start, price_scale)
         });
 
         // If this trade belongs to a new candle, complete the old one
@@ -443,7 +569,7 @@ impl FootprintPanel {
 
             // Start new candle
             let new_candle_start = (event.timestamp / BASE_TIMEFRAME_MS) * BASE_TIMEFRAME_MS;
-            *candle = FootprintCandle::new(new_candle_start, self.price_scale);
+            *candle = FootprintCandle::new(new_candle_start, price_scale);
         }
 
         // Add trade to current candle
@@ -453,14 +579,110 @@ impl FootprintPanel {
         self.cache_valid = false;
     }
 
+    /// Place stored base candles, and ones built from historical trades (ordered
+    /// by trade id) for the minutes without a stored candle, before the live ones.
+    /// Minutes already covered by live trades are skipped so no trade is counted
+    /// twice.
+    pub fn load_history(&mut self, history: &FootprintHistory) {
+        const BASE_TIMEFRAME_MS: u64 = 60_000;
+        let symbol = history.symbol.as_str();
+
+        let live_start = self.base_completed_candles
+            .get(symbol)
+            .and_then(|candles| candles.front())
+            .or_else(|| self.base_current_candles.get(symbol))
+            .map(|candle| candle.timestamp);
+
+        let price_scale = self.price_scale(symbol);
+        let mut rebuilt: Vec<FootprintCandle> = Vec::new();
+        for trade in &history.trades {
+            let candle_start = (trade.timestamp / BASE_TIMEFRAME_MS) * BASE_TIMEFRAME_MS;
+            if live_start.is_some_and(|live| candle_start >= live) {
+                break;
+            }
+            if rebuilt.last().is_none_or(|candle| candle.timestamp != candle_start) {
+                rebuilt.push(FootprintCandle::new(candle_start, price_scale));
+            }
+            if let Some(candle) = rebuilt.last_mut() {
+                candle.add_trade(trade);
+            }
+        }
+
+        let mut candles: Vec<FootprintCandle> = history.candles
+            .iter()
+            .filter(|stored| live_start.is_none_or(|live| stored.open_time < live))
+            .map(|stored| FootprintCandle::from_stored(stored, price_scale))
+            .chain(rebuilt)
+            .collect();
+        if candles.is_empty() {
+            return;
+        }
+        candles.sort_by_key(|candle| candle.timestamp);
+        candles.dedup_by_key(|candle| candle.timestamp);
+
+        let completed_candles = self.base_completed_candles.entry(symbol.to_string()).or_default();
+        for candle in candles.into_iter().rev() {
+            completed_candles.push_front(candle);
+        }
+        while completed_candles.len() > 1000 {
+            completed_candles.pop_front();
+        }
+
+        self.cache_valid = false;
+    }
+
+    /// Add an open interest reading; history and live polls may arrive out of order
+    pub fn add_open_interest(&mut self, reading: OpenInterestData) {
+        let readings = self.open_interest.entry(reading.symbol.clone()).or_default();
+        let position = readings.partition_point(|r| r.timestamp < reading.timestamp);
+        if readings.get(position).is_some_and(|r| r.timestamp == reading.timestamp) {
+            readings[position] = reading;
+        } else {
+            readings.insert(position, reading);
+        }
+
+        while readings.len() > 2000 {
+            readings.pop_front();
+        }
+    }
+
+    pub fn set_open_interest_spike(&mut self, symbol: String, change_percentage: f64, timeframe: String) {
+        self.last_oi_spike.insert(symbol, (change_percentage, timeframe));
+    }
+
+    /// Mark a spoof or iceberg candidate on the heatmap
+    pub fn add_book_pattern(&mut self, alert: BookPatternAlert) {
+        let detections = self.book_patterns.entry(alert.symbol.clone()).or_default();
+        detections.push_back(alert);
+        while detections.len() > MAX_BOOK_PATTERNS_PER_SYMBOL {
+            detections.pop_front();
+        }
+    }
+
     pub fn add_depth_snapshot(&mut self, symbol: String, snapshot: DepthSnapshot) {
-        let snapshots = self.depth_snapshots.entry(symbol).or_insert_with(VecDeque::new);
-        snapshots.push_back(snapshot);
+        // The tick may have arrived from exchangeInfo since the history was created
+        let granularity = self.price_scale(&symbol);
+        let max_depth_snapshots = self.max_depth_snapshots;
+        let history = self.depth_snapshots
+            .entry(symbol.clone())
+            .or_insert_with(|| DepthHistory::new(symbol, max_depth_snapshots, granularity));
+        history.set_granularity(granularity);
+        history.add_depth(snapshot.timestamp, snapshot);
+    }
+
+    pub fn add_wide_depth_snapshot(&mut self, symbol: String, snapshot: DepthSnapshot) {
+        let granularity = self.price_scale(&symbol);
+        if let Some(wide_heatmap) = &mut self.wide_heatmap {
+            wide_heatmap.add_snapshot(&symbol, &snapshot, granularity);
+        }
+    }
 
-        // Maintain max size
-        while snapshots.len() > self.max_depth_snapshots {
-            snapshots.pop_front();
+    /// Candles closing after this are covered by the full-depth texture
+    fn wide_heatmap_start(&self) -> Option<u64> {
+        if !self.show_wide_heatmap {
+            return None;
         }
+        self.wide_heatmap.as_ref()?.oldest(&self.selected_symbol)
     }
 
     pub fn get_profile_count(&self) -> usize {
@@ -632,23 +854,27 @@ impl FootprintPanel {
 
                 // Scale controls
                 ui.label("Scale:");
-                let mut scale_changed = false;
+                let tick_size = self.tick_size(&self.selected_symbol);
+                let decimals = self.symbol_registry.get(&self.selected_symbol).map_or(4, |info| info.price_decimals());
+                let tick_multiple = self.depth_aggregation.tick_multiple(&self.selected_symbol);
+                let mut selected_multiple = tick_multiple;
                 egui::ComboBox::from_id_source("footprint_scale_selector")
-                    .selected_text(format!("{}", self.price_scale))
-                    .width(80.0)
+                    .selected_text(format!("{}x ({:.*})", tick_multiple, decimals, tick_size * tick_multiple as f64))
+                    .width(100.0)
                     .show_ui(ui, |ui| {
-                        for (i, &scale) in self.available_scales.iter().enumerate() {
-                            if ui.selectable_value(&mut self.scale_index, i, format!("{}", scale)).clicked() {
-                                scale_changed = true;
-                            }
+                        for &multiple in &self.available_tick_multiples {
+                            let label = format!("{}x ({:.*})", multiple, decimals, tick_size * multiple as f64);
+                            ui.selectable_value(&mut selected_multiple, multiple, label);
                         }
                     });
 
-                if scale_changed {
-                    self.price_scale = self.available_scales[self.scale_index];
-                    // Clear current candles to force regeneration with new scale
-                    self.base_current_candles.clear();
+                if selected_multiple != tick_multiple {
+                    // Shared with the order book managers, whose depth history follows
+                    self.depth_aggregation.set_tick_multiple(&self.selected_symbol, selected_multiple);
+                    // Clear the current candle to force regeneration with the new scale
+                    self.base_current_candles.remove(&self.selected_symbol);
                     self.cache_valid = false;
+                    self.rebucket_depth_history();
                 }
 
                 ui.separator();
@@ -680,6 +906,10 @@ impl FootprintPanel {
                 // LOB Heatmap controls
                 ui.checkbox(&mut self.enable_heatmap, "Heatmap");
                 if self.enable_heatmap {
+                    if self.wide_heatmap.is_some() {
+                        ui.checkbox(&mut self.show_wide_heatmap, "Full depth")
+                            .on_hover_text("Full local book of the focus symbols");
+                    }
                     ui.label("Opacity:");
                     ui.add(egui::Slider::new(&mut self.heatmap_opacity, 0.0..=1.0)
                         .show_value(false)
@@ -770,6 +1000,10 @@ impl FootprintPanel {
                         ui.label("Period:");
                         ui.add(egui::DragValue::new(&mut self.rsi_period).speed(1).clamp_range(1..=100));
                     }
+
+                    ui.separator();
+
+                    ui.checkbox(&mut self.show_open_interest, "OI");
                 });
             }
 
@@ -787,10 +1021,15 @@ impl FootprintPanel {
         let axis_width = 80.0;
         let axis_height = 30.0;
         let stats_height = 60.0; // Space for statistics above chart
+        let oi_height = if self.show_open_interest { 90.0 } else { 0.0 }; // Open interest sub-chart
 
         let chart_rect = Rect::from_min_size(
             available_rect.min + Vec2::new(axis_width, stats_height),
-            Vec2::new(available_rect.width() - axis_width, available_rect.height() - axis_height - stats_height - 20.0)
+            Vec2::new(available_rect.width() - axis_width, available_rect.height() - axis_height - stats_height - oi_height - 20.0)
+        );
+        let oi_rect = Rect::from_min_size(
+            Pos2::new(chart_rect.min.x, chart_rect.max.y + 10.0),
+            Vec2::new(chart_rect.width(), oi_height)
         );
 
         // Handle mouse interactions for pan and zoom
@@ -800,6 +1039,11 @@ impl FootprintPanel {
         let selected_symbol = self.selected_symbol.clone();
         let (completed_candles, current_candle) = self.get_candles_for_timeframe(&selected_symbol);
 
+        // The shown symbol's book is recorded into the heatmap history from now on
+        if let Some(store) = self.heatmap_store.as_ref().filter(|_| self.enable_heatmap) {
+            store.open(&selected_symbol);
+        }
+
         if completed_candles.is_empty() && current_candle.is_none() {
             ui.centered_and_justified(|ui| {
                 ui.label(format!("No footprint data for {}", self.selected_symbol));
@@ -862,21 +1106,143 @@ impl FootprintPanel {
         let visible_start_index = (-self.pan_x / candle_width).max(0.0) as usize;
         let visible_end_index = ((chart_rect.width() - self.pan_x) / candle_width).min(all_candles.len() as f32) as usize;
 
+        // Full-depth book as one cached texture behind the visible candles
+        if self.enable_heatmap && self.wide_heatmap_start().is_some() && visible_end_index > visible_start_index {
+            let view = WideHeatmapView {
+                symbol: selected_symbol.clone(),
+                candle_starts: all_candles[visible_start_index..visible_end_index].iter().map(|candle| candle.timestamp).collect(),
+                timeframe_ms: self.timeframe_ms,
+                min_price: overall_min_price,
+                max_price: overall_max_price,
+                granularity: self.price_scale(&selected_symbol),
+                color_scheme: self.heatmap_color_scheme,
+            };
+            let strip = Rect::from_x_y_ranges(
+                chart_rect.min.x + visible_start_index as f32 * candle_width + self.pan_x
+                    ..=chart_rect.min.x + visible_end_index as f32 * candle_width + self.pan_x,
+                chart_rect.y_range(),
+            );
+            let opacity = self.heatmap_opacity;
+            if let Some(wide_heatmap) = &mut self.wide_heatmap {
+                wide_heatmap.paint(ui, view, strip, chart_rect, opacity);
+            }
+        }
+
         // Draw visible candles
         for (i, candle) in all_candles.iter().enumerate().skip(visible_start_index).take(visible_end_index - visible_start_index) {
             let x = chart_rect.min.x + i as f32 * candle_width + self.pan_x;
 
             // Only draw if candle is within chart bounds
             if x + candle_width >= chart_rect.min.x && x <= chart_rect.max.x {
-                self.draw_footprint_candle(ui, candle, x, candle_width, chart_rect, overall_min_price, overall_max_price, max_volume);
+                // The full-depth texture, when it reaches this candle, replaces its heatmap column
+                let covered_by_wide = self.wide_heatmap_start().is_some_and(|start| candle.timestamp + self.timeframe_ms > start);
+                let heatmap = if self.enable_heatmap && !covered_by_wide { self.heatmap_column(candle.timestamp) } else { None };
+                self.draw_footprint_candle(ui, candle, heatmap.as_deref(), x, candle_width, chart_rect, overall_min_price, overall_max_price, max_volume);
             }
         }
 
+        if self.enable_heatmap {
+            self.draw_book_patterns(ui, chart_rect, &all_candles, candle_width, overall_min_price, overall_max_price);
+        }
+
         // Draw drawing tools on top of candles
         self.draw_drawing_tools(ui, chart_rect, &all_candles, candle_width, overall_min_price, overall_max_price);
 
         // Draw indicators on top of everything
         self.draw_indicators(ui, chart_rect, &all_candles, candle_width, overall_min_price, overall_max_price);
+
+        if self.show_open_interest {
+            self.draw_open_interest(ui, oi_rect, &all_candles, candle_width);
+        }
+    }
+
+    /// Open interest at each candle's close as a line, with its change from the
+    /// previous candle as bars around the midline
+    fn draw_open_interest(&self, ui: &mut Ui, rect: Rect, candles: &[FootprintCandle], candle_width: f32) {
+        let painter = ui.painter().with_clip_rect(rect);
+        painter.rect_filled(rect, 0.0, Color32::from_rgb(25, 25, 25));
+
+        let readings = match self.open_interest.get(&self.selected_symbol) {
+            Some(readings) if !readings.is_empty() => readings,
+            _ => {
+                painter.text(
+                    rect.left_top() + Vec2::new(6.0, 4.0),
+                    egui::Align2::LEFT_TOP,
+                    "Open interest: no data",
+                    egui::FontId::proportional(11.0),
+                    Color32::GRAY,
+                );
+                return;
+            }
+        };
+
+        // Last reading before each candle closes
+        let values: Vec<Option<f64>> = candles
+            .iter()
+            .map(|candle| {
+                let close_time = candle.timestamp + self.timeframe_ms;
+                readings
+                    .partition_point(|r| r.timestamp < close_time)
+                    .checked_sub(1)
+                    .map(|i| readings[i].open_interest)
+            })
+            .collect();
+        let deltas: Vec<Option<f64>> = (0..values.len())
+            .map(|i| match (i.checked_sub(1).and_then(|p| values[p]), values[i]) {
+                (Some(previous), Some(current)) => Some(current - previous),
+                _ => None,
+            })
+            .collect();
+
+        let (min_oi, max_oi) = values.iter().flatten().fold((f64::MAX, f64::MIN), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+        let max_delta = deltas.iter().flatten().fold(0.0f64, |max, d| max.max(d.abs()));
+        let mid_y = rect.center().y;
+        let x_at = |i: usize| rect.min.x + i as f32 * candle_width + self.pan_x;
+
+        painter.line_segment([Pos2::new(rect.min.x, mid_y), Pos2::new(rect.max.x, mid_y)], Stroke::new(1.0, Color32::from_gray(60)));
+
+        // Delta bars
+        if max_delta > 0.0 {
+            for (i, delta) in deltas.iter().enumerate() {
+                let Some(delta) = delta else { continue };
+                let height = (*delta / max_delta) as f32 * (rect.height() / 2.0 - 4.0);
+                let color = if *delta >= 0.0 { Color32::from_rgb(0, 160, 0) } else { Color32::from_rgb(160, 0, 0) };
+                let bar = Rect::from_two_pos(
+                    Pos2::new(x_at(i) + candle_width * 0.2, mid_y),
+                    Pos2::new(x_at(i) + candle_width * 0.8, mid_y - height),
+                );
+                painter.rect_filled(bar, 0.0, color);
+            }
+        }
+
+        // Open interest line
+        let oi_range = (max_oi - min_oi).max(f64::EPSILON);
+        let points: Vec<Pos2> = values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, value)| {
+                value.map(|v| Pos2::new(x_at(i) + candle_width / 2.0, rect.max.y - 4.0 - ((v - min_oi) / oi_range) as f32 * (rect.height() - 8.0)))
+            })
+            .collect();
+        if points.len() >= 2 {
+            painter.add(egui::Shape::line(points, Stroke::new(1.5, Color32::from_rgb(255, 200, 60))));
+        }
+
+        let mut label = match (values.iter().rev().flatten().next(), deltas.iter().rev().flatten().next()) {
+            (Some(oi), Some(delta)) => format!("OI {:.0}  Δ {:+.0}", oi, delta),
+            (Some(oi), None) => format!("OI {:.0}", oi),
+            _ => "OI".to_string(),
+        };
+        if let Some((change, window)) = self.last_oi_spike.get(&self.selected_symbol) {
+            label.push_str(&format!("  last spike {:+.2}% / {}", change, window));
+        }
+        painter.text(
+            rect.left_top() + Vec2::new(6.0, 4.0),
+            egui::Align2::LEFT_TOP,
+            label,
+            egui::FontId::proportional(11.0),
+            Color32::from_rgb(255, 200, 60),
+        );
     }
 
     fn calculate_overall_price_range(&self, candles: &[FootprintCandle]) -> (f64, f64) {
@@ -898,12 +1264,14 @@ impl FootprintPanel {
         (min_price, max_price)
     }
 
-    fn draw_footprint_candle(&self, ui: &mut Ui, candle: &FootprintCandle, x: f32, width: f32, chart_rect: Rect, min_price: f64, max_price: f64, max_volume: u64) {
+    #[allow(clippy::too_many_arguments)]
+    fn draw_footprint_candle(&self, ui: &mut Ui, candle: &FootprintCandle, heatmap: Option<&HeatmapColumn>, x: f32, width: f32, chart_rect: Rect, min_price: f64, max_price: f64, max_volume: u64) {
         let price_range = max_price - min_price;
 
         // Draw LOB Heatmap background FIRST (if enabled)
-        if self.enable_heatmap {
-            self.draw_heatmap_for_candle(ui, candle, x, width, chart_rect, min_price, max_price);
+        if let Some(column) = heatmap {
+            let column_rect = Rect::from_x_y_ranges(x..=x + width, chart_rect.y_range());
+            self.draw_heatmap_for_candle(ui, column, column_rect, min_price, max_price);
         }
 
         // Draw OHLC outline
@@ -1004,78 +1372,123 @@ impl FootprintPanel {
         }
     }
 
-    fn draw_heatmap_for_candle(&self, ui: &mut Ui, candle: &FootprintCandle, x: f32, width: f32, chart_rect: Rect, min_price: f64, max_price: f64) {
-        let price_range = max_price - min_price;
-
-        // Find the nearest depth snapshot for this candle's timestamp
-        // We'll use the most recent snapshot available (depth snapshots are per-symbol)
+    /// Resting liquidity behind the candle opening at `candle_start`, averaged over
+    /// its span from the heatmap history; without a history store every candle
+    /// shows the latest book. Columns are cached until a newer column closes inside
+    /// the candle, so settled candles are not re-bucketed every frame.
+    fn heatmap_column(&mut self, candle_start: u64) -> Option<Arc<HeatmapColumn>> {
         let symbol = &self.selected_symbol;
-        if let Some(snapshots) = self.depth_snapshots.get(symbol) {
-            if let Some(snapshot) = snapshots.back() {
-                // Find max volume for normalization
-                let max_bid_volume = snapshot.bids.iter().map(|(_, qty)| *qty).fold(0.0f64, f64::max);
-                let max_ask_volume = snapshot.asks.iter().map(|(_, qty)| *qty).fold(0.0f64, f64::max);
-                let max_volume = max_bid_volume.max(max_ask_volume);
-
-                if max_volume > 0.0 {
-                    // Draw bid levels (green) - only within visible price range
-                    for (price, quantity) in &snapshot.bids {
-                        if *price >= min_price && *price <= max_price {
-                            let cell_y = chart_rect.max.y - ((*price - min_price) / price_range) as f32 * chart_rect.height();
-
-                            // Calculate height for this price level (proportional to tick size)
-                            let level_height = (candle.tick_size / price_range) as f32 * chart_rect.height();
-                            let level_height = level_height.max(2.0); // Minimum 2px
-
-                            let rect = Rect::from_min_size(
-                                Pos2::new(x, cell_y - level_height / 2.0),
-                                Vec2::new(width, level_height)
-                            );
+        let Some(store) = &self.heatmap_store else {
+            let snapshot = self.depth_snapshots.get(symbol)?.snapshots.last()?;
+            let cached = self.latest_heatmap_column.as_ref().is_some_and(|column| {
+                column.symbol == *symbol && column.start == snapshot.timestamp && column.granularity == snapshot.granularity
+            });
+            if !cached {
+                self.latest_heatmap_column = Some(Arc::new(HeatmapColumn {
+                    symbol: symbol.clone(),
+                    start: snapshot.timestamp,
+                    resolution_ms: 0,
+                    granularity: snapshot.granularity,
+                    bids: snapshot.aggregated_bids.iter().map(|(tick, quantity)| (*tick, *quantity)).collect(),
+                    asks: snapshot.aggregated_asks.iter().map(|(tick, quantity)| (*tick, *quantity)).collect(),
+                }));
+            }
+            return self.latest_heatmap_column.clone();
+        };
 
-                            // Calculate color intensity based on volume
-                            let volume_pct = (*quantity / max_volume) as f32;
-                            let color = self.heatmap_color_scheme.get_bid_color(volume_pct);
+        let end = candle_start + self.timeframe_ms;
+        let (closed_through, restored) = store.revision(symbol);
+        let key = (symbol.clone(), candle_start, self.timeframe_ms, self.depth_aggregation.tick_multiple(symbol));
+        if let Some(cached) = self.heatmap_columns.get(&key) {
+            let settled = cached.closed_through >= end || cached.closed_through == closed_through;
+            if settled && cached.restored == restored {
+                return cached.column.clone();
+            }
+        }
 
-                            // Apply user-controlled opacity
-                            let color_with_opacity = Color32::from_rgba_premultiplied(
-                                color.r(),
-                                color.g(),
-                                color.b(),
-                                (color.a() as f32 * self.heatmap_opacity) as u8
-                            );
+        let column = store.column_between(symbol, candle_start, end, self.price_scale(symbol)).map(Arc::new);
+        if self.heatmap_columns.len() >= MAX_CACHED_HEATMAP_COLUMNS {
+            self.heatmap_columns.clear();
+        }
+        self.heatmap_columns.insert(key, CachedHeatmapColumn { column: column.clone(), closed_through, restored });
+        column
+    }
 
-                            ui.painter().rect_filled(rect, 0.0, color_with_opacity);
-                        }
-                    }
+    /// Paint `column` behind one candle; `column_rect` is the candle's slice of the chart, full height
+    fn draw_heatmap_for_candle(&self, ui: &mut Ui, column: &HeatmapColumn, column_rect: Rect, min_price: f64, max_price: f64) {
+        let price_range = max_price - min_price;
 
-                    // Draw ask levels (red) - only within visible price range
-                    for (price, quantity) in &snapshot.asks {
-                        if *price >= min_price && *price <= max_price {
-                            let cell_y = chart_rect.max.y - ((*price - min_price) / price_range) as f32 * chart_rect.height();
+        // Levels are bucketed at the footprint price scale, one row per bin
+        let granularity = column.granularity;
+        let level_height = ((granularity / price_range) as f32 * column_rect.height()).max(2.0); // Minimum 2px
 
-                            let level_height = (candle.tick_size / price_range) as f32 * chart_rect.height();
-                            let level_height = level_height.max(2.0);
+        // Find max volume for normalization
+        let max_volume = column.max_quantity();
+        if max_volume <= 0.0 {
+            return;
+        }
 
-                            let rect = Rect::from_min_size(
-                                Pos2::new(x, cell_y - level_height / 2.0),
-                                Vec2::new(width, level_height)
-                            );
+        // Bid levels (green) then ask levels (red), only within the visible price range
+        let sides = [(&column.bids, false), (&column.asks, true)];
+        for (levels, is_ask) in sides {
+            for (price_tick, quantity) in levels {
+                let price = *price_tick as f64 * granularity;
+                if price < min_price || price > max_price {
+                    continue;
+                }
+                let cell_y = column_rect.max.y - ((price - min_price) / price_range) as f32 * column_rect.height();
 
-                            let volume_pct = (*quantity / max_volume) as f32;
-                            let color = self.heatmap_color_scheme.get_ask_color(volume_pct);
+                let rect = Rect::from_min_size(
+                    Pos2::new(column_rect.min.x, cell_y - level_height / 2.0),
+                    Vec2::new(column_rect.width(), level_height)
+                );
 
-                            let color_with_opacity = Color32::from_rgba_premultiplied(
-                                color.r(),
-                                color.g(),
-                                color.b(),
-                                (color.a() as f32 * self.heatmap_opacity) as u8
-                            );
+                // Calculate color intensity based on volume
+                let volume_pct = (*quantity / max_volume) as f32;
+                let color = if is_ask {
+                    self.heatmap_color_scheme.get_ask_color(volume_pct)
+                } else {
+                    self.heatmap_color_scheme.get_bid_color(volume_pct)
+                };
 
-                            ui.painter().rect_filled(rect, 0.0, color_with_opacity);
-                        }
-                    }
-                }
+                // Apply user-controlled opacity
+                let color_with_opacity = Color32::from_rgba_premultiplied(
+                    color.r(),
+                    color.g(),
+                    color.b(),
+                    (color.a() as f32 * self.heatmap_opacity) as u8
+                );
+
+                ui.painter().rect_filled(rect, 0.0, color_with_opacity);
+            }
+        }
+    }
+
+    /// Spoof (orange) and iceberg (blue) detections at their price, in the candle they happened
+    fn draw_book_patterns(&self, ui: &mut Ui, chart_rect: Rect, candles: &[FootprintCandle], candle_width: f32, min_price: f64, max_price: f64) {
+        let Some(detections) = self.book_patterns.get(&self.selected_symbol) else {
+            return;
+        };
+        let price_range = max_price - min_price;
+        let painter = ui.painter().with_clip_rect(chart_rect);
+
+        for alert in detections {
+            if alert.price < min_price || alert.price > max_price {
+                continue;
             }
+            let index = candles.partition_point(|candle| candle.timestamp + self.timeframe_ms <= alert.timestamp);
+            if candles.get(index).is_none_or(|candle| candle.timestamp > alert.timestamp) {
+                continue;
+            }
+
+            let x = chart_rect.min.x + index as f32 * candle_width + self.pan_x + candle_width / 2.0;
+            let y = chart_rect.max.y - ((alert.price - min_price) / price_range) as f32 * chart_rect.height();
+            let (label, color) = match alert.pattern {
+                BookPattern::Spoof => ("S", Color32::from_rgb(255, 140, 0)),
+                BookPattern::Iceberg => ("I", Color32::from_rgb(0, 200, 255)),
+            };
+            painter.circle(Pos2::new(x, y), 6.0, Color32::from_black_alpha(160), Stroke::new(1.5, color));
+            painter.text(Pos2::new(x, y), egui::Align2::CENTER_CENTER, label, egui::FontId::monospace(9.0), color);
         }
     }
 
@@ -1591,6 +2004,29 @@ impl FootprintPanel {
         }
     }
 
+    /// Track a market's listings; "All" and "New" follow the exchange once it has been read
+    pub fn apply_universe_change(&mut self, change: UniverseChange) {
+        let initial = !self.listed_symbols.contains_key(&change.market);
+        if !initial {
+            for symbol in change.added.iter().rev() {
+                if !self.new_listings.contains(symbol) {
+                    self.new_listings.insert(0, symbol.clone());
+                }
+            }
+        }
+        self.new_listings.retain(|symbol| !change.removed.contains(symbol));
+        self.listed_symbols.insert(change.market, change.symbols);
+
+        if self.symbol_category == "All" || self.symbol_category == "New" {
+            self.update_symbols_for_category();
+        } else if !change.removed.is_empty() {
+            self.symbols.retain(|symbol| !change.removed.contains(symbol));
+            if !self.symbols.contains(&self.selected_symbol) {
+                self.selected_symbol = self.symbols.first().unwrap_or(&"BTCUSDT".to_string()).clone();
+            }
+        }
+    }
+
     fn update_symbols_for_category(&mut self) {
         let symbols_by_category = BinanceSymbols::get_symbols_by_category();
 
@@ -1603,11 +2039,31 @@ impl FootprintPanel {
             "AI" => symbols_by_category.get("AI").unwrap_or(&vec![]).clone(),
             "Meme" => symbols_by_category.get("Meme").unwrap_or(&vec![]).clone(),
             "Infrastructure" => symbols_by_category.get("Infrastructure").unwrap_or(&vec![]).clone(),
-            "New" => symbols_by_category.get("New").unwrap_or(&vec![]).clone(),
-            "All" => BinanceSymbols::get_all_symbols(),
+            "New" => {
+                let mut symbols = self.new_listings.clone();
+                for symbol in symbols_by_category.get("New").unwrap_or(&vec![]) {
+                    if !symbols.contains(symbol) {
+                        symbols.push(symbol.clone());
+                    }
+                }
+                symbols
+            }
+            "All" if !self.listed_symbols.is_empty() => {
+                let mut symbols: Vec<String> = self.listed_symbols.values().flatten().cloned().collect();
+                symbols.sort();
+                symbols.dedup();
+                symbols
+            }
+            "All" if !self.symbol_registry.is_empty() => self.symbol_registry.symbols(),
             _ => BinanceSymbols::get_default_symbols(),
         };
 
+        // Delisted symbols leave every category once the listing is known
+        if !self.listed_symbols.is_empty() {
+            let listed = &self.listed_symbols;
+            self.symbols.retain(|symbol| listed.values().any(|symbols| symbols.contains(symbol)));
+        }
+
         // Update selected symbol if it's not in the new list
         if !self.symbols.contains(&self.selected_symbol) {
             self.selected_symbol = self.symbols.first().unwrap_or(&"BTCUSDT".to_string()).clone();
@@ -1940,4 +2396,134 @@ impl FootprintPanel {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{FootprintLevel, FOOTPRINT_BASE_TIMEFRAME};
+
+    fn trade(trade_id: u64, timestamp: u64, price: f64, quantity: f64, is_buyer_maker: bool) -> OrderflowEvent {
+        OrderflowEvent { symbol: "BTCUSDT".to_string(), timestamp, price, quantity, is_buyer_maker, trade_id }
+    }
+
+    #[test]
+    fn test_stored_candles_are_reaggregated_at_the_panel_scale() {
+        let stored = StoredFootprintCandle {
+            symbol: "BTCUSDT".to_string(),
+            timeframe: FOOTPRINT_BASE_TIMEFRAME.to_string(),
+            open_time: 1_700_000_040_000,
+            tick_size: 0.1,
+            open: 37000.1,
+            high: 37002.5,
+            low: 36999.9,
+            close: 37002.5,
+            levels: vec![
+                FootprintLevel { tick: 369_999, bid_volume: 4.0, ask_volume: 0.0 },
+                FootprintLevel { tick: 370_001, bid_volume: 1.5, ask_volume: 0.75 },
+                FootprintLevel { tick: 370_002, bid_volume: 0.5, ask_volume: 2.25 },
+                FootprintLevel { tick: 370_025, bid_volume: 0.0, ask_volume: 1.0 },
+            ],
+        };
+
+        let restored = FootprintCandle::from_stored(&stored, 0.1);
+        assert_eq!(restored.timestamp, stored.open_time);
+        assert_eq!((restored.open, restored.high, restored.low, restored.close), (37000.1, 37002.5, 36999.9, 37002.5));
+        assert_eq!(restored.cells.len(), 4);
+        assert!((restored.cells[&370_001].price - 37000.1).abs() < 1e-9);
+
+        // Ten contract ticks per bin: fractional volumes are summed before truncating
+        let restored = FootprintCandle::from_stored(&stored, 1.0);
+        assert_eq!(restored.tick_size, 1.0);
+        assert_eq!(restored.cells.keys().copied().collect::<Vec<_>>(), vec![37_000, 37_003]);
+        let cell = &restored.cells[&37_000];
+        assert_eq!((cell.bid_volume, cell.ask_volume), (6, 3));
+        assert!((cell.price - 37_000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_price_scale_follows_each_symbols_tick_multiple() {
+        let aggregation = DepthAggregation::new(DEFAULT_TICK_MULTIPLE);
+        aggregation.set_tick_multiple("ETHUSDT", 2);
+
+        let mut panel = FootprintPanel::new();
+        panel.set_depth_aggregation(aggregation.clone());
+        let btc_tick = panel.tick_size("BTCUSDT");
+        let eth_tick = panel.tick_size("ETHUSDT");
+        assert_eq!(panel.price_scale("BTCUSDT"), btc_tick * 10.0);
+        assert_eq!(panel.price_scale("ETHUSDT"), eth_tick * 2.0);
+
+        // Changes made by the order book side are seen without rewiring the panel
+        aggregation.set_tick_multiple("BTCUSDT", 50);
+        assert_eq!(panel.price_scale("BTCUSDT"), btc_tick * 50.0);
+    }
+
+    #[test]
+    fn test_candle_heatmap_columns_are_cached_until_a_column_closes_inside() {
+        use crate::config::settings::HeatmapConfig;
+
+        let store = HeatmapStore::new(&HeatmapConfig::default());
+        let snapshot = |timestamp: u64, bid: f64| DepthSnapshot { bids: vec![(99.0, bid)], asks: vec![(101.0, 1.0)], timestamp };
+        let mut panel = FootprintPanel::new();
+        panel.selected_symbol = "BTCUSDT".to_string();
+        panel.timeframe_ms = 60_000;
+        panel.set_heatmap_store(store.clone());
+        let granularity = panel.price_scale("BTCUSDT");
+
+        store.add_snapshot("BTCUSDT", &snapshot(30_000, 2.0), granularity);
+        store.add_snapshot("BTCUSDT", &snapshot(61_000, 2.0), granularity);
+        let first = panel.heatmap_column(0).unwrap();
+        let current = panel.heatmap_column(60_000).unwrap();
+        assert!(Arc::ptr_eq(&first, &panel.heatmap_column(0).unwrap()));
+
+        // A column closing inside the current candle refreshes it; the settled candle keeps its column
+        store.add_snapshot("BTCUSDT", &snapshot(61_500, 6.0), granularity);
+        assert!(Arc::ptr_eq(&current, &panel.heatmap_column(60_000).unwrap()));
+        store.add_snapshot("BTCUSDT", &snapshot(62_000, 6.0), granularity);
+        let refreshed = panel.heatmap_column(60_000).unwrap();
+        assert_eq!((current.bids[0].1, refreshed.bids[0].1), (2.0, 5.0));
+        assert!(Arc::ptr_eq(&first, &panel.heatmap_column(0).unwrap()));
+
+        // A different scale is a different column
+        panel.depth_aggregation.set_tick_multiple("BTCUSDT", 100);
+        assert!(!Arc::ptr_eq(&first, &panel.heatmap_column(0).unwrap()));
+    }
+
+    #[test]
+    fn test_history_is_placed_before_the_live_candles() {
+        const MINUTE: u64 = 60_000;
+        let base = 1_700_000_040_000;
+
+        let mut panel = FootprintPanel::new();
+        panel.add_orderflow_event(&trade(10, base + 3 * MINUTE, 37000.0, 2.0, true));
+        panel.add_orderflow_event(&trade(11, base + 4 * MINUTE, 37000.0, 2.0, false));
+
+        // Minute 0 was stored earlier, minute 1 is rebuilt from trades, and trades
+        // from the live minutes onwards are left to the live candles
+        let stored = StoredFootprintCandle {
+            symbol: "BTCUSDT".to_string(),
+            timeframe: FOOTPRINT_BASE_TIMEFRAME.to_string(),
+            open_time: base,
+            tick_size: 0.1,
+            open: 37000.0,
+            high: 37000.0,
+            low: 37000.0,
+            close: 37000.0,
+            levels: vec![FootprintLevel { tick: 370_000, bid_volume: 2.0, ask_volume: 0.0 }],
+        };
+        panel.load_history(&FootprintHistory {
+            symbol: "BTCUSDT".to_string(),
+            candles: vec![stored],
+            trades: vec![
+                trade(5, base + MINUTE, 37000.0, 2.0, true),
+                trade(6, base + MINUTE + 10, 37000.0, 2.0, false),
+                trade(10, base + 3 * MINUTE, 37000.0, 2.0, true),
+            ],
+        });
+
+        let starts: Vec<u64> = panel.base_completed_candles["BTCUSDT"].iter().map(|candle| candle.timestamp).collect();
+        assert_eq!(starts, vec![base, base + MINUTE, base + 3 * MINUTE]);
+        let live = &panel.base_completed_candles["BTCUSDT"][2];
+        assert_eq!(live.cells.values().map(|cell| cell.total_volume()).sum::<u64>(), 2);
+    }
+}
@@ -1,7 +1,7 @@
 use egui::Color32;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct HeatmapColorScheme {
     pub bid_color_low: [u8; 4],
     pub bid_color_high: [u8; 4],
@@ -150,15 +150,16 @@ mod tests {
         // Test 0% - should be close to low color
         let color_0 = scheme.get_bid_color(0.0);
         assert_eq!(color_0.r(), 0);
+        assert_eq!(color_0.g(), 100);
 
         // Test 100% - should be close to high color (with intensity)
         let color_100 = scheme.get_bid_color(1.0);
-        // With 70% intensity, should be between low and high
 
         // Test 50% - should be middle
         let color_50 = scheme.get_bid_color(0.5);
-        assert!(color_50.r() > color_0.r());
-        assert!(color_50.r() < 255);
+        // Green channel ramps from 100 towards 255
+        assert!(color_50.g() > color_0.g());
+        assert!(color_50.g() < color_100.g());
     }
 
     #[test]
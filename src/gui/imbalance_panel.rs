@@ -1,10 +1,11 @@
 use egui::{Color32, RichText, Ui};
 use std::collections::HashMap;
-use crate::data::{OrderImbalance, BinanceSymbols};
+use crate::data::{OrderImbalance, BookAnalytics, BinanceSymbols, SymbolRegistry};
 use super::{ScreenerTheme, VolumeBar, ImbalanceIndicator};
 
 pub struct ImbalancePanel {
     imbalances: HashMap<String, OrderImbalance>,
+    book_analytics: HashMap<String, BookAnalytics>,
     display_mode: DisplayMode,
     sort_by: SortBy,
     sort_ascending: bool,
@@ -16,6 +17,7 @@ pub struct ImbalancePanel {
     // Symbol management
     symbol_category: String,
     watched_symbols: Vec<String>,
+    symbol_registry: SymbolRegistry,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -23,6 +25,7 @@ enum DisplayMode {
     Table,
     Grid,
     Chart,
+    Book,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -41,6 +44,7 @@ impl ImbalancePanel {
 
         Self {
             imbalances: HashMap::new(),
+            book_analytics: HashMap::new(),
             display_mode: DisplayMode::Grid,
             sort_by: SortBy::ImbalanceRatio,
             sort_ascending: false, // Highest imbalance first
@@ -52,13 +56,24 @@ impl ImbalancePanel {
             // Symbol management
             symbol_category: "High Volume".to_string(),
             watched_symbols,
+            symbol_registry: SymbolRegistry::new(),
         }
     }
 
+    /// Source of the "All" category
+    pub fn set_symbol_registry(&mut self, registry: SymbolRegistry) {
+        self.symbol_registry = registry;
+    }
+
     pub fn add_imbalance(&mut self, imbalance: OrderImbalance) {
         self.imbalances.insert(imbalance.symbol.clone(), imbalance);
     }
 
+    /// Latest order book analytics of a symbol, shown in the Book view
+    pub fn add_book_analytics(&mut self, analytics: BookAnalytics) {
+        self.book_analytics.insert(analytics.symbol.clone(), analytics);
+    }
+
     pub fn get_symbol_count(&self) -> usize {
         self.imbalances.len()
     }
@@ -75,6 +90,7 @@ impl ImbalancePanel {
                 DisplayMode::Table => self.show_table_view(ui),
                 DisplayMode::Grid => self.show_grid_view(ui),
                 DisplayMode::Chart => self.show_chart_view(ui),
+                DisplayMode::Book => self.show_book_view(ui),
             }
         });
     }
@@ -85,6 +101,7 @@ impl ImbalancePanel {
             ui.selectable_value(&mut self.display_mode, DisplayMode::Table, "Table");
             ui.selectable_value(&mut self.display_mode, DisplayMode::Grid, "Grid");
             ui.selectable_value(&mut self.display_mode, DisplayMode::Chart, "Chart");
+            ui.selectable_value(&mut self.display_mode, DisplayMode::Book, "Book");
 
             ui.separator();
 
@@ -357,6 +374,71 @@ impl ImbalancePanel {
         ui.add_space(2.0);
     }
 
+    /// Resting-book view: spread, depth band imbalance, slippage and walls per symbol
+    fn show_book_view(&mut self, ui: &mut Ui) {
+        let filter_lower = self.filter_text.to_lowercase();
+        let mut rows: Vec<&BookAnalytics> = self.book_analytics
+            .values()
+            .filter(|analytics| filter_lower.is_empty() || analytics.symbol.to_lowercase().contains(&filter_lower))
+            .collect();
+        rows.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+        let Some(first) = rows.first() else {
+            ui.label("Waiting for synced order books...");
+            return;
+        };
+        // Every row shares the configured bands and notionals
+        let bands: Vec<f64> = first.bands.iter().map(|band| band.band_pct).collect();
+        let notionals: Vec<f64> = first.slippage.iter().map(|estimate| estimate.notional).collect();
+
+        egui::ScrollArea::both().show(ui, |ui| {
+            egui::Grid::new("book_analytics_grid").striped(true).show(ui, |ui| {
+                for header in ["Symbol", "Mid", "Spread (bps)", "Avg / Max"] {
+                    ui.label(RichText::new(header).strong());
+                }
+                for band_pct in &bands {
+                    ui.label(RichText::new(format!("Imb ±{}%", band_pct)).strong());
+                }
+                for notional in &notionals {
+                    ui.label(RichText::new(format!("Slip {} (bps)", ScreenerTheme::format_currency(*notional))).strong());
+                }
+                ui.label(RichText::new("Walls").strong());
+                ui.end_row();
+
+                for analytics in rows {
+                    let decimals = self.symbol_registry.get(&analytics.symbol).map_or(2, |info| info.price_decimals()) as u32;
+                    ui.strong(&analytics.symbol);
+                    ui.label(ScreenerTheme::format_price(analytics.mid_price, decimals));
+                    ui.label(format!("{:.2}", analytics.spread.spread_bps));
+                    ui.label(format!("{:.2} / {:.2}", analytics.spread.mean_bps, analytics.spread.max_bps));
+                    for band in &analytics.bands {
+                        ui.colored_label(
+                            ScreenerTheme::get_imbalance_color(band.imbalance),
+                            ScreenerTheme::format_imbalance_ratio(band.imbalance),
+                        );
+                    }
+                    for estimate in &analytics.slippage {
+                        let bps = |bps: Option<f64>| bps.map_or("thin".to_string(), |bps| format!("{:.1}", bps));
+                        ui.label(format!("{} / {}", bps(estimate.buy_bps), bps(estimate.sell_bps)));
+                    }
+                    ui.horizontal(|ui| {
+                        for wall in &analytics.walls {
+                            let color = if wall.side == "BID" { ScreenerTheme::BUY_COLOR } else { ScreenerTheme::SELL_COLOR };
+                            ui.colored_label(color, format!(
+                                "{} {} ({:.1}x, {:.2}%)",
+                                ScreenerTheme::format_price(wall.price, decimals),
+                                ScreenerTheme::format_volume(wall.quantity),
+                                wall.size_multiple,
+                                wall.distance_pct
+                            ));
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
     fn toggle_sort(&mut self, sort_by: SortBy) {
         if self.sort_by == sort_by {
             self.sort_ascending = !self.sort_ascending;
@@ -432,12 +514,13 @@ impl ImbalancePanel {
             "Meme" => symbols_by_category.get("Meme").unwrap_or(&vec![]).clone(),
             "Infrastructure" => symbols_by_category.get("Infrastructure").unwrap_or(&vec![]).clone(),
             "New" => symbols_by_category.get("New").unwrap_or(&vec![]).clone(),
-            "All" => BinanceSymbols::get_all_symbols(),
+            "All" if !self.symbol_registry.is_empty() => self.symbol_registry.symbols(),
             _ => BinanceSymbols::get_default_symbols(),
         };
 
         // Filter existing imbalances to only show watched symbols
         self.imbalances.retain(|symbol, _| self.watched_symbols.contains(symbol));
+        self.book_analytics.retain(|symbol, _| self.watched_symbols.contains(symbol));
     }
 
     pub fn get_watched_symbols(&self) -> &Vec<String> {
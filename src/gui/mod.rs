@@ -5,8 +5,10 @@ pub mod footprint_panel;
 pub mod liquidation_panel;
 pub mod theme;
 pub mod heatmap_colors;
+pub mod wide_heatmap;
 pub mod drawing_tools;
 pub mod dom_window;
+pub mod diagnostics_panel;
 
 pub use app::*;
 pub use screener_panel::*;
@@ -15,5 +17,7 @@ pub use footprint_panel::*;
 pub use liquidation_panel::*;
 pub use theme::*;
 pub use heatmap_colors::*;
+pub use wide_heatmap::*;
 pub use drawing_tools::*;
-pub use dom_window::*;
\ No newline at end of file
+pub use dom_window::*;
+pub use diagnostics_panel::*;
\ No newline at end of file
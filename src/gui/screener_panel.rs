@@ -1,6 +1,6 @@
 use egui::{Color32, RichText};
-use std::collections::VecDeque;
-use crate::data::BigOrderflowAlert;
+use std::collections::{HashMap, HashSet, VecDeque};
+use crate::data::{BigOrderflowAlert, FundingRateData};
 use super::{ScreenerTheme, VolumeBar};
 
 pub struct ScreenerPanel {
@@ -13,6 +13,11 @@ pub struct ScreenerPanel {
     min_notional_filter: f64,
     show_buy_only: bool,
     show_sell_only: bool,
+
+    // Per-symbol market context shown next to each alert
+    funding: HashMap<String, FundingRateData>,
+    last_prices: HashMap<String, f64>,
+    extreme_funding: HashSet<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -38,6 +43,9 @@ impl ScreenerPanel {
             min_notional_filter: 0.0,
             show_buy_only: false,
             show_sell_only: false,
+            funding: HashMap::new(),
+            last_prices: HashMap::new(),
+            extreme_funding: HashSet::new(),
         }
     }
 
@@ -50,6 +58,26 @@ impl ScreenerPanel {
         }
     }
 
+    pub fn update_funding(&mut self, readings: &[FundingRateData]) {
+        for reading in readings {
+            self.funding.insert(reading.symbol.clone(), reading.clone());
+        }
+    }
+
+    pub fn update_last_price(&mut self, symbol: &str, price: f64) {
+        match self.last_prices.get_mut(symbol) {
+            Some(last_price) => *last_price = price,
+            None => {
+                self.last_prices.insert(symbol.to_string(), price);
+            }
+        }
+    }
+
+    /// Highlight the symbol's funding after a FundingExtreme alert
+    pub fn mark_funding_extreme(&mut self, symbol: String) {
+        self.extreme_funding.insert(symbol);
+    }
+
     pub fn get_alert_count(&self) -> usize {
         self.alerts.len()
     }
@@ -114,6 +142,8 @@ impl ScreenerPanel {
             .column(Column::auto().resizable(true)) // Price
             .column(Column::auto().resizable(true)) // % of Daily
             .column(Column::auto().resizable(true)) // Notional
+            .column(Column::auto().resizable(true)) // Funding
+            .column(Column::auto().resizable(true)) // Basis
             .column(Column::remainder())            // Volume Bar
             .header(25.0, |mut header| {
                 header.col(|ui| {
@@ -151,6 +181,12 @@ impl ScreenerPanel {
                         self.toggle_sort(SortColumn::Notional);
                     }
                 });
+                header.col(|ui| {
+                    ui.label("Funding");
+                });
+                header.col(|ui| {
+                    ui.label("Basis");
+                });
                 header.col(|ui| {
                     ui.label("Volume");
                 });
@@ -211,6 +247,42 @@ impl ScreenerPanel {
             ui.label(ScreenerTheme::format_currency(alert.notional_value));
         });
 
+        let funding = self.funding.get(&alert.symbol);
+        row.col(|ui| {
+            match funding {
+                Some(funding) => {
+                    let color = if self.extreme_funding.contains(&alert.symbol) {
+                        ScreenerTheme::ERROR
+                    } else if funding.funding_rate >= 0.0 {
+                        ScreenerTheme::BUY_COLOR
+                    } else {
+                        ScreenerTheme::SELL_COLOR
+                    };
+                    ui.colored_label(color, format!("{:.4}%", funding.funding_rate * 100.0))
+                        .on_hover_text(format!("{:.1}% annualized", funding.funding_cost_annual()));
+                }
+                None => {
+                    ui.label("-");
+                }
+            }
+        });
+
+        row.col(|ui| {
+            // Mark price premium over the last trade
+            match (funding, self.last_prices.get(&alert.symbol)) {
+                (Some(funding), Some(&last_price)) => {
+                    ui.label(format!("{:+.1} bps", funding.basis_bps(last_price)))
+                        .on_hover_text(format!(
+                            "Mark {} / Index {} / Last {}",
+                            funding.mark_price, funding.index_price, last_price
+                        ));
+                }
+                _ => {
+                    ui.label("-");
+                }
+            }
+        });
+
         row.col(|ui| {
             // Volume intensity bar
             let intensity = (alert.percentage_of_daily / 2.0).min(1.0); // Scale to 0-1
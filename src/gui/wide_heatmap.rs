@@ -0,0 +1,253 @@
+use egui::{Color32, ColorImage, Pos2, Rect, TextureHandle, TextureOptions, Ui};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use crate::data::DepthSnapshot;
+use super::HeatmapColorScheme;
+
+/// Texture columns per visible candle while the texture stays under the side limit
+const COLUMNS_PER_CANDLE: usize = 8;
+const MAX_TEXTURE_SIDE: usize = 512;
+/// Snapshots closer together than this replace each other in the history
+const COLUMN_SPACING_MS: u64 = 1_000;
+/// A texture column is left empty when the newest snapshot before it is older than this
+const MAX_SNAPSHOT_AGE_MS: u64 = 5_000;
+/// New snapshots alone re-rasterize the texture at most this often
+const TEXTURE_REFRESH_MS: u64 = 250;
+
+/// One full-depth snapshot, bucketed at the footprint price scale
+#[derive(Debug, Clone)]
+struct WideColumn {
+    timestamp: u64,
+    granularity: f64,
+    bids: Vec<(i64, f32)>,
+    asks: Vec<(i64, f32)>,
+}
+
+impl WideColumn {
+    fn new(snapshot: &DepthSnapshot, granularity: f64) -> Self {
+        let bucket = |levels: &[(f64, f64)]| {
+            let mut buckets: BTreeMap<i64, f32> = BTreeMap::new();
+            for &(price, quantity) in levels {
+                *buckets.entry((price / granularity).round() as i64).or_default() += quantity as f32;
+            }
+            buckets.into_iter().collect()
+        };
+        Self {
+            timestamp: snapshot.timestamp,
+            granularity,
+            bids: bucket(&snapshot.bids),
+            asks: bucket(&snapshot.asks),
+        }
+    }
+}
+
+/// What the cached texture shows; any change re-rasterizes it immediately
+#[derive(Debug, Clone, PartialEq)]
+pub struct WideHeatmapView {
+    pub symbol: String,
+    /// Open time of each visible candle, left to right
+    pub candle_starts: Vec<u64>,
+    pub timeframe_ms: u64,
+    pub min_price: f64,
+    pub max_price: f64,
+    /// Price step of one texture row
+    pub granularity: f64,
+    pub color_scheme: HeatmapColorScheme,
+}
+
+impl WideHeatmapView {
+    fn size(&self) -> [usize; 2] {
+        let candles = self.candle_starts.len().max(1);
+        let per_candle = (MAX_TEXTURE_SIDE / candles).clamp(1, COLUMNS_PER_CANDLE);
+        let rows = ((self.max_price - self.min_price) / self.granularity).ceil().max(1.0) as usize;
+        [candles * per_candle, rows.min(MAX_TEXTURE_SIDE)]
+    }
+}
+
+/// Full-depth book history of the wide heatmap focus symbols, painted as one
+/// CPU-rasterized texture behind the visible candles instead of a rect per level
+pub struct WideHeatmap {
+    columns: HashMap<String, VecDeque<WideColumn>>,
+    history_ms: u64,
+    version: u64, // bumped on every stored snapshot
+    texture: Option<TextureHandle>,
+    built: Option<(WideHeatmapView, u64, Instant)>, // view, data version, time
+}
+
+impl WideHeatmap {
+    pub fn new(history_secs: u64) -> Self {
+        Self {
+            columns: HashMap::new(),
+            history_ms: history_secs * 1_000,
+            version: 0,
+            texture: None,
+            built: None,
+        }
+    }
+
+    pub fn add_snapshot(&mut self, symbol: &str, snapshot: &DepthSnapshot, granularity: f64) {
+        let column = WideColumn::new(snapshot, granularity);
+        let history = self.columns.entry(symbol.to_string()).or_default();
+        match history.back_mut() {
+            Some(last) if column.timestamp < last.timestamp => return,
+            Some(last) if column.timestamp - last.timestamp < COLUMN_SPACING_MS => *last = column,
+            _ => history.push_back(column),
+        }
+
+        let cutoff = snapshot.timestamp.saturating_sub(self.history_ms);
+        while history.front().is_some_and(|column| column.timestamp < cutoff) {
+            history.pop_front();
+        }
+        self.version += 1;
+    }
+
+    /// Time of the oldest full-depth snapshot of `symbol`, if any
+    pub fn oldest(&self, symbol: &str) -> Option<u64> {
+        self.columns.get(symbol)?.front().map(|column| column.timestamp)
+    }
+
+    /// Paint the texture for `view` over `rect`, the visible candles' strip of the chart.
+    /// Unchanged views reuse the cached texture until new snapshots are due for a redraw.
+    pub fn paint(&mut self, ui: &mut Ui, view: WideHeatmapView, rect: Rect, clip_rect: Rect, opacity: f32) {
+        let stale = match &self.built {
+            Some((built_view, built_version, built_at)) => {
+                *built_view != view
+                    || (*built_version != self.version
+                        && built_at.elapsed() >= Duration::from_millis(TEXTURE_REFRESH_MS))
+            }
+            None => true,
+        };
+        if stale || self.texture.is_none() {
+            let image = self.rasterize(&view);
+            match &mut self.texture {
+                Some(texture) => texture.set(image, TextureOptions::NEAREST),
+                None => {
+                    self.texture = Some(ui.ctx().load_texture("wide_heatmap", image, TextureOptions::NEAREST));
+                }
+            }
+            self.built = Some((view, self.version, Instant::now()));
+        }
+
+        if let Some(texture) = &self.texture {
+            let uv = Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0));
+            let tint = Color32::from_white_alpha((opacity.clamp(0.0, 1.0) * 255.0) as u8);
+            ui.painter().with_clip_rect(clip_rect).image(texture.id(), rect, uv, tint);
+        }
+    }
+
+    /// One texel row per price bin and a few columns per candle, each column the
+    /// newest snapshot before its end; quantities are normalized over the whole image
+    fn rasterize(&self, view: &WideHeatmapView) -> ColorImage {
+        let [width, height] = view.size();
+        let mut image = ColorImage::new([width, height], Color32::TRANSPARENT);
+        let Some(history) = self.columns.get(&view.symbol) else {
+            return image;
+        };
+        let price_range = view.max_price - view.min_price;
+        if price_range <= 0.0 || view.candle_starts.is_empty() {
+            return image;
+        }
+
+        let per_candle = width / view.candle_starts.len();
+        let slot_ms = (view.timeframe_ms / per_candle as u64).max(1);
+        let mut bids = vec![0.0f32; width * height];
+        let mut asks = vec![0.0f32; width * height];
+        for (x, (candle, slot)) in view.candle_starts.iter()
+            .flat_map(|start| (0..per_candle).map(move |slot| (*start, slot as u64)))
+            .enumerate()
+        {
+            let slot_end = candle + (slot + 1) * slot_ms;
+            let newer = history.partition_point(|column| column.timestamp < slot_end);
+            let Some(column) = newer.checked_sub(1).map(|index| &history[index]) else {
+                continue;
+            };
+            if column.timestamp + MAX_SNAPSHOT_AGE_MS < slot_end - slot_ms {
+                continue;
+            }
+
+            for (levels, texels) in [(&column.bids, &mut bids), (&column.asks, &mut asks)] {
+                for &(tick, quantity) in levels {
+                    let from_top = (view.max_price - tick as f64 * column.granularity) / price_range;
+                    if !(0.0..1.0).contains(&from_top) {
+                        continue;
+                    }
+                    let y = (from_top * height as f64) as usize;
+                    texels[y * width + x] += quantity;
+                }
+            }
+        }
+
+        let max_quantity = bids.iter().chain(&asks).fold(0.0f32, |max, &quantity| max.max(quantity));
+        if max_quantity <= 0.0 {
+            return image;
+        }
+        for (pixel, (&bid, &ask)) in image.pixels.iter_mut().zip(bids.iter().zip(&asks)) {
+            *pixel = if ask > bid {
+                view.color_scheme.get_ask_color(ask / max_quantity)
+            } else if bid > 0.0 {
+                view.color_scheme.get_bid_color(bid / max_quantity)
+            } else {
+                continue;
+            };
+        }
+        image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(timestamp: u64, bids: Vec<(f64, f64)>, asks: Vec<(f64, f64)>) -> DepthSnapshot {
+        DepthSnapshot { bids, asks, timestamp }
+    }
+
+    fn view(candle_starts: Vec<u64>) -> WideHeatmapView {
+        WideHeatmapView {
+            symbol: "BTCUSDT".to_string(),
+            candle_starts,
+            timeframe_ms: 60_000,
+            min_price: 90.0,
+            max_price: 110.0,
+            granularity: 1.0,
+            color_scheme: HeatmapColorScheme::default(),
+        }
+    }
+
+    #[test]
+    fn test_history_thins_and_expires_snapshots() {
+        let mut heatmap = WideHeatmap::new(10);
+        heatmap.add_snapshot("BTCUSDT", &snapshot(1_000, vec![(99.4, 1.0), (98.6, 2.0)], vec![]), 1.0);
+        heatmap.add_snapshot("BTCUSDT", &snapshot(1_250, vec![(99.0, 5.0)], vec![]), 1.0);
+        let history = &heatmap.columns["BTCUSDT"];
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].bids, vec![(99, 5.0)]);
+
+        heatmap.add_snapshot("BTCUSDT", &snapshot(2_500, vec![(99.4, 1.0), (98.6, 2.0)], vec![]), 1.0);
+        assert_eq!(heatmap.columns["BTCUSDT"][1].bids, vec![(99, 3.0)]);
+
+        heatmap.add_snapshot("BTCUSDT", &snapshot(12_000, vec![], vec![(101.0, 1.0)]), 1.0);
+        assert_eq!(heatmap.oldest("BTCUSDT"), Some(2_500));
+        assert_eq!(heatmap.version, 4);
+    }
+
+    #[test]
+    fn test_rasterize_places_levels_by_time_and_price() {
+        let mut heatmap = WideHeatmap::new(3_600);
+        heatmap.add_snapshot("BTCUSDT", &snapshot(60_000, vec![(99.0, 2.0)], vec![(101.0, 4.0)]), 1.0);
+
+        let view = view(vec![0, 60_000, 120_000]);
+        assert_eq!(view.size(), [24, 20]);
+        let image = heatmap.rasterize(&view);
+
+        // Rows from the top: 101 -> 9, 99 -> 11; the first candle predates the book
+        let pixel = |x: usize, y: usize| image.pixels[y * 24 + x];
+        let scheme = HeatmapColorScheme::default();
+        assert_eq!(pixel(8, 9), scheme.get_ask_color(1.0));
+        assert_eq!(pixel(8, 11), scheme.get_bid_color(0.5));
+        // Later columns wait for newer snapshots instead of stretching this one
+        assert_eq!(pixel(7, 11), Color32::TRANSPARENT);
+        assert_eq!(pixel(9, 11), Color32::TRANSPARENT);
+        assert_eq!(pixel(8, 10), Color32::TRANSPARENT);
+    }
+}
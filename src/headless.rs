@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use tokio::sync::{mpsc, watch};
+use tracing::{info, warn};
+
+use crate::config::Settings;
+use crate::data::*;
+
+/// Imbalance ratio above which the daemon logs an alert
+const IMBALANCE_ALERT_THRESHOLD: f64 = 0.5;
+/// Minimum spacing between imbalance alerts for one symbol
+const IMBALANCE_ALERT_COOLDOWN_MS: u64 = 60_000;
+
+/// Pipeline outputs that the GUI would normally consume
+pub struct HeadlessChannels {
+    pub orderflow: Subscription<OrderflowEvent>,
+    pub imbalance: mpsc::Receiver<OrderImbalance>,
+    pub liquidation: Subscription<LiquidationEvent>,
+    pub volume: mpsc::Receiver<VolumeProfile>,
+    pub gui_update: mpsc::Receiver<GuiUpdate>,
+    pub depth_snapshot: Subscription<(String, DepthSnapshot)>,
+    pub alerts: Subscription<BigOrderflowAlert>,
+}
+
+/// Replaces the GUI when running as a daemon: consumes the pipeline outputs
+/// and logs alerts until shutdown. Persistence happens upstream in the
+/// `PersistenceHandle` tee, exactly as in GUI mode.
+pub struct HeadlessSink {
+    channels: HeadlessChannels,
+    liquidation_alert_threshold: f64,
+    last_imbalance_alert: HashMap<String, u64>,
+    alerts: u64,
+}
+
+impl HeadlessSink {
+    pub fn new(channels: HeadlessChannels, settings: &Settings) -> Self {
+        Self {
+            channels,
+            liquidation_alert_threshold: settings.analysis.liquidation_size_threshold,
+            last_imbalance_alert: HashMap::new(),
+            alerts: 0,
+        }
+    }
+
+    /// Run until shutdown, returning the number of alerts logged
+    pub async fn run(mut self, mut shutdown: watch::Receiver<bool>) -> u64 {
+        info!("Headless sink started");
+
+        loop {
+            tokio::select! {
+                Some(_) = self.channels.orderflow.recv() => {}
+                Some(imbalance) = self.channels.imbalance.recv() => self.handle_imbalance(imbalance),
+                Some(liquidation) = self.channels.liquidation.recv() => self.handle_liquidation(&liquidation),
+                Some(_) = self.channels.volume.recv() => {}
+                Some(update) = self.channels.gui_update.recv() => self.handle_gui_update(update),
+                Some(_) = self.channels.depth_snapshot.recv() => {}
+                Some(alert) = self.channels.alerts.recv() => self.handle_big_orderflow(&alert),
+                _ = shutdown.changed() => break,
+                else => break,
+            }
+        }
+
+        // Alerts already queued at shutdown still get logged
+        while let Ok(imbalance) = self.channels.imbalance.try_recv() {
+            self.handle_imbalance(imbalance);
+        }
+        while let Ok(liquidation) = self.channels.liquidation.try_recv() {
+            self.handle_liquidation(&liquidation);
+        }
+        while let Ok(alert) = self.channels.alerts.try_recv() {
+            self.handle_big_orderflow(&alert);
+        }
+
+        info!("Headless sink stopped after {} alerts", self.alerts);
+        self.alerts
+    }
+
+    fn handle_imbalance(&mut self, imbalance: OrderImbalance) {
+        if imbalance.is_significant(IMBALANCE_ALERT_THRESHOLD) {
+            let cooled_down = self.last_imbalance_alert
+                .get(&imbalance.symbol)
+                .is_none_or(|last| imbalance.timestamp >= last + IMBALANCE_ALERT_COOLDOWN_MS);
+            if cooled_down {
+                self.last_imbalance_alert.insert(imbalance.symbol.clone(), imbalance.timestamp);
+                self.alerts += 1;
+                warn!(
+                    "ALERT imbalance {} ratio {:+.2} (bid {:.2} / ask {:.2} over {}s)",
+                    imbalance.symbol, imbalance.imbalance_ratio, imbalance.bid_volume,
+                    imbalance.ask_volume, imbalance.window_duration_seconds
+                );
+            }
+        }
+    }
+
+    fn handle_liquidation(&mut self, liquidation: &LiquidationEvent) {
+        if liquidation.notional_value >= self.liquidation_alert_threshold {
+            self.alerts += 1;
+            warn!(
+                "ALERT liquidation {} {} {:.4} @ {:.4} (${:.0})",
+                liquidation.symbol, liquidation.side, liquidation.quantity,
+                liquidation.price, liquidation.notional_value
+            );
+        }
+    }
+
+    fn handle_big_orderflow(&mut self, alert: &BigOrderflowAlert) {
+        self.alerts += 1;
+        warn!(
+            "ALERT big orderflow {} {} {:.4} @ {:.4} ({:.2}% of daily)",
+            alert.symbol, alert.side, alert.quantity, alert.price, alert.percentage_of_daily
+        );
+    }
+
+    fn handle_gui_update(&mut self, update: GuiUpdate) {
+        match update {
+            GuiUpdate::BigOrderflow(alert) => self.handle_big_orderflow(&alert),
+            GuiUpdate::Alert(ScreenerAlert::OpenInterestSpike { symbol, oi_change, oi_change_percentage, timeframe }) => {
+                self.alerts += 1;
+                warn!(
+                    "ALERT open interest {} {:+.2}% ({:+.2}) over {}",
+                    symbol, oi_change_percentage, oi_change, timeframe
+                );
+            }
+            GuiUpdate::Alert(ScreenerAlert::FundingExtreme { symbol, funding_rate, deviation_from_norm, predicted_rate }) => {
+                self.alerts += 1;
+                warn!(
+                    "ALERT funding {} {:.4}% ({:+.4}% from norm, predicted {:.4}%)",
+                    symbol, funding_rate * 100.0, deviation_from_norm * 100.0, predicted_rate * 100.0
+                );
+            }
+            GuiUpdate::Alert(ScreenerAlert::BookPattern(alert)) => {
+                self.alerts += 1;
+                warn!(
+                    "ALERT {:?} candidate {} {} @ {:.4}: {} times in the window, {:.4} traded, up to {:.4} shown",
+                    alert.pattern, alert.symbol, alert.side, alert.price, alert.occurrences, alert.traded_quantity, alert.displayed_quantity
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Resolves on SIGTERM or Ctrl-C
+pub async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                tokio::select! {
+                    _ = sigterm.recv() => info!("Received SIGTERM"),
+                    _ = tokio::signal::ctrl_c() => info!("Received Ctrl-C"),
+                }
+            }
+            Err(e) => {
+                warn!("Cannot listen for SIGTERM: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        info!("Received Ctrl-C");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn imbalance_at(timestamp: u64, ratio: f64) -> OrderImbalance {
+        OrderImbalance {
+            symbol: "BTCUSDT".to_string(),
+            timestamp,
+            bid_volume: 1.0 + ratio,
+            ask_volume: 1.0 - ratio,
+            imbalance_ratio: ratio,
+            window_duration_seconds: 60,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sink_logs_queued_alerts_on_shutdown() {
+        let bus = EventBus::new(&Settings::default().analysis.event_bus);
+        let (imbalance_tx, imbalance) = mpsc::channel(10);
+        let (_volume_tx, volume) = mpsc::channel(10);
+        let (_gui_update_tx, gui_update) = mpsc::channel(10);
+        let channels = HeadlessChannels {
+            orderflow: bus.orderflow.subscribe("headless", OverflowPolicy::Drop),
+            imbalance,
+            liquidation: bus.liquidations.subscribe("headless", OverflowPolicy::Drop),
+            volume,
+            gui_update,
+            depth_snapshot: bus.depth.subscribe("headless", OverflowPolicy::Drop),
+            alerts: bus.alerts.subscribe("headless", OverflowPolicy::Drop),
+        };
+
+        // Second strong imbalance falls inside the cooldown, the weak one is below threshold
+        imbalance_tx.send(imbalance_at(0, 0.8)).await.unwrap();
+        imbalance_tx.send(imbalance_at(1_000, 0.9)).await.unwrap();
+        imbalance_tx.send(imbalance_at(IMBALANCE_ALERT_COOLDOWN_MS * 2, 0.1)).await.unwrap();
+        bus.liquidations.publish(LiquidationEvent {
+            symbol: "BTCUSDT".to_string(),
+            timestamp: 1,
+            side: "BUY".to_string(),
+            price: 100.0,
+            quantity: 5000.0,
+            is_forced: true,
+            notional_value: 500_000.0,
+        }).await;
+
+        // Shutdown is already requested, so everything is handled by the final drain
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        shutdown_tx.send(true).unwrap();
+
+        let alerts = HeadlessSink::new(channels, &Settings::default()).run(shutdown_rx).await;
+        assert_eq!(alerts, 2);
+    }
+}
@@ -1,5 +1,4 @@
 use eframe::egui;
-use std::sync::Arc;
 use tokio::sync::mpsc;
 use tracing::{info, error};
 
@@ -8,11 +7,13 @@ mod data;
 mod analysis;
 mod gui;
 mod utils;
+mod headless;
 
-use config::Settings;
+use config::{Settings, CliArgs};
 use data::*;
 use analysis::*;
 use gui::*;
+use headless::{HeadlessSink, HeadlessChannels, shutdown_signal};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -23,148 +24,506 @@ async fn main() -> anyhow::Result<()> {
     info!("Starting Binance Futures Orderflow Screener");
 
     // Load configuration
+    let cli = CliArgs::from_env()?;
     let settings = Settings::new()?;
     
     // Create data channels
     let (orderflow_tx, orderflow_rx) = mpsc::channel::<OrderflowEvent>(10000);
     let (imbalance_tx, imbalance_rx) = mpsc::channel::<OrderImbalance>(1000);
-    let (liquidation_tx, liquidation_rx) = mpsc::channel::<LiquidationEvent>(1000);
     let (volume_tx, volume_rx) = mpsc::channel::<VolumeProfile>(1000);
     let (gui_update_tx, gui_update_rx) = mpsc::channel::<GuiUpdate>(1000);
-    let (gui_orderflow_tx, gui_orderflow_rx) = mpsc::channel::<OrderflowEvent>(10000);
-    let (depth_tx, depth_rx) = mpsc::channel::<DepthUpdate>(10000);
-    let (depth_snapshot_tx, depth_snapshot_rx) = mpsc::channel::<(String, DepthSnapshot)>(1000);
+    // Fan-out of trades, book snapshots, liquidations and alerts to their consumers
+    let event_bus = EventBus::new(&settings.analysis.event_bus);
+    // Pipeline counters for the /metrics endpoint and the diagnostics panel
+    let metrics = Metrics::new();
+    metrics.set_event_bus(event_bus.clone());
+
+    // Trading rules per symbol; the cache covers starts without exchange access
+    let symbol_registry = SymbolRegistry::with_cache(&settings.binance.symbol_cache_path);
+    // Depth history step per symbol, adjustable from the footprint scale selector
+    let depth_aggregation = DepthAggregation::from(&settings.analysis.lob);
 
     // Initialize database
-    let db_manager = DatabaseManager::new("data.db").await?;
+    let db_manager = DatabaseManager::new(&settings.database.path).await?;
     db_manager.initialize_schema().await?;
 
-    // Start WebSocket manager
-    let mut ws_manager = WebSocketManager::new(settings.clone(), orderflow_tx.clone());
-    ws_manager.set_liquidation_sender(liquidation_tx.clone());
-    ws_manager.set_depth_sender(depth_tx.clone());
-    let ws_handle = tokio::spawn(async move {
-        if let Err(e) = ws_manager.start().await {
-            error!("WebSocket manager error: {}", e);
+    // Background writer for the analysis streams; an attached GUI only reads
+    let (persistence, persistence_handle) = if cli.attach {
+        (None, None)
+    } else {
+        let (persistence, handle) = PersistenceHandle::start(
+            db_manager.clone(),
+            PersistenceConfig::from(&settings.database),
+        );
+        metrics.set_persistence(&persistence);
+        (Some(persistence), Some(handle))
+    };
+
+    // Optional capture of everything received from the exchange
+    let (recorder, recorder_handle) = match &cli.record {
+        Some(path) => {
+            let (recorder, handle) = CaptureRecorder::start(path)?;
+            (Some(recorder), Some(handle))
         }
-    });
+        None => (None, None),
+    };
 
-    // Start OrderBookManager
-    let orderbook_manager = OrderBookManager::new(depth_rx, depth_snapshot_tx);
-    let orderbook_handle = tokio::spawn(async move {
-        orderbook_manager.start().await;
-    });
+    // forceOrder liquidations pass through the event distributor before joining the detector's
+    let (force_order_tx, force_order_rx) = mpsc::channel::<LiquidationEvent>(1000);
 
-    // Start analysis engines
-    let analysis_handles = start_analysis_engines(
-        orderflow_rx,
-        imbalance_tx,
-        liquidation_tx,
-        volume_tx,
-        gui_update_tx,
-        gui_orderflow_tx,
-        db_manager.clone(),
-        settings.binance.api_base_url.clone(),
-    ).await;
-
-    // Start GUI application
-    let native_options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([1920.0, 1080.0])
-            .with_title("Binance Futures Orderflow Screener"),
-        ..Default::default()
-    };
+    let mut ws_handles = Vec::new();
+    let mut analysis_handles = Vec::new();
+    let mut trade_gap_metrics = Vec::new();
+    if cli.attach {
+        // Show what a headless daemon is writing instead of connecting ourselves
+        info!("Attaching to daemon database {}", settings.database.path);
+        let follower = DatabaseFollower::new(db_manager.clone(), &event_bus, imbalance_tx);
+        analysis_handles.push(tokio::spawn(async move {
+            if let Err(e) = follower.start().await {
+                error!("Database follower error: {}", e);
+            }
+        }));
+    } else {
+        if let Some(replay_path) = &cli.replay {
+            // Replay a capture through the same channels the live sources feed
+            let source = create_sources(&settings)?
+                .into_iter()
+                .next()
+                .ok_or_else(|| anyhow::anyhow!("No market configured"))?;
+
+            let (depth_tx, depth_rx) = mpsc::channel::<DepthUpdate>(10000);
+            let mut orderbook_manager = OrderBookManager::new(depth_rx, event_bus.depth.clone(), source);
+            orderbook_manager.set_symbol_registry(symbol_registry.clone());
+            orderbook_manager.set_depth_aggregation(depth_aggregation.clone());
+            orderbook_manager.set_metrics(&metrics);
+            orderbook_manager.set_book_analytics(event_bus.book_analytics.clone(), settings.analysis.book.clone());
+            orderbook_manager.set_book_deltas(event_bus.book_deltas.clone());
+            if settings.analysis.heatmap.wide.enabled {
+                orderbook_manager.set_wide_depth(event_bus.wide_depth.clone(), settings.analysis.heatmap.wide.clone());
+            }
 
-    let app = ScreenerApp::new(
-        imbalance_rx,
-        liquidation_rx,
-        volume_rx,
-        gui_update_rx,
-        gui_orderflow_rx,
-        depth_snapshot_rx,
-        db_manager,
-        settings.binance.symbols.clone(), // Pass the actual subscribed symbols
-    ).await?;
-
-    eframe::run_native(
-        "Binance Futures Orderflow Screener",
-        native_options,
-        Box::new(|_cc| Box::new(app)),
-    ).map_err(|e| anyhow::anyhow!("GUI error: {}", e))?;
+            let mut replay = ReplaySource::new(replay_path, cli.replay_speed, orderflow_tx.clone());
+            replay.set_depth_sender(depth_tx);
+            replay.set_liquidation_sender(force_order_tx.clone());
+            replay.set_depth_snapshot_injector(orderbook_manager.snapshot_injector());
+
+            ws_handles.push(tokio::spawn(async move {
+                orderbook_manager.start().await;
+            }));
+            ws_handles.push(tokio::spawn(async move {
+                if let Err(e) = replay.run().await {
+                    error!("Replay error: {}", e);
+                }
+            }));
+        } else {
+            // Mark price frames from every market feed one funding monitor
+            let (mark_price_tx, mark_price_rx) = mpsc::channel::<Vec<FundingRateData>>(100);
+            let mut funding_monitor = FundingMonitor::new(
+                mark_price_rx,
+                FundingConfig::from(&settings.analysis),
+                gui_update_tx.clone(),
+            );
+            if let Some(persistence) = &persistence {
+                funding_monitor.set_persistence(persistence.clone());
+            }
+            ws_handles.push(tokio::spawn(async move {
+                if let Err(e) = funding_monitor.start().await {
+                    error!("Funding monitor error: {}", e);
+                }
+            }));
+
+            // Every market's connections report to one supervisor, which also feeds the status bar
+            let connection_monitor = ConnectionMonitor::new(HealthConfig::from(&settings.binance));
+            let mut supervisor = ConnectionSupervisor::new(connection_monitor.clone());
+            supervisor.set_gui_sender(gui_update_tx.clone());
+            metrics.set_connection_monitor(connection_monitor.clone());
+            ws_handles.push(tokio::spawn(supervisor.run()));
+
+            // Start one WebSocket manager and OrderBookManager per configured market
+            for source in create_sources(&settings)? {
+                info!("Starting market data source {}", source.name());
+
+                // Listings and delistings are picked up without a restart
+                let mut universe = SymbolUniverse::new(source.clone(), settings.binance.symbol_refresh_interval_secs);
+                universe.set_gui_sender(gui_update_tx.clone());
+                universe.set_symbol_registry(symbol_registry.clone());
+
+                // Trades lost across reconnects are fetched over REST and put back in order
+                let (stream_orderflow_tx, stream_orderflow_rx) = mpsc::channel::<OrderflowEvent>(10000);
+                let gap_filler = TradeGapFiller::new(
+                    source.clone(),
+                    stream_orderflow_rx,
+                    orderflow_tx.clone(),
+                    TradeGapConfig::from(&settings.binance),
+                );
+                metrics.add_trade_gaps(source.name(), gap_filler.metrics());
+                trade_gap_metrics.push((source.name().to_string(), gap_filler.metrics()));
+                ws_handles.push(tokio::spawn(gap_filler.run()));
+
+                let (depth_tx, depth_rx) = mpsc::channel::<DepthUpdate>(10000);
+                let mut ws_manager = WebSocketManager::new(settings.clone(), source.clone(), stream_orderflow_tx);
+                ws_manager.set_liquidation_sender(force_order_tx.clone());
+                ws_manager.set_depth_sender(depth_tx);
+                ws_manager.set_mark_price_sender(mark_price_tx.clone());
+                ws_manager.set_symbol_universe(universe.subscribe());
+                let open_interest_universe = universe.subscribe();
+                ws_manager.set_connection_monitor(connection_monitor.clone());
+                ws_handles.push(tokio::spawn(universe.run()));
+                ws_handles.push(tokio::spawn(async move {
+                    if let Err(e) = ws_manager.start().await {
+                        error!("WebSocket manager error: {}", e);
+                    }
+                }));
+
+                // Polls the same listed universe the stream manager subscribes
+                let mut oi_collector = OpenInterestCollector::new(
+                    source.clone(),
+                    Vec::new(),
+                    OpenInterestConfig::from(&settings.analysis),
+                    gui_update_tx.clone(),
+                );
+                oi_collector.set_symbol_universe(open_interest_universe);
+                if let Some(persistence) = &persistence {
+                    oi_collector.set_persistence(persistence.clone());
+                }
+                ws_handles.push(tokio::spawn(async move {
+                    if let Err(e) = oi_collector.start().await {
+                        error!("Open interest collector error: {}", e);
+                    }
+                }));
+
+                let mut orderbook_manager = OrderBookManager::new(depth_rx, event_bus.depth.clone(), source);
+                orderbook_manager.set_symbol_registry(symbol_registry.clone());
+                orderbook_manager.set_depth_aggregation(depth_aggregation.clone());
+                orderbook_manager.set_metrics(&metrics);
+                orderbook_manager.set_book_analytics(event_bus.book_analytics.clone(), settings.analysis.book.clone());
+                orderbook_manager.set_book_deltas(event_bus.book_deltas.clone());
+                if settings.analysis.heatmap.wide.enabled {
+                    orderbook_manager.set_wide_depth(event_bus.wide_depth.clone(), settings.analysis.heatmap.wide.clone());
+                }
+                if let Some(recorder) = &recorder {
+                    orderbook_manager.set_recorder(recorder.clone());
+                }
+                ws_handles.push(tokio::spawn(async move {
+                    orderbook_manager.start().await;
+                }));
+            }
+        }
+
+        // Start analysis engines
+        analysis_handles = start_analysis_engines(
+            orderflow_rx,
+            force_order_rx,
+            imbalance_tx,
+            volume_tx,
+            gui_update_tx.clone(),
+            event_bus.clone(),
+            metrics.clone(),
+            persistence.clone(),
+            settings.binance.api_base_url.clone(),
+            settings.analysis.patterns.clone(),
+            settings.analysis.book_events.clone(),
+            recorder.clone(),
+        ).await;
+    }
+
+    analysis_handles.push(tokio::spawn(event_bus.clone().report(Some(gui_update_tx.clone()))));
+
+    // Downsampled heatmap history of the recorded symbols, seeded from the database as each is opened; attached GUIs only read it
+    let heatmap_store = settings.analysis.heatmap.enabled.then(|| HeatmapStore::new(&settings.analysis.heatmap));
+    if let Some(store) = &heatmap_store {
+        let mut heatmap_recorder = HeatmapRecorder::new(store.clone(), symbol_registry.clone(), depth_aggregation.clone());
+        heatmap_recorder.set_database(db_manager.clone());
+        if let Some(persistence) = &persistence {
+            heatmap_recorder.set_persistence(persistence.clone());
+        }
+        let depth = event_bus.depth.subscribe("heatmap", event_bus.policy());
+        analysis_handles.push(tokio::spawn(heatmap_recorder.start(depth)));
+    }
+
+    // Book analytics history, thinned to the stored series
+    if let Some(persistence) = &persistence {
+        let recorder = BookAnalyticsRecorder::new(&settings.analysis.book, persistence.clone(), db_manager.clone());
+        let book_analytics = event_bus.book_analytics.subscribe("persistence", event_bus.policy());
+        analysis_handles.push(tokio::spawn(recorder.start(book_analytics)));
+    }
+
+    // Base footprint candles; blocking so a full queue never costs a stored minute its trades
+    if let Some(persistence) = &persistence {
+        let recorder = FootprintRecorder::new(symbol_registry.clone(), persistence.clone());
+        let orderflow = event_bus.orderflow.subscribe("footprint", OverflowPolicy::Block);
+        analysis_handles.push(tokio::spawn(recorder.start(orderflow)));
+    }
+
+    if settings.metrics.enabled {
+        match tokio::net::TcpListener::bind(&settings.metrics.listen_address).await {
+            Ok(listener) => analysis_handles.push(tokio::spawn(serve_metrics(listener, metrics.clone()))),
+            Err(e) => error!("Cannot serve metrics on {}: {}", settings.metrics.listen_address, e),
+        }
+    }
+
+    if cli.headless {
+        // Daemon mode: log alerts until SIGTERM; persistence runs as in GUI mode
+        let channels = HeadlessChannels {
+            orderflow: event_bus.orderflow.subscribe("headless", OverflowPolicy::Drop),
+            imbalance: imbalance_rx,
+            liquidation: event_bus.liquidations.subscribe("headless", OverflowPolicy::Drop),
+            volume: volume_rx,
+            gui_update: gui_update_rx,
+            depth_snapshot: event_bus.depth.subscribe("headless", OverflowPolicy::Drop),
+            alerts: event_bus.alerts.subscribe("headless", OverflowPolicy::Drop),
+        };
+        let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+        let sink = tokio::spawn(HeadlessSink::new(channels, &settings).run(shutdown_rx));
+
+        info!("Running headless, send SIGTERM to stop");
+        shutdown_signal().await;
+        info!("Shutting down");
+
+        // Stop producers first so the final drains see a fixed backlog
+        for handle in ws_handles.drain(..) {
+            handle.abort();
+        }
+        for handle in analysis_handles.drain(..) {
+            handle.abort();
+        }
+        let _ = shutdown_tx.send(true);
+        let _ = tokio::time::timeout(std::time::Duration::from_secs(5), sink).await;
+    } else {
+        // Start GUI application
+        let native_options = eframe::NativeOptions {
+            viewport: egui::ViewportBuilder::default()
+                .with_inner_size([1920.0, 1080.0])
+                .with_title("Binance Futures Orderflow Screener"),
+            ..Default::default()
+        };
+
+        let mut app = ScreenerApp::new(
+            imbalance_rx,
+            volume_rx,
+            gui_update_rx,
+            &event_bus,
+            db_manager.clone(),
+            settings.binance.symbols.clone(), // Pass the actual subscribed symbols
+        ).await?;
+        app.set_symbol_registry(symbol_registry.clone());
+        app.set_depth_aggregation(depth_aggregation.clone());
+        app.set_metrics(metrics.clone());
+        if let Some(store) = heatmap_store {
+            app.set_heatmap_store(store);
+        }
+        if settings.analysis.heatmap.wide.enabled {
+            app.set_wide_heatmap(event_bus.wide_depth.subscribe("gui", OverflowPolicy::Drop), &settings.analysis.heatmap.wide);
+        }
+
+        // Seed the footprint chart from the database, filling its gaps over REST
+        if cli.replay.is_none() {
+            let (history_tx, history_rx) = mpsc::channel::<FootprintHistory>(100);
+            app.set_history_receiver(history_rx);
+
+            let mut backfill_config = BackfillConfig::from(&settings.analysis);
+            let mut sources = create_sources(&settings)?;
+            if cli.attach {
+                // Attached GUIs show only what the daemon stored
+                backfill_config.max_rest_pages = 0;
+                sources.truncate(1);
+            }
+            for source in sources {
+                let backfill = TradeBackfill::new(db_manager.clone(), source, backfill_config.clone());
+                analysis_handles.push(tokio::spawn(backfill.run(settings.binance.symbols.clone(), history_tx.clone())));
+            }
+        }
+
+        eframe::run_native(
+            "Binance Futures Orderflow Screener",
+            native_options,
+            Box::new(|_cc| Box::new(app)),
+        ).map_err(|e| anyhow::anyhow!("GUI error: {}", e))?;
+    }
 
     // Cleanup
-    ws_handle.abort();
+    for handle in ws_handles {
+        handle.abort();
+    }
     for handle in analysis_handles {
         handle.abort();
     }
 
+    let bus_stats = event_bus.stats();
+    for stats in bus_stats.iter().filter(|stats| stats.dropped > 0) {
+        tracing::warn!("{} subscriber {} dropped {} events (max lag {})", stats.topic, stats.name, stats.dropped, stats.max_lag);
+    }
+    info!("Event bus: {}", bus_summary(&bus_stats));
+
+    for (market, metrics) in &trade_gap_metrics {
+        let stats = metrics.stats();
+        info!(
+            "{} trade gaps: {} gaps, {} trades missing, {} backfilled, {} unrecovered ({} aggTrades requests)",
+            market, stats.gaps, stats.missing_trades, stats.backfilled_trades, stats.unrecovered_trades, stats.requests
+        );
+    }
+
+    // Aborted producers drop their persistence handles; wait for the writer to commit the rest
+    if let Some(persistence) = persistence {
+        let stats = persistence.stats();
+        drop(persistence);
+        if let Some(handle) = persistence_handle {
+            if tokio::time::timeout(std::time::Duration::from_secs(20), handle).await.is_err() {
+                error!("Timed out flushing pending database writes");
+            }
+        }
+        info!("Persistence: {} rows queued, {} dropped under backpressure", stats.enqueued, stats.dropped);
+    }
+
+    // Let the capture writer drain and flush once every producer is gone
+    if let Some(recorder) = recorder {
+        if recorder.dropped_count() > 0 {
+            tracing::warn!("Capture recorder dropped {} events", recorder.dropped_count());
+        }
+    }
+    if let Some(handle) = recorder_handle {
+        let _ = tokio::time::timeout(std::time::Duration::from_secs(5), handle).await;
+    }
+
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn start_analysis_engines(
     mut orderflow_rx: mpsc::Receiver<OrderflowEvent>,
+    mut force_order_rx: mpsc::Receiver<LiquidationEvent>,
     imbalance_tx: mpsc::Sender<OrderImbalance>,
-    liquidation_tx: mpsc::Sender<LiquidationEvent>,
     volume_tx: mpsc::Sender<VolumeProfile>,
-    _gui_update_tx: mpsc::Sender<GuiUpdate>,
-    gui_orderflow_tx: mpsc::Sender<OrderflowEvent>,
-    _db_manager: Arc<DatabaseManager>,
+    gui_update_tx: mpsc::Sender<GuiUpdate>,
+    event_bus: EventBus,
+    metrics: Metrics,
+    persistence: Option<PersistenceHandle>,
     api_base_url: String,
+    pattern_config: config::settings::BookPatternConfig,
+    book_event_config: config::settings::BookEventConfig,
+    recorder: Option<CaptureRecorder>,
 ) -> Vec<tokio::task::JoinHandle<()>> {
     let mut handles = Vec::new();
 
-    // Create channels for distributing orderflow events to multiple analyzers
-    let (orderflow_broadcast_tx, orderflow_broadcast_rx1) = mpsc::channel::<OrderflowEvent>(1000);
-    let (orderflow_broadcast_tx2, orderflow_broadcast_rx2) = mpsc::channel::<OrderflowEvent>(1000);
-    let (orderflow_broadcast_tx3, orderflow_broadcast_rx3) = mpsc::channel::<OrderflowEvent>(1000);
+    // Analyzer outputs pass through a tee that persists them before they reach the GUI
+    let (analysis_imbalance_tx, mut analysis_imbalance_rx) = mpsc::channel::<OrderImbalance>(1000);
+    let (analysis_liquidation_tx, mut analysis_liquidation_rx) = mpsc::channel::<LiquidationEvent>(1000);
+    let (analysis_volume_tx, mut analysis_volume_rx) = mpsc::channel::<VolumeProfile>(1000);
+
+    // Analyzers subscribe before the distributor starts publishing
+    let imbalance_orderflow = event_bus.orderflow.subscribe("imbalance", event_bus.policy());
+    let volume_orderflow = event_bus.orderflow.subscribe("volume", event_bus.policy());
+    let liquidation_orderflow = event_bus.orderflow.subscribe("liquidation detector", event_bus.policy());
+    let pattern_orderflow = event_bus.orderflow.subscribe("book patterns", event_bus.policy());
+    let pattern_deltas = event_bus.book_deltas.subscribe("book patterns", event_bus.policy());
+    let book_event_orderflow = event_bus.orderflow.subscribe("book events", event_bus.policy());
+    let book_event_deltas = event_bus.book_deltas.subscribe("book events", event_bus.policy());
 
     // Event distributor
+    let distributor_bus = event_bus.clone();
+    let distributor_liquidation_tx = analysis_liquidation_tx.clone();
+    let distributor_persistence = persistence.clone();
     let handle = tokio::spawn(async move {
         let mut event_count = 0;
-        while let Some(event) = orderflow_rx.recv().await {
-            event_count += 1;
-            if event_count % 100 == 0 {
-                tracing::info!("Event distributor processed {} events. Latest: {} @ {}", event_count, event.symbol, event.price);
+        loop {
+            tokio::select! {
+                Some(event) = orderflow_rx.recv() => {
+                    event_count += 1;
+                    if event_count % 100 == 0 {
+                        tracing::info!("Event distributor processed {} events. Latest: {} @ {}", event_count, event.symbol, event.price);
+                    }
+
+                    if let Some(recorder) = &recorder {
+                        recorder.record(CaptureEvent::Trade(event.clone()));
+                    }
+                    if let Some(persistence) = &distributor_persistence {
+                        persistence.persist(PersistRecord::Trade(event.clone()));
+                    }
+
+                    // Distribute to all analyzers and GUI
+                    distributor_bus.orderflow.publish(event).await;
+                }
+                Some(liquidation) = force_order_rx.recv() => {
+                    if let Some(recorder) = &recorder {
+                        recorder.record(CaptureEvent::Liquidation(liquidation.clone()));
+                    }
+                    let _ = distributor_liquidation_tx.try_send(liquidation);
+                }
+                else => break,
             }
+        }
+    });
+    handles.push(handle);
 
-            // Distribute to all analyzers and GUI
-            let _ = orderflow_broadcast_tx.try_send(event.clone());
-            let _ = orderflow_broadcast_tx2.try_send(event.clone());
-            let _ = orderflow_broadcast_tx3.try_send(event.clone());
-            let _ = gui_orderflow_tx.try_send(event.clone());
+    // Output tee
+    let tee_bus = event_bus.clone();
+    let handle = tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                Some(imbalance) = analysis_imbalance_rx.recv() => {
+                    if let Some(persistence) = &persistence {
+                        persistence.persist(PersistRecord::Imbalance(imbalance.clone()));
+                    }
+                    let _ = imbalance_tx.try_send(imbalance);
+                }
+                Some(liquidation) = analysis_liquidation_rx.recv() => {
+                    if let Some(persistence) = &persistence {
+                        persistence.persist(PersistRecord::Liquidation(liquidation.clone()));
+                    }
+                    tee_bus.liquidations.publish(liquidation).await;
+                }
+                Some(profile) = analysis_volume_rx.recv() => {
+                    if let Some(persistence) = &persistence {
+                        persistence.persist(PersistRecord::VolumeProfile(profile.clone()));
+                    }
+                    let _ = volume_tx.try_send(profile);
+                }
+                else => break,
+            }
         }
     });
     handles.push(handle);
 
     // Imbalance analyzer
-    let imbalance_analyzer = ImbalanceAnalyzer::new(imbalance_tx.clone());
-    let orderflow_rx_arc = Arc::new(tokio::sync::Mutex::new(orderflow_broadcast_rx1));
+    let mut imbalance_analyzer = ImbalanceAnalyzer::new(analysis_imbalance_tx);
+    imbalance_analyzer.set_metrics(&metrics);
     let handle = tokio::spawn(async move {
-        if let Err(e) = imbalance_analyzer.start(orderflow_rx_arc).await {
+        if let Err(e) = imbalance_analyzer.start(imbalance_orderflow).await {
             error!("Imbalance analyzer error: {}", e);
         }
     });
     handles.push(handle);
 
     // Volume analyzer
-    let mut volume_analyzer = VolumeAnalyzer::new(volume_tx.clone(), api_base_url);
-    let orderflow_rx2_arc = Arc::new(tokio::sync::Mutex::new(orderflow_broadcast_rx2));
+    let mut volume_analyzer = VolumeAnalyzer::new(analysis_volume_tx, api_base_url);
+    volume_analyzer.set_alert_topic(event_bus.alerts.clone());
+    volume_analyzer.set_metrics(&metrics);
     let handle = tokio::spawn(async move {
-        if let Err(e) = volume_analyzer.start_with_receiver(orderflow_rx2_arc).await {
+        if let Err(e) = volume_analyzer.start_with_receiver(volume_orderflow).await {
             error!("Volume analyzer error: {}", e);
         }
     });
     handles.push(handle);
 
     // Liquidation detector
-    let mut liquidation_detector = LiquidationDetector::new(liquidation_tx.clone());
-    let orderflow_rx3_arc = Arc::new(tokio::sync::Mutex::new(orderflow_broadcast_rx3));
+    let mut liquidation_detector = LiquidationDetector::new(analysis_liquidation_tx);
+    liquidation_detector.set_metrics(&metrics);
     let handle = tokio::spawn(async move {
-        if let Err(e) = liquidation_detector.start_with_receiver(orderflow_rx3_arc).await {
+        if let Err(e) = liquidation_detector.start_with_receiver(liquidation_orderflow).await {
             error!("Liquidation detector error: {}", e);
         }
     });
     handles.push(handle);
 
+    // Spoof and iceberg detector
+    let mut pattern_detector = BookPatternDetector::new(pattern_config);
+    pattern_detector.set_metrics(&metrics);
+    handles.push(tokio::spawn(pattern_detector.start(pattern_deltas, pattern_orderflow, gui_update_tx)));
+
+    // Add / cancel / execution classifier
+    let mut book_event_classifier = BookEventClassifier::new(book_event_config);
+    book_event_classifier.set_metrics(&metrics);
+    handles.push(tokio::spawn(book_event_classifier.start(book_event_deltas, book_event_orderflow, event_bus.book_flow.clone())));
+
     handles
 }
\ No newline at end of file
@@ -0,0 +1,87 @@
+-- Schema written by builds before PRAGMA user_version tracking (user_version = 0)
+
+CREATE TABLE candles (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    open_price REAL NOT NULL,
+    high_price REAL NOT NULL,
+    low_price REAL NOT NULL,
+    close_price REAL NOT NULL,
+    volume REAL NOT NULL,
+    buy_volume REAL NOT NULL,
+    sell_volume REAL NOT NULL,
+    timeframe TEXT NOT NULL,
+    trade_count INTEGER NOT NULL DEFAULT 0,
+    created_at INTEGER,
+    UNIQUE(symbol, timestamp, timeframe)
+);
+
+CREATE TABLE volume_profile (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    price_level REAL NOT NULL,
+    buy_volume REAL NOT NULL,
+    sell_volume REAL NOT NULL,
+    total_volume REAL NOT NULL,
+    trade_count INTEGER NOT NULL DEFAULT 0,
+    timeframe TEXT NOT NULL,
+    created_at INTEGER
+);
+
+CREATE TABLE order_imbalances (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    bid_volume REAL NOT NULL,
+    ask_volume REAL NOT NULL,
+    imbalance_ratio REAL NOT NULL,
+    window_duration_seconds INTEGER NOT NULL,
+    created_at INTEGER
+);
+
+CREATE TABLE liquidations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    side TEXT NOT NULL,
+    price REAL NOT NULL,
+    quantity REAL NOT NULL,
+    is_forced INTEGER NOT NULL,
+    notional_value REAL NOT NULL,
+    created_at INTEGER
+);
+
+CREATE TABLE daily_stats (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    date TEXT NOT NULL,
+    avg_volume REAL NOT NULL,
+    total_volume REAL NOT NULL,
+    avg_price REAL NOT NULL,
+    high_price REAL NOT NULL,
+    low_price REAL NOT NULL,
+    trade_count INTEGER NOT NULL,
+    created_at INTEGER,
+    updated_at INTEGER,
+    UNIQUE(symbol, date)
+);
+
+CREATE TABLE raw_trades (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    price REAL NOT NULL,
+    quantity REAL NOT NULL,
+    is_buyer_maker INTEGER NOT NULL,
+    trade_id INTEGER NOT NULL,
+    created_at INTEGER
+);
+
+INSERT INTO raw_trades (symbol, timestamp, price, quantity, is_buyer_maker, trade_id)
+VALUES ('BTCUSDT', 1700000000000, 37000.5, 0.25, 1, 1001),
+       ('BTCUSDT', 1700000000100, 37001.0, 0.10, 0, 1002);
+
+INSERT INTO liquidations (symbol, timestamp, side, price, quantity, is_forced, notional_value)
+VALUES ('ETHUSDT', 1700000000200, 'SELL', 2000.0, 5.0, 1, 10000.0);